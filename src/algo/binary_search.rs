@@ -1,5 +1,5 @@
 #![allow(dead_code)]
-use std::{ops::Deref, usize};
+use std::ops::Deref;
 
 /// The binary search offers an effecient way to find a value in an ORDERED
 /// array. The way it does this, it's by starting at the middle of the array,
@@ -0,0 +1,936 @@
+#![allow(dead_code)]
+//! Arithmetic expressions handled with stacks: tokenizing, checking that the
+//! brackets are balanced, converting an infix expression into postfix or
+//! prefix notation, and evaluating a postfix expression.
+//!
+//! Every function working with a stack takes an empty one by value, so any
+//! [`Stack`] implementation can be used, as long as it has room enough for the
+//! expression. A stack which overflows is reported as [`ExprErr::Stack`].
+//!
+//! Positions in errors are byte offsets in the input string.
+
+use std::fmt::Display;
+
+use crate::structures::stack::{ArrayStack, Stack, StackErr};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Pow,
+    /// Unary minus, a `-` with no operand on its left.
+    Neg,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Assoc {
+    Left,
+    Right,
+}
+
+impl Operator {
+    pub fn precedence(&self) -> u8 {
+        match self {
+            Operator::Add | Operator::Sub => 1,
+            Operator::Mul | Operator::Div | Operator::Rem => 2,
+            Operator::Neg => 3,
+            Operator::Pow => 4,
+        }
+    }
+
+    /// Power is right associative, `2^3^2` is `2^(3^2)`, as the unary minus
+    /// is, the others are left associative, `8-4-2` is `(8-4)-2`.
+    pub fn assoc(&self) -> Assoc {
+        match self {
+            Operator::Pow | Operator::Neg => Assoc::Right,
+            _ => Assoc::Left,
+        }
+    }
+
+    pub fn is_unary(&self) -> bool {
+        *self == Operator::Neg
+    }
+}
+
+/// Functions which can be called in an expression, like `max(1, 2)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Function {
+    Abs,
+    Min,
+    Max,
+}
+
+impl Function {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "abs" => Some(Function::Abs),
+            "min" => Some(Function::Min),
+            "max" => Some(Function::Max),
+            _ => None,
+        }
+    }
+
+    pub fn arity(&self) -> usize {
+        match self {
+            Function::Abs => 1,
+            Function::Min | Function::Max => 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token {
+    /// Number literal, parsed only by the evaluation, into the operand type.
+    Number(String),
+    Op(Operator),
+    Func(Function),
+    /// Opening bracket, `(`, `[` or `{`.
+    Open(char),
+    /// Closing bracket, `)`, `]` or `}`.
+    Close(char),
+    /// Separator between the arguments of a function.
+    Comma,
+}
+
+/// Token with its position in the input.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spanned {
+    pub token: Token,
+    pub pos: usize,
+}
+
+/// Entry of the operator stack used by [`to_postfix`], the operand stack being
+/// the output itself.
+pub struct Pending {
+    tok: Spanned,
+    // Number of commas met inside an opening bracket.
+    commas: usize,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ExprErr {
+    UnknownToken {
+        pos: usize,
+        token: String,
+    },
+    InvalidNumber {
+        pos: usize,
+    },
+    UnexpectedClose {
+        pos: usize,
+    },
+    MismatchedBracket {
+        open: usize,
+        close: usize,
+    },
+    UnclosedBracket {
+        pos: usize,
+    },
+    UnexpectedComma {
+        pos: usize,
+    },
+    MissingCallParen {
+        pos: usize,
+    },
+    WrongArity {
+        pos: usize,
+        expected: usize,
+        found: usize,
+    },
+    MissingOperand {
+        pos: usize,
+    },
+    MissingOperator,
+    EmptyExpression,
+    DivisionByZero {
+        pos: usize,
+    },
+    Overflow {
+        pos: usize,
+    },
+    InvalidOperand {
+        pos: usize,
+    },
+    Stack(StackErr),
+}
+
+impl From<StackErr> for ExprErr {
+    fn from(err: StackErr) -> Self {
+        ExprErr::Stack(err)
+    }
+}
+
+impl std::error::Error for ExprErr {}
+
+impl Display for ExprErr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExprErr::UnknownToken { pos, token } => {
+                write!(f, "Unknown token '{token}' at {pos}")
+            }
+            ExprErr::InvalidNumber { pos } => write!(f, "Invalid number at {pos}"),
+            ExprErr::UnexpectedClose { pos } => {
+                write!(f, "Closing bracket at {pos} was never opened")
+            }
+            ExprErr::MismatchedBracket { open, close } => write!(
+                f,
+                "Bracket opened at {open} does not match the one closing it at {close}"
+            ),
+            ExprErr::UnclosedBracket { pos } => {
+                write!(f, "Bracket opened at {pos} is never closed")
+            }
+            ExprErr::UnexpectedComma { pos } => {
+                write!(f, "Comma at {pos} is not between function arguments")
+            }
+            ExprErr::MissingCallParen { pos } => {
+                write!(f, "Function at {pos} is not followed by a bracket")
+            }
+            ExprErr::WrongArity {
+                pos,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Function at {pos} takes {expected} arguments, {found} were given"
+            ),
+            ExprErr::MissingOperand { pos } => {
+                write!(f, "Operator at {pos} is missing an operand")
+            }
+            ExprErr::MissingOperator => {
+                write!(f, "Some operands are not combined by any operator")
+            }
+            ExprErr::EmptyExpression => write!(f, "The expression is empty"),
+            ExprErr::DivisionByZero { pos } => write!(f, "Division by zero at {pos}"),
+            ExprErr::Overflow { pos } => write!(f, "Operation at {pos} overflows"),
+            ExprErr::InvalidOperand { pos } => {
+                write!(f, "Operation at {pos} is not defined for its operands")
+            }
+            ExprErr::Stack(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+/// Failure of an operation on operands, whose position is added by
+/// [`eval_postfix`] to make an [`ExprErr`].
+#[derive(Debug, PartialEq, Eq)]
+pub enum ArithErr {
+    DivisionByZero,
+    Overflow,
+    InvalidOperand,
+}
+
+impl std::error::Error for ArithErr {}
+
+impl Display for ArithErr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArithErr::DivisionByZero => write!(f, "Division by zero"),
+            ArithErr::Overflow => write!(f, "The result does not fit the operand type"),
+            ArithErr::InvalidOperand => write!(f, "Operation not defined for the operands"),
+        }
+    }
+}
+
+/// Type of the values an expression can be evaluated to.
+pub trait Operand: Copy {
+    fn parse(literal: &str) -> Option<Self>;
+    fn apply(op: Operator, lhs: Self, rhs: Self) -> Result<Self, ArithErr>;
+    fn negate(self) -> Result<Self, ArithErr>;
+    fn call(func: Function, args: &[Self]) -> Result<Self, ArithErr>;
+}
+
+impl Operand for i64 {
+    fn parse(literal: &str) -> Option<Self> {
+        literal.parse().ok()
+    }
+
+    fn apply(op: Operator, lhs: Self, rhs: Self) -> Result<Self, ArithErr> {
+        if matches!(op, Operator::Div | Operator::Rem) && rhs == 0 {
+            return Err(ArithErr::DivisionByZero);
+        }
+        let res = match op {
+            Operator::Add => lhs.checked_add(rhs),
+            Operator::Sub => lhs.checked_sub(rhs),
+            Operator::Mul => lhs.checked_mul(rhs),
+            Operator::Div => lhs.checked_div(rhs),
+            Operator::Rem => lhs.checked_rem(rhs),
+            Operator::Pow => {
+                // An integer has no negative power, except for 1 and -1,
+                // which are their own inverse. Their powers only depend on the
+                // parity, so they don't overflow with a huge exponent either.
+                // A negative power of 0 divides by 0.
+                match (lhs, u32::try_from(rhs)) {
+                    (_, Ok(exp)) => lhs.checked_pow(exp),
+                    (0, Err(_)) if rhs < 0 => return Err(ArithErr::DivisionByZero),
+                    (1, Err(_)) => Some(1),
+                    (-1, Err(_)) => Some(if rhs % 2 == 0 { 1 } else { -1 }),
+                    (_, Err(_)) if rhs < 0 => return Err(ArithErr::InvalidOperand),
+                    (_, Err(_)) => None,
+                }
+            }
+            Operator::Neg => return Err(ArithErr::InvalidOperand),
+        };
+        res.ok_or(ArithErr::Overflow)
+    }
+
+    fn negate(self) -> Result<Self, ArithErr> {
+        self.checked_neg().ok_or(ArithErr::Overflow)
+    }
+
+    fn call(func: Function, args: &[Self]) -> Result<Self, ArithErr> {
+        match (func, args) {
+            (Function::Abs, [val]) => val.checked_abs().ok_or(ArithErr::Overflow),
+            (Function::Min, [a, b]) => Ok(*a.min(b)),
+            (Function::Max, [a, b]) => Ok(*a.max(b)),
+            _ => Err(ArithErr::InvalidOperand),
+        }
+    }
+}
+
+impl Operand for f64 {
+    fn parse(literal: &str) -> Option<Self> {
+        literal.parse().ok()
+    }
+
+    fn apply(op: Operator, lhs: Self, rhs: Self) -> Result<Self, ArithErr> {
+        if matches!(op, Operator::Div | Operator::Rem) && rhs == 0.0 {
+            return Err(ArithErr::DivisionByZero);
+        }
+        let res = match op {
+            Operator::Add => lhs + rhs,
+            Operator::Sub => lhs - rhs,
+            Operator::Mul => lhs * rhs,
+            Operator::Div => lhs / rhs,
+            Operator::Rem => lhs % rhs,
+            Operator::Pow => lhs.powf(rhs),
+            Operator::Neg => return Err(ArithErr::InvalidOperand),
+        };
+        // NaN comes from things like the square root of a negative number.
+        if res.is_nan() {
+            Err(ArithErr::InvalidOperand)
+        } else if res.is_infinite() {
+            Err(ArithErr::Overflow)
+        } else {
+            Ok(res)
+        }
+    }
+
+    fn negate(self) -> Result<Self, ArithErr> {
+        Ok(-self)
+    }
+
+    fn call(func: Function, args: &[Self]) -> Result<Self, ArithErr> {
+        match (func, args) {
+            (Function::Abs, [val]) => Ok(val.abs()),
+            (Function::Min, [a, b]) => Ok(a.min(*b)),
+            (Function::Max, [a, b]) => Ok(a.max(*b)),
+            _ => Err(ArithErr::InvalidOperand),
+        }
+    }
+}
+
+impl Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Number(literal) => write!(f, "{literal}"),
+            Token::Op(op) => {
+                let symbol = match op {
+                    Operator::Add => "+",
+                    Operator::Sub => "-",
+                    Operator::Mul => "*",
+                    Operator::Div => "/",
+                    Operator::Rem => "%",
+                    Operator::Pow => "^",
+                    Operator::Neg => "neg",
+                };
+                write!(f, "{symbol}")
+            }
+            Token::Func(func) => {
+                let name = match func {
+                    Function::Abs => "abs",
+                    Function::Min => "min",
+                    Function::Max => "max",
+                };
+                write!(f, "{name}")
+            }
+            Token::Open(bracket) | Token::Close(bracket) => write!(f, "{bracket}"),
+            Token::Comma => write!(f, ","),
+        }
+    }
+}
+
+/// Join the tokens with spaces, the unary minus is written `neg` so postfix and
+/// prefix expressions stay readable.
+pub fn render(tokens: &[Spanned]) -> String {
+    tokens
+        .iter()
+        .map(|spanned| spanned.token.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn closing_of(open: char) -> char {
+    match open {
+        '(' => ')',
+        '[' => ']',
+        _ => '}',
+    }
+}
+
+/// Split the input into tokens. A `-` is a unary minus when nothing on its
+/// left can be its left operand: at the start, after an operator, an opening
+/// bracket or a comma.
+pub fn tokenize(input: &str) -> Result<Vec<Spanned>, ExprErr> {
+    let mut tokens: Vec<Spanned> = Vec::new();
+    let mut chars = input.char_indices().peekable();
+
+    while let Some((pos, c)) = chars.next() {
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            c if c.is_ascii_digit() || c == '.' => {
+                let mut end = pos + c.len_utf8();
+                while let Some(&(next_pos, next)) = chars.peek() {
+                    if !(next.is_ascii_digit() || next == '.') {
+                        break;
+                    }
+                    end = next_pos + next.len_utf8();
+                    chars.next();
+                }
+                Token::Number(input[pos..end].to_string())
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut end = pos + c.len_utf8();
+                while let Some(&(next_pos, next)) = chars.peek() {
+                    if !(next.is_alphanumeric() || next == '_') {
+                        break;
+                    }
+                    end = next_pos + next.len_utf8();
+                    chars.next();
+                }
+                let name = &input[pos..end];
+                match Function::from_name(name) {
+                    Some(func) => Token::Func(func),
+                    None => {
+                        return Err(ExprErr::UnknownToken {
+                            pos,
+                            token: name.to_string(),
+                        })
+                    }
+                }
+            }
+            '-' => {
+                let unary = matches!(
+                    tokens.last().map(|spanned| &spanned.token),
+                    None | Some(Token::Op(_) | Token::Open(_) | Token::Comma)
+                );
+                Token::Op(if unary { Operator::Neg } else { Operator::Sub })
+            }
+            '+' => Token::Op(Operator::Add),
+            '*' => Token::Op(Operator::Mul),
+            '/' => Token::Op(Operator::Div),
+            '%' => Token::Op(Operator::Rem),
+            '^' => Token::Op(Operator::Pow),
+            '(' | '[' | '{' => Token::Open(c),
+            ')' | ']' | '}' => Token::Close(c),
+            ',' => Token::Comma,
+            _ => {
+                return Err(ExprErr::UnknownToken {
+                    pos,
+                    token: c.to_string(),
+                })
+            }
+        };
+        tokens.push(Spanned { token, pos });
+    }
+    Ok(tokens)
+}
+
+/// Check that every bracket of the input is closed by a bracket of the same
+/// kind, in the right order. Characters other than brackets are ignored. The
+/// stack holds the opening brackets waiting to be closed, with their position.
+pub fn check_brackets<S>(input: &str, mut stack: S) -> Result<(), ExprErr>
+where
+    S: Stack<Item = (char, usize)>,
+{
+    for (pos, c) in input.char_indices() {
+        match c {
+            '(' | '[' | '{' => stack.push((c, pos))?,
+            ')' | ']' | '}' => match stack.pop() {
+                None => return Err(ExprErr::UnexpectedClose { pos }),
+                Some((open, open_pos)) if closing_of(open) != c => {
+                    return Err(ExprErr::MismatchedBracket {
+                        open: open_pos,
+                        close: pos,
+                    })
+                }
+                Some(_) => {}
+            },
+            _ => {}
+        }
+    }
+    match stack.pop() {
+        Some((_, pos)) => Err(ExprErr::UnclosedBracket { pos }),
+        None => Ok(()),
+    }
+}
+
+/// Convert infix tokens into postfix (reverse polish) notation with the
+/// shunting-yard algorithm: operands go straight to the output, while
+/// operators wait on the stack until an operator with a lower precedence, or a
+/// closing bracket, comes. Brackets are checked along the way, as well as the
+/// number of arguments given to functions.
+pub fn to_postfix<S>(tokens: &[Spanned], mut stack: S) -> Result<Vec<Spanned>, ExprErr>
+where
+    S: Stack<Item = Pending>,
+{
+    let mut output = Vec::with_capacity(tokens.len());
+
+    for (i, spanned) in tokens.iter().enumerate() {
+        let pos = spanned.pos;
+        match &spanned.token {
+            Token::Number(_) => output.push(spanned.clone()),
+            Token::Func(_) => {
+                if !matches!(
+                    tokens.get(i + 1).map(|next| &next.token),
+                    Some(Token::Open(_))
+                ) {
+                    return Err(ExprErr::MissingCallParen { pos });
+                }
+                stack.push(Pending {
+                    tok: spanned.clone(),
+                    commas: 0,
+                })?;
+            }
+            Token::Op(op) => {
+                // A unary operator has nothing on its left to take, it never
+                // makes the other operators leave the stack.
+                if !op.is_unary() {
+                    while let Some(Pending {
+                        tok:
+                            Spanned {
+                                token: Token::Op(top),
+                                ..
+                            },
+                        ..
+                    }) = stack.peek_next()
+                    {
+                        let goes_first = top.precedence() > op.precedence()
+                            || (top.precedence() == op.precedence() && op.assoc() == Assoc::Left);
+                        if !goes_first {
+                            break;
+                        }
+                        output.push(stack.pop().expect("peeked").tok);
+                    }
+                }
+                stack.push(Pending {
+                    tok: spanned.clone(),
+                    commas: 0,
+                })?;
+            }
+            Token::Open(_) => stack.push(Pending {
+                tok: spanned.clone(),
+                commas: 0,
+            })?,
+            Token::Comma => {
+                pop_until_open(&mut stack, &mut output);
+                // The comma must be inside the brackets of a function call,
+                // the function being right under its opening bracket.
+                let in_call = matches!(
+                    stack.peek(1),
+                    Some(Pending {
+                        tok: Spanned {
+                            token: Token::Func(_),
+                            ..
+                        },
+                        ..
+                    })
+                );
+                match stack.peek_next_mut() {
+                    Some(open) if in_call => open.commas += 1,
+                    _ => return Err(ExprErr::UnexpectedComma { pos }),
+                }
+            }
+            Token::Close(close) => {
+                pop_until_open(&mut stack, &mut output);
+                let Some(open) = stack.pop() else {
+                    return Err(ExprErr::UnexpectedClose { pos });
+                };
+                match open.tok.token {
+                    Token::Open(bracket) if closing_of(bracket) == *close => {}
+                    _ => {
+                        return Err(ExprErr::MismatchedBracket {
+                            open: open.tok.pos,
+                            close: pos,
+                        })
+                    }
+                }
+                if let Some(Pending {
+                    tok:
+                        Spanned {
+                            token: Token::Func(func),
+                            pos: func_pos,
+                        },
+                    ..
+                }) = stack.peek_next()
+                {
+                    let empty_call = matches!(tokens[i - 1].token, Token::Open(_));
+                    let found = if empty_call { 0 } else { open.commas + 1 };
+                    if found != func.arity() {
+                        return Err(ExprErr::WrongArity {
+                            pos: *func_pos,
+                            expected: func.arity(),
+                            found,
+                        });
+                    }
+                    output.push(stack.pop().expect("peeked").tok);
+                }
+            }
+        }
+    }
+
+    while let Some(pending) = stack.pop() {
+        if let Token::Open(_) = pending.tok.token {
+            return Err(ExprErr::UnclosedBracket {
+                pos: pending.tok.pos,
+            });
+        }
+        output.push(pending.tok);
+    }
+    Ok(output)
+}
+
+// Move the operators to the output, up to the innermost opening bracket which
+// stays on the stack.
+fn pop_until_open<S: Stack<Item = Pending>>(stack: &mut S, output: &mut Vec<Spanned>) {
+    while let Some(pending) = stack.peek_next() {
+        if let Token::Open(_) = pending.tok.token {
+            break;
+        }
+        output.push(stack.pop().expect("peeked").tok);
+    }
+}
+
+// Number of operands taken by a postfix token, None for tokens which can't be
+// in a postfix expression.
+fn operand_count(token: &Token) -> Option<usize> {
+    match token {
+        Token::Number(_) => Some(0),
+        Token::Op(op) if op.is_unary() => Some(1),
+        Token::Op(_) => Some(2),
+        Token::Func(func) => Some(func.arity()),
+        Token::Open(_) | Token::Close(_) | Token::Comma => None,
+    }
+}
+
+/// Convert a postfix expression into prefix (polish) notation. The stack holds
+/// the prefix form of every subexpression built so far, an operator takes the
+/// subexpressions of its operands and puts itself in front of them.
+pub fn to_prefix<S>(postfix: &[Spanned], mut stack: S) -> Result<Vec<Spanned>, ExprErr>
+where
+    S: Stack<Item = Vec<Spanned>>,
+{
+    for spanned in postfix {
+        let Some(count) = operand_count(&spanned.token) else {
+            return Err(ExprErr::UnknownToken {
+                pos: spanned.pos,
+                token: spanned.token.to_string(),
+            });
+        };
+        let mut operands = Vec::with_capacity(count);
+        for _ in 0..count {
+            let operand = stack
+                .pop()
+                .ok_or(ExprErr::MissingOperand { pos: spanned.pos })?;
+            operands.push(operand);
+        }
+        let mut subexpr = vec![spanned.clone()];
+        operands
+            .into_iter()
+            .rev()
+            .for_each(|operand| subexpr.extend(operand));
+        stack.push(subexpr)?;
+    }
+
+    let prefix = stack.pop().ok_or(ExprErr::EmptyExpression)?;
+    if !stack.is_empty() {
+        return Err(ExprErr::MissingOperator);
+    }
+    Ok(prefix)
+}
+
+/// Evaluate a postfix expression: operands are pushed on the stack, and every
+/// operator pops its operands and pushes its result back, the last value on
+/// the stack being the result of the expression.
+pub fn eval_postfix<T, S>(postfix: &[Spanned], mut stack: S) -> Result<T, ExprErr>
+where
+    T: Operand,
+    S: Stack<Item = T>,
+{
+    for spanned in postfix {
+        let pos = spanned.pos;
+        let Some(count) = operand_count(&spanned.token) else {
+            return Err(ExprErr::UnknownToken {
+                pos,
+                token: spanned.token.to_string(),
+            });
+        };
+        let mut args = Vec::with_capacity(count);
+        for _ in 0..count {
+            args.push(stack.pop().ok_or(ExprErr::MissingOperand { pos })?);
+        }
+        args.reverse();
+
+        let res = match (&spanned.token, args.as_slice()) {
+            (Token::Number(literal), []) => {
+                T::parse(literal).ok_or(ExprErr::InvalidNumber { pos })?
+            }
+            (Token::Op(Operator::Neg), [val]) => val.negate().map_err(|err| at(err, pos))?,
+            (Token::Op(op), [lhs, rhs]) => T::apply(*op, *lhs, *rhs).map_err(|err| at(err, pos))?,
+            (Token::Func(func), args) => T::call(*func, args).map_err(|err| at(err, pos))?,
+            _ => unreachable!("operand count matches the token"),
+        };
+        stack.push(res)?;
+    }
+
+    let res = stack.pop().ok_or(ExprErr::EmptyExpression)?;
+    if !stack.is_empty() {
+        return Err(ExprErr::MissingOperator);
+    }
+    Ok(res)
+}
+
+fn at(err: ArithErr, pos: usize) -> ExprErr {
+    match err {
+        ArithErr::DivisionByZero => ExprErr::DivisionByZero { pos },
+        ArithErr::Overflow => ExprErr::Overflow { pos },
+        ArithErr::InvalidOperand => ExprErr::InvalidOperand { pos },
+    }
+}
+
+/// Evaluate an infix expression, going through postfix notation, with
+/// [`ArrayStack`] for every stack.
+pub fn eval<T: Operand>(input: &str) -> Result<T, ExprErr> {
+    let tokens = tokenize(input)?;
+    let postfix = to_postfix(&tokens, ArrayStack::new())?;
+    eval_postfix(&postfix, ArrayStack::new())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::structures::deque::LinkedDeque;
+    use crate::structures::stack::{LinkedStack, UnsafeStack};
+
+    fn postfix(input: &str) -> Result<String, ExprErr> {
+        let tokens = tokenize(input)?;
+        Ok(render(&to_postfix(&tokens, LinkedStack::new())?))
+    }
+
+    fn prefix(input: &str) -> Result<String, ExprErr> {
+        let tokens = tokenize(input)?;
+        let postfix = to_postfix(&tokens, ArrayStack::new())?;
+        Ok(render(&to_prefix(&postfix, ArrayStack::new())?))
+    }
+
+    #[test]
+    fn expression_tokenize() {
+        let tokens = tokenize("12 + max(3.5, -x)");
+        assert_eq!(
+            Err(ExprErr::UnknownToken {
+                pos: 15,
+                token: "x".to_string()
+            }),
+            tokens
+        );
+        let tokens = tokenize("-12*(3-4)").unwrap();
+        assert_eq!(
+            vec![
+                Token::Op(Operator::Neg),
+                Token::Number("12".to_string()),
+                Token::Op(Operator::Mul),
+                Token::Open('('),
+                Token::Number("3".to_string()),
+                Token::Op(Operator::Sub),
+                Token::Number("4".to_string()),
+                Token::Close(')'),
+            ],
+            tokens.into_iter().map(|t| t.token).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            Err(ExprErr::UnknownToken {
+                pos: 2,
+                token: "$".to_string()
+            }),
+            tokenize("1 $ 2")
+        );
+    }
+
+    #[test]
+    fn expression_check_brackets() {
+        assert_eq!(Ok(()), check_brackets("{a[b(c)d]}()", ArrayStack::new()));
+        assert_eq!(
+            Err(ExprErr::MismatchedBracket { open: 1, close: 3 }),
+            check_brackets("{(a]}", LinkedStack::new())
+        );
+        assert_eq!(
+            Err(ExprErr::UnexpectedClose { pos: 2 }),
+            check_brackets("()]", ArrayStack::new())
+        );
+        assert_eq!(
+            Err(ExprErr::UnclosedBracket { pos: 1 }),
+            check_brackets("[(", ArrayStack::new())
+        );
+    }
+
+    #[test]
+    fn expression_check_brackets_bounded_stack() {
+        let stack = ArrayStack::with_capacity(2).unwrap();
+        assert_eq!(
+            Err(ExprErr::Stack(StackErr::StackOverflow)),
+            check_brackets("(((", stack)
+        );
+    }
+
+    #[test]
+    fn expression_to_postfix_precedence_assoc() {
+        assert_eq!(Ok("1 2 3 * +".to_string()), postfix("1 + 2 * 3"));
+        assert_eq!(Ok("1 2 + 3 *".to_string()), postfix("(1 + 2) * 3"));
+        assert_eq!(Ok("8 4 - 2 -".to_string()), postfix("8 - 4 - 2"));
+        assert_eq!(Ok("2 3 2 ^ ^".to_string()), postfix("2 ^ 3 ^ 2"));
+        assert_eq!(Ok("2 2 ^ neg".to_string()), postfix("-2 ^ 2"));
+        assert_eq!(Ok("2 3 neg ^".to_string()), postfix("2 ^ -3"));
+        assert_eq!(Ok("3 neg 2 *".to_string()), postfix("-3 * 2"));
+        assert_eq!(Ok("1 2 neg neg -".to_string()), postfix("1 - --2"));
+    }
+
+    #[test]
+    fn expression_to_postfix_functions() {
+        assert_eq!(
+            Ok("1 2 3 + max abs".to_string()),
+            postfix("abs(max(1, 2 + 3))")
+        );
+        assert_eq!(
+            Err(ExprErr::WrongArity {
+                pos: 0,
+                expected: 2,
+                found: 3
+            }),
+            postfix("min(1, 2, 3)")
+        );
+        assert_eq!(
+            Err(ExprErr::WrongArity {
+                pos: 0,
+                expected: 1,
+                found: 0
+            }),
+            postfix("abs()")
+        );
+        assert_eq!(Err(ExprErr::MissingCallParen { pos: 0 }), postfix("abs 1"));
+        assert_eq!(Err(ExprErr::UnexpectedComma { pos: 2 }), postfix("(1, 2)"));
+        assert_eq!(Err(ExprErr::UnexpectedComma { pos: 1 }), postfix("1, 2"));
+    }
+
+    #[test]
+    fn expression_to_postfix_brackets() {
+        assert_eq!(Ok("1 2 + 3 *".to_string()), postfix("[1 + 2] * {3}"));
+        assert_eq!(
+            Err(ExprErr::MismatchedBracket { open: 0, close: 6 }),
+            postfix("(1 + 2]")
+        );
+        assert_eq!(Err(ExprErr::UnexpectedClose { pos: 5 }), postfix("1 + 2)"));
+        assert_eq!(Err(ExprErr::UnclosedBracket { pos: 4 }), postfix("1 + (2"));
+    }
+
+    #[test]
+    fn expression_to_prefix() {
+        assert_eq!(Ok("+ 1 * 2 3".to_string()), prefix("1 + 2 * 3"));
+        assert_eq!(Ok("- - 8 4 2".to_string()), prefix("8 - 4 - 2"));
+        assert_eq!(Ok("^ 2 ^ 3 2".to_string()), prefix("2 ^ 3 ^ 2"));
+        assert_eq!(Ok("max neg 1 abs 2".to_string()), prefix("max(-1, abs(2))"));
+        assert_eq!(Err(ExprErr::MissingOperand { pos: 2 }), prefix("1 +"));
+        assert_eq!(Err(ExprErr::EmptyExpression), prefix(""));
+    }
+
+    #[test]
+    fn expression_eval_i64() {
+        assert_eq!(Ok(7), eval::<i64>("1 + 2 * 3"));
+        assert_eq!(Ok(9), eval::<i64>("(1 + 2) * 3"));
+        assert_eq!(Ok(512), eval::<i64>("2 ^ 3 ^ 2"));
+        assert_eq!(Ok(-4), eval::<i64>("-2 ^ 2"));
+        assert_eq!(Ok(2), eval::<i64>("8 - 4 - 2"));
+        assert_eq!(Ok(1), eval::<i64>("7 % 3"));
+        assert_eq!(Ok(5), eval::<i64>("max(-1, abs(-5)) * min(1, 2)"));
+        assert_eq!(Ok(-6), eval::<i64>("-(1 + 2) * 2"));
+    }
+
+    #[test]
+    fn expression_eval_i64_negative_power() {
+        assert_eq!(Ok(1), eval::<i64>("1 ^ -5"));
+        assert_eq!(Ok(1), eval::<i64>("(-1) ^ -4"));
+        assert_eq!(Ok(-1), eval::<i64>("(-1) ^ -3"));
+        assert_eq!(Ok(-1), eval::<i64>("(-1) ^ 9999999999"));
+        assert_eq!(
+            Err(ExprErr::InvalidOperand { pos: 2 }),
+            eval::<i64>("2 ^ -1")
+        );
+        assert_eq!(
+            Err(ExprErr::DivisionByZero { pos: 2 }),
+            eval::<i64>("0 ^ -1")
+        );
+        assert_eq!(Err(ExprErr::Overflow { pos: 2 }), eval::<i64>("2 ^ 64"));
+    }
+
+    #[test]
+    fn expression_eval_f64() {
+        assert_eq!(Ok(3.5), eval::<f64>("7 / 2"));
+        assert_eq!(Ok(0.25), eval::<f64>("2 ^ -2"));
+        assert_eq!(Ok(1.5), eval::<f64>("abs(-1.5)"));
+        assert_eq!(
+            Err(ExprErr::InvalidOperand { pos: 5 }),
+            eval::<f64>("(-8) ^ 0.5")
+        );
+    }
+
+    #[test]
+    fn expression_eval_errors() {
+        assert_eq!(
+            Err(ExprErr::DivisionByZero { pos: 2 }),
+            eval::<i64>("1 / (2 - 2)")
+        );
+        assert_eq!(
+            Err(ExprErr::DivisionByZero { pos: 4 }),
+            eval::<f64>("1.0 / 0")
+        );
+        assert_eq!(
+            Err(ExprErr::Overflow { pos: 20 }),
+            eval::<i64>("9223372036854775807 + 1")
+        );
+        assert_eq!(
+            Err(ExprErr::InvalidOperand { pos: 2 }),
+            eval::<i64>("2 ^ -1")
+        );
+        assert_eq!(Err(ExprErr::InvalidNumber { pos: 0 }), eval::<i64>("1.5"));
+        assert_eq!(Err(ExprErr::InvalidNumber { pos: 0 }), eval::<f64>("1.2.3"));
+        assert_eq!(Err(ExprErr::MissingOperand { pos: 2 }), eval::<i64>("1 *"));
+        assert_eq!(Err(ExprErr::MissingOperator), eval::<i64>("1 2"));
+        assert_eq!(Err(ExprErr::EmptyExpression), eval::<i64>("()"));
+    }
+
+    #[test]
+    fn expression_generic_over_stacks() {
+        let tokens = tokenize("(4 + 2) * -3").unwrap();
+        let postfix = to_postfix(&tokens, LinkedDeque::new()).unwrap();
+        assert_eq!(
+            Ok(-18),
+            eval_postfix::<i64, _>(&postfix, UnsafeStack::with_capacity(2).unwrap())
+        );
+        // the operands never pile up higher than 2 here, but 1 isn't enough
+        assert_eq!(
+            Err(ExprErr::Stack(StackErr::StackOverflow)),
+            eval_postfix::<i64, _>(&postfix, UnsafeStack::with_capacity(1).unwrap())
+        );
+    }
+}
@@ -1,5 +1,8 @@
 pub mod binary_search;
 pub mod bubble_sort;
+pub mod expression;
 pub mod linear_search;
+pub mod monotonic_stack;
 pub mod recursion;
+pub mod sliding_window;
 pub mod two_crystal_balls;
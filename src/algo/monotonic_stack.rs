@@ -0,0 +1,209 @@
+#![allow(dead_code)]
+//! Monotonic stack algorithms. A monotonic stack holds indices whose values
+//! are kept sorted from the bottom to the top: before pushing an index, every
+//! index breaking the order is popped, and the index being pushed is the
+//! answer for them (their next greater or smaller element). Each index is
+//! pushed and popped once, so all of these are O(n) in time and space.
+//!
+//! They take slices, an [`Array`](crate::structures::array::Array)
+//! can be given as well as it derefs into a slice.
+
+use crate::structures::stack::{ArrayStack, Stack};
+
+// For every index, the first index on its right whose value makes `pops`
+// true, `pops` being called with the value waiting on the stack first.
+fn next_matching<T>(values: &[T], pops: impl Fn(&T, &T) -> bool) -> Vec<Option<usize>> {
+    let mut res = vec![None; values.len()];
+    let mut stack = ArrayStack::new();
+    for (idx, val) in values.iter().enumerate() {
+        while let Some(&top) = stack.peek_next() {
+            if !pops(&values[top], val) {
+                break;
+            }
+            res[top] = Some(idx);
+            stack.pop();
+        }
+        stack.push(idx).expect("unbounded stack");
+    }
+    res
+}
+
+// For every index, the closest index on its left whose value doesn't make
+// `pops` true, `pops` being called with the value waiting on the stack first.
+fn previous_matching<T>(values: &[T], pops: impl Fn(&T, &T) -> bool) -> Vec<Option<usize>> {
+    let mut res = vec![None; values.len()];
+    let mut stack = ArrayStack::new();
+    for (idx, val) in values.iter().enumerate() {
+        while let Some(&top) = stack.peek_next() {
+            if !pops(&values[top], val) {
+                break;
+            }
+            stack.pop();
+        }
+        res[idx] = stack.peek_next().copied();
+        stack.push(idx).expect("unbounded stack");
+    }
+    res
+}
+
+/// Index of the first strictly greater value on the right of every value.
+///
+/// # Examples
+///
+/// The crate is a binary, so the example isn't run as a doctest, the unit
+/// tests of this module check it instead.
+///
+/// ```ignore
+/// use crate::algo::monotonic_stack::next_greater;
+///
+/// assert_eq!(vec![Some(1), None, Some(3), None], next_greater(&[2, 5, 1, 3]));
+/// ```
+pub fn next_greater<T: Ord>(values: &[T]) -> Vec<Option<usize>> {
+    next_matching(values, |top, val| top < val)
+}
+
+/// Index of the first strictly smaller value on the right of every value.
+pub fn next_smaller<T: Ord>(values: &[T]) -> Vec<Option<usize>> {
+    next_matching(values, |top, val| top > val)
+}
+
+/// Index of the closest strictly smaller value on the left of every value.
+pub fn previous_smaller<T: Ord>(values: &[T]) -> Vec<Option<usize>> {
+    previous_matching(values, |top, val| top >= val)
+}
+
+/// Area of the largest rectangle fitting under a histogram whose bars all
+/// have a width of 1. The largest rectangle as high as a bar spreads from
+/// the previous smaller bar to the next smaller one, both excluded.
+pub fn largest_rectangle(heights: &[usize]) -> usize {
+    let previous = previous_smaller(heights);
+    let next = next_smaller(heights);
+    heights
+        .iter()
+        .enumerate()
+        .map(|(idx, height)| {
+            let left = previous[idx].map_or(0, |prev| prev + 1);
+            let right = next[idx].unwrap_or(heights.len());
+            height * (right - left)
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+/// Span of every price: the number of consecutive days, ending with the day
+/// of the price, whose price is lower or equal to it.
+pub fn stock_span<T: Ord>(prices: &[T]) -> Vec<usize> {
+    previous_matching(prices, |top, price| top <= price)
+        .into_iter()
+        .enumerate()
+        .map(|(day, previous_greater)| day - previous_greater.map_or(0, |prev| prev + 1) + 1)
+        .collect()
+}
+
+/// Number of days to wait for a warmer temperature, 0 when it never comes.
+pub fn daily_temperatures<T: Ord>(temperatures: &[T]) -> Vec<usize> {
+    next_greater(temperatures)
+        .into_iter()
+        .enumerate()
+        .map(|(day, warmer)| warmer.map_or(0, |warmer| warmer - day))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::structures::array::Array;
+    use crate::test_utils::XorShift;
+
+    fn random_values(rng: &mut XorShift, len: usize) -> Vec<usize> {
+        (0..len).map(|_| rng.below(10) as usize).collect()
+    }
+
+    #[test]
+    fn monotonic_next_greater_smaller() {
+        // the example of the doc
+        assert_eq!(
+            vec![Some(1), None, Some(3), None],
+            next_greater(&[2, 5, 1, 3])
+        );
+        let values = [2, 5, 1, 3, 3, 0];
+        assert_eq!(
+            vec![Some(1), None, Some(3), None, None, None],
+            next_greater(&values)
+        );
+        assert_eq!(
+            vec![Some(2), Some(2), Some(5), Some(5), Some(5), None],
+            next_smaller(&values)
+        );
+        assert_eq!(
+            vec![None, Some(0), None, Some(2), Some(2), None],
+            previous_smaller(&values)
+        );
+    }
+
+    #[test]
+    fn monotonic_on_array() {
+        let mut arr = Array::new();
+        [73, 74, 75, 71, 69, 72, 76, 73]
+            .into_iter()
+            .for_each(|v| arr.push(v));
+        assert_eq!(vec![1, 1, 4, 2, 1, 1, 0, 0], daily_temperatures(&arr));
+        assert_eq!(Some(1), next_greater(&arr)[0]);
+    }
+
+    #[test]
+    fn monotonic_largest_rectangle() {
+        assert_eq!(10, largest_rectangle(&[2, 1, 5, 6, 2, 3]));
+        assert_eq!(4, largest_rectangle(&[2, 4]));
+        assert_eq!(9, largest_rectangle(&[3, 3, 3]));
+        assert_eq!(0, largest_rectangle(&[]));
+    }
+
+    #[test]
+    fn monotonic_stock_span() {
+        assert_eq!(
+            vec![1, 1, 1, 2, 1, 4, 6],
+            stock_span(&[100, 80, 60, 70, 60, 75, 85])
+        );
+        assert_eq!(vec![1, 2, 3], stock_span(&[5, 5, 5]));
+    }
+
+    #[test]
+    fn monotonic_against_brute_force() {
+        let mut rng = XorShift::new(3);
+        for _ in 0..200 {
+            let len = rng.below(30) as usize;
+            let values = random_values(&mut rng, len);
+            let after = |idx: usize, matches: &dyn Fn(usize) -> bool| {
+                (idx + 1..len).find(|&other| matches(values[other]))
+            };
+            let before = |idx: usize, matches: &dyn Fn(usize) -> bool| {
+                (0..idx).rev().find(|&other| matches(values[other]))
+            };
+
+            let expected = (0..len)
+                .map(|i| after(i, &|v| v > values[i]))
+                .collect::<Vec<_>>();
+            assert_eq!(expected, next_greater(&values));
+            let expected = (0..len)
+                .map(|i| after(i, &|v| v < values[i]))
+                .collect::<Vec<_>>();
+            assert_eq!(expected, next_smaller(&values));
+            let expected = (0..len)
+                .map(|i| before(i, &|v| v < values[i]))
+                .collect::<Vec<_>>();
+            assert_eq!(expected, previous_smaller(&values));
+            let expected = (0..len)
+                .map(|i| i - before(i, &|v| v > values[i]).map_or(0, |prev| prev + 1) + 1)
+                .collect::<Vec<_>>();
+            assert_eq!(expected, stock_span(&values));
+
+            let expected = (0..len)
+                .flat_map(|start| (start..len).map(move |end| (start, end)))
+                .map(|(start, end)| values[start..=end].iter().min().unwrap() * (end - start + 1))
+                .max()
+                .unwrap_or(0);
+            assert_eq!(expected, largest_rectangle(&values));
+        }
+    }
+}
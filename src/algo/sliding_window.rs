@@ -0,0 +1,478 @@
+#![allow(dead_code)]
+//! Sliding window algorithms over streams: everything takes an iterator and
+//! keeps only what the window needs, so it works on input which doesn't fit
+//! in memory, or which is still coming.
+//!
+//! The window extremes come from a [`MonotonicQueue`], the rolling median
+//! from two heaps.
+
+use std::{cmp::Ordering, collections::HashMap, hash::Hash};
+
+use crate::structures::deque::{ArrayDeque, Deque};
+use crate::structures::queue::{ArrayQueue, BinaryHeap, Queue};
+
+/// Queue keeping only the items which can still become the front: when an
+/// item is pushed, every item before it with a lower priority is dropped, as
+/// it will leave the window before the new one and can't be the extreme
+/// anymore. The items left are then sorted by priority from the front, which
+/// is the extreme of every item pushed since the last expiry.
+///
+/// The comparator works as for the [`BinaryHeap`]: when `cmp(a, b)` is
+/// `Ordering::Less`, `a` has the priority. Every item is pushed and dropped
+/// once, so a push is O(1) amortized.
+///
+/// Each push gets a sequence number, counting from 0, by which items expire.
+pub struct MonotonicQueue<T> {
+    // Items with their sequence number, increasing from the front.
+    items: ArrayDeque<(usize, T)>,
+    pushed: usize,
+    cmp: fn(&T, &T) -> Ordering,
+}
+
+impl<T: Ord> MonotonicQueue<T> {
+    /// Queue whose front is the smallest item.
+    pub fn new_min() -> Self {
+        Self::with_comparator(T::cmp)
+    }
+
+    /// Queue whose front is the greatest item.
+    pub fn new_max() -> Self {
+        Self::with_comparator(|a, b| b.cmp(a))
+    }
+}
+
+impl<T> MonotonicQueue<T> {
+    pub fn with_comparator(cmp: fn(&T, &T) -> Ordering) -> Self {
+        Self {
+            items: ArrayDeque::new(),
+            pushed: 0,
+            cmp,
+        }
+    }
+
+    /// Push an item and give back its sequence number.
+    pub fn push(&mut self, val: T) -> usize {
+        // Items equal to the new one are kept, they expire first and have the
+        // same value anyway.
+        while self
+            .items
+            .back()
+            .is_some_and(|(_, back)| (self.cmp)(&val, back) == Ordering::Less)
+        {
+            self.items.pop_back();
+        }
+        let seq = self.pushed;
+        self.items.push_back((seq, val)).expect("unbounded deque");
+        self.pushed += 1;
+        seq
+    }
+
+    /// Drop the items pushed before the sequence number `seq`.
+    pub fn expire(&mut self, seq: usize) {
+        while self.items.front().is_some_and(|(front, _)| *front < seq) {
+            self.items.pop_front();
+        }
+    }
+
+    /// Extreme of the items not expired yet, with its sequence number.
+    pub fn front(&self) -> Option<(usize, &T)> {
+        self.items.front().map(|(seq, val)| (*seq, val))
+    }
+
+    pub fn pop_front(&mut self) -> Option<(usize, T)> {
+        self.items.pop_front()
+    }
+
+    /// Number of items kept, not the number of items in the window.
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        Deque::is_empty(&self.items)
+    }
+}
+
+// Extreme of every window, according to the queue's comparator.
+fn sliding_extreme<I>(
+    values: I,
+    window: usize,
+    mut queue: MonotonicQueue<I::Item>,
+) -> impl Iterator<Item = I::Item>
+where
+    I: IntoIterator,
+    I::Item: Clone,
+{
+    assert!(window > 0, "the window must not be empty");
+    values.into_iter().filter_map(move |val| {
+        let seq = queue.push(val);
+        if seq + 1 < window {
+            return None;
+        }
+        queue.expire(seq + 1 - window);
+        queue.front().map(|(_, extreme)| extreme.clone())
+    })
+}
+
+/// Greatest value of every window of `window` consecutive values, the first
+/// one being given once the first window is full. O(n) in time, and
+/// O(window) in space.
+///
+/// # Panics
+///
+/// Panics if the window is empty.
+pub fn sliding_max<I>(values: I, window: usize) -> impl Iterator<Item = I::Item>
+where
+    I: IntoIterator,
+    I::Item: Ord + Clone,
+{
+    sliding_extreme(values, window, MonotonicQueue::new_max())
+}
+
+/// Smallest value of every window, see [`sliding_max`].
+pub fn sliding_min<I>(values: I, window: usize) -> impl Iterator<Item = I::Item>
+where
+    I: IntoIterator,
+    I::Item: Ord + Clone,
+{
+    sliding_extreme(values, window, MonotonicQueue::new_min())
+}
+
+/// Length of the shortest run of consecutive values whose sum is at least
+/// `k`, values may be negative. O(n).
+///
+/// The sum of `values[i..j]` is `prefix[j] - prefix[i]`. The queue keeps the
+/// prefixes smallest first: once the current prefix is at least `k` over the
+/// front, the front can't start a shorter run with a later prefix, so it is
+/// dropped. And a prefix pushed drops the greater ones before it, as starting
+/// later from a smaller prefix is always better.
+pub fn shortest_subarray_sum_at_least<I>(values: I, k: i64) -> Option<usize>
+where
+    I: IntoIterator<Item = i64>,
+{
+    let mut prefixes = MonotonicQueue::new_min();
+    let mut prefix = 0;
+    prefixes.push(prefix);
+    let mut shortest: Option<usize> = None;
+    for (idx, val) in values.into_iter().enumerate() {
+        prefix += val;
+        while let Some((start, _)) = prefixes.front().filter(|(_, front)| prefix - **front >= k) {
+            let len = idx + 1 - start;
+            shortest = Some(shortest.map_or(len, |shortest| shortest.min(len)));
+            prefixes.pop_front();
+        }
+        prefixes.push(prefix);
+    }
+    shortest
+}
+
+/// Mean of every window of `window` consecutive values, O(1) per value: the
+/// value leaving the window is taken out of the running sum.
+///
+/// # Panics
+///
+/// Panics if the window is empty.
+pub fn rolling_mean<I>(values: I, window: usize) -> impl Iterator<Item = f64>
+where
+    I: IntoIterator,
+    I::Item: Into<f64>,
+{
+    let mut kept = ArrayQueue::with_capacity(window).expect("the window must not be empty");
+    let mut sum = 0.0;
+    values.into_iter().filter_map(move |val| {
+        let val = val.into();
+        if kept.is_full() {
+            sum -= kept.dequeue().expect("the window is full");
+        }
+        kept.enqueue(val).expect("room has been made");
+        sum += val;
+        kept.is_full().then(|| sum / window as f64)
+    })
+}
+
+/// Median of a window sliding over a stream, kept in two heaps: a max heap
+/// with the lower half of the window, and a min heap with the upper half,
+/// the lower one holding the extra value when the window is odd. The medians
+/// are then at the top of the heaps.
+///
+/// A value leaving the window can't be removed from the middle of a heap, so
+/// it is only counted as deleted, and dropped once it reaches the top.
+/// Push is O(log window) amortized.
+pub struct RollingMedian<T> {
+    window: ArrayQueue<T>,
+    low: BinaryHeap<T>,
+    high: BinaryHeap<T>,
+    // Number of values in the heaps which are still in the window.
+    low_len: usize,
+    high_len: usize,
+    deleted: HashMap<T, usize>,
+}
+
+impl<T: Ord + Hash + Clone> RollingMedian<T> {
+    /// # Panics
+    ///
+    /// Panics if the window is empty.
+    pub fn new(window: usize) -> Self {
+        Self {
+            window: ArrayQueue::with_capacity(window).expect("the window must not be empty"),
+            low: BinaryHeap::new_max(),
+            high: BinaryHeap::new_min(),
+            low_len: 0,
+            high_len: 0,
+            deleted: HashMap::new(),
+        }
+    }
+
+    /// Push a value, the oldest one leaving the window when it is full.
+    pub fn push(&mut self, val: T) {
+        if self.window.is_full() {
+            let oldest = self.window.dequeue().expect("the window is full");
+            self.delete(oldest);
+        }
+        self.window
+            .enqueue(val.clone())
+            .expect("room has been made");
+        if self.low.peek().is_none_or(|top| val <= *top) {
+            self.low.push(val);
+            self.low_len += 1;
+        } else {
+            self.high.push(val);
+            self.high_len += 1;
+        }
+        self.rebalance();
+    }
+
+    /// Lower and upper medians of the values in the window, the same value
+    /// when the window holds an odd number of values.
+    pub fn median(&self) -> Option<(&T, &T)> {
+        let low = self.low.peek()?;
+        if self.low_len > self.high_len {
+            Some((low, low))
+        } else {
+            Some((low, self.high.peek()?))
+        }
+    }
+
+    /// Whether the window holds as many values as it can.
+    pub fn is_full(&self) -> bool {
+        self.window.is_full()
+    }
+
+    fn delete(&mut self, val: T) {
+        let in_low = self.low.peek().is_some_and(|top| val <= *top);
+        *self.deleted.entry(val).or_insert(0) += 1;
+        if in_low {
+            self.low_len -= 1;
+        } else {
+            self.high_len -= 1;
+        }
+        Self::prune(&mut self.low, &mut self.deleted);
+        Self::prune(&mut self.high, &mut self.deleted);
+        self.rebalance();
+    }
+
+    // Keep the lower half as large as the upper one, or one value larger.
+    fn rebalance(&mut self) {
+        if self.low_len > self.high_len + 1 {
+            let top = self.low.pop().expect("the lower half is not empty");
+            self.high.push(top);
+            self.low_len -= 1;
+            self.high_len += 1;
+            Self::prune(&mut self.low, &mut self.deleted);
+        } else if self.low_len < self.high_len {
+            let top = self.high.pop().expect("the upper half is not empty");
+            self.low.push(top);
+            self.high_len -= 1;
+            self.low_len += 1;
+            Self::prune(&mut self.high, &mut self.deleted);
+        }
+    }
+
+    // Drop the deleted values from the top of the heap, so the top is always
+    // in the window.
+    fn prune(heap: &mut BinaryHeap<T>, deleted: &mut HashMap<T, usize>) {
+        while let Some(count) = heap.peek().and_then(|top| deleted.get_mut(top)) {
+            *count -= 1;
+            if *count == 0 {
+                let top = heap.pop().expect("the heap has a top");
+                deleted.remove(&top);
+            } else {
+                heap.pop();
+            }
+        }
+    }
+}
+
+/// Median of every window of `window` consecutive values, the mean of the two
+/// middle values when the window is even.
+///
+/// # Panics
+///
+/// Panics if the window is empty.
+pub fn rolling_median<I>(values: I, window: usize) -> impl Iterator<Item = f64>
+where
+    I: IntoIterator,
+    I::Item: Ord + Hash + Copy + Into<f64>,
+{
+    let mut median = RollingMedian::new(window);
+    values.into_iter().filter_map(move |val| {
+        median.push(val);
+        if !median.is_full() {
+            return None;
+        }
+        median
+            .median()
+            .map(|(low, high)| ((*low).into() + (*high).into()) / 2.0)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_utils::XorShift;
+
+    fn random_values(rng: &mut XorShift, len: usize) -> Vec<i32> {
+        (0..len).map(|_| rng.below(20) as i32 - 10).collect()
+    }
+
+    #[test]
+    fn monotonic_queue_push_expire() {
+        let mut queue = MonotonicQueue::new_max();
+        assert_eq!(None, queue.front());
+        [3, 1, 2, 2].into_iter().for_each(|v| {
+            queue.push(v);
+        });
+        // 1 can never be the max of a window ending after 2
+        assert_eq!(3, queue.len());
+        assert_eq!(Some((0, &3)), queue.front());
+        queue.expire(1);
+        assert_eq!(Some((2, &2)), queue.front());
+        queue.expire(3);
+        assert_eq!(Some((3, &2)), queue.front());
+        assert_eq!(Some((3, 2)), queue.pop_front());
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn sliding_window_extremes() {
+        let values = [1, 3, -1, -3, 5, 3, 6, 7];
+        assert_eq!(
+            vec![3, 3, 5, 5, 6, 7],
+            sliding_max(values, 3).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![-1, -3, -3, -3, 3, 3],
+            sliding_min(values, 3).collect::<Vec<_>>()
+        );
+        // a window larger than the input is never full
+        assert_eq!(None, sliding_max(values, 9).next());
+    }
+
+    #[test]
+    fn sliding_window_streaming_input() {
+        let mut rng = XorShift::new(11);
+        let stream = std::iter::from_fn(move || Some(rng.below(1_000)));
+        // only the window is kept, the stream never ends
+        let maxima = sliding_max(stream, 16).take(10_000).count();
+        assert_eq!(10_000, maxima);
+    }
+
+    #[test]
+    fn sliding_window_shortest_subarray() {
+        assert_eq!(Some(1), shortest_subarray_sum_at_least([1], 1));
+        assert_eq!(None, shortest_subarray_sum_at_least([1, 2], 4));
+        assert_eq!(Some(3), shortest_subarray_sum_at_least([2, -1, 2], 3));
+        assert_eq!(
+            Some(3),
+            shortest_subarray_sum_at_least([84, -37, 32, 40, 95], 167)
+        );
+        assert_eq!(None, shortest_subarray_sum_at_least([], 1));
+    }
+
+    #[test]
+    fn sliding_window_rolling_mean() {
+        let means = rolling_mean([1, 2, 3, 4, 5], 2).collect::<Vec<_>>();
+        assert_eq!(vec![1.5, 2.5, 3.5, 4.5], means);
+        let means = rolling_mean([1.0, 2.0], 3).collect::<Vec<_>>();
+        assert!(means.is_empty());
+    }
+
+    #[test]
+    fn sliding_window_rolling_median() {
+        let medians = rolling_median([1, 3, -1, -3, 5, 3, 6, 7], 3).collect::<Vec<_>>();
+        assert_eq!(vec![1.0, -1.0, -1.0, 3.0, 5.0, 6.0], medians);
+        let medians = rolling_median([1, 4, 2, 3], 2).collect::<Vec<_>>();
+        assert_eq!(vec![2.5, 3.0, 2.5], medians);
+
+        let mut median = RollingMedian::new(4);
+        assert_eq!(None, median.median());
+        [5, 5, 5, 1].into_iter().for_each(|v| median.push(v));
+        assert_eq!(Some((&5, &5)), median.median());
+        median.push(1);
+        assert_eq!(Some((&1, &5)), median.median());
+    }
+
+    #[test]
+    fn sliding_window_against_brute_force() {
+        let mut rng = XorShift::new(5);
+        for _ in 0..200 {
+            let len = rng.below(40) as usize;
+            let window = rng.below(6) as usize + 1;
+            let values = random_values(&mut rng, len);
+
+            let expected = values
+                .windows(window)
+                .map(|w| *w.iter().max().unwrap())
+                .collect::<Vec<_>>();
+            assert_eq!(
+                expected,
+                sliding_max(values.iter().copied(), window).collect::<Vec<_>>()
+            );
+            let expected = values
+                .windows(window)
+                .map(|w| *w.iter().min().unwrap())
+                .collect::<Vec<_>>();
+            assert_eq!(
+                expected,
+                sliding_min(values.iter().copied(), window).collect::<Vec<_>>()
+            );
+
+            let expected = values
+                .windows(window)
+                .map(|w| w.iter().sum::<i32>() as f64 / window as f64)
+                .collect::<Vec<_>>();
+            let means = rolling_mean(values.iter().copied(), window).collect::<Vec<_>>();
+            assert_eq!(expected.len(), means.len());
+            assert!(expected
+                .iter()
+                .zip(&means)
+                .all(|(a, b)| (a - b).abs() < 1e-9));
+
+            let expected = values
+                .windows(window)
+                .map(|w| {
+                    let mut sorted = w.to_vec();
+                    sorted.sort();
+                    (sorted[(window - 1) / 2] + sorted[window / 2]) as f64 / 2.0
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(
+                expected,
+                rolling_median(values.iter().copied(), window).collect::<Vec<_>>()
+            );
+
+            let k = rng.below(20) as i64;
+            let expected = (0..len)
+                .flat_map(|start| (start..len).map(move |end| (start, end)))
+                .filter(|&(start, end)| {
+                    values[start..=end].iter().map(|&v| v as i64).sum::<i64>() >= k
+                })
+                .map(|(start, end)| end - start + 1)
+                .min();
+            assert_eq!(
+                expected,
+                shortest_subarray_sum_at_least(values.iter().map(|&v| v as i64), k)
+            );
+        }
+    }
+}
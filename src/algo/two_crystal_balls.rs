@@ -1,5 +1,4 @@
 #![allow(dead_code)]
-use std::usize;
 
 /// Based on an interview of ThePrimeng.
 /// It was given this statement:
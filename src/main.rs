@@ -1,5 +1,7 @@
 mod algo;
 mod structures;
+#[cfg(test)]
+mod test_utils;
 // TODO: make fn which use algos for solving the same problems, and do benchs
 
 fn main() {
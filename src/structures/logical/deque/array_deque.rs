@@ -0,0 +1,354 @@
+use std::ops::{Index, IndexMut};
+
+use super::{Deque, DequeErr};
+use crate::structures::physical::array::Array;
+
+/// Deque stored in an [`Array`] used as a ring buffer, `head` points to the
+/// front item and the back item is `len - 1` slots after it, wrapping around
+/// the end of the array. Pushing and popping at both ends are O(1).
+///
+/// The deque is bounded when built with [`ArrayDeque::with_capacity`], or
+/// grows on demand when built with [`ArrayDeque::new`].
+pub struct ArrayDeque<T> {
+    inner_deque: Array<Option<T>>,
+    head: usize,
+    len: usize,
+    cap: Option<usize>,
+}
+
+impl<T> ArrayDeque<T> {
+    /// Create an unbounded deque, it is never full.
+    pub fn new() -> Self {
+        Self {
+            inner_deque: Array::new(),
+            head: 0,
+            len: 0,
+            cap: None,
+        }
+    }
+
+    pub fn with_capacity(cap: usize) -> Result<Self, DequeErr> {
+        if cap == 0 {
+            return Err(DequeErr::ZeroCapacityNotAllowed);
+        }
+
+        let mut inner_deque = Array::new();
+        (0..cap).for_each(|_| {
+            inner_deque.push(None);
+        });
+
+        Ok(Self {
+            inner_deque,
+            head: 0,
+            len: 0,
+            cap: Some(cap),
+        })
+    }
+
+    /// Maximum number of items the deque can hold, `usize::MAX` when the deque
+    /// is unbounded.
+    pub fn cap(&self) -> usize {
+        self.cap.unwrap_or(usize::MAX)
+    }
+
+    pub fn is_bounded(&self) -> bool {
+        self.cap.is_some()
+    }
+
+    /// Iterate over the items from the front to the back, the iterator can
+    /// also be consumed from the back.
+    pub fn iter(&self) -> ArrayDequeIter<'_, T> {
+        let (front, wrapped) = self.live_ranges();
+        let (wrapped_slots, front_slots) = self.inner_deque.split_at(self.head);
+        ArrayDequeIter {
+            inner: front_slots[..front]
+                .iter()
+                .chain(wrapped_slots[..wrapped].iter()),
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> ArrayDequeIterMut<'_, T> {
+        let (front, wrapped) = self.live_ranges();
+        let (wrapped_slots, front_slots) = self.inner_deque.split_at_mut(self.head);
+        ArrayDequeIterMut {
+            inner: front_slots[..front]
+                .iter_mut()
+                .chain(wrapped_slots[..wrapped].iter_mut()),
+        }
+    }
+
+    // Number of live items stored from head to the end of the array, and of
+    // those which wrapped around to the beginning of the array.
+    fn live_ranges(&self) -> (usize, usize) {
+        let front = self.len.min(self.slots() - self.head);
+        (front, self.len - front)
+    }
+
+    fn slots(&self) -> usize {
+        self.inner_deque.len()
+    }
+
+    fn slot(&self, idx: usize) -> Option<usize> {
+        if idx < self.len {
+            Some((self.head + idx) % self.slots())
+        } else {
+            None
+        }
+    }
+
+    fn check_room(&mut self) -> Result<(), DequeErr> {
+        if self.is_full() {
+            return Err(DequeErr::DequeOverflow);
+        }
+        if self.len == self.slots() {
+            self.grow();
+        }
+        Ok(())
+    }
+
+    // Only called when every slot is used, see ArrayQueue::grow.
+    fn grow(&mut self) {
+        self.inner_deque.rotate_left(self.head);
+        let added_slots = self.slots().max(1);
+        (0..added_slots).for_each(|_| self.inner_deque.push(None));
+        self.head = 0;
+    }
+}
+
+impl<T> Deque for ArrayDeque<T> {
+    type Item = T;
+
+    fn push_front(&mut self, val: Self::Item) -> Result<(), DequeErr> {
+        self.check_room()?;
+        self.head = (self.head + self.slots() - 1) % self.slots();
+        self.inner_deque[self.head] = Some(val);
+        self.len += 1;
+        Ok(())
+    }
+
+    fn push_back(&mut self, val: Self::Item) -> Result<(), DequeErr> {
+        self.check_room()?;
+        let tail = (self.head + self.len) % self.slots();
+        self.inner_deque[tail] = Some(val);
+        self.len += 1;
+        Ok(())
+    }
+
+    fn pop_front(&mut self) -> Option<Self::Item> {
+        let slot = self.slot(0)?;
+        self.head = (self.head + 1) % self.slots();
+        self.len -= 1;
+        self.inner_deque[slot].take()
+    }
+
+    fn pop_back(&mut self) -> Option<Self::Item> {
+        let slot = self.slot(self.len.checked_sub(1)?)?;
+        self.len -= 1;
+        self.inner_deque[slot].take()
+    }
+
+    fn front(&self) -> Option<&Self::Item> {
+        self.get(0)
+    }
+
+    fn front_mut(&mut self) -> Option<&mut Self::Item> {
+        self.get_mut(0)
+    }
+
+    fn back(&self) -> Option<&Self::Item> {
+        self.get(self.len.checked_sub(1)?)
+    }
+
+    fn back_mut(&mut self) -> Option<&mut Self::Item> {
+        self.get_mut(self.len.checked_sub(1)?)
+    }
+
+    fn get(&self, idx: usize) -> Option<&Self::Item> {
+        self.slot(idx)
+            .and_then(|slot| self.inner_deque[slot].as_ref())
+    }
+
+    fn get_mut(&mut self, idx: usize) -> Option<&mut Self::Item> {
+        self.slot(idx)
+            .and_then(|slot| self.inner_deque[slot].as_mut())
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn is_full(&self) -> bool {
+        self.cap.is_some_and(|cap| self.len == cap)
+    }
+}
+
+impl<T> Index<usize> for ArrayDeque<T> {
+    type Output = T;
+
+    fn index(&self, idx: usize) -> &Self::Output {
+        self.get(idx).expect("index out of bounds")
+    }
+}
+
+impl<T> IndexMut<usize> for ArrayDeque<T> {
+    fn index_mut(&mut self, idx: usize) -> &mut Self::Output {
+        self.get_mut(idx).expect("index out of bounds")
+    }
+}
+
+type RingIter<'a, T> = std::iter::Chain<std::slice::Iter<'a, T>, std::slice::Iter<'a, T>>;
+type RingIterMut<'a, T> = std::iter::Chain<std::slice::IterMut<'a, T>, std::slice::IterMut<'a, T>>;
+
+pub struct ArrayDequeIter<'a, T> {
+    inner: RingIter<'a, Option<T>>,
+}
+
+impl<'a, T> Iterator for ArrayDequeIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().and_then(|slot| slot.as_ref())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<T> DoubleEndedIterator for ArrayDequeIter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().and_then(|slot| slot.as_ref())
+    }
+}
+
+pub struct ArrayDequeIterMut<'a, T> {
+    inner: RingIterMut<'a, Option<T>>,
+}
+
+impl<'a, T> Iterator for ArrayDequeIterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().and_then(|slot| slot.as_mut())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<T> DoubleEndedIterator for ArrayDequeIterMut<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().and_then(|slot| slot.as_mut())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn array_deque_new() {
+        let deque = ArrayDeque::<i32>::new();
+        assert!(deque.is_empty());
+        assert!(!deque.is_bounded());
+        assert_eq!(usize::MAX, deque.cap());
+    }
+
+    #[test]
+    fn array_deque_new_zero_cap() {
+        let deque = ArrayDeque::<i32>::with_capacity(0);
+        assert_eq!(Some(DequeErr::ZeroCapacityNotAllowed), deque.err());
+    }
+
+    #[test]
+    fn array_deque_push_pop_both_ends() {
+        let mut deque = ArrayDeque::with_capacity(3).unwrap();
+        deque.push_front(2).unwrap();
+        deque.push_front(1).unwrap();
+        deque.push_back(3).unwrap();
+        assert!(deque.is_full());
+        assert_eq!(Err(DequeErr::DequeOverflow), deque.push_back(4));
+        assert_eq!(Some(&1), deque.front());
+        assert_eq!(Some(&3), deque.back());
+        assert_eq!(Some(3), deque.pop_back());
+        assert_eq!(Some(1), deque.pop_front());
+        assert_eq!(Some(2), deque.pop_back());
+        assert_eq!(None, deque.pop_back());
+        assert_eq!(None, deque.pop_front());
+    }
+
+    #[test]
+    fn array_deque_grow() {
+        let mut deque = ArrayDeque::new();
+        (0..5).for_each(|i| deque.push_front(i).unwrap());
+        (5..10).for_each(|i| deque.push_back(i).unwrap());
+        assert_eq!(10, deque.len());
+        assert_eq!(
+            vec![4, 3, 2, 1, 0, 5, 6, 7, 8, 9],
+            deque.iter().copied().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn array_deque_get_and_index() {
+        let mut deque = ArrayDeque::with_capacity(4).unwrap();
+        deque.push_back(2).unwrap();
+        deque.push_back(3).unwrap();
+        deque.push_front(1).unwrap();
+        assert_eq!(Some(&1), deque.get(0));
+        assert_eq!(Some(&3), deque.get(2));
+        assert_eq!(None, deque.get(3));
+        assert_eq!(2, deque[1]);
+        deque[1] = 7;
+        assert_eq!(Some(&mut 7), deque.get_mut(1));
+    }
+
+    #[test]
+    #[should_panic]
+    fn array_deque_index_out_of_bounds() {
+        let deque = ArrayDeque::<i32>::with_capacity(4).unwrap();
+        let _ = deque[0];
+    }
+
+    #[test]
+    fn array_deque_front_back_mut() {
+        let mut deque = ArrayDeque::new();
+        deque.push_back(1).unwrap();
+        deque.push_back(2).unwrap();
+        deque.front_mut().map(|v| *v = 10).unwrap();
+        deque.back_mut().map(|v| *v = 20).unwrap();
+        assert_eq!(Some(&10), deque.front());
+        assert_eq!(Some(&20), deque.back());
+    }
+
+    #[test]
+    fn array_deque_iter_double_ended() {
+        let mut deque = ArrayDeque::with_capacity(4).unwrap();
+        deque.push_back(3).unwrap();
+        deque.push_back(4).unwrap();
+        deque.push_front(2).unwrap();
+        deque.push_front(1).unwrap();
+        let mut iter = deque.iter();
+        assert_eq!(Some(&1), iter.next());
+        assert_eq!(Some(&4), iter.next_back());
+        assert_eq!(Some(&2), iter.next());
+        assert_eq!(Some(&3), iter.next_back());
+        assert_eq!(None, iter.next());
+        assert_eq!(vec![&4, &3, &2, &1], deque.iter().rev().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn array_deque_iter_mut() {
+        let mut deque = ArrayDeque::with_capacity(3).unwrap();
+        deque.push_back(2).unwrap();
+        deque.push_front(1).unwrap();
+        deque.iter_mut().for_each(|v| *v *= 10);
+        assert_eq!(Some(&mut 20), deque.iter_mut().next_back());
+        assert_eq!(vec![&10, &20], deque.iter().collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,202 @@
+use std::ops::{Index, IndexMut};
+
+use super::{Deque, DequeErr};
+use crate::structures::physical::linked_list::{DoublyLinkedIter, DoublyLinkedList};
+
+/// Deque backed by the arena based [`DoublyLinkedList`], pushing and popping
+/// at both ends are O(1) while accessing an item by index is O(n).
+///
+/// The deque is bounded when built with [`LinkedDeque::with_capacity`], or
+/// unbounded when built with [`LinkedDeque::new`].
+pub struct LinkedDeque<T> {
+    list: DoublyLinkedList<T>,
+    cap: Option<usize>,
+}
+
+impl<T> LinkedDeque<T> {
+    /// Create an unbounded deque, it is never full.
+    pub fn new() -> Self {
+        Self {
+            list: DoublyLinkedList::new(),
+            cap: None,
+        }
+    }
+
+    pub fn with_capacity(cap: usize) -> Result<Self, DequeErr> {
+        if cap == 0 {
+            return Err(DequeErr::ZeroCapacityNotAllowed);
+        }
+
+        Ok(Self {
+            list: DoublyLinkedList::with_capacity(cap),
+            cap: Some(cap),
+        })
+    }
+
+    /// Maximum number of items the deque can hold, `usize::MAX` when the deque
+    /// is unbounded.
+    pub fn cap(&self) -> usize {
+        self.cap.unwrap_or(usize::MAX)
+    }
+
+    pub fn is_bounded(&self) -> bool {
+        self.cap.is_some()
+    }
+
+    /// Iterate over the items from the front to the back, the iterator can
+    /// also be consumed from the back.
+    pub fn iter(&self) -> DoublyLinkedIter<'_, T> {
+        self.list.iter()
+    }
+}
+
+impl<T> Deque for LinkedDeque<T> {
+    type Item = T;
+
+    fn push_front(&mut self, val: Self::Item) -> Result<(), DequeErr> {
+        if self.is_full() {
+            return Err(DequeErr::DequeOverflow);
+        }
+        self.list.push_front(val);
+        Ok(())
+    }
+
+    fn push_back(&mut self, val: Self::Item) -> Result<(), DequeErr> {
+        if self.is_full() {
+            return Err(DequeErr::DequeOverflow);
+        }
+        self.list.push_back(val);
+        Ok(())
+    }
+
+    fn pop_front(&mut self) -> Option<Self::Item> {
+        self.list.pop_front()
+    }
+
+    fn pop_back(&mut self) -> Option<Self::Item> {
+        self.list.pop_back()
+    }
+
+    fn front(&self) -> Option<&Self::Item> {
+        self.list.front()
+    }
+
+    fn front_mut(&mut self) -> Option<&mut Self::Item> {
+        self.list.front_mut()
+    }
+
+    fn back(&self) -> Option<&Self::Item> {
+        self.list.back()
+    }
+
+    fn back_mut(&mut self) -> Option<&mut Self::Item> {
+        self.list.back_mut()
+    }
+
+    fn get(&self, idx: usize) -> Option<&Self::Item> {
+        if idx >= self.len() {
+            return None;
+        }
+        // Walk from the closest end of the list.
+        if idx < self.len() / 2 {
+            self.list.iter().nth(idx)
+        } else {
+            self.list.iter().nth_back(self.len() - 1 - idx)
+        }
+    }
+
+    fn get_mut(&mut self, idx: usize) -> Option<&mut Self::Item> {
+        if idx >= self.len() {
+            return None;
+        }
+        let mut handle = self.list.front_handle()?;
+        for _ in 0..idx {
+            handle = self.list.next_handle(handle).ok()??;
+        }
+        self.list.get_mut(handle).ok()
+    }
+
+    fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    fn is_full(&self) -> bool {
+        self.cap.is_some_and(|cap| self.len() == cap)
+    }
+}
+
+impl<T> Index<usize> for LinkedDeque<T> {
+    type Output = T;
+
+    fn index(&self, idx: usize) -> &Self::Output {
+        self.get(idx).expect("index out of bounds")
+    }
+}
+
+impl<T> IndexMut<usize> for LinkedDeque<T> {
+    fn index_mut(&mut self, idx: usize) -> &mut Self::Output {
+        self.get_mut(idx).expect("index out of bounds")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn linked_deque_new() {
+        let deque = LinkedDeque::<i32>::new();
+        assert!(deque.is_empty());
+        assert!(!deque.is_bounded());
+        assert_eq!(usize::MAX, deque.cap());
+    }
+
+    #[test]
+    fn linked_deque_new_zero_cap() {
+        let deque = LinkedDeque::<i32>::with_capacity(0);
+        assert_eq!(Some(DequeErr::ZeroCapacityNotAllowed), deque.err());
+    }
+
+    #[test]
+    fn linked_deque_push_pop_both_ends() {
+        let mut deque = LinkedDeque::with_capacity(3).unwrap();
+        deque.push_front(2).unwrap();
+        deque.push_front(1).unwrap();
+        deque.push_back(3).unwrap();
+        assert!(deque.is_full());
+        assert_eq!(Err(DequeErr::DequeOverflow), deque.push_front(0));
+        assert_eq!(Some(&1), deque.front());
+        assert_eq!(Some(&3), deque.back());
+        assert_eq!(Some(3), deque.pop_back());
+        assert_eq!(Some(1), deque.pop_front());
+        assert_eq!(Some(2), deque.pop_front());
+        assert_eq!(None, deque.pop_back());
+    }
+
+    #[test]
+    fn linked_deque_get_and_index() {
+        let mut deque = LinkedDeque::new();
+        (1..=5).for_each(|i| deque.push_back(i).unwrap());
+        assert_eq!(Some(&1), deque.get(0));
+        assert_eq!(Some(&4), deque.get(3));
+        assert_eq!(None, deque.get(5));
+        assert_eq!(5, deque[4]);
+        deque[3] = 7;
+        assert_eq!(Some(&mut 7), deque.get_mut(3));
+        assert_eq!(None, deque.get_mut(5));
+    }
+
+    #[test]
+    fn linked_deque_iter_double_ended() {
+        let mut deque = LinkedDeque::new();
+        deque.push_back(2).unwrap();
+        deque.push_front(1).unwrap();
+        deque.push_back(3).unwrap();
+        assert_eq!(vec![&1, &2, &3], deque.iter().collect::<Vec<_>>());
+        assert_eq!(vec![&3, &2, &1], deque.iter().rev().collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,219 @@
+#![allow(dead_code)]
+
+use std::fmt::Display;
+
+pub use array_deque::ArrayDeque;
+pub use linked_deque::LinkedDeque;
+
+use super::queue::{Queue, QueueErr};
+use super::stack::{Stack, StackErr};
+
+mod array_deque;
+mod linked_deque;
+
+/// Double-ended queue abstract data type, items can be pushed and popped at
+/// both ends. Indices used by `get` are relative to the front of the deque.
+///
+/// Any deque can be used as a FIFO [`Queue`] (push at the back, pop at the
+/// front) and as a LIFO [`Stack`] (push and pop at the back), both traits are
+/// implemented for every `Deque`.
+pub trait Deque {
+    type Item;
+    fn push_front(&mut self, val: Self::Item) -> Result<(), DequeErr>;
+    fn push_back(&mut self, val: Self::Item) -> Result<(), DequeErr>;
+    fn pop_front(&mut self) -> Option<Self::Item>;
+    fn pop_back(&mut self) -> Option<Self::Item>;
+    fn front(&self) -> Option<&Self::Item>;
+    fn front_mut(&mut self) -> Option<&mut Self::Item>;
+    fn back(&self) -> Option<&Self::Item>;
+    fn back_mut(&mut self) -> Option<&mut Self::Item>;
+    fn get(&self, idx: usize) -> Option<&Self::Item>;
+    fn get_mut(&mut self, idx: usize) -> Option<&mut Self::Item>;
+    fn len(&self) -> usize;
+    fn is_empty(&self) -> bool;
+    fn is_full(&self) -> bool;
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum DequeErr {
+    ZeroCapacityNotAllowed,
+    DequeOverflow,
+}
+
+impl std::error::Error for DequeErr {}
+
+impl Display for DequeErr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DequeErr::DequeOverflow => write!(
+                f,
+                "Unable to insert a new value in the deque, limit reached"
+            ),
+            DequeErr::ZeroCapacityNotAllowed => {
+                write!(f, "Unable to initialize a deque with no capacity")
+            }
+        }
+    }
+}
+
+impl From<DequeErr> for QueueErr {
+    fn from(err: DequeErr) -> Self {
+        match err {
+            DequeErr::ZeroCapacityNotAllowed => QueueErr::ZeroCapacityNotAllowed,
+            DequeErr::DequeOverflow => QueueErr::QueueOverflow,
+        }
+    }
+}
+
+impl From<DequeErr> for StackErr {
+    fn from(err: DequeErr) -> Self {
+        match err {
+            DequeErr::ZeroCapacityNotAllowed => StackErr::ZeroCapacityNotAllowed,
+            DequeErr::DequeOverflow => StackErr::StackOverflow,
+        }
+    }
+}
+
+// FIFO: items enter at the back and leave from the front, `peek(0)` is the
+// next item to be dequeued.
+impl<D: Deque> Queue for D {
+    type Item = D::Item;
+
+    fn enqueue(&mut self, val: Self::Item) -> Result<(), QueueErr> {
+        Ok(self.push_back(val)?)
+    }
+
+    fn dequeue(&mut self) -> Option<Self::Item> {
+        self.pop_front()
+    }
+
+    fn peek_next(&self) -> Option<&Self::Item> {
+        self.front()
+    }
+
+    fn peek_next_mut(&mut self) -> Option<&mut Self::Item> {
+        self.front_mut()
+    }
+
+    fn peek(&self, idx: usize) -> Option<&Self::Item> {
+        self.get(idx)
+    }
+
+    fn peek_mut(&mut self, idx: usize) -> Option<&mut Self::Item> {
+        self.get_mut(idx)
+    }
+
+    fn is_empty(&self) -> bool {
+        Deque::is_empty(self)
+    }
+
+    fn is_full(&self) -> bool {
+        Deque::is_full(self)
+    }
+}
+
+// LIFO: the back of the deque is the top of the stack, `peek(0)` is the next
+// item to be popped.
+impl<D: Deque> Stack for D {
+    type Item = D::Item;
+
+    fn push(&mut self, val: Self::Item) -> Result<(), StackErr> {
+        Ok(self.push_back(val)?)
+    }
+
+    fn pop(&mut self) -> Option<Self::Item> {
+        self.pop_back()
+    }
+
+    fn peek_next(&self) -> Option<&Self::Item> {
+        self.back()
+    }
+
+    fn peek_next_mut(&mut self) -> Option<&mut Self::Item> {
+        self.back_mut()
+    }
+
+    fn peek(&self, idx: usize) -> Option<&Self::Item> {
+        if idx < self.len() {
+            self.get(self.len() - 1 - idx)
+        } else {
+            None
+        }
+    }
+
+    fn peek_mut(&mut self, idx: usize) -> Option<&mut Self::Item> {
+        if idx < self.len() {
+            let len = self.len();
+            self.get_mut(len - 1 - idx)
+        } else {
+            None
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        Deque::is_empty(self)
+    }
+
+    fn is_full(&self) -> bool {
+        Deque::is_full(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn fill<D: Deque<Item = i32>>(deque: &mut D) {
+        deque.push_back(2).unwrap();
+        deque.push_back(3).unwrap();
+        deque.push_front(1).unwrap();
+    }
+
+    #[test]
+    fn deque_as_queue() {
+        let mut deque = ArrayDeque::new();
+        fill(&mut deque);
+        assert_eq!(Some(&1), Queue::peek_next(&deque));
+        assert_eq!(Some(&3), Queue::peek(&deque, 2));
+        Queue::enqueue(&mut deque, 4).unwrap();
+        assert_eq!(Some(1), Queue::dequeue(&mut deque));
+        assert_eq!(Some(2), Queue::dequeue(&mut deque));
+
+        let mut deque = LinkedDeque::new();
+        fill(&mut deque);
+        Queue::enqueue(&mut deque, 4).unwrap();
+        assert_eq!(Some(1), Queue::dequeue(&mut deque));
+        assert_eq!(Some(&4), Queue::peek(&deque, 2));
+    }
+
+    #[test]
+    fn deque_as_stack() {
+        let mut deque = ArrayDeque::new();
+        fill(&mut deque);
+        assert_eq!(Some(&3), Stack::peek_next(&deque));
+        assert_eq!(Some(&1), Stack::peek(&deque, 2));
+        assert_eq!(None, Stack::peek(&deque, 3));
+        Stack::push(&mut deque, 4).unwrap();
+        assert_eq!(Some(4), Stack::pop(&mut deque));
+        assert_eq!(Some(3), Stack::pop(&mut deque));
+
+        let mut deque = LinkedDeque::new();
+        fill(&mut deque);
+        assert_eq!(Some(&2), Stack::peek(&deque, 1));
+        Stack::peek_mut(&mut deque, 0).map(|v| *v = 7).unwrap();
+        assert_eq!(Some(7), Stack::pop(&mut deque));
+    }
+
+    #[test]
+    fn deque_overflow_errors() {
+        let mut deque = ArrayDeque::<i32>::with_capacity(1).unwrap();
+        Queue::enqueue(&mut deque, 1).unwrap();
+        assert_eq!(Err(QueueErr::QueueOverflow), Queue::enqueue(&mut deque, 2));
+        assert_eq!(Err(StackErr::StackOverflow), Stack::push(&mut deque, 2));
+
+        let mut deque = LinkedDeque::<i32>::with_capacity(1).unwrap();
+        Queue::enqueue(&mut deque, 1).unwrap();
+        assert_eq!(Err(QueueErr::QueueOverflow), Queue::enqueue(&mut deque, 2));
+        assert_eq!(Err(StackErr::StackOverflow), Stack::push(&mut deque, 2));
+    }
+}
@@ -0,0 +1,256 @@
+//! Hazard pointers, to free the nodes of a lock-free structure once no thread
+//! can read them anymore.
+//!
+//! A thread about to read a shared node first publishes its address in a
+//! hazard record, then checks the node is still reachable. A node removed from
+//! the structure is retired instead of being freed, and the retired nodes are
+//! freed in batches, except those still published in a record, which wait for
+//! the next batch. As a node can't be freed, hence its address can't be
+//! reused, while it is protected, this also rules out the ABA problem of the
+//! compare and swap loops.
+
+use std::ptr;
+
+use super::sync::{fence, AtomicBool, AtomicPtr, AtomicUsize, Ordering};
+
+// Retired nodes are reclaimed once there are that many of them, scaled by the
+// number of records, so that reclaiming is O(1) amortized per retire.
+const RECLAIM_FACTOR: usize = 2;
+const RECLAIM_MIN: usize = 64;
+
+// Records are only ever added to the list, an inactive one being reused by the
+// next thread asking for one, and freed with the domain.
+struct HazardRecord<N> {
+    ptr: AtomicPtr<N>,
+    active: AtomicBool,
+    next: *mut HazardRecord<N>,
+}
+
+struct Retired<N> {
+    ptr: *mut N,
+    next: *mut Retired<N>,
+}
+
+/// Hazard records and retired nodes of one structure, whose nodes are boxed
+/// `N`. Everything still retired is freed along with the domain.
+pub(crate) struct HazardDomain<N> {
+    records: AtomicPtr<HazardRecord<N>>,
+    record_count: AtomicUsize,
+    retired: AtomicPtr<Retired<N>>,
+    retired_count: AtomicUsize,
+}
+
+// SAFETY: The domain only hands out pointers, and frees boxed nodes, possibly
+// from another thread than the one which created them.
+unsafe impl<N: Send> Send for HazardDomain<N> {}
+unsafe impl<N: Send> Sync for HazardDomain<N> {}
+
+impl<N> HazardDomain<N> {
+    pub(crate) fn new() -> Self {
+        Self {
+            records: AtomicPtr::new(ptr::null_mut()),
+            record_count: AtomicUsize::new(0),
+            retired: AtomicPtr::new(ptr::null_mut()),
+            retired_count: AtomicUsize::new(0),
+        }
+    }
+
+    /// Borrow a record for the current thread, until the guard is dropped.
+    pub(crate) fn guard(&self) -> HazardGuard<'_, N> {
+        let mut cur = self.records.load(Ordering::Acquire);
+        while !cur.is_null() {
+            // SAFETY: Records are only freed with the domain.
+            let record = unsafe { &*cur };
+            if !record.active.load(Ordering::Relaxed)
+                && record
+                    .active
+                    .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+                    .is_ok()
+            {
+                return HazardGuard { record };
+            }
+            cur = record.next;
+        }
+
+        let record = Box::into_raw(Box::new(HazardRecord {
+            ptr: AtomicPtr::new(ptr::null_mut()),
+            active: AtomicBool::new(true),
+            next: ptr::null_mut(),
+        }));
+        let mut head = self.records.load(Ordering::Relaxed);
+        loop {
+            // SAFETY: The record is not shared until the exchange succeeds.
+            unsafe { (*record).next = head };
+            match self.records.compare_exchange_weak(
+                head,
+                record,
+                Ordering::Release,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => break,
+                Err(current) => head = current,
+            }
+        }
+        self.record_count.fetch_add(1, Ordering::Relaxed);
+        // SAFETY: Records are only freed with the domain.
+        HazardGuard {
+            record: unsafe { &*record },
+        }
+    }
+
+    /// Hand over a node removed from the structure, it is freed once no
+    /// record protects it anymore.
+    ///
+    /// # Safety
+    ///
+    /// The node must come from `Box::into_raw`, be unreachable from the
+    /// structure, and be retired only once.
+    pub(crate) unsafe fn retire(&self, node: *mut N) {
+        let retired = Box::into_raw(Box::new(Retired {
+            ptr: node,
+            next: ptr::null_mut(),
+        }));
+        // Counted before being pushed, otherwise another thread could reclaim
+        // it and take it off the count before it is added, wrapping it around.
+        let count = self.retired_count.fetch_add(1, Ordering::Relaxed) + 1;
+        self.push_retired(retired, retired);
+        let threshold = RECLAIM_MIN.max(RECLAIM_FACTOR * self.record_count.load(Ordering::Relaxed));
+        if count >= threshold {
+            self.reclaim();
+        }
+    }
+
+    /// Number of retired nodes waiting to be freed.
+    #[cfg(test)]
+    pub(crate) fn retired_len(&self) -> usize {
+        self.retired_count.load(Ordering::Relaxed)
+    }
+
+    // Free every retired node no record protects, the others go back to the
+    // retired list.
+    fn reclaim(&self) {
+        let mut cur = self.retired.swap(ptr::null_mut(), Ordering::Acquire);
+        if cur.is_null() {
+            return;
+        }
+        // A node published before the records are scanned is kept, and a node
+        // published after it is unreachable already, which the protecting
+        // thread sees when checking it is still reachable.
+        let protected = self.protected();
+
+        let mut kept_head: *mut Retired<N> = ptr::null_mut();
+        let mut kept_tail: *mut Retired<N> = ptr::null_mut();
+        let mut freed = 0;
+        while !cur.is_null() {
+            // SAFETY: The retired list has been taken, only this thread sees
+            // these entries.
+            let entry = unsafe { Box::from_raw(cur) };
+            cur = entry.next;
+            if protected.contains(&entry.ptr) {
+                let entry = Box::into_raw(entry);
+                // SAFETY: Same as above, the entry is not shared yet.
+                unsafe { (*entry).next = kept_head };
+                if kept_tail.is_null() {
+                    kept_tail = entry;
+                }
+                kept_head = entry;
+            } else {
+                // SAFETY: The node is unreachable, and no thread protects it,
+                // so no thread can read it anymore.
+                drop(unsafe { Box::from_raw(entry.ptr) });
+                freed += 1;
+            }
+        }
+        self.retired_count.fetch_sub(freed, Ordering::Relaxed);
+        if !kept_head.is_null() {
+            self.push_retired(kept_head, kept_tail);
+        }
+    }
+
+    // Addresses published in the active records.
+    fn protected(&self) -> Vec<*mut N> {
+        fence(Ordering::SeqCst);
+        let mut protected = Vec::new();
+        let mut cur = self.records.load(Ordering::Acquire);
+        while !cur.is_null() {
+            // SAFETY: Records are only freed with the domain.
+            let record = unsafe { &*cur };
+            let ptr = record.ptr.load(Ordering::SeqCst);
+            if !ptr.is_null() {
+                protected.push(ptr);
+            }
+            cur = record.next;
+        }
+        protected
+    }
+
+    // Push the chain of retired entries from `first` to `last` on the list.
+    fn push_retired(&self, first: *mut Retired<N>, last: *mut Retired<N>) {
+        let mut head = self.retired.load(Ordering::Relaxed);
+        loop {
+            // SAFETY: The chain is not shared until the exchange succeeds.
+            unsafe { (*last).next = head };
+            match self.retired.compare_exchange_weak(
+                head,
+                first,
+                Ordering::Release,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return,
+                Err(current) => head = current,
+            }
+        }
+    }
+}
+
+impl<N> Drop for HazardDomain<N> {
+    fn drop(&mut self) {
+        let mut cur = self.retired.load(Ordering::Relaxed);
+        while !cur.is_null() {
+            // SAFETY: With the domain gone, no thread can read the nodes.
+            let entry = unsafe { Box::from_raw(cur) };
+            drop(unsafe { Box::from_raw(entry.ptr) });
+            cur = entry.next;
+        }
+        let mut cur = self.records.load(Ordering::Relaxed);
+        while !cur.is_null() {
+            // SAFETY: Same, no guard can outlive the domain.
+            let record = unsafe { Box::from_raw(cur) };
+            cur = record.next;
+        }
+    }
+}
+
+/// Record borrowed by a thread, which protects at most one node at a time.
+pub(crate) struct HazardGuard<'a, N> {
+    record: &'a HazardRecord<N>,
+}
+
+impl<N> HazardGuard<'_, N> {
+    /// Load a pointer from `src` and protect it, so the node it points to
+    /// can't be freed until the guard protects another one or is dropped.
+    pub(crate) fn protect(&self, src: &AtomicPtr<N>) -> *mut N {
+        let mut ptr = src.load(Ordering::Relaxed);
+        loop {
+            self.record.ptr.store(ptr, Ordering::SeqCst);
+            // The node may have been retired before being published, it is
+            // safe to use only if it is still reachable once published.
+            let current = src.load(Ordering::SeqCst);
+            if current == ptr {
+                return ptr;
+            }
+            ptr = current;
+        }
+    }
+
+    pub(crate) fn clear(&self) {
+        self.record.ptr.store(ptr::null_mut(), Ordering::Release);
+    }
+}
+
+impl<N> Drop for HazardGuard<'_, N> {
+    fn drop(&mut self) {
+        self.clear();
+        self.record.active.store(false, Ordering::Release);
+    }
+}
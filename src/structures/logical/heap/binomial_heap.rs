@@ -0,0 +1,281 @@
+use std::{marker::PhantomData, mem, ptr::NonNull};
+
+use super::{alloc, free, free_trees, HeapErr, HeapHandle, HeapId, HeapNode, MergeableHeap};
+
+type NodePtr<T> = NonNull<BinomialNode<T>>;
+
+struct BinomialNode<T> {
+    val: T,
+    // decrease_key swaps the values of a node and its parent, the handles are
+    // swapped along, so they keep following their value.
+    handle: HeapHandle,
+    parent: Option<NodePtr<T>>,
+    // Children are chained through their siblings, by decreasing degree.
+    child: Option<NodePtr<T>>,
+    sibling: Option<NodePtr<T>>,
+    degree: usize,
+}
+
+impl<T> HeapNode for BinomialNode<T> {
+    fn handle(&self) -> &HeapHandle {
+        &self.handle
+    }
+
+    fn links_down(&self) -> [Option<NonNull<Self>>; 2] {
+        [self.child, self.sibling]
+    }
+}
+
+/// Forest of binomial trees, with at most one tree of each degree. A binomial
+/// tree of degree k holds 2^k nodes, it is made of two trees of degree k - 1,
+/// one being the first child of the other's root. The forest of a heap of n
+/// items then matches the binary representation of n, and merging two heaps
+/// works like a binary addition, where two trees of the same degree are linked
+/// into a carry of the next degree.
+pub struct BinomialHeap<T> {
+    // roots[k] is the root of the tree of degree k, if there is one.
+    roots: Vec<Option<NodePtr<T>>>,
+    len: usize,
+    id: HeapId,
+    nodes: PhantomData<Box<BinomialNode<T>>>,
+}
+
+impl<T: Ord> BinomialHeap<T> {
+    pub fn new() -> Self {
+        Self {
+            roots: Vec::new(),
+            len: 0,
+            id: HeapId::default(),
+            nodes: PhantomData,
+        }
+    }
+
+    fn node(&self, ptr: NodePtr<T>) -> &BinomialNode<T> {
+        // SAFETY: Every node linked from the heap is alive and owned by the
+        // heap, which is borrowed for as long as the node.
+        unsafe { ptr.as_ref() }
+    }
+
+    fn node_mut(&mut self, mut ptr: NodePtr<T>) -> &mut BinomialNode<T> {
+        // SAFETY: Same as node, the heap being borrowed mutably.
+        unsafe { ptr.as_mut() }
+    }
+
+    fn val(&self, ptr: NodePtr<T>) -> &T {
+        &self.node(ptr).val
+    }
+
+    // Link two trees of the same degree, the greatest root becomes the first
+    // child of the other one.
+    fn link(&mut self, a: NodePtr<T>, b: NodePtr<T>) -> NodePtr<T> {
+        let (top, other) = if self.val(b) < self.val(a) {
+            (b, a)
+        } else {
+            (a, b)
+        };
+        let first_child = self.node(top).child;
+        let other_node = self.node_mut(other);
+        other_node.parent = Some(top);
+        other_node.sibling = first_child;
+        let top_node = self.node_mut(top);
+        top_node.child = Some(other);
+        top_node.degree += 1;
+        top
+    }
+
+    // Add a tree to the forest, propagating the carry like a binary addition.
+    fn add_tree(&mut self, mut tree: NodePtr<T>) {
+        let mut degree = self.node(tree).degree;
+        loop {
+            if degree >= self.roots.len() {
+                self.roots.resize(degree + 1, None);
+            }
+            match self.roots[degree].take() {
+                Some(root) => {
+                    tree = self.link(root, tree);
+                    degree += 1;
+                }
+                None => {
+                    self.roots[degree] = Some(tree);
+                    return;
+                }
+            }
+        }
+    }
+
+    fn min_root(&self) -> Option<NodePtr<T>> {
+        self.roots.iter().flatten().copied().reduce(|min, root| {
+            if self.val(root) < self.val(min) {
+                root
+            } else {
+                min
+            }
+        })
+    }
+}
+
+impl<T: Ord> Default for BinomialHeap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for BinomialHeap<T> {
+    fn drop(&mut self) {
+        // SAFETY: The heap is gone, nothing follows the links anymore.
+        unsafe { free_trees(mem::take(&mut self.roots).into_iter().flatten()) };
+    }
+}
+
+impl<T: Ord> MergeableHeap for BinomialHeap<T> {
+    type Item = T;
+
+    fn insert(&mut self, val: Self::Item) -> HeapHandle {
+        let handle = HeapHandle::new(&self.id);
+        let ptr = alloc(BinomialNode {
+            val,
+            handle: handle.clone(),
+            parent: None,
+            child: None,
+            sibling: None,
+            degree: 0,
+        });
+        self.len += 1;
+        self.add_tree(ptr);
+        handle
+    }
+
+    fn find_min(&self) -> Option<&Self::Item> {
+        self.min_root().map(|root| self.val(root))
+    }
+
+    fn delete_min(&mut self) -> Option<Self::Item> {
+        let min = self.min_root()?;
+        // SAFETY: The root is unlinked right away, only its children remain.
+        let node = unsafe { free(min) };
+        self.len -= 1;
+        self.roots[node.degree] = None;
+        while self.roots.last().is_some_and(|root| root.is_none()) {
+            self.roots.pop();
+        }
+
+        // The children of a root of degree k are trees of degree k - 1 down
+        // to 0, they are added back to the forest.
+        let mut curr = node.child;
+        while let Some(child) = curr {
+            let child_node = self.node_mut(child);
+            curr = child_node.sibling;
+            child_node.parent = None;
+            child_node.sibling = None;
+            self.add_tree(child);
+        }
+        Some(node.val)
+    }
+
+    fn merge(&mut self, mut other: Self) {
+        // The other heap is left empty, so dropping it frees nothing.
+        self.len += mem::take(&mut other.len);
+        other.id.merge_into(&self.id);
+        for root in mem::take(&mut other.roots).into_iter().flatten() {
+            self.add_tree(root);
+        }
+    }
+
+    fn decrease_key(&mut self, handle: &HeapHandle, val: Self::Item) -> Result<(), HeapErr> {
+        let mut ptr = handle.node(&self.id)?;
+        if val > self.node(ptr).val {
+            return Err(HeapErr::KeyNotDecreased);
+        }
+        self.node_mut(ptr).val = val;
+
+        // Bubble the value up, by swapping it with the value of the parent
+        // while it is smaller.
+        while let Some(parent) = self.node(ptr).parent {
+            if self.val(ptr) >= self.val(parent) {
+                break;
+            }
+            // SAFETY: A node is never its own parent, the two borrows don't
+            // overlap.
+            let (node, parent_node) = unsafe { (&mut *ptr.as_ptr(), &mut *parent.as_ptr()) };
+            mem::swap(&mut node.val, &mut parent_node.val);
+            mem::swap(&mut node.handle, &mut parent_node.handle);
+            node.handle.point_to(Some(ptr));
+            parent_node.handle.point_to(Some(parent));
+            ptr = parent;
+        }
+        Ok(())
+    }
+
+    fn get(&self, handle: &HeapHandle) -> Result<&Self::Item, HeapErr> {
+        handle.node(&self.id).map(|ptr| self.val(ptr))
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn binomial_heap_insert_delete_min() {
+        let mut heap = BinomialHeap::new();
+        [5, 3, 8, 1, 9, 2].into_iter().for_each(|v| {
+            heap.insert(v);
+        });
+        assert_eq!(Some(&1), heap.find_min());
+        let popped = std::iter::from_fn(|| heap.delete_min()).collect::<Vec<_>>();
+        assert_eq!(vec![1, 2, 3, 5, 8, 9], popped);
+    }
+
+    #[test]
+    fn binomial_heap_forest_matches_binary_len() {
+        let mut heap = BinomialHeap::new();
+        (0..11).for_each(|v| {
+            heap.insert(v);
+        });
+        // 11 = 0b1011, so trees of degree 0, 1 and 3
+        let degrees = heap
+            .roots
+            .iter()
+            .enumerate()
+            .filter(|(_, root)| root.is_some())
+            .map(|(degree, _)| degree)
+            .collect::<Vec<_>>();
+        assert_eq!(vec![0, 1, 3], degrees);
+    }
+
+    #[test]
+    fn binomial_heap_merge() {
+        let mut heap = BinomialHeap::new();
+        let mut other = BinomialHeap::new();
+        [4, 2, 6].into_iter().for_each(|v| {
+            heap.insert(v);
+        });
+        [5, 1, 3].into_iter().for_each(|v| {
+            other.insert(v);
+        });
+        heap.merge(other);
+        let popped = std::iter::from_fn(|| heap.delete_min()).collect::<Vec<_>>();
+        assert_eq!(vec![1, 2, 3, 4, 5, 6], popped);
+    }
+
+    #[test]
+    fn binomial_heap_decrease_key_keeps_handles() {
+        let mut heap = BinomialHeap::new();
+        let handles = (10..18).map(|v| heap.insert(v)).collect::<Vec<_>>();
+        heap.decrease_key(&handles[7], 1).unwrap();
+        // values have been swapped between nodes, handles still follow them
+        for (i, handle) in handles.iter().enumerate().take(7) {
+            assert_eq!(Ok(&(10 + i as i32)), heap.get(handle));
+        }
+        assert_eq!(Ok(&1), heap.get(&handles[7]));
+        assert_eq!(Some(1), heap.delete_min());
+    }
+}
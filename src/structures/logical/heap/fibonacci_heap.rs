@@ -0,0 +1,359 @@
+use std::{marker::PhantomData, mem, ptr::NonNull};
+
+use super::{alloc, free, free_trees, HeapErr, HeapHandle, HeapId, HeapNode, MergeableHeap};
+
+type NodePtr<T> = NonNull<FibonacciNode<T>>;
+
+struct FibonacciNode<T> {
+    val: T,
+    handle: HeapHandle,
+    parent: Option<NodePtr<T>>,
+    // First child, the other children are reached through the siblings.
+    child: Option<NodePtr<T>>,
+    // Siblings, or the other roots for a root.
+    prev: Option<NodePtr<T>>,
+    next: Option<NodePtr<T>>,
+    degree: usize,
+    // Whether the node lost a child since it became the child of its parent.
+    marked: bool,
+}
+
+impl<T> HeapNode for FibonacciNode<T> {
+    fn handle(&self) -> &HeapHandle {
+        &self.handle
+    }
+
+    fn links_down(&self) -> [Option<NonNull<Self>>; 2] {
+        [self.child, self.next]
+    }
+}
+
+/// Forest of heap ordered trees, as lazy as possible: insert and merge just add
+/// trees to the forest, and decrease_key cuts the node from its parent. The
+/// trees are only consolidated by delete_min, which links the trees of the same
+/// degree until every degree is unique. Cutting a second child from a node
+/// cuts the node as well (cascading cut), which keeps the size of a tree
+/// exponential in its degree, with a base of the golden ratio.
+pub struct FibonacciHeap<T> {
+    // The roots are linked together, so that the roots of another heap are
+    // appended in one go.
+    first_root: Option<NodePtr<T>>,
+    last_root: Option<NodePtr<T>>,
+    min: Option<NodePtr<T>>,
+    len: usize,
+    id: HeapId,
+    nodes: PhantomData<Box<FibonacciNode<T>>>,
+}
+
+impl<T: Ord> FibonacciHeap<T> {
+    pub fn new() -> Self {
+        Self {
+            first_root: None,
+            last_root: None,
+            min: None,
+            len: 0,
+            id: HeapId::default(),
+            nodes: PhantomData,
+        }
+    }
+
+    fn node(&self, ptr: NodePtr<T>) -> &FibonacciNode<T> {
+        // SAFETY: Every node linked from the heap is alive and owned by the
+        // heap, which is borrowed for as long as the node.
+        unsafe { ptr.as_ref() }
+    }
+
+    fn node_mut(&mut self, mut ptr: NodePtr<T>) -> &mut FibonacciNode<T> {
+        // SAFETY: Same as node, the heap being borrowed mutably.
+        unsafe { ptr.as_mut() }
+    }
+
+    fn roots(&self) -> Vec<NodePtr<T>> {
+        let mut roots = Vec::new();
+        let mut curr = self.first_root;
+        while let Some(root) = curr {
+            roots.push(root);
+            curr = self.node(root).next;
+        }
+        roots
+    }
+
+    fn update_min(&mut self, ptr: NodePtr<T>) {
+        if self
+            .min
+            .is_none_or(|min| self.node(ptr).val < self.node(min).val)
+        {
+            self.min = Some(ptr);
+        }
+    }
+
+    fn add_root(&mut self, ptr: NodePtr<T>) {
+        let last_root = self.last_root;
+        let node = self.node_mut(ptr);
+        node.parent = None;
+        node.prev = last_root;
+        node.next = None;
+        node.marked = false;
+        match last_root {
+            Some(last_root) => self.node_mut(last_root).next = Some(ptr),
+            None => self.first_root = Some(ptr),
+        }
+        self.last_root = Some(ptr);
+        self.update_min(ptr);
+    }
+
+    fn remove_root(&mut self, ptr: NodePtr<T>) {
+        let (prev, next) = (self.node(ptr).prev, self.node(ptr).next);
+        match prev {
+            Some(prev) => self.node_mut(prev).next = next,
+            None => self.first_root = next,
+        }
+        match next {
+            Some(next) => self.node_mut(next).prev = prev,
+            None => self.last_root = prev,
+        }
+    }
+
+    // Make the tree with the greatest root the first child of the other root.
+    fn link(&mut self, a: NodePtr<T>, b: NodePtr<T>) -> NodePtr<T> {
+        let (top, other) = if self.node(b).val < self.node(a).val {
+            (b, a)
+        } else {
+            (a, b)
+        };
+        let first_child = self.node(top).child;
+        if let Some(first_child) = first_child {
+            self.node_mut(first_child).prev = Some(other);
+        }
+        let other_node = self.node_mut(other);
+        other_node.parent = Some(top);
+        other_node.next = first_child;
+        other_node.prev = None;
+        other_node.marked = false;
+        let top_node = self.node_mut(top);
+        top_node.child = Some(other);
+        top_node.degree += 1;
+        top
+    }
+
+    // Link the roots of the same degree until all degrees are unique.
+    fn consolidate(&mut self) {
+        let roots = self.roots();
+        self.first_root = None;
+        self.last_root = None;
+        let mut by_degree: Vec<Option<NodePtr<T>>> = Vec::new();
+        for mut tree in roots {
+            let mut degree = self.node(tree).degree;
+            loop {
+                if degree >= by_degree.len() {
+                    by_degree.resize(degree + 1, None);
+                }
+                match by_degree[degree].take() {
+                    Some(other) => {
+                        tree = self.link(tree, other);
+                        degree += 1;
+                    }
+                    None => {
+                        by_degree[degree] = Some(tree);
+                        break;
+                    }
+                }
+            }
+        }
+        self.min = None;
+        for root in by_degree.into_iter().flatten() {
+            self.add_root(root);
+        }
+    }
+
+    // Detach a node from its parent and its siblings, and make it a root.
+    fn cut(&mut self, ptr: NodePtr<T>, parent: NodePtr<T>) {
+        let (prev, next) = (self.node(ptr).prev, self.node(ptr).next);
+        match prev {
+            Some(prev) => self.node_mut(prev).next = next,
+            None => self.node_mut(parent).child = next,
+        }
+        if let Some(next) = next {
+            self.node_mut(next).prev = prev;
+        }
+        self.node_mut(parent).degree -= 1;
+        self.add_root(ptr);
+    }
+}
+
+impl<T: Ord> Default for FibonacciHeap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for FibonacciHeap<T> {
+    fn drop(&mut self) {
+        // The other roots are reached through the next links of the first one.
+        // SAFETY: The heap is gone, nothing follows the links anymore.
+        unsafe { free_trees(self.first_root.take()) };
+    }
+}
+
+impl<T: Ord> MergeableHeap for FibonacciHeap<T> {
+    type Item = T;
+
+    fn insert(&mut self, val: Self::Item) -> HeapHandle {
+        let handle = HeapHandle::new(&self.id);
+        let ptr = alloc(FibonacciNode {
+            val,
+            handle: handle.clone(),
+            parent: None,
+            child: None,
+            prev: None,
+            next: None,
+            degree: 0,
+            marked: false,
+        });
+        self.len += 1;
+        self.add_root(ptr);
+        handle
+    }
+
+    fn find_min(&self) -> Option<&Self::Item> {
+        self.min.map(|min| &self.node(min).val)
+    }
+
+    fn delete_min(&mut self) -> Option<Self::Item> {
+        let min = self.min?;
+        self.remove_root(min);
+        // SAFETY: The root has just been unlinked, only its children remain.
+        let node = unsafe { free(min) };
+        self.len -= 1;
+
+        let mut curr = node.child;
+        while let Some(child) = curr {
+            curr = self.node(child).next;
+            self.add_root(child);
+        }
+        self.consolidate();
+        Some(node.val)
+    }
+
+    fn merge(&mut self, mut other: Self) {
+        // The other heap is left empty, so dropping it frees nothing.
+        self.len += mem::take(&mut other.len);
+        other.id.merge_into(&self.id);
+        let (Some(first_root), Some(min)) = (other.first_root.take(), other.min.take()) else {
+            return;
+        };
+        match self.last_root {
+            Some(last_root) => {
+                self.node_mut(last_root).next = Some(first_root);
+                self.node_mut(first_root).prev = Some(last_root);
+            }
+            None => self.first_root = Some(first_root),
+        }
+        self.last_root = other.last_root.take();
+        self.update_min(min);
+    }
+
+    fn decrease_key(&mut self, handle: &HeapHandle, val: Self::Item) -> Result<(), HeapErr> {
+        let ptr = handle.node(&self.id)?;
+        if val > self.node(ptr).val {
+            return Err(HeapErr::KeyNotDecreased);
+        }
+        self.node_mut(ptr).val = val;
+
+        match self.node(ptr).parent {
+            Some(parent) if self.node(ptr).val < self.node(parent).val => {
+                self.cut(ptr, parent);
+                // Cascading cut: a parent which already lost a child is cut
+                // as well, and so on up to an unmarked node.
+                let mut curr = parent;
+                while let Some(parent) = self.node(curr).parent {
+                    if !self.node(curr).marked {
+                        self.node_mut(curr).marked = true;
+                        break;
+                    }
+                    self.cut(curr, parent);
+                    curr = parent;
+                }
+            }
+            None => self.update_min(ptr),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn get(&self, handle: &HeapHandle) -> Result<&Self::Item, HeapErr> {
+        handle.node(&self.id).map(|ptr| &self.node(ptr).val)
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn fibonacci_heap_insert_delete_min() {
+        let mut heap = FibonacciHeap::new();
+        [5, 3, 8, 1, 9, 2].into_iter().for_each(|v| {
+            heap.insert(v);
+        });
+        assert_eq!(Some(&1), heap.find_min());
+        let popped = std::iter::from_fn(|| heap.delete_min()).collect::<Vec<_>>();
+        assert_eq!(vec![1, 2, 3, 5, 8, 9], popped);
+    }
+
+    #[test]
+    fn fibonacci_heap_consolidate_unique_degrees() {
+        let mut heap = FibonacciHeap::new();
+        (0..16).for_each(|v| {
+            heap.insert(v);
+        });
+        assert_eq!(16, heap.roots().len());
+        heap.delete_min();
+        // 15 = 0b1111, so trees of degree 0, 1, 2 and 3
+        let mut degrees = heap
+            .roots()
+            .into_iter()
+            .map(|root| heap.node(root).degree)
+            .collect::<Vec<_>>();
+        degrees.sort();
+        assert_eq!(vec![0, 1, 2, 3], degrees);
+    }
+
+    #[test]
+    fn fibonacci_heap_merge() {
+        let mut heap = FibonacciHeap::new();
+        let mut other = FibonacciHeap::new();
+        [4, 2, 6].into_iter().for_each(|v| {
+            heap.insert(v);
+        });
+        [5, 1, 3].into_iter().for_each(|v| {
+            other.insert(v);
+        });
+        heap.merge(other);
+        assert_eq!(Some(&1), heap.find_min());
+        let popped = std::iter::from_fn(|| heap.delete_min()).collect::<Vec<_>>();
+        assert_eq!(vec![1, 2, 3, 4, 5, 6], popped);
+    }
+
+    #[test]
+    fn fibonacci_heap_cascading_cut() {
+        let mut heap = FibonacciHeap::new();
+        let handles = (0..9).map(|v| heap.insert(v * 10)).collect::<Vec<_>>();
+        // a single tree of degree 3 remains after consolidation
+        heap.delete_min();
+        assert_eq!(Some(&10), heap.find_min());
+        heap.decrease_key(&handles[8], 5).unwrap();
+        heap.decrease_key(&handles[7], 4).unwrap();
+        assert!(heap.roots().len() >= 3);
+        let popped = std::iter::from_fn(|| heap.delete_min()).collect::<Vec<_>>();
+        assert_eq!(vec![4, 5, 10, 20, 30, 40, 50, 60], popped);
+    }
+}
@@ -0,0 +1,263 @@
+use std::{marker::PhantomData, mem, ptr::NonNull};
+
+use super::{alloc, free, free_trees, HeapErr, HeapHandle, HeapId, HeapNode, MergeableHeap};
+
+type NodePtr<T> = NonNull<LeftistNode<T>>;
+
+struct LeftistNode<T> {
+    val: T,
+    handle: HeapHandle,
+    left: Option<NodePtr<T>>,
+    right: Option<NodePtr<T>>,
+    parent: Option<NodePtr<T>>,
+    // Length of the shortest path to a missing child (also called s-value or
+    // null path length), a missing node has a rank of 0.
+    rank: usize,
+}
+
+impl<T> HeapNode for LeftistNode<T> {
+    fn handle(&self) -> &HeapHandle {
+        &self.handle
+    }
+
+    fn links_down(&self) -> [Option<NonNull<Self>>; 2] {
+        [self.left, self.right]
+    }
+}
+
+/// Heap ordered binary tree where the rank of a left child is never smaller
+/// than the rank of its right sibling. The right spine is then at most
+/// O(log n) long, and merging two heaps only walks down their right spines.
+/// Every other operation is built on top of the merge.
+pub struct LeftistHeap<T> {
+    root: Option<NodePtr<T>>,
+    len: usize,
+    id: HeapId,
+    nodes: PhantomData<Box<LeftistNode<T>>>,
+}
+
+impl<T: Ord> LeftistHeap<T> {
+    pub fn new() -> Self {
+        Self {
+            root: None,
+            len: 0,
+            id: HeapId::default(),
+            nodes: PhantomData,
+        }
+    }
+
+    fn node(&self, ptr: NodePtr<T>) -> &LeftistNode<T> {
+        // SAFETY: Every node linked from the heap is alive and owned by the
+        // heap, which is borrowed for as long as the node.
+        unsafe { ptr.as_ref() }
+    }
+
+    fn node_mut(&mut self, mut ptr: NodePtr<T>) -> &mut LeftistNode<T> {
+        // SAFETY: Same as node, the heap being borrowed mutably.
+        unsafe { ptr.as_mut() }
+    }
+
+    fn rank(&self, ptr: Option<NodePtr<T>>) -> usize {
+        ptr.map_or(0, |ptr| self.node(ptr).rank)
+    }
+
+    // Swap the children if the leftist property is broken, and update the
+    // rank. Return whether the rank changed.
+    fn fix_rank(&mut self, ptr: NodePtr<T>) -> bool {
+        let (left, right) = (self.node(ptr).left, self.node(ptr).right);
+        if self.rank(left) < self.rank(right) {
+            let node = self.node_mut(ptr);
+            node.left = right;
+            node.right = left;
+        }
+        let rank = self.rank(self.node(ptr).right) + 1;
+        let node = self.node_mut(ptr);
+        let changed = node.rank != rank;
+        node.rank = rank;
+        changed
+    }
+
+    // Merge two subtrees and return the new root, whose parent is left as it
+    // was, so the caller has to set it.
+    fn meld(&mut self, a: Option<NodePtr<T>>, b: Option<NodePtr<T>>) -> Option<NodePtr<T>> {
+        let (a, b) = match (a, b) {
+            (None, tree) | (tree, None) => return tree,
+            (Some(a), Some(b)) => (a, b),
+        };
+        // The smallest root stays on top, the other tree is merged with its
+        // right subtree.
+        let (top, other) = if self.node(b).val < self.node(a).val {
+            (b, a)
+        } else {
+            (a, b)
+        };
+        let right = self.node(top).right;
+        let merged = self.meld(right, Some(other)).expect("merging two trees");
+        self.node_mut(merged).parent = Some(top);
+        self.node_mut(top).right = Some(merged);
+        self.fix_rank(top);
+        Some(top)
+    }
+
+    fn meld_with_root(&mut self, tree: Option<NodePtr<T>>) {
+        self.root = self.meld(self.root, tree);
+        if let Some(root) = self.root {
+            self.node_mut(root).parent = None;
+        }
+    }
+}
+
+impl<T: Ord> Default for LeftistHeap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for LeftistHeap<T> {
+    fn drop(&mut self) {
+        // SAFETY: The heap is gone, nothing follows the links anymore.
+        unsafe { free_trees(self.root.take()) };
+    }
+}
+
+impl<T: Ord> MergeableHeap for LeftistHeap<T> {
+    type Item = T;
+
+    fn insert(&mut self, val: Self::Item) -> HeapHandle {
+        let handle = HeapHandle::new(&self.id);
+        let ptr = alloc(LeftistNode {
+            val,
+            handle: handle.clone(),
+            left: None,
+            right: None,
+            parent: None,
+            rank: 1,
+        });
+        self.len += 1;
+        self.meld_with_root(Some(ptr));
+        handle
+    }
+
+    fn find_min(&self) -> Option<&Self::Item> {
+        self.root.map(|root| &self.node(root).val)
+    }
+
+    fn delete_min(&mut self) -> Option<Self::Item> {
+        let root = self.root?;
+        // SAFETY: The root is unlinked right away, only its children remain.
+        let node = unsafe { free(root) };
+        self.len -= 1;
+        for child in [node.left, node.right].into_iter().flatten() {
+            self.node_mut(child).parent = None;
+        }
+        self.root = self.meld(node.left, node.right);
+        Some(node.val)
+    }
+
+    fn merge(&mut self, mut other: Self) {
+        // The other heap is left empty, so dropping it frees nothing.
+        let other_root = other.root.take();
+        self.len += mem::take(&mut other.len);
+        other.id.merge_into(&self.id);
+        self.meld_with_root(other_root);
+    }
+
+    fn decrease_key(&mut self, handle: &HeapHandle, val: Self::Item) -> Result<(), HeapErr> {
+        let ptr = handle.node(&self.id)?;
+        if val > self.node(ptr).val {
+            return Err(HeapErr::KeyNotDecreased);
+        }
+        self.node_mut(ptr).val = val;
+
+        // The subtree of the node is still a valid heap, so it's cut from its
+        // parent and merged back with the root. The ranks on the path from the
+        // parent to the root may drop, they are fixed until one doesn't change.
+        let Some(parent) = self.node(ptr).parent else {
+            return Ok(());
+        };
+        let parent_node = self.node_mut(parent);
+        if parent_node.left == Some(ptr) {
+            parent_node.left = None;
+        } else {
+            parent_node.right = None;
+        }
+        self.node_mut(ptr).parent = None;
+        let mut curr = Some(parent);
+        while let Some(node) = curr {
+            if !self.fix_rank(node) {
+                break;
+            }
+            curr = self.node(node).parent;
+        }
+        self.meld_with_root(Some(ptr));
+        Ok(())
+    }
+
+    fn get(&self, handle: &HeapHandle) -> Result<&Self::Item, HeapErr> {
+        handle.node(&self.id).map(|ptr| &self.node(ptr).val)
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn leftist_heap_insert_delete_min() {
+        let mut heap = LeftistHeap::new();
+        [5, 3, 8, 1, 9, 2].into_iter().for_each(|v| {
+            heap.insert(v);
+        });
+        assert_eq!(Some(&1), heap.find_min());
+        let popped = std::iter::from_fn(|| heap.delete_min()).collect::<Vec<_>>();
+        assert_eq!(vec![1, 2, 3, 5, 8, 9], popped);
+    }
+
+    #[test]
+    fn leftist_heap_right_spine_is_short() {
+        let mut heap = LeftistHeap::new();
+        (0..1024).rev().for_each(|v| {
+            heap.insert(v);
+        });
+        // the rank of the root is the length of the right spine
+        let root = heap.root.unwrap();
+        assert!(heap.node(root).rank <= 11);
+    }
+
+    #[test]
+    fn leftist_heap_merge() {
+        let mut heap = LeftistHeap::new();
+        let mut other = LeftistHeap::new();
+        [4, 2, 6].into_iter().for_each(|v| {
+            heap.insert(v);
+        });
+        [5, 1, 3].into_iter().for_each(|v| {
+            other.insert(v);
+        });
+        heap.merge(other);
+        assert_eq!(6, heap.len());
+        let popped = std::iter::from_fn(|| heap.delete_min()).collect::<Vec<_>>();
+        assert_eq!(vec![1, 2, 3, 4, 5, 6], popped);
+    }
+
+    #[test]
+    fn leftist_heap_decrease_key() {
+        let mut heap = LeftistHeap::new();
+        heap.insert(1);
+        heap.insert(5);
+        let handle = heap.insert(7);
+        heap.decrease_key(&handle, 0).unwrap();
+        assert_eq!(Some(&0), heap.find_min());
+        assert_eq!(Ok(&0), heap.get(&handle));
+        assert_eq!(Some(0), heap.delete_min());
+        assert_eq!(Err(HeapErr::StaleHandle), heap.get(&handle));
+    }
+}
@@ -0,0 +1,339 @@
+#![allow(dead_code)]
+
+use std::{
+    cell::{Cell, RefCell},
+    fmt::Display,
+    hash::{Hash, Hasher},
+    mem,
+    ptr::NonNull,
+    rc::Rc,
+};
+
+pub use binomial_heap::BinomialHeap;
+pub use fibonacci_heap::FibonacciHeap;
+pub use leftist_heap::LeftistHeap;
+pub use pairing_heap::PairingHeap;
+
+mod binomial_heap;
+mod fibonacci_heap;
+mod leftist_heap;
+mod pairing_heap;
+
+/// Min heap which can be merged with another heap of the same kind faster than
+/// by inserting every item one by one, and whose items can be reached through
+/// the handle given on insertion to decrease their key.
+///
+/// | heap      | insert     | delete_min | merge      | decrease_key |
+/// |-----------|------------|------------|------------|--------------|
+/// | leftist   | O(log n)   | O(log n)   | O(log n)   | O(log n)     |
+/// | binomial  | O(1) amort | O(log n)   | O(log n)   | O(log n)     |
+/// | pairing   | O(1)       | O(log n) * | O(1)       | o(log n) *   |
+/// | fibonacci | O(1)       | O(log n) * | O(1)       | O(1) *       |
+///
+/// (*) amortized.
+///
+/// All of them own their nodes through pointers, so merging only links the
+/// roots of the other heap, whatever its size. A handle follows its item
+/// until it is deleted, also into the heap its own heap has been merged
+/// into, and is rejected with [`HeapErr::StaleHandle`] after that, or by any
+/// other heap.
+pub trait MergeableHeap {
+    type Item: Ord;
+    fn insert(&mut self, val: Self::Item) -> HeapHandle;
+    fn find_min(&self) -> Option<&Self::Item>;
+    fn delete_min(&mut self) -> Option<Self::Item>;
+    fn merge(&mut self, other: Self);
+    fn decrease_key(&mut self, handle: &HeapHandle, val: Self::Item) -> Result<(), HeapErr>;
+    fn get(&self, handle: &HeapHandle) -> Result<&Self::Item, HeapErr>;
+    fn len(&self) -> usize;
+    fn is_empty(&self) -> bool;
+}
+
+/// Handle on an item of a [`MergeableHeap`]. It remembers which heap the item
+/// is in, so it can't be used on another heap by mistake. Clones of a handle
+/// are equal, and refer to the same item.
+#[derive(Debug, Clone)]
+pub struct HeapHandle(Rc<Ticket>);
+
+// Shared by a node and the handles on its item.
+#[derive(Debug)]
+struct Ticket {
+    // Node holding the item, None once the item has left the heap.
+    node: Cell<Option<NonNull<()>>>,
+    // Heap the item has been inserted into, which may have been merged since.
+    heap: HeapId,
+}
+
+impl HeapHandle {
+    fn new(heap: &HeapId) -> Self {
+        Self(Rc::new(Ticket {
+            node: Cell::new(None),
+            heap: heap.clone(),
+        }))
+    }
+
+    // Node of the item, as long as it is in the heap with this id.
+    fn node<N>(&self, heap: &HeapId) -> Result<NonNull<N>, HeapErr> {
+        match self.0.node.get() {
+            Some(node) if self.0.heap.is_in(heap) => Ok(node.cast()),
+            _ => Err(HeapErr::StaleHandle),
+        }
+    }
+
+    fn point_to<N>(&self, node: Option<NonNull<N>>) {
+        self.0.node.set(node.map(NonNull::cast));
+    }
+}
+
+impl PartialEq for HeapHandle {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for HeapHandle {}
+
+impl Hash for HeapHandle {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Rc::as_ptr(&self.0).hash(state);
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum HeapErr {
+    StaleHandle,
+    KeyNotDecreased,
+}
+
+impl std::error::Error for HeapErr {}
+
+impl Display for HeapErr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HeapErr::StaleHandle => write!(
+                f,
+                "The handle points to an item which is no longer in this heap"
+            ),
+            HeapErr::KeyNotDecreased => {
+                write!(f, "The new key is greater than the current one")
+            }
+        }
+    }
+}
+
+// Identity of a heap, shared with the handles it gives. Merged heaps form a
+// union-find forest: a heap merged into another one points to it, and the
+// root of a tree is the heap which holds every item inserted in the tree.
+#[derive(Debug, Clone, Default)]
+struct HeapId(Rc<RefCell<Option<HeapId>>>);
+
+impl HeapId {
+    fn merge_into(&self, heap: &HeapId) {
+        *self.0.borrow_mut() = Some(heap.clone());
+    }
+
+    // Whether this heap is, or has been merged into, the given live heap.
+    // The path to the root is compressed on the way, so that a long chain of
+    // merges is only walked once.
+    fn is_in(&self, heap: &HeapId) -> bool {
+        let mut path = Vec::new();
+        let mut root = self.clone();
+        loop {
+            let next = root.0.borrow().clone();
+            let Some(next) = next else { break };
+            path.push(mem::replace(&mut root, next));
+        }
+        path.iter().for_each(|id| id.merge_into(&root));
+        Rc::ptr_eq(&root.0, &heap.0)
+    }
+}
+
+// Node of a heap, owned by the heap through a pointer.
+trait HeapNode: Sized {
+    fn handle(&self) -> &HeapHandle;
+    // Links leading from the root of a tree to all its nodes, without going
+    // back up.
+    fn links_down(&self) -> [Option<NonNull<Self>>; 2];
+}
+
+fn alloc<N: HeapNode>(node: N) -> NonNull<N> {
+    let ptr = NonNull::from(Box::leak(Box::new(node)));
+    // SAFETY: The node has just been allocated.
+    unsafe { ptr.as_ref() }.handle().point_to(Some(ptr));
+    ptr
+}
+
+// Take a node out of the heap, the handle on its item becomes stale.
+//
+// SAFETY: The node must have been allocated by `alloc` and not freed yet, and
+// no other link to it may be followed afterwards.
+unsafe fn free<N: HeapNode>(ptr: NonNull<N>) -> N {
+    let node = *unsafe { Box::from_raw(ptr.as_ptr()) };
+    node.handle().point_to::<N>(None);
+    node
+}
+
+// Free every node of the trees, without recursing so that a degenerate tree
+// doesn't blow the stack.
+//
+// SAFETY: The trees must be owned by a heap which doesn't use them anymore.
+unsafe fn free_trees<N: HeapNode>(roots: impl IntoIterator<Item = NonNull<N>>) {
+    let mut stack = roots.into_iter().collect::<Vec<_>>();
+    while let Some(ptr) = stack.pop() {
+        let node = unsafe { free(ptr) };
+        stack.extend(node.links_down().into_iter().flatten());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_utils::XorShift;
+
+    // Values are kept unique (random high bits, counter low bits), this way
+    // the model knows which handle has been deleted by delete_min.
+    fn unique_val(rng: &mut XorShift, counter: &mut i64) -> i64 {
+        *counter += 1;
+        ((rng.below(1000) as i64) << 20) | *counter
+    }
+
+    // Run a random sequence of operations on the heap and on a sorted vector
+    // model, and compare every output. The model holds (value, handle), the
+    // handles of the items coming from a merged heap included.
+    fn check_against_model<H>(new_heap: impl Fn() -> H, seed: u64)
+    where
+        H: MergeableHeap<Item = i64>,
+    {
+        let mut rng = XorShift::new(seed);
+        let mut counter = 0;
+        let mut heap = new_heap();
+        let mut model: Vec<(i64, HeapHandle)> = Vec::new();
+        let mut dead_handles = Vec::new();
+
+        for _ in 0..2_000 {
+            match rng.below(10) {
+                0..=3 => {
+                    let val = unique_val(&mut rng, &mut counter);
+                    let handle = heap.insert(val);
+                    model.push((val, handle));
+                }
+                4 | 5 => {
+                    model.sort_by_key(|(val, _)| *val);
+                    let expected = if model.is_empty() {
+                        None
+                    } else {
+                        Some(model.remove(0))
+                    };
+                    assert_eq!(expected.as_ref().map(|(val, _)| *val), heap.delete_min());
+                    dead_handles.extend(expected.map(|(_, handle)| handle));
+                }
+                6 | 7 if !model.is_empty() => {
+                    let pos = rng.below(model.len() as u64) as usize;
+                    let (val, handle) = &model[pos];
+                    assert_eq!(Ok(val), heap.get(handle));
+                    let lower = ((val >> 20) - rng.below(500) as i64) << 20;
+                    let new_val = lower | (val & 0xFFFFF);
+                    assert_eq!(Ok(()), heap.decrease_key(handle, new_val));
+                    assert_eq!(
+                        Err(HeapErr::KeyNotDecreased),
+                        heap.decrease_key(handle, new_val + (1 << 20))
+                    );
+                    model[pos].0 = new_val;
+                }
+                8 => {
+                    let mut other = new_heap();
+                    for _ in 0..rng.below(20) {
+                        let val = unique_val(&mut rng, &mut counter);
+                        model.push((val, other.insert(val)));
+                    }
+                    heap.merge(other);
+                }
+                _ => {
+                    let expected = model.iter().map(|(val, _)| val).min();
+                    assert_eq!(expected, heap.find_min());
+                }
+            }
+            assert_eq!(model.len(), heap.len());
+            assert_eq!(model.is_empty(), heap.is_empty());
+        }
+
+        for handle in &dead_handles {
+            assert_eq!(Err(HeapErr::StaleHandle), heap.get(handle));
+            assert_eq!(Err(HeapErr::StaleHandle), heap.decrease_key(handle, 0));
+        }
+        model.sort_by_key(|(val, _)| *val);
+        for (val, _) in model {
+            assert_eq!(Some(val), heap.delete_min());
+        }
+        assert_eq!(None, heap.delete_min());
+    }
+
+    #[test]
+    fn leftist_heap_against_model() {
+        (1..=5).for_each(|seed| check_against_model(LeftistHeap::new, seed));
+    }
+
+    #[test]
+    fn binomial_heap_against_model() {
+        (1..=5).for_each(|seed| check_against_model(BinomialHeap::new, seed));
+    }
+
+    #[test]
+    fn pairing_heap_against_model() {
+        (1..=5).for_each(|seed| check_against_model(PairingHeap::new, seed));
+    }
+
+    #[test]
+    fn fibonacci_heap_against_model() {
+        (1..=5).for_each(|seed| check_against_model(FibonacciHeap::new, seed));
+    }
+
+    #[test]
+    fn heap_handle_from_another_heap() {
+        let mut heap = PairingHeap::new();
+        let mut other = PairingHeap::new();
+        heap.insert(1);
+        let handle = other.insert(1);
+        assert_eq!(Err(HeapErr::StaleHandle), heap.get(&handle));
+    }
+
+    #[test]
+    fn heap_handle_follows_merge() {
+        let mut heap = FibonacciHeap::new();
+        let mut other = FibonacciHeap::new();
+        let mut third = FibonacciHeap::new();
+        heap.insert(5);
+        let handle = third.insert(7);
+        other.merge(third);
+        heap.merge(other);
+        assert_eq!(Ok(&7), heap.get(&handle));
+        heap.decrease_key(&handle, 1).unwrap();
+        assert_eq!(Some(1), heap.delete_min());
+        assert_eq!(Err(HeapErr::StaleHandle), heap.get(&handle));
+    }
+
+    #[test]
+    fn heap_handle_of_dropped_heap() {
+        let mut heap = LeftistHeap::new();
+        let handle = heap.insert(1);
+        let clone = handle.clone();
+        assert_eq!(handle, clone);
+        drop(heap);
+        let mut heap = LeftistHeap::new();
+        heap.insert(1);
+        assert_eq!(Err(HeapErr::StaleHandle), heap.get(&clone));
+    }
+
+    #[test]
+    fn heap_merge_long_chain() {
+        // merge only links roots, and dropping a deep tree doesn't recurse
+        let mut heap = PairingHeap::new();
+        for val in 0..100_000 {
+            let mut other = PairingHeap::new();
+            other.insert(val);
+            heap.merge(other);
+        }
+        assert_eq!(100_000, heap.len());
+        assert_eq!(Some(0), heap.delete_min());
+    }
+}
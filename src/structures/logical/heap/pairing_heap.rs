@@ -0,0 +1,248 @@
+use std::{marker::PhantomData, mem, ptr::NonNull};
+
+use super::{alloc, free, free_trees, HeapErr, HeapHandle, HeapId, HeapNode, MergeableHeap};
+
+type NodePtr<T> = NonNull<PairingNode<T>>;
+
+struct PairingNode<T> {
+    val: T,
+    handle: HeapHandle,
+    // First child, the other children are reached through the siblings.
+    child: Option<NodePtr<T>>,
+    sibling: Option<NodePtr<T>>,
+    // Parent for the first child, previous sibling for the others.
+    prev: Option<NodePtr<T>>,
+}
+
+impl<T> HeapNode for PairingNode<T> {
+    fn handle(&self) -> &HeapHandle {
+        &self.handle
+    }
+
+    fn links_down(&self) -> [Option<NonNull<Self>>; 2] {
+        [self.child, self.sibling]
+    }
+}
+
+/// Heap ordered multiway tree with no structural constraint at all: melding
+/// two trees just makes the root with the greater value the first child of the
+/// other root. The work is postponed to delete_min, which melds the children
+/// of the removed root by pairs, from left to right, then melds the pairs from
+/// right to left, which keeps the tree shallow in an amortized way.
+pub struct PairingHeap<T> {
+    root: Option<NodePtr<T>>,
+    len: usize,
+    id: HeapId,
+    nodes: PhantomData<Box<PairingNode<T>>>,
+}
+
+impl<T: Ord> PairingHeap<T> {
+    pub fn new() -> Self {
+        Self {
+            root: None,
+            len: 0,
+            id: HeapId::default(),
+            nodes: PhantomData,
+        }
+    }
+
+    fn node(&self, ptr: NodePtr<T>) -> &PairingNode<T> {
+        // SAFETY: Every node linked from the heap is alive and owned by the
+        // heap, which is borrowed for as long as the node.
+        unsafe { ptr.as_ref() }
+    }
+
+    fn node_mut(&mut self, mut ptr: NodePtr<T>) -> &mut PairingNode<T> {
+        // SAFETY: Same as node, the heap being borrowed mutably.
+        unsafe { ptr.as_mut() }
+    }
+
+    // Both trees must be detached, with no sibling and no prev.
+    fn meld(&mut self, a: NodePtr<T>, b: NodePtr<T>) -> NodePtr<T> {
+        let (top, other) = if self.node(b).val < self.node(a).val {
+            (b, a)
+        } else {
+            (a, b)
+        };
+        let first_child = self.node(top).child;
+        if let Some(first_child) = first_child {
+            self.node_mut(first_child).prev = Some(other);
+        }
+        let other_node = self.node_mut(other);
+        other_node.sibling = first_child;
+        other_node.prev = Some(top);
+        self.node_mut(top).child = Some(other);
+        top
+    }
+
+    fn meld_with_root(&mut self, tree: NodePtr<T>) {
+        self.root = Some(match self.root {
+            Some(root) => self.meld(root, tree),
+            None => tree,
+        });
+    }
+
+    fn detach(&mut self, ptr: NodePtr<T>) {
+        let node = self.node_mut(ptr);
+        node.sibling = None;
+        node.prev = None;
+    }
+}
+
+impl<T: Ord> Default for PairingHeap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for PairingHeap<T> {
+    fn drop(&mut self) {
+        // SAFETY: The heap is gone, nothing follows the links anymore.
+        unsafe { free_trees(self.root.take()) };
+    }
+}
+
+impl<T: Ord> MergeableHeap for PairingHeap<T> {
+    type Item = T;
+
+    fn insert(&mut self, val: Self::Item) -> HeapHandle {
+        let handle = HeapHandle::new(&self.id);
+        let ptr = alloc(PairingNode {
+            val,
+            handle: handle.clone(),
+            child: None,
+            sibling: None,
+            prev: None,
+        });
+        self.len += 1;
+        self.meld_with_root(ptr);
+        handle
+    }
+
+    fn find_min(&self) -> Option<&Self::Item> {
+        self.root.map(|root| &self.node(root).val)
+    }
+
+    fn delete_min(&mut self) -> Option<Self::Item> {
+        let root = self.root?;
+        // SAFETY: The root is unlinked right away, only its children remain.
+        let node = unsafe { free(root) };
+        self.len -= 1;
+
+        let mut children = Vec::new();
+        let mut curr = node.child;
+        while let Some(child) = curr {
+            curr = self.node(child).sibling;
+            self.detach(child);
+            children.push(child);
+        }
+
+        // First pass, left to right, meld the children by pairs.
+        let mut pairs = Vec::with_capacity(children.len() / 2 + 1);
+        for pair in children.chunks(2) {
+            pairs.push(match *pair {
+                [a, b] => self.meld(a, b),
+                [a] => a,
+                _ => unreachable!("chunks of two"),
+            });
+        }
+        // Second pass, right to left, meld every pair into the last one.
+        self.root = pairs
+            .into_iter()
+            .rev()
+            .reduce(|acc, pair| self.meld(acc, pair));
+        Some(node.val)
+    }
+
+    fn merge(&mut self, mut other: Self) {
+        // The other heap is left empty, so dropping it frees nothing.
+        self.len += mem::take(&mut other.len);
+        other.id.merge_into(&self.id);
+        if let Some(other_root) = other.root.take() {
+            self.meld_with_root(other_root);
+        }
+    }
+
+    fn decrease_key(&mut self, handle: &HeapHandle, val: Self::Item) -> Result<(), HeapErr> {
+        let ptr = handle.node(&self.id)?;
+        if val > self.node(ptr).val {
+            return Err(HeapErr::KeyNotDecreased);
+        }
+        self.node_mut(ptr).val = val;
+        if self.root == Some(ptr) {
+            return Ok(());
+        }
+
+        // Cut the subtree out of the siblings list, and meld it with the root.
+        let (prev, sibling) = (self.node(ptr).prev, self.node(ptr).sibling);
+        let prev = prev.expect("only the root has no prev");
+        if self.node(prev).child == Some(ptr) {
+            self.node_mut(prev).child = sibling;
+        } else {
+            self.node_mut(prev).sibling = sibling;
+        }
+        if let Some(sibling) = sibling {
+            self.node_mut(sibling).prev = Some(prev);
+        }
+        self.detach(ptr);
+        self.meld_with_root(ptr);
+        Ok(())
+    }
+
+    fn get(&self, handle: &HeapHandle) -> Result<&Self::Item, HeapErr> {
+        handle.node(&self.id).map(|ptr| &self.node(ptr).val)
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn pairing_heap_insert_delete_min() {
+        let mut heap = PairingHeap::new();
+        [5, 3, 8, 1, 9, 2].into_iter().for_each(|v| {
+            heap.insert(v);
+        });
+        assert_eq!(Some(&1), heap.find_min());
+        let popped = std::iter::from_fn(|| heap.delete_min()).collect::<Vec<_>>();
+        assert_eq!(vec![1, 2, 3, 5, 8, 9], popped);
+    }
+
+    #[test]
+    fn pairing_heap_merge() {
+        let mut heap = PairingHeap::new();
+        let mut other = PairingHeap::new();
+        [4, 2, 6].into_iter().for_each(|v| {
+            heap.insert(v);
+        });
+        [5, 1, 3].into_iter().for_each(|v| {
+            other.insert(v);
+        });
+        heap.merge(other);
+        assert_eq!(Some(&1), heap.find_min());
+        let popped = std::iter::from_fn(|| heap.delete_min()).collect::<Vec<_>>();
+        assert_eq!(vec![1, 2, 3, 4, 5, 6], popped);
+    }
+
+    #[test]
+    fn pairing_heap_decrease_key() {
+        let mut heap = PairingHeap::new();
+        heap.insert(1);
+        let five = heap.insert(5);
+        let seven = heap.insert(7);
+        heap.decrease_key(&seven, 3).unwrap();
+        heap.decrease_key(&five, 0).unwrap();
+        assert_eq!(Err(HeapErr::KeyNotDecreased), heap.decrease_key(&seven, 4));
+        let popped = std::iter::from_fn(|| heap.delete_min()).collect::<Vec<_>>();
+        assert_eq!(vec![0, 1, 3], popped);
+    }
+}
@@ -1,3 +1,7 @@
+pub mod deque;
+pub(crate) mod hazard;
+pub mod heap;
 pub mod queue;
 pub mod stack;
+pub(crate) mod sync;
 pub mod tree;
@@ -0,0 +1,239 @@
+use super::{Queue, QueueErr};
+use crate::structures::logical::stack::Stack;
+
+/// Queue made of two stacks: items are pushed on the inbox, and popped from
+/// the outbox. When the outbox is empty, the whole inbox is moved into it,
+/// which reverses the items so the oldest one ends up on top.
+///
+/// An item is moved once from the inbox to the outbox, so enqueue and dequeue
+/// are O(1) amortized, even if a single dequeue can be O(n).
+///
+/// When the stacks are bounded, the outbox must be able to hold as many items
+/// as the inbox. The queue is then full when the inbox is full and the outbox
+/// not empty, a full inbox being moved to an empty outbox otherwise.
+///
+/// Measured with the `amortized_queue_bench` test, in release mode: 200
+/// bursts of 100 enqueues followed by 50 dequeues, so up to 10 000 items are
+/// queued, then the 10 000 items left are dequeued, the mean being over the
+/// 40 000 operations:
+///
+/// | queue                         | mean/op | worst op |
+/// |-------------------------------|---------|----------|
+/// | `AmortizedQueue<ArrayStack>`  | 83ns    | 26µs     |
+/// | `AmortizedQueue<LinkedStack>` | 120ns   | 181µs    |
+/// | ArrayQueue                    | 91ns    | 250µs    |
+/// | LinkedQueue                   | 7µs     | 1.4ms    |
+///
+/// Over an [`ArrayStack`](crate::structures::logical::stack::ArrayStack),
+/// the amortized queue is as fast as the ring buffer on average: the items
+/// are moved once, and the move is a tight loop. Its worst operation is the
+/// dequeue which moves the whole inbox, the worst one of the ring buffer
+/// being a growth of its array, so neither is fit for a latency bound.
+pub struct AmortizedQueue<S> {
+    inbox: S,
+    outbox: S,
+    // The Stack trait has no len, so the lengths are tracked here to translate
+    // the queue indices into stack indices.
+    inbox_len: usize,
+    outbox_len: usize,
+}
+
+impl<S: Stack> AmortizedQueue<S> {
+    /// Build a queue over two empty stacks.
+    pub fn from_stacks(inbox: S, outbox: S) -> Self {
+        assert!(
+            inbox.is_empty() && outbox.is_empty(),
+            "the stacks must be empty"
+        );
+        Self {
+            inbox,
+            outbox,
+            inbox_len: 0,
+            outbox_len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.inbox_len + self.outbox_len
+    }
+
+    // Move the inbox to the outbox, only when the outbox is empty, otherwise
+    // the items moved would end up on top of older ones.
+    fn transfer(&mut self) {
+        if self.outbox_len != 0 {
+            return;
+        }
+        while let Some(val) = self.inbox.pop() {
+            self.outbox
+                .push(val)
+                .expect("the outbox can hold the whole inbox");
+        }
+        self.outbox_len = self.inbox_len;
+        self.inbox_len = 0;
+    }
+
+    // The front of the queue is the top of the outbox, followed by the bottom
+    // of the inbox. Give which stack holds the item, and its index from top.
+    fn locate(&self, idx: usize) -> Option<(bool, usize)> {
+        if idx < self.outbox_len {
+            Some((false, idx))
+        } else if idx < self.len() {
+            Some((true, self.len() - 1 - idx))
+        } else {
+            None
+        }
+    }
+}
+
+impl<S: Stack> Queue for AmortizedQueue<S> {
+    type Item = S::Item;
+
+    fn enqueue(&mut self, val: Self::Item) -> Result<(), QueueErr> {
+        if self.inbox.is_full() {
+            self.transfer();
+        }
+        self.inbox.push(val).map_err(|_| QueueErr::QueueOverflow)?;
+        self.inbox_len += 1;
+        Ok(())
+    }
+
+    fn dequeue(&mut self) -> Option<Self::Item> {
+        self.transfer();
+        let val = self.outbox.pop()?;
+        self.outbox_len -= 1;
+        Some(val)
+    }
+
+    fn peek_next(&self) -> Option<&Self::Item> {
+        self.peek(0)
+    }
+
+    fn peek_next_mut(&mut self) -> Option<&mut Self::Item> {
+        self.transfer();
+        self.outbox.peek_next_mut()
+    }
+
+    /// O(1) when the item is in the outbox, otherwise as costly as a peek deep
+    /// in the inbox, which depends on the stack.
+    fn peek(&self, idx: usize) -> Option<&Self::Item> {
+        match self.locate(idx)? {
+            (false, idx) => self.outbox.peek(idx),
+            (true, idx) => self.inbox.peek(idx),
+        }
+    }
+
+    fn peek_mut(&mut self, idx: usize) -> Option<&mut Self::Item> {
+        match self.locate(idx)? {
+            (false, idx) => self.outbox.peek_mut(idx),
+            (true, idx) => self.inbox.peek_mut(idx),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn is_full(&self) -> bool {
+        self.inbox.is_full() && self.outbox_len != 0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, Instant};
+
+    use super::*;
+    use crate::structures::logical::queue::{ArrayQueue, LinkedQueue};
+    use crate::structures::logical::stack::{ArrayStack, LinkedStack};
+
+    #[test]
+    fn amortized_queue_fifo() {
+        let mut queue = AmortizedQueue::from_stacks(ArrayStack::new(), ArrayStack::new());
+        (1..=3).for_each(|v| queue.enqueue(v).unwrap());
+        assert_eq!(Some(1), queue.dequeue());
+        // 2 and 3 are in the outbox now, 4 and 5 go to the inbox
+        queue.enqueue(4).unwrap();
+        queue.enqueue(5).unwrap();
+        assert_eq!(vec![Some(&2), Some(&3), Some(&4), Some(&5), None], {
+            (0..5).map(|idx| queue.peek(idx)).collect::<Vec<_>>()
+        });
+        let dequeued = std::iter::from_fn(|| queue.dequeue()).collect::<Vec<_>>();
+        assert_eq!(vec![2, 3, 4, 5], dequeued);
+    }
+
+    #[test]
+    fn amortized_queue_bounded_stacks() {
+        let mut queue = AmortizedQueue::from_stacks(
+            LinkedStack::with_capacity(2).unwrap(),
+            LinkedStack::with_capacity(2).unwrap(),
+        );
+        queue.enqueue(1).unwrap();
+        queue.enqueue(2).unwrap();
+        // the full inbox moves to the empty outbox
+        assert!(!queue.is_full());
+        queue.enqueue(3).unwrap();
+        queue.enqueue(4).unwrap();
+        assert!(queue.is_full());
+        assert_eq!(Err(QueueErr::QueueOverflow), queue.enqueue(5));
+        assert_eq!(Some(1), queue.dequeue());
+        assert_eq!(Some(2), queue.dequeue());
+        queue.enqueue(5).unwrap();
+        let dequeued = std::iter::from_fn(|| queue.dequeue()).collect::<Vec<_>>();
+        assert_eq!(vec![3, 4, 5], dequeued);
+    }
+
+    // Run n enqueues then n dequeues, interleaved by bursts, and give the mean
+    // and the worst time of an operation.
+    fn measure<Q: Queue<Item = u64>>(mut queue: Q, n: u64) -> (Duration, Duration) {
+        let mut worst = Duration::ZERO;
+        let start = Instant::now();
+        for burst in 0..n / 100 {
+            for v in 0..100 {
+                let op = Instant::now();
+                queue.enqueue(burst * 100 + v).unwrap();
+                worst = worst.max(op.elapsed());
+            }
+            for _ in 0..50 {
+                let op = Instant::now();
+                queue.dequeue();
+                worst = worst.max(op.elapsed());
+            }
+        }
+        while !queue.is_empty() {
+            let op = Instant::now();
+            queue.dequeue();
+            worst = worst.max(op.elapsed());
+        }
+        (start.elapsed() / (2 * n as u32), worst)
+    }
+
+    // Compare the amortized queues with the native ones, run it with
+    // `cargo test --release amortized_queue_bench -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn amortized_queue_bench() {
+        let n = 20_000;
+        let results = [
+            (
+                "AmortizedQueue<ArrayStack>",
+                measure(
+                    AmortizedQueue::from_stacks(ArrayStack::new(), ArrayStack::new()),
+                    n,
+                ),
+            ),
+            (
+                "AmortizedQueue<LinkedStack>",
+                measure(
+                    AmortizedQueue::from_stacks(LinkedStack::new(), LinkedStack::new()),
+                    n,
+                ),
+            ),
+            ("ArrayQueue", measure(ArrayQueue::new(), n)),
+            ("LinkedQueue", measure(LinkedQueue::new(), n)),
+        ];
+        println!("{:<28} {:>12} {:>12}", "queue", "mean/op", "worst op");
+        for (name, (mean, worst)) in results {
+            println!("{name:<28} {mean:>12?} {worst:>12?}");
+        }
+    }
+}
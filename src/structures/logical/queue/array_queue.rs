@@ -1,37 +1,121 @@
-use std::ops::{Deref, DerefMut};
-
-use super::{Queue, QueueErr};
+use super::{OverflowPolicy, Queue, QueueErr};
+use crate::structures::physical::array::Array;
 
+/// Queue stored in an [`Array`] used as a ring buffer: `head` points to the
+/// front item and `tail` to the slot where the next item will be written, both
+/// wrapping around the end of the array. This way no item has to be shifted,
+/// enqueue and dequeue are O(1).
+///
+/// The queue is bounded when built with [`ArrayQueue::with_capacity`], or
+/// grows on demand when built with [`ArrayQueue::new`], in which case the items
+/// are moved back to the beginning of a larger array when every slot is used.
 pub struct ArrayQueue<T> {
-    inner_queue: Box<[Option<T>]>,
+    inner_queue: Array<Option<T>>,
+    head: usize,
+    tail: usize,
     len: usize,
-    cap: usize,
+    cap: Option<usize>,
+    policy: OverflowPolicy,
 }
 
 impl<T> ArrayQueue<T> {
+    /// Create an unbounded queue, it is never full.
+    pub fn new() -> Self {
+        Self {
+            inner_queue: Array::new(),
+            head: 0,
+            tail: 0,
+            len: 0,
+            cap: None,
+            policy: OverflowPolicy::default(),
+        }
+    }
+
     pub fn with_capacity(cap: usize) -> Result<Self, QueueErr> {
+        Self::with_policy(cap, OverflowPolicy::default())
+    }
+
+    /// Create a bounded queue, which handles the enqueuing of an item while it
+    /// is full according to the given policy.
+    pub fn with_policy(cap: usize, policy: OverflowPolicy) -> Result<Self, QueueErr> {
         if cap == 0 {
             return Err(QueueErr::ZeroCapacityNotAllowed);
         }
 
-        let mut inner_queue = Vec::new();
+        let mut inner_queue = Array::new();
         (0..cap).for_each(|_| {
             inner_queue.push(None);
         });
 
         Ok(Self {
-            inner_queue: inner_queue.into_boxed_slice(),
+            inner_queue,
+            head: 0,
+            tail: 0,
             len: 0,
-            cap,
+            cap: Some(cap),
+            policy,
         })
     }
 
-    pub fn iter(&self) -> std::slice::Iter<'_, Option<T>> {
-        self.inner_queue.iter()
+    /// Maximum number of items the queue can hold, `usize::MAX` when the queue
+    /// is unbounded.
+    pub fn cap(&self) -> usize {
+        self.cap.unwrap_or(usize::MAX)
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_bounded(&self) -> bool {
+        self.cap.is_some()
+    }
+
+    pub fn policy(&self) -> OverflowPolicy {
+        self.policy
+    }
+
+    /// Iterate over the live items, from the front to the back of the queue.
+    pub fn iter(&self) -> ArrayQueueIter<'_, T> {
+        let (wrapped, front) = self.inner_queue.split_at(self.head);
+        ArrayQueueIter {
+            inner: front.iter().chain(wrapped.iter()).take(self.len),
+        }
+    }
+
+    /// Iterate mutably over the live items, from the front to the back of the
+    /// queue.
+    pub fn iter_mut(&mut self) -> ArrayQueueIterMut<'_, T> {
+        let (wrapped, front) = self.inner_queue.split_at_mut(self.head);
+        ArrayQueueIterMut {
+            inner: front.iter_mut().chain(wrapped.iter_mut()).take(self.len),
+        }
+    }
+
+    // Number of slots in the ring, which is the capacity for a bounded queue.
+    fn slots(&self) -> usize {
+        self.inner_queue.len()
     }
 
-    pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, Option<T>> {
-        self.inner_queue.iter_mut()
+    // Translate an index relative to the front of the queue into a position in
+    // the inner array.
+    fn slot(&self, idx: usize) -> Option<usize> {
+        if idx < self.len {
+            Some((self.head + idx) % self.slots())
+        } else {
+            None
+        }
+    }
+
+    // Only called when every slot is used. The items are rotated so the front
+    // is back at the first slot, then as many free slots are appended, letting
+    // the array double its own capacity.
+    fn grow(&mut self) {
+        self.inner_queue.rotate_left(self.head);
+        let added_slots = self.slots().max(1);
+        (0..added_slots).for_each(|_| self.inner_queue.push(None));
+        self.head = 0;
+        self.tail = self.len;
     }
 }
 
@@ -40,43 +124,52 @@ impl<T> Queue for ArrayQueue<T> {
 
     fn enqueue(&mut self, val: Self::Item) -> Result<(), QueueErr> {
         if self.is_full() {
-            Err(QueueErr::QueueOverflow)
-        } else {
-            self.inner_queue[self.len] = Some(val);
-            self.len += 1;
-            Ok(())
+            match self.policy {
+                OverflowPolicy::Error => return Err(QueueErr::QueueOverflow),
+                OverflowPolicy::Block => return Err(QueueErr::WouldBlock),
+                OverflowPolicy::OverwriteOldest => {
+                    self.dequeue();
+                }
+            }
+        }
+
+        if self.len == self.slots() {
+            self.grow();
         }
+
+        self.inner_queue[self.tail] = Some(val);
+        self.tail = (self.tail + 1) % self.slots();
+        self.len += 1;
+        Ok(())
     }
 
     fn dequeue(&mut self) -> Option<Self::Item> {
         if self.is_empty() {
             None
         } else {
-            let prev_head = self.inner_queue[0].take();
+            let prev_head = self.inner_queue[self.head].take();
+            self.head = (self.head + 1) % self.slots();
             self.len -= 1;
-            // downside here: shifting happen even if all element are None
-            // in case of a queue with one element left
-            for i in 0..self.len {
-                self.inner_queue.swap(i, i + 1);
-            }
             prev_head
         }
     }
 
     fn peek_next(&self) -> Option<&Self::Item> {
-        self.inner_queue[0].as_ref()
+        self.peek(0)
     }
 
     fn peek_next_mut(&mut self) -> Option<&mut Self::Item> {
-        self.inner_queue[0].as_mut()
+        self.peek_mut(0)
     }
 
     fn peek(&self, idx: usize) -> Option<&Self::Item> {
-        self.inner_queue[idx].as_ref()
+        self.slot(idx)
+            .and_then(|slot| self.inner_queue[slot].as_ref())
     }
 
     fn peek_mut(&mut self, idx: usize) -> Option<&mut Self::Item> {
-        self.inner_queue[idx].as_mut()
+        self.slot(idx)
+            .and_then(|slot| self.inner_queue[slot].as_mut())
     }
 
     fn is_empty(&self) -> bool {
@@ -84,7 +177,45 @@ impl<T> Queue for ArrayQueue<T> {
     }
 
     fn is_full(&self) -> bool {
-        self.len == self.cap
+        self.cap.is_some_and(|cap| self.len == cap)
+    }
+}
+
+type RingIter<'a, T> =
+    std::iter::Take<std::iter::Chain<std::slice::Iter<'a, T>, std::slice::Iter<'a, T>>>;
+type RingIterMut<'a, T> =
+    std::iter::Take<std::iter::Chain<std::slice::IterMut<'a, T>, std::slice::IterMut<'a, T>>>;
+
+pub struct ArrayQueueIter<'a, T> {
+    inner: RingIter<'a, Option<T>>,
+}
+
+impl<'a, T> Iterator for ArrayQueueIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Every slot between head and tail holds a value.
+        self.inner.next().and_then(|slot| slot.as_ref())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+pub struct ArrayQueueIterMut<'a, T> {
+    inner: RingIterMut<'a, Option<T>>,
+}
+
+impl<'a, T> Iterator for ArrayQueueIterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().and_then(|slot| slot.as_mut())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
     }
 }
 
@@ -97,7 +228,7 @@ mod test {
         let queue = ArrayQueue::<i32>::with_capacity(5);
         assert!(queue.is_ok());
         let queue = queue.unwrap();
-        assert_eq!(5, queue.cap);
+        assert_eq!(5, queue.cap());
         assert_eq!(0, queue.len);
     }
 
@@ -193,9 +324,9 @@ mod test {
         let _ = queue.enqueue(2);
         let _ = queue.enqueue(3);
         let mut iter = queue.iter();
-        assert_eq!(Some(&Some(1)), iter.next());
-        assert_eq!(Some(&Some(2)), iter.next());
-        assert_eq!(Some(&Some(3)), iter.next());
+        assert_eq!(Some(&1), iter.next());
+        assert_eq!(Some(&2), iter.next());
+        assert_eq!(Some(&3), iter.next());
         assert_eq!(None, iter.next());
     }
 
@@ -206,9 +337,91 @@ mod test {
         let _ = queue.enqueue(2);
         let _ = queue.enqueue(3);
         let mut iter = queue.iter_mut();
-        assert_eq!(Some(&mut Some(1)), iter.next());
-        assert_eq!(Some(&mut Some(2)), iter.next());
-        assert_eq!(Some(&mut Some(3)), iter.next());
+        assert_eq!(Some(&mut 1), iter.next());
+        assert_eq!(Some(&mut 2), iter.next());
+        assert_eq!(Some(&mut 3), iter.next());
         assert_eq!(None, iter.next());
     }
+
+    #[test]
+    fn array_queue_wrap_around() {
+        let mut queue = ArrayQueue::<i32>::with_capacity(3).unwrap();
+        let _ = queue.enqueue(1);
+        let _ = queue.enqueue(2);
+        let _ = queue.enqueue(3);
+        assert_eq!(Some(1), queue.dequeue());
+        assert_eq!(Some(2), queue.dequeue());
+        let _ = queue.enqueue(4);
+        let _ = queue.enqueue(5);
+        assert!(queue.is_full());
+        // the tail wrapped around, 4 and 5 are stored before 3 in the slice
+        assert_eq!(Some(4), queue.inner_queue[0]);
+        assert_eq!(Some(&3), queue.peek_next());
+        assert_eq!(Some(&5), queue.peek(2));
+        assert_eq!(None, queue.peek(3));
+        assert_eq!(vec![&3, &4, &5], queue.iter().collect::<Vec<_>>());
+        assert_eq!(Some(3), queue.dequeue());
+        assert_eq!(Some(4), queue.dequeue());
+        assert_eq!(Some(5), queue.dequeue());
+        assert_eq!(None, queue.dequeue());
+    }
+
+    #[test]
+    fn array_queue_iter_live_items_only() {
+        let mut queue = ArrayQueue::<i32>::with_capacity(4).unwrap();
+        let _ = queue.enqueue(1);
+        let _ = queue.enqueue(2);
+        let _ = queue.dequeue();
+        assert_eq!(vec![&2], queue.iter().collect::<Vec<_>>());
+        queue.iter_mut().for_each(|v| *v *= 10);
+        assert_eq!(Some(&20), queue.peek_next());
+    }
+
+    #[test]
+    fn array_queue_peek_out_of_bounds() {
+        let queue = ArrayQueue::<i32>::with_capacity(2).unwrap();
+        assert_eq!(None, queue.peek_next());
+        assert_eq!(None, queue.peek(5));
+    }
+
+    #[test]
+    fn array_queue_new_unbounded() {
+        let mut queue = ArrayQueue::<i32>::new();
+        assert!(!queue.is_bounded());
+        assert_eq!(usize::MAX, queue.cap());
+        (0..10).for_each(|i| queue.enqueue(i).unwrap());
+        assert!(!queue.is_full());
+        assert_eq!(10, queue.len());
+        assert_eq!(Some(0), queue.dequeue());
+        assert_eq!(Some(&9), queue.peek(8));
+    }
+
+    #[test]
+    fn array_queue_grow_keeps_order() {
+        let mut queue = ArrayQueue::<i32>::new();
+        (0..4).for_each(|i| queue.enqueue(i).unwrap());
+        assert_eq!(Some(0), queue.dequeue());
+        assert_eq!(Some(1), queue.dequeue());
+        // the tail wraps around the 4 slots, then the ring has to grow
+        (4..7).for_each(|i| queue.enqueue(i).unwrap());
+        assert_eq!(8, queue.inner_queue.len());
+        assert_eq!(vec![&2, &3, &4, &5, &6], queue.iter().collect::<Vec<_>>());
+        assert_eq!(Some(2), queue.dequeue());
+    }
+
+    #[test]
+    fn array_queue_overwrite_oldest() {
+        let mut queue = ArrayQueue::<i32>::with_policy(3, OverflowPolicy::OverwriteOldest).unwrap();
+        (1..=5).for_each(|i| queue.enqueue(i).unwrap());
+        assert!(queue.is_full());
+        assert_eq!(vec![&3, &4, &5], queue.iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn array_queue_block_policy() {
+        let mut queue = ArrayQueue::<i32>::with_policy(1, OverflowPolicy::Block).unwrap();
+        queue.enqueue(1).unwrap();
+        assert_eq!(Err(QueueErr::WouldBlock), queue.enqueue(2));
+        assert_eq!(OverflowPolicy::Block, queue.policy());
+    }
 }
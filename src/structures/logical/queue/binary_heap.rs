@@ -0,0 +1,280 @@
+use std::{
+    cmp::Ordering,
+    ops::{Deref, DerefMut},
+};
+
+use crate::structures::physical::array::Array;
+
+/// Priority queue stored as a complete binary tree in an [`Array`]: the
+/// children of the item at index `i` are at `2i + 1` and `2i + 2`, and every
+/// item comes before its children according to the comparator. So the item
+/// with the highest priority is always at the root.
+///
+/// The comparator tells which item comes out first: when `cmp(a, b)` is
+/// `Ordering::Less`, `a` is popped before `b`. [`BinaryHeap::new_min`] and
+/// [`BinaryHeap::new_max`] build the usual min and max heaps.
+///
+/// Push and pop are O(log n), peek is O(1). Zero sized items are rejected,
+/// as the [`Array`] doesn't store them.
+pub struct BinaryHeap<T> {
+    data: Array<T>,
+    cmp: fn(&T, &T) -> Ordering,
+}
+
+impl<T: Ord> BinaryHeap<T> {
+    /// Heap popping the smallest item first.
+    pub fn new_min() -> Self {
+        Self::with_comparator(T::cmp)
+    }
+
+    /// Heap popping the greatest item first.
+    pub fn new_max() -> Self {
+        Self::with_comparator(|a, b| b.cmp(a))
+    }
+}
+
+impl<T> BinaryHeap<T> {
+    /// # Panics
+    ///
+    /// Panics when `T` is zero sized.
+    pub fn with_comparator(cmp: fn(&T, &T) -> Ordering) -> Self {
+        assert_ne!(size_of::<T>(), 0, "a heap can't hold zero sized items");
+        Self {
+            data: Array::new(),
+            cmp,
+        }
+    }
+
+    /// Build a heap from all the items of the iterator in O(n), instead of the
+    /// O(n log n) needed to push them one by one. Items are first stored as
+    /// they come, then every parent is sifted down, starting from the last one:
+    /// most of the nodes are close to the leaves, so they move only a little.
+    pub fn from_iter_with<I>(iter: I, cmp: fn(&T, &T) -> Ordering) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut heap = Self::with_comparator(cmp);
+        iter.into_iter().for_each(|item| heap.data.push(item));
+        let len = heap.len();
+        (0..len / 2).rev().for_each(|idx| heap.sift_down(idx, len));
+        heap
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn push(&mut self, item: T) {
+        self.data.push(item);
+        self.sift_up(self.len() - 1);
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        if self.is_empty() {
+            return None;
+        }
+        // The root is swapped with the last leaf, which can be removed without
+        // breaking the tree shape, then the new root is sifted down.
+        let last = self.len() - 1;
+        self.data.swap(0, last);
+        let top = self.data.pop();
+        self.sift_down(0, self.len());
+        top
+    }
+
+    pub fn peek(&self) -> Option<&T> {
+        self.data.first()
+    }
+
+    /// Mutable access to the top item. Changing it may break the heap order,
+    /// so the item is sifted down to its right place when the guard is dropped.
+    pub fn peek_mut(&mut self) -> Option<PeekMut<'_, T>> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(PeekMut { heap: self })
+        }
+    }
+
+    /// Consume the heap and return its items in the order they would have been
+    /// popped. The sort is done in place, like a heap sort.
+    pub fn into_sorted_vec(mut self) -> Vec<T> {
+        // Moving the root at the end of the shrinking heap leaves the array
+        // sorted from the lowest to the highest priority, so popping from the
+        // end gives back the items in priority order.
+        for end in (1..self.len()).rev() {
+            self.data.swap(0, end);
+            self.sift_down(0, end);
+        }
+        let mut sorted = Vec::with_capacity(self.len());
+        while let Some(item) = self.data.pop() {
+            sorted.push(item);
+        }
+        sorted
+    }
+
+    fn sift_up(&mut self, mut idx: usize) {
+        while idx > 0 {
+            let parent = (idx - 1) / 2;
+            if (self.cmp)(&self.data[idx], &self.data[parent]) != Ordering::Less {
+                break;
+            }
+            self.data.swap(idx, parent);
+            idx = parent;
+        }
+    }
+
+    // Only the items in `0..len` are considered part of the heap.
+    fn sift_down(&mut self, mut idx: usize, len: usize) {
+        loop {
+            let left = 2 * idx + 1;
+            let right = left + 1;
+            let mut first = idx;
+            if left < len && (self.cmp)(&self.data[left], &self.data[first]) == Ordering::Less {
+                first = left;
+            }
+            if right < len && (self.cmp)(&self.data[right], &self.data[first]) == Ordering::Less {
+                first = right;
+            }
+            if first == idx {
+                break;
+            }
+            self.data.swap(idx, first);
+            idx = first;
+        }
+    }
+}
+
+/// Guard given by [`BinaryHeap::peek_mut`], restores the heap order on drop.
+pub struct PeekMut<'a, T> {
+    heap: &'a mut BinaryHeap<T>,
+}
+
+impl<T> PeekMut<'_, T> {
+    /// Remove the peeked item from the heap.
+    pub fn pop(this: Self) -> T {
+        let item = this.heap.pop();
+        // Nothing left to sift, the heap has just been fixed by pop.
+        std::mem::forget(this);
+        item.expect("peek_mut is only given on a non empty heap")
+    }
+}
+
+impl<T> Deref for PeekMut<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.heap.data[0]
+    }
+}
+
+impl<T> DerefMut for PeekMut<'_, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.heap.data[0]
+    }
+}
+
+impl<T> Drop for PeekMut<'_, T> {
+    fn drop(&mut self) {
+        let len = self.heap.len();
+        self.heap.sift_down(0, len);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    #[should_panic(expected = "zero sized items")]
+    fn binary_heap_rejects_zero_sized_items() {
+        BinaryHeap::<()>::new_min();
+    }
+
+    #[test]
+    fn binary_heap_new() {
+        let heap = BinaryHeap::<i32>::new_min();
+        assert!(heap.is_empty());
+        assert_eq!(None, heap.peek());
+    }
+
+    #[test]
+    fn binary_heap_min_push_pop() {
+        let mut heap = BinaryHeap::new_min();
+        [5, 3, 8, 1, 9, 2].into_iter().for_each(|v| heap.push(v));
+        assert_eq!(6, heap.len());
+        assert_eq!(Some(&1), heap.peek());
+        assert_eq!(Some(1), heap.pop());
+        assert_eq!(Some(2), heap.pop());
+        assert_eq!(Some(3), heap.pop());
+        assert_eq!(Some(5), heap.pop());
+        assert_eq!(Some(8), heap.pop());
+        assert_eq!(Some(9), heap.pop());
+        assert_eq!(None, heap.pop());
+    }
+
+    #[test]
+    fn binary_heap_max_push_pop() {
+        let mut heap = BinaryHeap::new_max();
+        [5, 3, 8, 1, 9, 2].into_iter().for_each(|v| heap.push(v));
+        assert_eq!(Some(&9), heap.peek());
+        assert_eq!(Some(9), heap.pop());
+        assert_eq!(Some(8), heap.pop());
+        assert_eq!(Some(5), heap.pop());
+    }
+
+    #[test]
+    fn binary_heap_custom_comparator() {
+        // pop the longest word first
+        let mut heap = BinaryHeap::with_comparator(|a: &&str, b: &&str| b.len().cmp(&a.len()));
+        heap.push("a");
+        heap.push("abc");
+        heap.push("ab");
+        assert_eq!(Some("abc"), heap.pop());
+        assert_eq!(Some("ab"), heap.pop());
+        assert_eq!(Some("a"), heap.pop());
+    }
+
+    #[test]
+    fn binary_heap_from_iter_with() {
+        let heap = BinaryHeap::from_iter_with([7, 2, 9, 4, 1, 8, 3], i32::cmp);
+        assert_eq!(Some(&1), heap.peek());
+        assert_eq!(vec![1, 2, 3, 4, 7, 8, 9], heap.into_sorted_vec());
+    }
+
+    #[test]
+    fn binary_heap_into_sorted_vec_max() {
+        let mut heap = BinaryHeap::new_max();
+        [4, 1, 3, 2].into_iter().for_each(|v| heap.push(v));
+        assert_eq!(vec![4, 3, 2, 1], heap.into_sorted_vec());
+    }
+
+    #[test]
+    fn binary_heap_peek_mut_sifts_on_drop() {
+        let mut heap = BinaryHeap::from_iter_with([1, 5, 3], i32::cmp);
+        if let Some(mut top) = heap.peek_mut() {
+            *top = 10;
+        }
+        assert_eq!(Some(&3), heap.peek());
+        assert_eq!(vec![3, 5, 10], heap.into_sorted_vec());
+    }
+
+    #[test]
+    fn binary_heap_peek_mut_pop() {
+        let mut heap = BinaryHeap::from_iter_with([1, 5, 3], i32::cmp);
+        let top = heap.peek_mut().unwrap();
+        assert_eq!(1, PeekMut::pop(top));
+        assert_eq!(Some(&3), heap.peek());
+        assert_eq!(2, heap.len());
+    }
+
+    #[test]
+    fn binary_heap_peek_mut_empty() {
+        let mut heap = BinaryHeap::<i32>::new_min();
+        assert!(heap.peek_mut().is_none());
+    }
+}
@@ -0,0 +1,415 @@
+use std::{
+    sync::{Condvar, Mutex, MutexGuard, PoisonError},
+    time::{Duration, Instant},
+};
+
+use super::{ArrayQueue, OverflowPolicy, Queue, QueueErr};
+
+struct BlockingState<T> {
+    queue: ArrayQueue<T>,
+    closed: bool,
+}
+
+/// Bounded queue shared between threads, usually behind an `Arc`. Every
+/// method takes `&self`, the items being protected by a mutex, and two
+/// condition variables wake up the threads waiting for an item or for room.
+///
+/// With [`OverflowPolicy::Block`], which is the policy of
+/// [`BlockingQueue::with_capacity`], enqueuing on a full queue waits until an
+/// item is dequeued. The other policies never wait, as for the single threaded
+/// queues. Dequeuing from an empty queue always waits.
+///
+/// Once closed, items can't be enqueued anymore, and every waiting thread is
+/// woken up. The items left can still be dequeued, after which dequeuing fails
+/// with [`QueueErr::Closed`] instead of waiting, which lets the consumers of a
+/// work queue stop.
+///
+/// The items stay behind the mutex, so no reference to them can be given out,
+/// that's why the [`Queue`] trait and its peek methods are not implemented.
+/// An enqueue which fails gives the item back along with the error, as
+/// [`std::sync::mpsc::SendError`] does.
+pub struct BlockingQueue<T> {
+    state: Mutex<BlockingState<T>>,
+    not_empty: Condvar,
+    not_full: Condvar,
+    cap: usize,
+    policy: OverflowPolicy,
+}
+
+impl<T> BlockingQueue<T> {
+    pub fn with_capacity(cap: usize) -> Result<Self, QueueErr> {
+        Self::with_policy(cap, OverflowPolicy::Block)
+    }
+
+    pub fn with_policy(cap: usize, policy: OverflowPolicy) -> Result<Self, QueueErr> {
+        // The inner queue never sees a full enqueue, the policy is handled
+        // here, the inner one is irrelevant.
+        let queue = ArrayQueue::with_capacity(cap)?;
+        Ok(Self {
+            state: Mutex::new(BlockingState {
+                queue,
+                closed: false,
+            }),
+            not_empty: Condvar::new(),
+            not_full: Condvar::new(),
+            cap,
+            policy,
+        })
+    }
+
+    pub fn cap(&self) -> usize {
+        self.cap
+    }
+
+    pub fn policy(&self) -> OverflowPolicy {
+        self.policy
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().queue.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock().queue.is_empty()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.lock().queue.is_full()
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.lock().closed
+    }
+
+    /// Enqueue an item, waiting for room as long as needed with the Block
+    /// policy.
+    pub fn enqueue(&self, val: T) -> Result<(), (QueueErr, T)> {
+        self.enqueue_until(val, None)
+    }
+
+    /// Same as enqueue, but gives up with [`QueueErr::Timeout`] if there is
+    /// still no room after the timeout.
+    pub fn enqueue_timeout(&self, val: T, timeout: Duration) -> Result<(), (QueueErr, T)> {
+        self.enqueue_until(val, Some(Instant::now() + timeout))
+    }
+
+    /// Enqueue an item without ever waiting, a full queue with the Block
+    /// policy gives [`QueueErr::WouldBlock`].
+    pub fn try_enqueue(&self, val: T) -> Result<(), (QueueErr, T)> {
+        let mut state = self.lock();
+        if state.closed {
+            return Err((QueueErr::Closed, val));
+        }
+        if state.queue.is_full() {
+            match self.policy {
+                OverflowPolicy::Error => return Err((QueueErr::QueueOverflow, val)),
+                OverflowPolicy::Block => return Err((QueueErr::WouldBlock, val)),
+                OverflowPolicy::OverwriteOldest => {
+                    state.queue.dequeue();
+                }
+            }
+        }
+        self.push(state, val);
+        Ok(())
+    }
+
+    /// Dequeue the front item, waiting for one as long as needed.
+    pub fn dequeue(&self) -> Result<T, QueueErr> {
+        self.dequeue_until(None)
+    }
+
+    /// Same as dequeue, but gives up with [`QueueErr::Timeout`] if there is
+    /// still no item after the timeout.
+    pub fn dequeue_timeout(&self, timeout: Duration) -> Result<T, QueueErr> {
+        self.dequeue_until(Some(Instant::now() + timeout))
+    }
+
+    /// Dequeue the front item without ever waiting, an empty queue gives
+    /// [`QueueErr::WouldBlock`], or [`QueueErr::Closed`] once closed.
+    pub fn try_dequeue(&self) -> Result<T, QueueErr> {
+        let state = self.lock();
+        if state.queue.is_empty() {
+            return Err(match state.closed {
+                true => QueueErr::Closed,
+                false => QueueErr::WouldBlock,
+            });
+        }
+        Ok(self.pop(state))
+    }
+
+    /// Refuse any new item, and wake up every waiting thread. The items left
+    /// can still be dequeued. Closing twice does nothing.
+    pub fn close(&self) {
+        self.lock().closed = true;
+        self.not_empty.notify_all();
+        self.not_full.notify_all();
+    }
+
+    // A thread panicking while holding the lock can't leave the state half
+    // updated, the queue operations under the lock don't panic, so a poisoned
+    // lock is used anyway.
+    fn lock(&self) -> MutexGuard<'_, BlockingState<T>> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    // Wait on the condition variable while the condition holds, until the
+    // deadline if any. Give back the guard, and whether the deadline passed.
+    fn wait_while<'a>(
+        &self,
+        condvar: &Condvar,
+        mut state: MutexGuard<'a, BlockingState<T>>,
+        deadline: Option<Instant>,
+        condition: impl Fn(&BlockingState<T>) -> bool,
+    ) -> (MutexGuard<'a, BlockingState<T>>, bool) {
+        while condition(&state) {
+            match deadline {
+                None => {
+                    state = condvar.wait(state).unwrap_or_else(PoisonError::into_inner);
+                }
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return (state, true);
+                    }
+                    state = condvar
+                        .wait_timeout(state, deadline - now)
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .0;
+                }
+            }
+        }
+        (state, false)
+    }
+
+    fn enqueue_until(&self, val: T, deadline: Option<Instant>) -> Result<(), (QueueErr, T)> {
+        if self.policy != OverflowPolicy::Block {
+            return self.try_enqueue(val);
+        }
+        let state = self.lock();
+        let (state, timed_out) = self.wait_while(&self.not_full, state, deadline, |state| {
+            !state.closed && state.queue.is_full()
+        });
+        if state.closed {
+            return Err((QueueErr::Closed, val));
+        }
+        if timed_out {
+            return Err((QueueErr::Timeout, val));
+        }
+        self.push(state, val);
+        Ok(())
+    }
+
+    fn dequeue_until(&self, deadline: Option<Instant>) -> Result<T, QueueErr> {
+        let state = self.lock();
+        let (state, timed_out) = self.wait_while(&self.not_empty, state, deadline, |state| {
+            !state.closed && state.queue.is_empty()
+        });
+        if state.queue.is_empty() {
+            return Err(match timed_out {
+                true => QueueErr::Timeout,
+                false => QueueErr::Closed,
+            });
+        }
+        Ok(self.pop(state))
+    }
+
+    // The guard is released before waking up a waiting thread, so it doesn't
+    // wake up just to wait for the lock.
+    fn push(&self, mut state: MutexGuard<'_, BlockingState<T>>, val: T) {
+        state
+            .queue
+            .enqueue(val)
+            .expect("room has been made for the item");
+        drop(state);
+        self.not_empty.notify_one();
+    }
+
+    fn pop(&self, mut state: MutexGuard<'_, BlockingState<T>>) -> T {
+        let val = state.queue.dequeue().expect("the queue is not empty");
+        drop(state);
+        self.not_full.notify_one();
+        val
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{sync::Arc, thread};
+
+    use super::*;
+
+    #[test]
+    fn blocking_queue_new() {
+        let queue = BlockingQueue::<i32>::with_capacity(4).unwrap();
+        assert_eq!(4, queue.cap());
+        assert_eq!(OverflowPolicy::Block, queue.policy());
+        assert!(queue.is_empty());
+        assert!(!queue.is_closed());
+        assert_eq!(
+            Some(QueueErr::ZeroCapacityNotAllowed),
+            BlockingQueue::<i32>::with_capacity(0).err()
+        );
+    }
+
+    #[test]
+    fn blocking_queue_try_variants() {
+        let queue = BlockingQueue::with_capacity(2).unwrap();
+        assert_eq!(Err(QueueErr::WouldBlock), queue.try_dequeue());
+        queue.try_enqueue(1).unwrap();
+        queue.try_enqueue(2).unwrap();
+        assert!(queue.is_full());
+        assert_eq!(Err((QueueErr::WouldBlock, 3)), queue.try_enqueue(3));
+        assert_eq!(Ok(1), queue.try_dequeue());
+        assert_eq!(1, queue.len());
+    }
+
+    #[test]
+    fn blocking_queue_other_policies_never_wait() {
+        let queue = BlockingQueue::with_policy(1, OverflowPolicy::Error).unwrap();
+        queue.enqueue(1).unwrap();
+        assert_eq!(Err((QueueErr::QueueOverflow, 2)), queue.enqueue(2));
+        let queue = BlockingQueue::with_policy(2, OverflowPolicy::OverwriteOldest).unwrap();
+        (1..=3).for_each(|v| queue.enqueue(v).unwrap());
+        assert_eq!(Ok(2), queue.dequeue());
+        assert_eq!(Ok(3), queue.dequeue());
+    }
+
+    #[test]
+    fn blocking_queue_timeouts() {
+        let queue = BlockingQueue::with_capacity(1).unwrap();
+        let start = Instant::now();
+        assert_eq!(
+            Err(QueueErr::Timeout),
+            queue.dequeue_timeout(Duration::from_millis(20))
+        );
+        assert!(start.elapsed() >= Duration::from_millis(20));
+        queue.enqueue(1).unwrap();
+        assert_eq!(
+            Err((QueueErr::Timeout, 2)),
+            queue.enqueue_timeout(2, Duration::from_millis(20))
+        );
+        assert_eq!(Ok(1), queue.dequeue_timeout(Duration::from_millis(20)));
+    }
+
+    #[test]
+    fn blocking_queue_enqueue_waits_for_room() {
+        let queue = Arc::new(BlockingQueue::with_capacity(1).unwrap());
+        queue.enqueue(1).unwrap();
+        let producer = {
+            let queue = Arc::clone(&queue);
+            thread::spawn(move || queue.enqueue(2))
+        };
+        thread::sleep(Duration::from_millis(20));
+        assert_eq!(Ok(1), queue.dequeue());
+        assert_eq!(Ok(()), producer.join().unwrap());
+        assert_eq!(Ok(2), queue.dequeue());
+    }
+
+    #[test]
+    fn blocking_queue_close() {
+        let queue = Arc::new(BlockingQueue::with_capacity(2).unwrap());
+        let consumer = {
+            let queue = Arc::clone(&queue);
+            thread::spawn(move || queue.dequeue())
+        };
+        thread::sleep(Duration::from_millis(20));
+        queue.close();
+        // the waiting consumer is woken up
+        assert_eq!(Err(QueueErr::Closed), consumer.join().unwrap());
+        assert!(queue.is_closed());
+        assert_eq!(Err((QueueErr::Closed, 1)), queue.enqueue(1));
+        assert_eq!(Err((QueueErr::Closed, 2)), queue.try_enqueue(2));
+    }
+
+    #[test]
+    fn blocking_queue_close_drains_items_left() {
+        let queue = BlockingQueue::with_capacity(2).unwrap();
+        queue.enqueue(1).unwrap();
+        queue.enqueue(2).unwrap();
+        queue.close();
+        // enqueuing fails right away, even with items left
+        assert_eq!(Err((QueueErr::Closed, 3)), queue.enqueue(3));
+        assert_eq!(Ok(1), queue.dequeue());
+        assert_eq!(Ok(2), queue.try_dequeue());
+        assert_eq!(Err(QueueErr::Closed), queue.dequeue());
+        assert_eq!(Err(QueueErr::Closed), queue.try_dequeue());
+        assert_eq!(
+            Err(QueueErr::Closed),
+            queue.dequeue_timeout(Duration::from_millis(1))
+        );
+    }
+
+    #[test]
+    fn blocking_queue_close_wakes_producers() {
+        let queue = Arc::new(BlockingQueue::with_capacity(1).unwrap());
+        queue.enqueue(0).unwrap();
+        let producers = (0..3)
+            .map(|v| {
+                let queue = Arc::clone(&queue);
+                thread::spawn(move || queue.enqueue(v))
+            })
+            .collect::<Vec<_>>();
+        thread::sleep(Duration::from_millis(20));
+        queue.close();
+        for (v, producer) in producers.into_iter().enumerate() {
+            assert_eq!(Err((QueueErr::Closed, v)), producer.join().unwrap());
+        }
+    }
+
+    #[test]
+    fn blocking_queue_mpmc_stress() {
+        const PRODUCERS: u64 = 4;
+        const CONSUMERS: usize = 4;
+        const ITEMS: u64 = 10_000;
+
+        let queue = Arc::new(BlockingQueue::with_capacity(16).unwrap());
+        let producers = (0..PRODUCERS)
+            .map(|producer| {
+                let queue = Arc::clone(&queue);
+                thread::spawn(move || {
+                    for seq in 0..ITEMS {
+                        queue.enqueue((producer, seq)).unwrap();
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        let consumers = (0..CONSUMERS)
+            .map(|_| {
+                let queue = Arc::clone(&queue);
+                thread::spawn(move || {
+                    let mut received = Vec::new();
+                    while let Ok(item) = queue.dequeue() {
+                        received.push(item);
+                    }
+                    received
+                })
+            })
+            .collect::<Vec<_>>();
+
+        producers
+            .into_iter()
+            .for_each(|producer| producer.join().unwrap());
+        queue.close();
+
+        let mut all = Vec::new();
+        for consumer in consumers {
+            let received = consumer.join().unwrap();
+            // FIFO: a consumer sees the items of a producer in order
+            for producer in 0..PRODUCERS {
+                let seqs = received
+                    .iter()
+                    .filter(|(from, _)| *from == producer)
+                    .map(|(_, seq)| *seq)
+                    .collect::<Vec<_>>();
+                assert!(seqs.windows(2).all(|pair| pair[0] < pair[1]));
+            }
+            all.extend(received);
+        }
+        // every item has been received exactly once
+        all.sort();
+        let expected = (0..PRODUCERS)
+            .flat_map(|producer| (0..ITEMS).map(move |seq| (producer, seq)))
+            .collect::<Vec<_>>();
+        assert_eq!(expected, all);
+    }
+}
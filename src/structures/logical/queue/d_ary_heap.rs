@@ -0,0 +1,177 @@
+use std::cmp::Ordering;
+
+use crate::structures::physical::array::Array;
+
+/// Generalization of the [`BinaryHeap`](super::BinaryHeap) where every node
+/// has `D` children, at `D * i + 1 ..= D * i + D`. A wider tree is shallower,
+/// push becomes O(log_D n) while pop becomes O(D log_D n), which pays off when
+/// pushes (or key decreases) are more frequent than pops.
+///
+/// Like the binary heap, `cmp(a, b) == Ordering::Less` means `a` is popped
+/// before `b`. Zero sized items are rejected as well.
+pub struct DaryHeap<T, const D: usize> {
+    data: Array<T>,
+    cmp: fn(&T, &T) -> Ordering,
+}
+
+impl<T: Ord, const D: usize> DaryHeap<T, D> {
+    /// Heap popping the smallest item first.
+    pub fn new_min() -> Self {
+        Self::with_comparator(T::cmp)
+    }
+
+    /// Heap popping the greatest item first.
+    pub fn new_max() -> Self {
+        Self::with_comparator(|a, b| b.cmp(a))
+    }
+}
+
+impl<T, const D: usize> DaryHeap<T, D> {
+    /// # Panics
+    ///
+    /// Panics when `D` is below 2, or when `T` is zero sized.
+    pub fn with_comparator(cmp: fn(&T, &T) -> Ordering) -> Self {
+        assert!(D >= 2, "a heap node needs at least two children");
+        assert_ne!(size_of::<T>(), 0, "a heap can't hold zero sized items");
+        Self {
+            data: Array::new(),
+            cmp,
+        }
+    }
+
+    /// Build a heap from all the items of the iterator in O(n).
+    pub fn from_iter_with<I>(iter: I, cmp: fn(&T, &T) -> Ordering) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut heap = Self::with_comparator(cmp);
+        iter.into_iter().for_each(|item| heap.data.push(item));
+        if heap.len() > 1 {
+            let last_parent = (heap.len() - 2) / D;
+            (0..=last_parent).rev().for_each(|idx| heap.sift_down(idx));
+        }
+        heap
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn push(&mut self, item: T) {
+        self.data.push(item);
+        self.sift_up(self.len() - 1);
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        if self.is_empty() {
+            return None;
+        }
+        let last = self.len() - 1;
+        self.data.swap(0, last);
+        let top = self.data.pop();
+        self.sift_down(0);
+        top
+    }
+
+    pub fn peek(&self) -> Option<&T> {
+        self.data.first()
+    }
+
+    /// Consume the heap and return its items in the order they would have been
+    /// popped.
+    pub fn into_sorted_vec(mut self) -> Vec<T> {
+        let mut sorted = Vec::with_capacity(self.len());
+        while let Some(item) = self.pop() {
+            sorted.push(item);
+        }
+        sorted
+    }
+
+    fn sift_up(&mut self, mut idx: usize) {
+        while idx > 0 {
+            let parent = (idx - 1) / D;
+            if (self.cmp)(&self.data[idx], &self.data[parent]) != Ordering::Less {
+                break;
+            }
+            self.data.swap(idx, parent);
+            idx = parent;
+        }
+    }
+
+    fn sift_down(&mut self, mut idx: usize) {
+        let len = self.len();
+        loop {
+            let first_child = D * idx + 1;
+            let last_child = (first_child + D).min(len);
+            let mut first = idx;
+            for child in first_child..last_child {
+                if (self.cmp)(&self.data[child], &self.data[first]) == Ordering::Less {
+                    first = child;
+                }
+            }
+            if first == idx {
+                break;
+            }
+            self.data.swap(idx, first);
+            idx = first;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    #[should_panic(expected = "zero sized items")]
+    fn d_ary_heap_rejects_zero_sized_items() {
+        DaryHeap::<(), 4>::new_min();
+    }
+
+    #[test]
+    fn d_ary_heap_new() {
+        let heap = DaryHeap::<i32, 4>::new_min();
+        assert!(heap.is_empty());
+        assert_eq!(None, heap.peek());
+    }
+
+    #[test]
+    #[should_panic]
+    fn d_ary_heap_unary_not_allowed() {
+        DaryHeap::<i32, 1>::new_min();
+    }
+
+    #[test]
+    fn d_ary_heap_min_push_pop() {
+        let mut heap = DaryHeap::<i32, 3>::new_min();
+        [5, 3, 8, 1, 9, 2, 7, 4, 6]
+            .into_iter()
+            .for_each(|v| heap.push(v));
+        assert_eq!(Some(&1), heap.peek());
+        assert_eq!((1..=9).collect::<Vec<_>>(), heap.into_sorted_vec());
+    }
+
+    #[test]
+    fn d_ary_heap_max_push_pop() {
+        let mut heap = DaryHeap::<i32, 4>::new_max();
+        [5, 3, 8, 1, 9, 2].into_iter().for_each(|v| heap.push(v));
+        assert_eq!(Some(9), heap.pop());
+        assert_eq!(Some(8), heap.pop());
+        assert_eq!(4, heap.len());
+    }
+
+    #[test]
+    fn d_ary_heap_from_iter_with() {
+        for len in 0..20 {
+            let items = (0..len).map(|v| (v * 7) % 20).collect::<Vec<i32>>();
+            let mut expected = items.clone();
+            expected.sort();
+            let heap = DaryHeap::<i32, 5>::from_iter_with(items, i32::cmp);
+            assert_eq!(expected, heap.into_sorted_vec());
+        }
+    }
+}
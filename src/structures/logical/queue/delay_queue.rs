@@ -0,0 +1,312 @@
+use std::{
+    cell::Cell,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use super::{Queue, QueueErr, TimerHandle, TimingWheel};
+
+/// Source of the current time of a [`DelayQueue`].
+pub trait Clock {
+    fn now(&self) -> Instant;
+}
+
+/// The real time, from [`Instant::now`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// Clock which only moves when told to, so that tests don't sleep. Clones
+/// share the same time, one can be given to the queue and another one kept
+/// to move the time forward.
+#[derive(Debug, Clone)]
+pub struct ManualClock {
+    now: Rc<Cell<Instant>>,
+}
+
+impl ManualClock {
+    pub fn new() -> Self {
+        Self {
+            now: Rc::new(Cell::new(Instant::now())),
+        }
+    }
+
+    pub fn advance(&self, by: Duration) {
+        self.now.set(self.now.get() + by);
+    }
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        self.now.get()
+    }
+}
+
+/// Queue whose items can only be dequeued once their deadline is reached,
+/// earliest deadline first, then in the order they have been scheduled. The
+/// deadlines are kept in a [`TimingWheel`], whose ticks last `resolution`
+/// from the creation of the queue. A deadline is rounded up to the next tick,
+/// so an item never comes out early, but may come out up to a tick late.
+///
+/// Through the [`Queue`] trait, `enqueue` schedules an item for now, and the
+/// front is the first item due. `is_empty` tells whether the queue holds no
+/// item at all, due or not, when `dequeue` may give `None` on a queue which
+/// is not empty. Peeking looks at every item, it is O(n log n).
+pub struct DelayQueue<T, C = SystemClock> {
+    wheel: TimingWheel<T>,
+    clock: C,
+    origin: Instant,
+    resolution: Duration,
+}
+
+impl<T> DelayQueue<T> {
+    /// Queue on the real time, with a resolution of a millisecond.
+    pub fn new() -> Self {
+        Self::with_clock(SystemClock, Duration::from_millis(1))
+    }
+}
+
+impl<T> Default for DelayQueue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, C: Clock> DelayQueue<T, C> {
+    /// # Panics
+    ///
+    /// Panics if the resolution is zero.
+    pub fn with_clock(clock: C, resolution: Duration) -> Self {
+        assert!(!resolution.is_zero(), "the resolution must not be zero");
+        Self {
+            wheel: TimingWheel::new(),
+            origin: clock.now(),
+            clock,
+            resolution,
+        }
+    }
+
+    pub fn clock(&self) -> &C {
+        &self.clock
+    }
+
+    /// Number of items, due or not.
+    pub fn len(&self) -> usize {
+        self.wheel.len()
+    }
+
+    /// Schedule an item to be due after the given delay.
+    pub fn insert(&mut self, val: T, delay: Duration) -> TimerHandle {
+        let deadline = self.clock.now() + delay;
+        self.insert_at(val, deadline)
+    }
+
+    pub fn insert_at(&mut self, val: T, deadline: Instant) -> TimerHandle {
+        let tick = self.deadline_tick(deadline);
+        self.wheel.insert(val, tick)
+    }
+
+    /// Remove an item, due or not, and give it back.
+    pub fn cancel(&mut self, handle: TimerHandle) -> Option<T> {
+        self.wheel.cancel(handle)
+    }
+
+    /// Make an item due after the given delay from now, instead of its
+    /// current deadline.
+    pub fn reschedule(&mut self, handle: TimerHandle, delay: Duration) -> Result<(), QueueErr> {
+        let deadline = self.clock.now() + delay;
+        self.reschedule_at(handle, deadline)
+    }
+
+    pub fn reschedule_at(
+        &mut self,
+        handle: TimerHandle,
+        deadline: Instant,
+    ) -> Result<(), QueueErr> {
+        let tick = self.deadline_tick(deadline);
+        self.wheel.reschedule(handle, tick)
+    }
+
+    pub fn get(&self, handle: TimerHandle) -> Option<&T> {
+        self.wheel.get(handle)
+    }
+
+    /// Time until which nothing can be dequeued, `None` when the queue is
+    /// empty. It may come earlier than the first deadline, see
+    /// [`TimingWheel::next_expiration`], so a consumer sleeping until then
+    /// should check again when it wakes up.
+    pub fn next_deadline(&self) -> Option<Instant> {
+        let tick = self.wheel.next_expiration()?;
+        let nanos = self.resolution.as_nanos() * tick as u128;
+        let secs = (nanos / 1_000_000_000) as u64;
+        Some(self.origin + Duration::new(secs, (nanos % 1_000_000_000) as u32))
+    }
+
+    fn now_tick(&self) -> u64 {
+        let since = self.clock.now().saturating_duration_since(self.origin);
+        (since.as_nanos() / self.resolution.as_nanos()) as u64
+    }
+
+    // First tick at or after the deadline, or the current tick when the
+    // deadline has passed.
+    fn deadline_tick(&self, deadline: Instant) -> u64 {
+        let now = self.now_tick();
+        let since = deadline.saturating_duration_since(self.origin);
+        let tick = since.as_nanos().div_ceil(self.resolution.as_nanos()) as u64;
+        tick.max(now)
+    }
+
+    fn due(&self, idx: usize) -> Option<TimerHandle> {
+        self.wheel.due(self.now_tick()).get(idx).copied()
+    }
+}
+
+impl<T, C: Clock> Queue for DelayQueue<T, C> {
+    type Item = T;
+
+    /// Schedule the item for now, the queue is unbounded.
+    fn enqueue(&mut self, val: Self::Item) -> Result<(), QueueErr> {
+        let now = self.now_tick();
+        self.wheel.insert(val, now);
+        Ok(())
+    }
+
+    fn dequeue(&mut self) -> Option<Self::Item> {
+        let now = self.now_tick();
+        self.wheel.advance(now);
+        self.wheel.pop_expired()
+    }
+
+    fn peek_next(&self) -> Option<&Self::Item> {
+        self.peek(0)
+    }
+
+    fn peek_next_mut(&mut self) -> Option<&mut Self::Item> {
+        self.peek_mut(0)
+    }
+
+    fn peek(&self, idx: usize) -> Option<&Self::Item> {
+        self.wheel.get(self.due(idx)?)
+    }
+
+    fn peek_mut(&mut self, idx: usize) -> Option<&mut Self::Item> {
+        let handle = self.due(idx)?;
+        self.wheel.get_mut(handle)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.wheel.is_empty()
+    }
+
+    fn is_full(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn delay_queue_waits_for_deadlines() {
+        let clock = ManualClock::new();
+        let mut queue = DelayQueue::with_clock(clock.clone(), ms(1));
+        queue.insert("later", ms(50));
+        queue.insert("soon", ms(10));
+        assert_eq!(2, queue.len());
+        assert_eq!(None, queue.dequeue());
+        assert_eq!(None, queue.peek_next());
+        assert!(!queue.is_empty());
+
+        clock.advance(ms(10));
+        assert_eq!(Some(&"soon"), queue.peek_next());
+        assert_eq!(Some("soon"), queue.dequeue());
+        assert_eq!(None, queue.dequeue());
+        clock.advance(ms(100));
+        assert_eq!(Some("later"), queue.dequeue());
+        assert!(queue.is_empty());
+        assert_eq!(None, queue.next_deadline());
+    }
+
+    #[test]
+    fn delay_queue_rounds_deadlines_up() {
+        let clock = ManualClock::new();
+        let mut queue = DelayQueue::with_clock(clock.clone(), ms(10));
+        queue.insert(1, ms(15));
+        clock.advance(ms(15));
+        // due at the tick of 20ms, never early
+        assert_eq!(None, queue.dequeue());
+        assert_eq!(Some(clock.now() + ms(5)), queue.next_deadline());
+        clock.advance(ms(5));
+        assert_eq!(Some(1), queue.dequeue());
+    }
+
+    #[test]
+    fn delay_queue_cancel_reschedule() {
+        let clock = ManualClock::new();
+        let mut queue = DelayQueue::with_clock(clock.clone(), ms(1));
+        let first = queue.insert("first", ms(5));
+        let second = queue.insert("second", ms(5));
+        assert_eq!(Some("first"), queue.cancel(first));
+        assert_eq!(Err(QueueErr::UnknownHandle), queue.reschedule(first, ms(1)));
+        queue.reschedule(second, ms(1_000)).unwrap();
+        clock.advance(ms(999));
+        assert_eq!(None, queue.dequeue());
+        assert_eq!(Some(&"second"), queue.get(second));
+        clock.advance(ms(1));
+        assert_eq!(Some("second"), queue.dequeue());
+        assert_eq!(None, queue.cancel(second));
+    }
+
+    #[test]
+    fn delay_queue_retry_backoff() {
+        // a job failing twice is retried after 100ms, then 200ms
+        let clock = ManualClock::new();
+        let start = clock.now();
+        let mut queue = DelayQueue::with_clock(clock.clone(), ms(1));
+        queue.enqueue(("job", 0)).unwrap();
+        let mut runs = Vec::new();
+        while let Some(deadline) = queue.next_deadline() {
+            clock.advance(deadline.saturating_duration_since(queue.clock().now()));
+            while let Some((job, attempt)) = queue.dequeue() {
+                runs.push((clock.now() - start, attempt));
+                if attempt < 2 {
+                    queue.insert((job, attempt + 1), ms(100 << attempt));
+                }
+            }
+        }
+        assert_eq!(vec![(ms(0), 0), (ms(100), 1), (ms(300), 2)], runs);
+    }
+
+    #[test]
+    fn delay_queue_peek_due_items() {
+        let clock = ManualClock::new();
+        let mut queue = DelayQueue::with_clock(clock.clone(), ms(1));
+        queue.insert('b', ms(2));
+        queue.insert('a', ms(1));
+        queue.insert('c', ms(3));
+        clock.advance(ms(2));
+        assert_eq!(Some(&'a'), queue.peek(0));
+        assert_eq!(Some(&'b'), queue.peek(1));
+        assert_eq!(None, queue.peek(2));
+        *queue.peek_mut(1).unwrap() = 'B';
+        assert_eq!(Some('a'), queue.dequeue());
+        assert_eq!(Some('B'), queue.dequeue());
+        assert_eq!(None, queue.dequeue());
+    }
+}
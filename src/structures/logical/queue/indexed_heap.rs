@@ -0,0 +1,313 @@
+use std::fmt::Display;
+
+/// Min priority queue over a fixed set of indices `0..cap`, each index being
+/// associated with at most one key. On top of the usual heap operations, it
+/// keeps track of where every index sits in the heap, so the key of an index
+/// can be decreased in O(log n), which is what Dijkstra or Prim algorithms need
+/// to update the distance of a vertex.
+pub struct IndexedMinHeap<K> {
+    // keys[i] is the key of the index i, if the index is in the heap.
+    keys: Vec<Option<K>>,
+    // heap[p] is the index stored at the position p of the heap.
+    heap: Vec<usize>,
+    // pos[i] is the position of the index i in the heap.
+    pos: Vec<Option<usize>>,
+}
+
+impl<K: Ord> IndexedMinHeap<K> {
+    pub fn with_capacity(cap: usize) -> Result<Self, IndexedHeapErr> {
+        if cap == 0 {
+            return Err(IndexedHeapErr::ZeroCapacityNotAllowed);
+        }
+
+        let mut keys = Vec::with_capacity(cap);
+        (0..cap).for_each(|_| keys.push(None));
+        Ok(Self {
+            keys,
+            heap: Vec::with_capacity(cap),
+            pos: vec![None; cap],
+        })
+    }
+
+    /// Number of indices the heap can hold, they go from 0 to `cap - 1`.
+    pub fn cap(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    pub fn contains(&self, idx: usize) -> bool {
+        self.pos.get(idx).is_some_and(|pos| pos.is_some())
+    }
+
+    pub fn key_of(&self, idx: usize) -> Option<&K> {
+        self.keys.get(idx).and_then(|key| key.as_ref())
+    }
+
+    pub fn push(&mut self, idx: usize, key: K) -> Result<(), IndexedHeapErr> {
+        self.check_bounds(idx)?;
+        if self.contains(idx) {
+            return Err(IndexedHeapErr::IndexAlreadyPresent);
+        }
+        self.keys[idx] = Some(key);
+        self.heap.push(idx);
+        self.pos[idx] = Some(self.len() - 1);
+        self.sift_up(self.len() - 1);
+        Ok(())
+    }
+
+    /// Index with the smallest key, and the key itself.
+    pub fn peek(&self) -> Option<(usize, &K)> {
+        self.heap.first().map(|&idx| (idx, self.key(idx)))
+    }
+
+    /// Remove the index with the smallest key, and return it with its key.
+    pub fn pop(&mut self) -> Option<(usize, K)> {
+        let idx = *self.heap.first()?;
+        self.remove(idx).ok().map(|key| (idx, key))
+    }
+
+    /// Give a smaller key to an index already in the heap, the index moves up
+    /// toward the root. O(log n).
+    pub fn decrease_key(&mut self, idx: usize, key: K) -> Result<(), IndexedHeapErr> {
+        let pos = self.position(idx)?;
+        if key > *self.key(idx) {
+            return Err(IndexedHeapErr::KeyNotDecreased);
+        }
+        self.keys[idx] = Some(key);
+        self.sift_up(pos);
+        Ok(())
+    }
+
+    /// Change the key of an index already in the heap, whatever the new key is.
+    pub fn change_key(&mut self, idx: usize, key: K) -> Result<(), IndexedHeapErr> {
+        let pos = self.position(idx)?;
+        self.keys[idx] = Some(key);
+        self.sift_up(pos);
+        self.sift_down(self.position(idx)?);
+        Ok(())
+    }
+
+    /// Remove an index from the heap wherever it is, and return its key.
+    pub fn remove(&mut self, idx: usize) -> Result<K, IndexedHeapErr> {
+        let pos = self.position(idx)?;
+        let last = self.len() - 1;
+        self.swap(pos, last);
+        self.heap.pop();
+        self.pos[idx] = None;
+        if pos < self.len() {
+            // The last item took the place of the removed one, it may have to
+            // go up or down.
+            let moved = self.heap[pos];
+            self.sift_up(pos);
+            self.sift_down(self.position(moved)?);
+        }
+        Ok(self.keys[idx].take().expect("index in heap has a key"))
+    }
+
+    fn check_bounds(&self, idx: usize) -> Result<(), IndexedHeapErr> {
+        if idx < self.cap() {
+            Ok(())
+        } else {
+            Err(IndexedHeapErr::IndexOutOfBounds)
+        }
+    }
+
+    fn position(&self, idx: usize) -> Result<usize, IndexedHeapErr> {
+        self.check_bounds(idx)?;
+        self.pos[idx].ok_or(IndexedHeapErr::IndexNotPresent)
+    }
+
+    fn key(&self, idx: usize) -> &K {
+        self.keys[idx].as_ref().expect("index in heap has a key")
+    }
+
+    fn less(&self, pos_a: usize, pos_b: usize) -> bool {
+        self.key(self.heap[pos_a]) < self.key(self.heap[pos_b])
+    }
+
+    fn swap(&mut self, pos_a: usize, pos_b: usize) {
+        self.heap.swap(pos_a, pos_b);
+        self.pos[self.heap[pos_a]] = Some(pos_a);
+        self.pos[self.heap[pos_b]] = Some(pos_b);
+    }
+
+    fn sift_up(&mut self, mut pos: usize) {
+        while pos > 0 {
+            let parent = (pos - 1) / 2;
+            if !self.less(pos, parent) {
+                break;
+            }
+            self.swap(pos, parent);
+            pos = parent;
+        }
+    }
+
+    fn sift_down(&mut self, mut pos: usize) {
+        loop {
+            let left = 2 * pos + 1;
+            let right = left + 1;
+            let mut first = pos;
+            if left < self.len() && self.less(left, first) {
+                first = left;
+            }
+            if right < self.len() && self.less(right, first) {
+                first = right;
+            }
+            if first == pos {
+                break;
+            }
+            self.swap(pos, first);
+            pos = first;
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum IndexedHeapErr {
+    ZeroCapacityNotAllowed,
+    IndexOutOfBounds,
+    IndexAlreadyPresent,
+    IndexNotPresent,
+    KeyNotDecreased,
+}
+
+impl std::error::Error for IndexedHeapErr {}
+
+impl Display for IndexedHeapErr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IndexedHeapErr::ZeroCapacityNotAllowed => {
+                write!(f, "Unable to initialize a heap with no capacity")
+            }
+            IndexedHeapErr::IndexOutOfBounds => {
+                write!(f, "The index is greater than the heap capacity")
+            }
+            IndexedHeapErr::IndexAlreadyPresent => write!(f, "The index is already in the heap"),
+            IndexedHeapErr::IndexNotPresent => write!(f, "The index is not in the heap"),
+            IndexedHeapErr::KeyNotDecreased => {
+                write!(f, "The new key is greater than the current one")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn indexed_heap_new() {
+        let heap = IndexedMinHeap::<i32>::with_capacity(4).unwrap();
+        assert!(heap.is_empty());
+        assert_eq!(4, heap.cap());
+    }
+
+    #[test]
+    fn indexed_heap_new_zero_cap() {
+        let heap = IndexedMinHeap::<i32>::with_capacity(0);
+        assert_eq!(Some(IndexedHeapErr::ZeroCapacityNotAllowed), heap.err());
+    }
+
+    #[test]
+    fn indexed_heap_push_pop() {
+        let mut heap = IndexedMinHeap::with_capacity(4).unwrap();
+        heap.push(0, 30).unwrap();
+        heap.push(1, 10).unwrap();
+        heap.push(2, 20).unwrap();
+        assert_eq!(Some((1, &10)), heap.peek());
+        assert_eq!(Some((1, 10)), heap.pop());
+        assert_eq!(Some((2, 20)), heap.pop());
+        assert_eq!(Some((0, 30)), heap.pop());
+        assert_eq!(None, heap.pop());
+    }
+
+    #[test]
+    fn indexed_heap_push_errors() {
+        let mut heap = IndexedMinHeap::with_capacity(2).unwrap();
+        heap.push(0, 1).unwrap();
+        assert_eq!(Err(IndexedHeapErr::IndexAlreadyPresent), heap.push(0, 2));
+        assert_eq!(Err(IndexedHeapErr::IndexOutOfBounds), heap.push(2, 2));
+    }
+
+    #[test]
+    fn indexed_heap_decrease_key() {
+        let mut heap = IndexedMinHeap::with_capacity(4).unwrap();
+        heap.push(0, 30).unwrap();
+        heap.push(1, 10).unwrap();
+        heap.push(2, 20).unwrap();
+        heap.decrease_key(0, 5).unwrap();
+        assert_eq!(Some((0, &5)), heap.peek());
+        assert_eq!(Some(&5), heap.key_of(0));
+        assert_eq!(
+            Err(IndexedHeapErr::KeyNotDecreased),
+            heap.decrease_key(2, 25)
+        );
+        assert_eq!(
+            Err(IndexedHeapErr::IndexNotPresent),
+            heap.decrease_key(3, 1)
+        );
+    }
+
+    #[test]
+    fn indexed_heap_change_key() {
+        let mut heap = IndexedMinHeap::with_capacity(3).unwrap();
+        heap.push(0, 1).unwrap();
+        heap.push(1, 2).unwrap();
+        heap.push(2, 3).unwrap();
+        heap.change_key(0, 10).unwrap();
+        assert_eq!(Some((1, 2)), heap.pop());
+        assert_eq!(Some((2, 3)), heap.pop());
+        assert_eq!(Some((0, 10)), heap.pop());
+    }
+
+    #[test]
+    fn indexed_heap_remove() {
+        let mut heap = IndexedMinHeap::with_capacity(6).unwrap();
+        [50, 10, 40, 20, 30, 60]
+            .into_iter()
+            .enumerate()
+            .for_each(|(idx, key)| heap.push(idx, key).unwrap());
+        assert_eq!(Ok(20), heap.remove(3));
+        assert!(!heap.contains(3));
+        assert_eq!(Err(IndexedHeapErr::IndexNotPresent), heap.remove(3));
+        let popped = std::iter::from_fn(|| heap.pop().map(|(_, key)| key)).collect::<Vec<_>>();
+        assert_eq!(vec![10, 30, 40, 50, 60], popped);
+    }
+
+    #[test]
+    fn indexed_heap_dijkstra() {
+        // adjacency list: (to, weight)
+        let graph: Vec<Vec<(usize, u32)>> = vec![
+            vec![(1, 4), (2, 1)],
+            vec![(3, 1)],
+            vec![(1, 2), (3, 5)],
+            vec![(4, 3)],
+            vec![],
+        ];
+        let mut dist = vec![u32::MAX; graph.len()];
+        let mut heap = IndexedMinHeap::with_capacity(graph.len()).unwrap();
+        dist[0] = 0;
+        heap.push(0, 0).unwrap();
+        while let Some((vertex, d)) = heap.pop() {
+            for &(next, weight) in &graph[vertex] {
+                let candidate = d + weight;
+                if candidate < dist[next] {
+                    dist[next] = candidate;
+                    if heap.contains(next) {
+                        heap.decrease_key(next, candidate).unwrap();
+                    } else {
+                        heap.push(next, candidate).unwrap();
+                    }
+                }
+            }
+        }
+        assert_eq!(vec![0, 3, 1, 4, 7], dist);
+    }
+}
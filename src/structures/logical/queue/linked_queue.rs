@@ -1,15 +1,30 @@
-use std::ops::Deref;
-
-use super::{Queue, QueueErr};
+use super::{OverflowPolicy, Queue, QueueErr};
 
 pub struct LinkedQueue<T> {
     head: Option<Box<LinkedQueueNode<T>>>,
     len: usize,
-    cap: usize,
+    cap: Option<usize>,
+    policy: OverflowPolicy,
 }
 
 impl<T> LinkedQueue<T> {
+    /// Create an unbounded queue, it is never full.
+    pub fn new() -> Self {
+        Self {
+            head: None,
+            len: 0,
+            cap: None,
+            policy: OverflowPolicy::default(),
+        }
+    }
+
     pub fn with_capacity(cap: usize) -> Result<Self, QueueErr> {
+        Self::with_policy(cap, OverflowPolicy::default())
+    }
+
+    /// Create a bounded queue, which handles the enqueuing of an item while it
+    /// is full according to the given policy.
+    pub fn with_policy(cap: usize, policy: OverflowPolicy) -> Result<Self, QueueErr> {
         if cap == 0 {
             return Err(QueueErr::ZeroCapacityNotAllowed);
         }
@@ -17,17 +32,36 @@ impl<T> LinkedQueue<T> {
         Ok(Self {
             head: None,
             len: 0,
-            cap,
+            cap: Some(cap),
+            policy,
         })
     }
 
-    pub fn iter(&self) -> LinkedQueueIter<T> {
+    /// Maximum number of items the queue can hold, `usize::MAX` when the queue
+    /// is unbounded.
+    pub fn cap(&self) -> usize {
+        self.cap.unwrap_or(usize::MAX)
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_bounded(&self) -> bool {
+        self.cap.is_some()
+    }
+
+    pub fn policy(&self) -> OverflowPolicy {
+        self.policy
+    }
+
+    pub fn iter(&self) -> LinkedQueueIter<'_, T> {
         LinkedQueueIter {
             next: self.head.as_deref(),
         }
     }
 
-    pub fn iter_mut(&mut self) -> LinkedQueueIterMut<T> {
+    pub fn iter_mut(&mut self) -> LinkedQueueIterMut<'_, T> {
         LinkedQueueIterMut {
             next: self.head.as_deref_mut(),
         }
@@ -62,6 +96,7 @@ impl<T> LinkedQueue<T> {
         // from the temporary, leaving us free to modify anchor. See the
         // related blog post Stuff the Identity Function Does (in Rust).
         // Link : https://bluss.github.io/rust/fun/2015/10/11/stuff-the-identity-function-does/
+        #[allow(clippy::blocks_in_conditions)]
         loop {
             match { linked_node_ref } {
                 &mut Some(ref mut node) => {
@@ -86,7 +121,13 @@ impl<T> Queue for LinkedQueue<T> {
 
     fn enqueue(&mut self, val: Self::Item) -> Result<(), QueueErr> {
         if self.is_full() {
-            return Err(QueueErr::QueueOverflow);
+            match self.policy {
+                OverflowPolicy::Error => return Err(QueueErr::QueueOverflow),
+                OverflowPolicy::Block => return Err(QueueErr::WouldBlock),
+                OverflowPolicy::OverwriteOldest => {
+                    self.dequeue();
+                }
+            }
         }
 
         if let Some(tail) = self.get_tail_mut() {
@@ -101,6 +142,7 @@ impl<T> Queue for LinkedQueue<T> {
     fn dequeue(&mut self) -> Option<Self::Item> {
         self.head.take().map(|mut prev_head| {
             self.head = prev_head.next.take();
+            self.len -= 1;
             prev_head.val
         })
     }
@@ -136,7 +178,17 @@ impl<T> Queue for LinkedQueue<T> {
     }
 
     fn is_full(&self) -> bool {
-        self.len == self.cap
+        self.cap.is_some_and(|cap| self.len == cap)
+    }
+}
+
+// Unlink the nodes one at a time, as the default drop of the Box chain would
+// recurse once per node and overflow the stack on a long list.
+impl<T> Drop for LinkedQueue<T> {
+    fn drop(&mut self) {
+        while let Some(mut node) = self.head.take() {
+            self.head = node.next.take();
+        }
     }
 }
 
@@ -174,13 +226,27 @@ impl<'a, T> Iterator for LinkedQueueIterMut<'a, T> {
 mod test {
     use super::*;
 
+    #[test]
+    fn linked_queue_drop_long_list() {
+        // Enqueuing walks to the tail, so the list is linked directly.
+        let mut queue = LinkedQueue::new();
+        for val in 0..1_000_000u64 {
+            queue.head = Some(Box::new(LinkedQueueNode {
+                val,
+                next: queue.head.take(),
+            }));
+            queue.len += 1;
+        }
+        drop(queue);
+    }
+
     #[test]
     fn linked_queue_new() {
         let queue = LinkedQueue::<i32>::with_capacity(2);
         assert!(queue.is_ok());
         let queue = queue.unwrap();
         assert_eq!(0, queue.len);
-        assert_eq!(2, queue.cap);
+        assert_eq!(2, queue.cap());
     }
 
     #[test]
@@ -258,4 +324,42 @@ mod test {
         assert_eq!(Some(&mut 1), queue.peek_mut(0));
         assert_eq!(None, queue.peek_mut(7));
     }
+
+    #[test]
+    fn linked_queue_dequeue_len() {
+        let mut queue = LinkedQueue::<i32>::with_capacity(2).unwrap();
+        let _ = queue.enqueue(1);
+        let _ = queue.enqueue(2);
+        assert!(queue.is_full());
+        let _ = queue.dequeue();
+        assert_eq!(1, queue.len());
+        assert!(queue.enqueue(3).is_ok());
+    }
+
+    #[test]
+    fn linked_queue_new_unbounded() {
+        let mut queue = LinkedQueue::<i32>::new();
+        assert!(!queue.is_bounded());
+        assert_eq!(usize::MAX, queue.cap());
+        (0..10).for_each(|i| queue.enqueue(i).unwrap());
+        assert!(!queue.is_full());
+        assert_eq!(10, queue.len());
+        assert_eq!(Some(0), queue.dequeue());
+    }
+
+    #[test]
+    fn linked_queue_overwrite_oldest() {
+        let mut queue =
+            LinkedQueue::<i32>::with_policy(3, OverflowPolicy::OverwriteOldest).unwrap();
+        (1..=5).for_each(|i| queue.enqueue(i).unwrap());
+        assert_eq!(3, queue.len());
+        assert_eq!(vec![&3, &4, &5], queue.iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn linked_queue_block_policy() {
+        let mut queue = LinkedQueue::<i32>::with_policy(1, OverflowPolicy::Block).unwrap();
+        queue.enqueue(1).unwrap();
+        assert_eq!(Err(QueueErr::WouldBlock), queue.enqueue(2));
+    }
 }
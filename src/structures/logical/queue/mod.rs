@@ -1,16 +1,43 @@
 #![allow(dead_code)]
-// TODO : implementations :
-//  - UnsafeQueue, use heap allocated array managed directly by pointers
 
 use std::fmt::Display;
 
+pub use amortized_queue::AmortizedQueue;
 pub use array_queue::ArrayQueue;
+pub use binary_heap::{BinaryHeap, PeekMut};
+pub use blocking_queue::BlockingQueue;
+pub use d_ary_heap::DaryHeap;
+pub use delay_queue::{Clock, DelayQueue, ManualClock, SystemClock};
+pub use indexed_heap::{IndexedHeapErr, IndexedMinHeap};
 pub use linked_queue::LinkedQueue;
+pub use mpmc_queue::MpmcQueue;
+pub use spsc_queue::{spsc_queue, Consumer, Producer};
+pub use timing_wheel::{TimerHandle, TimingWheel};
+pub use unsafe_queue::UnsafeQueue;
 
+mod amortized_queue;
 mod array_queue;
+mod binary_heap;
+mod blocking_queue;
+mod d_ary_heap;
+mod delay_queue;
+mod indexed_heap;
 mod linked_queue;
+mod mpmc_queue;
+mod spsc_queue;
+mod timing_wheel;
+mod unsafe_queue;
 
-/// TODO doc + FIFO
+/// First in, first out abstract data type: `dequeue` gives back the item
+/// enqueued the earliest, which is called the front of the queue.
+///
+/// Indices given to `peek` and `peek_mut` count from the front: `peek(0)` is
+/// the front, the same item as `peek_next`, `peek(1)` is the item enqueued
+/// right after it, and so on. An index past the back of the queue gives `None`.
+///
+/// A bounded queue is full once it holds as many items as its capacity, then
+/// `enqueue` behaves according to the [`OverflowPolicy`] of the queue. An
+/// unbounded queue is never full.
 pub trait Queue {
     type Item;
     fn enqueue(&mut self, val: Self::Item) -> Result<(), QueueErr>;
@@ -23,10 +50,33 @@ pub trait Queue {
     fn is_full(&self) -> bool;
 }
 
+/// What a bounded queue does when an item is enqueued while it is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverflowPolicy {
+    /// Reject the new item with [`QueueErr::QueueOverflow`].
+    #[default]
+    Error,
+    /// Drop the item at the front of the queue to make room for the new one,
+    /// the queue then acts as a rolling window over the last items.
+    OverwriteOldest,
+    /// Wait until an item is dequeued. A single threaded queue can't wait for
+    /// anyone, so it rejects the new item with [`QueueErr::WouldBlock`].
+    Block,
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub enum QueueErr {
     ZeroCapacityNotAllowed,
     QueueOverflow,
+    WouldBlock,
+    /// The queue has been closed. Enqueuing fails as soon as it is closed,
+    /// while dequeuing only fails once every item left has been dequeued.
+    Closed,
+    /// Waiting for room or for an item took longer than allowed.
+    Timeout,
+    /// The handle doesn't refer to an item of the queue anymore, it has been
+    /// dequeued or cancelled.
+    UnknownHandle,
 }
 
 impl std::error::Error for QueueErr {}
@@ -41,6 +91,191 @@ impl Display for QueueErr {
             QueueErr::ZeroCapacityNotAllowed => {
                 write!(f, "Unable to initialize a queue with no capacity")
             }
+            QueueErr::WouldBlock => {
+                write!(f, "Unable to use the queue without waiting")
+            }
+            QueueErr::Closed => write!(f, "The queue is closed"),
+            QueueErr::Timeout => write!(f, "Timed out waiting on the queue"),
+            QueueErr::UnknownHandle => {
+                write!(f, "The handle doesn't refer to an item of the queue")
+            }
+        }
+    }
+}
+
+// Conformance suite every Queue implementor goes through, with randomized
+// sequences of operations checked against a VecDeque, whose front is the front
+// of the queue. The macro takes the name of the generated test module, an
+// expression building an empty queue of i64 and its capacity, None when
+// unbounded. Bounded queues are expected to use the default policy.
+#[cfg(test)]
+mod conformance {
+    use std::collections::VecDeque;
+
+    use super::*;
+    use crate::structures::logical::deque::{ArrayDeque, LinkedDeque};
+    use crate::structures::logical::stack::{ArrayStack, LinkedStack};
+    use crate::test_utils::XorShift;
+
+    fn check_fifo_order<Q: Queue<Item = i64>>(mut queue: Q) {
+        assert!(queue.is_empty());
+        assert_eq!(None, queue.dequeue());
+        (0..5).for_each(|v| queue.enqueue(v).unwrap());
+        assert!(!queue.is_empty());
+        let dequeued = std::iter::from_fn(|| queue.dequeue()).collect::<Vec<_>>();
+        assert_eq!(vec![0, 1, 2, 3, 4], dequeued);
+        assert!(queue.is_empty());
+        assert_eq!(None, queue.dequeue());
+        queue.enqueue(7).unwrap();
+        assert_eq!(Some(&7), queue.peek_next());
+    }
+
+    fn check_peek_from_front<Q: Queue<Item = i64>>(mut queue: Q) {
+        assert_eq!(None, queue.peek(0));
+        (1..=3).for_each(|v| queue.enqueue(v).unwrap());
+        assert_eq!(Some(&1), queue.peek_next());
+        assert_eq!(Some(&1), queue.peek(0));
+        assert_eq!(Some(&2), queue.peek(1));
+        assert_eq!(Some(&3), queue.peek(2));
+        assert_eq!(None, queue.peek(3));
+        assert_eq!(None, queue.peek(usize::MAX));
+        *queue.peek_mut(2).unwrap() = 30;
+        *queue.peek_next_mut().unwrap() = 10;
+        assert_eq!(None, queue.peek_mut(3));
+        assert_eq!(Some(10), queue.dequeue());
+        assert_eq!(Some(2), queue.dequeue());
+        assert_eq!(Some(30), queue.dequeue());
+    }
+
+    fn check_overflow<Q: Queue<Item = i64>>(mut queue: Q, cap: Option<usize>) {
+        let Some(cap) = cap else {
+            (0..1_000).for_each(|v| queue.enqueue(v).unwrap());
+            assert!(!queue.is_full());
+            return;
+        };
+        (0..cap as i64).for_each(|v| queue.enqueue(v).unwrap());
+        assert!(queue.is_full());
+        assert_eq!(Err(QueueErr::QueueOverflow), queue.enqueue(-1));
+        // the rejected item didn't replace anything
+        assert_eq!(Some(&0), queue.peek_next());
+        assert_eq!(Some(&(cap as i64 - 1)), queue.peek(cap - 1));
+        queue.dequeue();
+        assert!(!queue.is_full());
+        queue.enqueue(-1).unwrap();
+        assert_eq!(Some(&-1), queue.peek(cap - 1));
+    }
+
+    fn check_against_model<Q: Queue<Item = i64>>(mut queue: Q, cap: Option<usize>, seed: u64) {
+        let mut rng = XorShift::new(seed);
+        let mut model: VecDeque<i64> = VecDeque::new();
+        for _ in 0..1_000 {
+            match rng.below(8) {
+                0..=2 => {
+                    let val = rng.below(1_000) as i64;
+                    if cap.is_some_and(|cap| model.len() == cap) {
+                        assert_eq!(Err(QueueErr::QueueOverflow), queue.enqueue(val));
+                    } else {
+                        assert_eq!(Ok(()), queue.enqueue(val));
+                        model.push_back(val);
+                    }
+                }
+                3 | 4 => assert_eq!(model.pop_front(), queue.dequeue()),
+                5 => {
+                    let idx = rng.below(model.len() as u64 + 2) as usize;
+                    assert_eq!(model.get(idx), queue.peek(idx));
+                }
+                6 => {
+                    let idx = rng.below(model.len() as u64 + 2) as usize;
+                    let expected = model.get_mut(idx);
+                    let actual = queue.peek_mut(idx);
+                    assert_eq!(expected.is_some(), actual.is_some());
+                    if let (Some(expected), Some(actual)) = (expected, actual) {
+                        *expected += 1;
+                        *actual += 1;
+                    }
+                }
+                _ => assert_eq!(model.front(), queue.peek_next()),
+            }
+            assert_eq!(model.is_empty(), queue.is_empty());
+            assert_eq!(cap.is_some_and(|cap| model.len() == cap), queue.is_full());
         }
+        while let Some(val) = model.pop_front() {
+            assert_eq!(Some(val), queue.dequeue());
+        }
+        assert_eq!(None, queue.dequeue());
     }
+
+    macro_rules! queue_conformance {
+        ($name:ident, $new:expr, $cap:expr) => {
+            mod $name {
+                use super::*;
+
+                #[test]
+                fn fifo_order() {
+                    check_fifo_order($new);
+                }
+
+                #[test]
+                fn peek_from_front() {
+                    check_peek_from_front($new);
+                }
+
+                #[test]
+                fn overflow() {
+                    check_overflow($new, $cap);
+                }
+
+                #[test]
+                fn against_model() {
+                    (1..=5).for_each(|seed| check_against_model($new, $cap, seed));
+                }
+            }
+        };
+    }
+
+    queue_conformance!(array_queue, ArrayQueue::new(), None);
+    queue_conformance!(
+        array_queue_bounded,
+        ArrayQueue::with_capacity(8).unwrap(),
+        Some(8)
+    );
+    queue_conformance!(linked_queue, LinkedQueue::new(), None);
+    queue_conformance!(
+        linked_queue_bounded,
+        LinkedQueue::with_capacity(8).unwrap(),
+        Some(8)
+    );
+    queue_conformance!(
+        unsafe_queue,
+        UnsafeQueue::with_capacity(8).unwrap(),
+        Some(8)
+    );
+    queue_conformance!(array_deque, ArrayDeque::new(), None);
+    queue_conformance!(
+        array_deque_bounded,
+        ArrayDeque::with_capacity(8).unwrap(),
+        Some(8)
+    );
+    queue_conformance!(linked_deque, LinkedDeque::new(), None);
+    queue_conformance!(
+        linked_deque_bounded,
+        LinkedDeque::with_capacity(8).unwrap(),
+        Some(8)
+    );
+    queue_conformance!(
+        amortized_queue,
+        AmortizedQueue::from_stacks(ArrayStack::new(), ArrayStack::new()),
+        None
+    );
+    queue_conformance!(
+        amortized_queue_linked,
+        AmortizedQueue::from_stacks(LinkedStack::new(), LinkedStack::new()),
+        None
+    );
+    // The clock never moves, so every item is due as soon as it is enqueued.
+    queue_conformance!(
+        delay_queue,
+        DelayQueue::with_clock(ManualClock::new(), std::time::Duration::from_millis(1)),
+        None
+    );
 }
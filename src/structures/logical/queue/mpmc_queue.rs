@@ -0,0 +1,444 @@
+use std::mem::MaybeUninit;
+
+use super::QueueErr;
+use crate::structures::logical::sync::{AtomicUsize, CachePadded, Ordering, UnsafeCell};
+
+// The sequence number of a slot tells who may use it next: the producer which
+// claimed position `pos` once it equals `pos`, the consumer which claimed it
+// once it equals `pos + 1`. The producer of the next lap gets it back once
+// the consumer sets it to `pos + cap`.
+struct Slot<T> {
+    seq: AtomicUsize,
+    val: UnsafeCell<MaybeUninit<T>>,
+}
+
+/// Lock-free bounded queue for any number of producer and consumer threads,
+/// after Dmitry Vyukov's design. Every method takes `&self`, so it is shared
+/// behind an `Arc`.
+///
+/// Producers and consumers claim a position with a compare and swap on their
+/// own counter, then wait for nobody: the sequence number of the slot at that
+/// position says whether it is ready. Enqueuing on a full queue fails with
+/// [`QueueErr::QueueOverflow`], giving the item back, and dequeuing from an
+/// empty one gives `None`. The capacity is rounded up to the next power of
+/// two.
+///
+/// Like the [`BlockingQueue`](super::BlockingQueue), it doesn't implement the
+/// [`Queue`](super::Queue) trait, as an item can't be peeked while another
+/// thread may dequeue it.
+pub struct MpmcQueue<T> {
+    slots: Box<[Slot<T>]>,
+    // The counters only ever grow, wrapping around usize::MAX, the slot of a
+    // position being its low bits. The capacity is a power of two, which
+    // divides usize::MAX + 1, so a position keeps its slot when it wraps
+    // around, any other capacity would send two consecutive positions to the
+    // same slot.
+    enqueue_pos: CachePadded<AtomicUsize>,
+    dequeue_pos: CachePadded<AtomicUsize>,
+}
+
+// SAFETY: Items are moved in by one thread and out by another, never shared,
+// so they just have to be Send for the queue to be sent and shared.
+unsafe impl<T: Send> Send for MpmcQueue<T> {}
+unsafe impl<T: Send> Sync for MpmcQueue<T> {}
+
+impl<T> MpmcQueue<T> {
+    pub fn with_capacity(cap: usize) -> Result<Self, QueueErr> {
+        Self::starting_at(cap, 0)
+    }
+
+    // Start both counters at `start` rather than 0, so that tests can reach
+    // the wrap around usize::MAX.
+    fn starting_at(cap: usize, start: usize) -> Result<Self, QueueErr> {
+        if cap == 0 {
+            return Err(QueueErr::ZeroCapacityNotAllowed);
+        }
+        let cap = cap.next_power_of_two();
+        // The slot of the position `start + i` waits for its producer.
+        let mut slots = (0..cap)
+            .map(|i| start.wrapping_add(i))
+            .map(|pos| Slot {
+                seq: AtomicUsize::new(pos),
+                val: UnsafeCell::new(MaybeUninit::uninit()),
+            })
+            .collect::<Vec<_>>();
+        slots.rotate_right(start & (cap - 1));
+        Ok(Self {
+            slots: slots.into_boxed_slice(),
+            enqueue_pos: CachePadded(AtomicUsize::new(start)),
+            dequeue_pos: CachePadded(AtomicUsize::new(start)),
+        })
+    }
+
+    pub fn cap(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Number of items in the queue, already outdated when other threads use
+    /// the queue.
+    pub fn len(&self) -> usize {
+        let dequeue_pos = self.dequeue_pos.load(Ordering::Acquire);
+        let enqueue_pos = self.enqueue_pos.load(Ordering::Acquire);
+        // Both counters are read one after the other, the dequeue one first
+        // as it trails the other, but items may come and go in between.
+        let len = enqueue_pos.wrapping_sub(dequeue_pos) as isize;
+        len.clamp(0, self.cap() as isize) as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len() == self.cap()
+    }
+
+    pub fn enqueue(&self, val: T) -> Result<(), (QueueErr, T)> {
+        let mut pos = self.enqueue_pos.load(Ordering::Relaxed);
+        loop {
+            let slot = self.slot(pos);
+            let seq = slot.seq.load(Ordering::Acquire);
+            match (seq.wrapping_sub(pos) as isize).signum() {
+                0 => match self.enqueue_pos.compare_exchange_weak(
+                    pos,
+                    pos.wrapping_add(1),
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => {
+                        // SAFETY: The position has been claimed, and the
+                        // acquire load of the sequence made sure the consumer
+                        // of the previous lap has moved its item out.
+                        slot.val.with_mut(|cell| unsafe { (*cell).write(val) });
+                        slot.seq.store(pos.wrapping_add(1), Ordering::Release);
+                        return Ok(());
+                    }
+                    Err(current) => pos = current,
+                },
+                // The item of the previous lap is still there.
+                -1 => return Err((QueueErr::QueueOverflow, val)),
+                // Another producer claimed the position, try the next one.
+                _ => pos = self.enqueue_pos.load(Ordering::Relaxed),
+            }
+        }
+    }
+
+    pub fn dequeue(&self) -> Option<T> {
+        let mut pos = self.dequeue_pos.load(Ordering::Relaxed);
+        loop {
+            let slot = self.slot(pos);
+            let seq = slot.seq.load(Ordering::Acquire);
+            match (seq.wrapping_sub(pos.wrapping_add(1)) as isize).signum() {
+                0 => match self.dequeue_pos.compare_exchange_weak(
+                    pos,
+                    pos.wrapping_add(1),
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => {
+                        // SAFETY: The position has been claimed, and the
+                        // acquire load of the sequence made sure the producer
+                        // has written its item.
+                        let val = slot.val.with(|cell| unsafe { (*cell).assume_init_read() });
+                        slot.seq
+                            .store(pos.wrapping_add(self.cap()), Ordering::Release);
+                        return Some(val);
+                    }
+                    Err(current) => pos = current,
+                },
+                // No producer has written this position yet.
+                -1 => return None,
+                // Another consumer claimed the position, try the next one.
+                _ => pos = self.dequeue_pos.load(Ordering::Relaxed),
+            }
+        }
+    }
+
+    fn slot(&self, pos: usize) -> &Slot<T> {
+        &self.slots[pos & (self.cap() - 1)]
+    }
+}
+
+impl<T> Drop for MpmcQueue<T> {
+    fn drop(&mut self) {
+        while self.dequeue().is_some() {}
+    }
+}
+
+#[cfg(all(test, not(loom)))]
+mod test {
+    use std::{
+        collections::VecDeque,
+        sync::{Arc, Mutex},
+        thread,
+        time::Instant,
+    };
+
+    use super::*;
+    use crate::structures::logical::queue::{spsc_queue, BlockingQueue};
+
+    #[test]
+    fn mpmc_queue_fifo() {
+        let queue = MpmcQueue::with_capacity(4).unwrap();
+        assert_eq!(None, queue.dequeue());
+        (1..=4).for_each(|v| queue.enqueue(v).unwrap());
+        assert!(queue.is_full());
+        assert_eq!(Err((QueueErr::QueueOverflow, 5)), queue.enqueue(5));
+        assert_eq!(Some(1), queue.dequeue());
+        // next lap on the first slot
+        queue.enqueue(5).unwrap();
+        assert_eq!(4, queue.len());
+        let dequeued = std::iter::from_fn(|| queue.dequeue()).collect::<Vec<_>>();
+        assert_eq!(vec![2, 3, 4, 5], dequeued);
+        assert!(queue.is_empty());
+        assert_eq!(
+            Some(QueueErr::ZeroCapacityNotAllowed),
+            MpmcQueue::<i32>::with_capacity(0).err()
+        );
+    }
+
+    #[test]
+    fn mpmc_queue_position_wraps_around() {
+        let queue = MpmcQueue::starting_at(3, usize::MAX - 1).unwrap();
+        assert_eq!(4, queue.cap());
+        (0..4).for_each(|v| queue.enqueue(v).unwrap());
+        assert!(queue.is_full());
+        assert_eq!(Err((QueueErr::QueueOverflow, 4)), queue.enqueue(4));
+        assert_eq!(Some(0), queue.dequeue());
+        queue.enqueue(4).unwrap();
+        assert_eq!(4, queue.len());
+        let dequeued = std::iter::from_fn(|| queue.dequeue()).collect::<Vec<_>>();
+        assert_eq!(vec![1, 2, 3, 4], dequeued);
+    }
+
+    #[test]
+    fn mpmc_queue_drops_items_left() {
+        let counter = Arc::new(());
+        let queue = MpmcQueue::with_capacity(4).unwrap();
+        (0..3).for_each(|_| queue.enqueue(Arc::clone(&counter)).unwrap());
+        assert_eq!(4, Arc::strong_count(&counter));
+        drop(queue);
+        assert_eq!(1, Arc::strong_count(&counter));
+    }
+
+    #[test]
+    fn mpmc_queue_stress() {
+        const PRODUCERS: u64 = 4;
+        const CONSUMERS: usize = 4;
+        const ITEMS: u64 = 20_000;
+
+        let queue = Arc::new(MpmcQueue::with_capacity(16).unwrap());
+        let producers = (0..PRODUCERS)
+            .map(|producer| {
+                let queue = Arc::clone(&queue);
+                thread::spawn(move || {
+                    for seq in 0..ITEMS {
+                        while queue.enqueue((producer, seq)).is_err() {
+                            thread::yield_now();
+                        }
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        let remaining = Arc::new(AtomicUsize::new((PRODUCERS * ITEMS) as usize));
+        let consumers = (0..CONSUMERS)
+            .map(|_| {
+                let queue = Arc::clone(&queue);
+                let remaining = Arc::clone(&remaining);
+                thread::spawn(move || {
+                    let mut received = Vec::new();
+                    while remaining.load(Ordering::Relaxed) > 0 {
+                        match queue.dequeue() {
+                            Some(item) => {
+                                remaining.fetch_sub(1, Ordering::Relaxed);
+                                received.push(item);
+                            }
+                            None => thread::yield_now(),
+                        }
+                    }
+                    received
+                })
+            })
+            .collect::<Vec<_>>();
+
+        producers
+            .into_iter()
+            .for_each(|producer| producer.join().unwrap());
+        let mut all = Vec::new();
+        for consumer in consumers {
+            let received = consumer.join().unwrap();
+            // FIFO: a consumer sees the items of a producer in order
+            for producer in 0..PRODUCERS {
+                let seqs = received
+                    .iter()
+                    .filter(|(from, _)| *from == producer)
+                    .map(|(_, seq)| *seq)
+                    .collect::<Vec<_>>();
+                assert!(seqs.windows(2).all(|pair| pair[0] < pair[1]));
+            }
+            all.extend(received);
+        }
+        // every item has been received exactly once
+        all.sort();
+        let expected = (0..PRODUCERS)
+            .flat_map(|producer| (0..ITEMS).map(move |seq| (producer, seq)))
+            .collect::<Vec<_>>();
+        assert_eq!(expected, all);
+        assert!(queue.is_empty());
+    }
+
+    // Move `items` integers from each producer to the consumers, retrying
+    // enqueue and dequeue until they succeed, and give the items per second.
+    fn throughput<Q: Send + Sync + 'static>(
+        queue: Q,
+        producers: usize,
+        consumers: usize,
+        items: usize,
+        enqueue: fn(&Q, usize) -> bool,
+        dequeue: fn(&Q) -> bool,
+    ) -> f64 {
+        let queue = Arc::new(queue);
+        let remaining = Arc::new(AtomicUsize::new(producers * items));
+        let start = Instant::now();
+        let mut handles = Vec::new();
+        for _ in 0..producers {
+            let queue = Arc::clone(&queue);
+            handles.push(thread::spawn(move || {
+                for v in 0..items {
+                    while !enqueue(&queue, v) {
+                        thread::yield_now();
+                    }
+                }
+            }));
+        }
+        for _ in 0..consumers {
+            let queue = Arc::clone(&queue);
+            let remaining = Arc::clone(&remaining);
+            handles.push(thread::spawn(move || {
+                while remaining.load(Ordering::Relaxed) > 0 {
+                    if dequeue(&queue) {
+                        remaining.fetch_sub(1, Ordering::Relaxed);
+                    } else {
+                        thread::yield_now();
+                    }
+                }
+            }));
+        }
+        handles
+            .into_iter()
+            .for_each(|handle| handle.join().unwrap());
+        (producers * items) as f64 / start.elapsed().as_secs_f64()
+    }
+
+    // Compare the lock-free queues with locking ones, run it with
+    // `cargo test --release lock_free_queue_bench -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn lock_free_queue_bench() {
+        const CAP: usize = 1024;
+        const ITEMS: usize = 1_000_000;
+
+        let spsc = {
+            let (mut producer, mut consumer) = spsc_queue(CAP).unwrap();
+            let start = Instant::now();
+            let handle = thread::spawn(move || {
+                for v in 0..ITEMS {
+                    while producer.enqueue(v).is_err() {
+                        thread::yield_now();
+                    }
+                }
+            });
+            let mut received = 0;
+            while received < ITEMS {
+                match consumer.dequeue() {
+                    Some(_) => received += 1,
+                    None => thread::yield_now(),
+                }
+            }
+            handle.join().unwrap();
+            ITEMS as f64 / start.elapsed().as_secs_f64()
+        };
+        let mpmc = |producers, consumers| {
+            throughput(
+                MpmcQueue::with_capacity(CAP).unwrap(),
+                producers,
+                consumers,
+                ITEMS / producers,
+                |queue, v| queue.enqueue(v).is_ok(),
+                |queue| queue.dequeue().is_some(),
+            )
+        };
+        let blocking = |producers, consumers| {
+            throughput(
+                BlockingQueue::with_capacity(CAP).unwrap(),
+                producers,
+                consumers,
+                ITEMS / producers,
+                |queue, v| queue.try_enqueue(v).is_ok(),
+                |queue| queue.try_dequeue().is_ok(),
+            )
+        };
+        let mutex = |producers, consumers| {
+            throughput(
+                Mutex::new(VecDeque::with_capacity(CAP)),
+                producers,
+                consumers,
+                ITEMS / producers,
+                |queue, v| {
+                    let mut queue = queue.lock().unwrap();
+                    queue.len() < CAP && {
+                        queue.push_back(v);
+                        true
+                    }
+                },
+                |queue| queue.lock().unwrap().pop_front().is_some(),
+            )
+        };
+
+        let results = [
+            ("spsc_queue 1p/1c", spsc),
+            ("MpmcQueue 1p/1c", mpmc(1, 1)),
+            ("MpmcQueue 4p/4c", mpmc(4, 4)),
+            ("BlockingQueue 1p/1c", blocking(1, 1)),
+            ("BlockingQueue 4p/4c", blocking(4, 4)),
+            ("Mutex<VecDeque> 1p/1c", mutex(1, 1)),
+            ("Mutex<VecDeque> 4p/4c", mutex(4, 4)),
+        ];
+        println!("{:<24} {:>14}", "queue", "items/s");
+        for (name, rate) in results {
+            println!("{name:<24} {rate:>14.0}");
+        }
+    }
+}
+
+#[cfg(all(test, loom))]
+mod loom_test {
+    use loom::{sync::Arc, thread};
+
+    use super::*;
+
+    #[test]
+    fn mpmc_queue_loom() {
+        loom::model(|| {
+            let queue = Arc::new(MpmcQueue::with_capacity(2).unwrap());
+            let producers = (0..2)
+                .map(|v| {
+                    let queue = Arc::clone(&queue);
+                    thread::spawn(move || queue.enqueue(v))
+                })
+                .collect::<Vec<_>>();
+            let consumer = {
+                let queue = Arc::clone(&queue);
+                thread::spawn(move || queue.dequeue())
+            };
+            producers
+                .into_iter()
+                .for_each(|producer| producer.join().unwrap().unwrap());
+            let first = consumer.join().unwrap();
+            let mut seen = first.into_iter().chain(queue.dequeue()).collect::<Vec<_>>();
+            seen.extend(queue.dequeue());
+            seen.sort();
+            assert_eq!(vec![0, 1], seen);
+        });
+    }
+}
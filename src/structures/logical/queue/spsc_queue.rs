@@ -0,0 +1,321 @@
+use std::mem::MaybeUninit;
+
+use super::QueueErr;
+use crate::structures::logical::sync::{Arc, AtomicUsize, CachePadded, Ordering, UnsafeCell};
+
+// Ring of `cap` slots shared by the two handles. `head` and `tail` only ever
+// grow (wrapping around usize::MAX), the slot of an index being its low bits,
+// so the number of items is always `tail - head`, even when the ring is full.
+// The capacity is a power of two, which divides usize::MAX + 1, so an index
+// keeps its slot when it wraps around, any other capacity would send two
+// consecutive indices to the same slot. The producer alone writes `tail`, the
+// consumer alone writes `head`, and each keeps its own index on its own cache
+// line.
+struct Ring<T> {
+    slots: Box<[UnsafeCell<MaybeUninit<T>>]>,
+    head: CachePadded<AtomicUsize>,
+    tail: CachePadded<AtomicUsize>,
+}
+
+impl<T> Ring<T> {
+    fn slot(&self, idx: usize) -> &UnsafeCell<MaybeUninit<T>> {
+        &self.slots[idx & (self.slots.len() - 1)]
+    }
+}
+
+impl<T> Drop for Ring<T> {
+    fn drop(&mut self) {
+        let tail = self.tail.load(Ordering::Relaxed);
+        let mut head = self.head.load(Ordering::Relaxed);
+        while head != tail {
+            // SAFETY: The slots from head to tail hold the items enqueued but
+            // not dequeued yet, and both handles are gone.
+            self.slot(head)
+                .with_mut(|slot| unsafe { (*slot).assume_init_drop() });
+            head = head.wrapping_add(1);
+        }
+    }
+}
+
+/// Create a wait-free bounded queue for exactly one producer thread and one
+/// consumer thread, given as two handles which can be sent to them.
+///
+/// The capacity is rounded up to the next power of two.
+///
+/// Neither side ever waits for the other: enqueuing on a full queue fails
+/// with [`QueueErr::QueueOverflow`], and dequeuing from an empty one gives
+/// `None`. Each side reads the index of the other side only when the one it
+/// cached doesn't tell whether there is room or an item, which keeps the
+/// cache line of that index from bouncing between the two threads.
+pub fn spsc_queue<T>(cap: usize) -> Result<(Producer<T>, Consumer<T>), QueueErr> {
+    spsc_queue_from(cap, 0)
+}
+
+// Start both indices at `start` rather than 0, so that tests can reach the
+// wrap around usize::MAX.
+fn spsc_queue_from<T>(cap: usize, start: usize) -> Result<(Producer<T>, Consumer<T>), QueueErr> {
+    if cap == 0 {
+        return Err(QueueErr::ZeroCapacityNotAllowed);
+    }
+    let ring = Arc::new(Ring {
+        slots: (0..cap.next_power_of_two())
+            .map(|_| UnsafeCell::new(MaybeUninit::uninit()))
+            .collect(),
+        head: CachePadded(AtomicUsize::new(start)),
+        tail: CachePadded(AtomicUsize::new(start)),
+    });
+    let producer = Producer {
+        ring: Arc::clone(&ring),
+        tail: start,
+        cached_head: start,
+    };
+    let consumer = Consumer {
+        ring,
+        head: start,
+        cached_tail: start,
+    };
+    Ok((producer, consumer))
+}
+
+/// Enqueuing side of a [`spsc_queue`].
+pub struct Producer<T> {
+    ring: Arc<Ring<T>>,
+    // Own copy of the tail, only this handle writes it.
+    tail: usize,
+    // Last head seen, the real one can only be further.
+    cached_head: usize,
+}
+
+// SAFETY: The handle only moves items into the ring, the consumer being the
+// only one reaching them afterwards, so items just have to be Send.
+unsafe impl<T: Send> Send for Producer<T> {}
+
+impl<T> Producer<T> {
+    pub fn cap(&self) -> usize {
+        self.ring.slots.len()
+    }
+
+    /// Enqueue an item, or fail with [`QueueErr::QueueOverflow`] when the
+    /// queue is full, giving the item back.
+    pub fn enqueue(&mut self, val: T) -> Result<(), (QueueErr, T)> {
+        if self.tail.wrapping_sub(self.cached_head) == self.cap() {
+            self.cached_head = self.ring.head.load(Ordering::Acquire);
+            if self.tail.wrapping_sub(self.cached_head) == self.cap() {
+                return Err((QueueErr::QueueOverflow, val));
+            }
+        }
+        // SAFETY: The slot is past the tail, the consumer doesn't read it
+        // until the new tail is published, and the acquire load of the head
+        // made sure it has finished moving the previous item out of it.
+        self.ring
+            .slot(self.tail)
+            .with_mut(|slot| unsafe { (*slot).write(val) });
+        self.tail = self.tail.wrapping_add(1);
+        self.ring.tail.store(self.tail, Ordering::Release);
+        Ok(())
+    }
+
+    /// Number of items in the queue, the consumer may have dequeued some of
+    /// them already.
+    pub fn len(&self) -> usize {
+        self.tail
+            .wrapping_sub(self.ring.head.load(Ordering::Acquire))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len() == self.cap()
+    }
+}
+
+/// Dequeuing side of a [`spsc_queue`].
+pub struct Consumer<T> {
+    ring: Arc<Ring<T>>,
+    // Own copy of the head, only this handle writes it.
+    head: usize,
+    // Last tail seen, the real one can only be further.
+    cached_tail: usize,
+}
+
+// SAFETY: See the Producer, the consumer only moves items out of the ring.
+unsafe impl<T: Send> Send for Consumer<T> {}
+
+impl<T> Consumer<T> {
+    pub fn cap(&self) -> usize {
+        self.ring.slots.len()
+    }
+
+    pub fn dequeue(&mut self) -> Option<T> {
+        if !self.has_next() {
+            return None;
+        }
+        // SAFETY: The slot is before the tail, so the producer has written it
+        // and won't touch it again until the new head is published.
+        let val = self
+            .ring
+            .slot(self.head)
+            .with(|slot| unsafe { (*slot).assume_init_read() });
+        self.head = self.head.wrapping_add(1);
+        self.ring.head.store(self.head, Ordering::Release);
+        Some(val)
+    }
+
+    /// Front item, the producer can't overwrite it while it is borrowed as
+    /// only this handle dequeues.
+    pub fn peek_next(&mut self) -> Option<&T> {
+        if !self.has_next() {
+            return None;
+        }
+        // SAFETY: Same as dequeue, and the borrow of the handle keeps the item
+        // from being dequeued while the reference lives.
+        Some(
+            self.ring
+                .slot(self.head)
+                .with(|slot| unsafe { (*slot).assume_init_ref() }),
+        )
+    }
+
+    /// Number of items in the queue, the producer may have enqueued more
+    /// since.
+    pub fn len(&self) -> usize {
+        self.ring
+            .tail
+            .load(Ordering::Acquire)
+            .wrapping_sub(self.head)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // Whether there is an item at the head, refreshing the cached tail only
+    // when it doesn't tell.
+    fn has_next(&mut self) -> bool {
+        if self.head == self.cached_tail {
+            self.cached_tail = self.ring.tail.load(Ordering::Acquire);
+        }
+        self.head != self.cached_tail
+    }
+}
+
+#[cfg(all(test, not(loom)))]
+mod test {
+    use std::thread;
+
+    use super::*;
+
+    #[test]
+    fn spsc_queue_fifo() {
+        let (mut producer, mut consumer) = spsc_queue(4).unwrap();
+        assert_eq!(4, producer.cap());
+        assert_eq!(None, consumer.dequeue());
+        (1..=4).for_each(|v| producer.enqueue(v).unwrap());
+        assert!(producer.is_full());
+        assert_eq!(Err((QueueErr::QueueOverflow, 5)), producer.enqueue(5));
+        assert_eq!(Some(&1), consumer.peek_next());
+        assert_eq!(Some(1), consumer.dequeue());
+        // wraps around the end of the ring
+        producer.enqueue(5).unwrap();
+        assert_eq!(4, consumer.len());
+        let dequeued = std::iter::from_fn(|| consumer.dequeue()).collect::<Vec<_>>();
+        assert_eq!(vec![2, 3, 4, 5], dequeued);
+        assert!(producer.is_empty());
+    }
+
+    #[test]
+    fn spsc_queue_capacity_power_of_two() {
+        let (producer, consumer) = spsc_queue::<i32>(3).unwrap();
+        assert_eq!(4, producer.cap());
+        assert_eq!(4, consumer.cap());
+    }
+
+    #[test]
+    fn spsc_queue_index_wraps_around() {
+        let (mut producer, mut consumer) = spsc_queue_from(3, usize::MAX - 1).unwrap();
+        (0..4).for_each(|v| producer.enqueue(v).unwrap());
+        assert!(producer.is_full());
+        assert_eq!(Some(0), consumer.dequeue());
+        producer.enqueue(4).unwrap();
+        let dequeued = std::iter::from_fn(|| consumer.dequeue()).collect::<Vec<_>>();
+        assert_eq!(vec![1, 2, 3, 4], dequeued);
+    }
+
+    #[test]
+    fn spsc_queue_zero_capacity() {
+        assert_eq!(
+            Some(QueueErr::ZeroCapacityNotAllowed),
+            spsc_queue::<i32>(0).err()
+        );
+    }
+
+    #[test]
+    fn spsc_queue_drops_items_left() {
+        let counter = std::sync::Arc::new(());
+        let (mut producer, mut consumer) = spsc_queue(4).unwrap();
+        (0..4).for_each(|_| producer.enqueue(std::sync::Arc::clone(&counter)).unwrap());
+        consumer.dequeue();
+        assert_eq!(4, std::sync::Arc::strong_count(&counter));
+        drop(producer);
+        drop(consumer);
+        assert_eq!(1, std::sync::Arc::strong_count(&counter));
+    }
+
+    #[test]
+    fn spsc_queue_threads() {
+        const ITEMS: u64 = 100_000;
+        let (mut producer, mut consumer) = spsc_queue(64).unwrap();
+        let handle = thread::spawn(move || {
+            for v in 0..ITEMS {
+                while producer.enqueue(v).is_err() {
+                    thread::yield_now();
+                }
+            }
+        });
+        let mut expected = 0;
+        while expected < ITEMS {
+            match consumer.dequeue() {
+                Some(v) => {
+                    assert_eq!(expected, v);
+                    expected += 1;
+                }
+                None => thread::yield_now(),
+            }
+        }
+        handle.join().unwrap();
+        assert_eq!(None, consumer.dequeue());
+    }
+}
+
+#[cfg(all(test, loom))]
+mod loom_test {
+    use loom::thread;
+
+    use super::*;
+
+    #[test]
+    fn spsc_queue_loom() {
+        loom::model(|| {
+            let (mut producer, mut consumer) = spsc_queue(2).unwrap();
+            let handle = thread::spawn(move || {
+                for v in 0..3 {
+                    while producer.enqueue(v).is_err() {
+                        thread::yield_now();
+                    }
+                }
+            });
+            for v in 0..3 {
+                loop {
+                    if let Some(got) = consumer.dequeue() {
+                        assert_eq!(v, got);
+                        break;
+                    }
+                    thread::yield_now();
+                }
+            }
+            handle.join().unwrap();
+        });
+    }
+}
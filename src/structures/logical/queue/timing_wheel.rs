@@ -0,0 +1,454 @@
+use std::cmp::Ordering;
+
+use super::{BinaryHeap, QueueErr};
+use crate::structures::physical::arena::{Arena, ArenaIndex};
+
+const SLOT_BITS: u32 = 6;
+const SLOTS: usize = 1 << SLOT_BITS;
+const LEVELS: usize = 6;
+// Number of ticks covered by the levels, deadlines further away than the end
+// of the current rotation of the top level wait in the overflow list.
+const RANGE_BITS: u32 = SLOT_BITS * LEVELS as u32;
+// Stale references are swept once they outnumber the items, and this many at
+// least, so that a small wheel isn't swept all the time.
+const MIN_SWEEP: usize = 64;
+
+/// Handle to an item of a [`TimingWheel`] or a
+/// [`DelayQueue`](super::DelayQueue), to cancel or reschedule it. It becomes
+/// stale once the item has been dequeued or cancelled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TimerHandle(ArenaIndex);
+
+struct WheelEntry<T> {
+    val: T,
+    deadline: u64,
+    // Changes with every rescheduling, so references to the entry left in the
+    // slots it was in before can be told apart from the current one.
+    seq: u64,
+}
+
+// Expired entries come out by deadline, then in the order they have been
+// scheduled.
+fn expiry_order(a: &(u64, u64, ArenaIndex), b: &(u64, u64, ArenaIndex)) -> Ordering {
+    (a.0, a.1).cmp(&(b.0, b.1))
+}
+
+struct Level {
+    // Bit `s` is set when the slot `s` may hold entries.
+    occupied: u64,
+    slots: Vec<Vec<(ArenaIndex, u64)>>,
+}
+
+/// Hierarchical timing wheel, with deadlines counted in ticks. Each of the 6
+/// levels has 64 slots, a slot of level `l` spanning `64^l` ticks: an entry
+/// goes to the level of the highest base 64 digit where its deadline differs
+/// from the current tick, so the closer the deadline, the finer the slot.
+/// When time reaches the start of a slot, its entries are placed again, in a
+/// lower level or as expired, which is called cascading.
+///
+/// Insertion and rescheduling are O(1) for a deadline still to come, and
+/// O(log n) for a deadline already reached, the entry going to the heap of
+/// expired entries. Advancing is O(1) per entry cascaded, and at most
+/// `levels` cascades happen per entry. Time can jump ahead by any amount, only
+/// the slots holding entries are visited.
+///
+/// Cancelling or rescheduling an entry leaves a stale reference where it was,
+/// which is skipped when reached. The stale references are swept all at once
+/// when they outnumber the items, so they take no more memory than the items,
+/// and cancellation is O(1) amortized.
+///
+/// Expired entries come out earliest deadline first, then in the order they
+/// have been scheduled.
+pub struct TimingWheel<T> {
+    entries: Arena<WheelEntry<T>>,
+    levels: Vec<Level>,
+    overflow: Vec<(ArenaIndex, u64)>,
+    // (deadline, seq, entry) of the expired entries.
+    expired: BinaryHeap<(u64, u64, ArenaIndex)>,
+    elapsed: u64,
+    next_seq: u64,
+    // References to entries which have been cancelled or rescheduled since.
+    stale: usize,
+}
+
+impl<T> TimingWheel<T> {
+    pub fn new() -> Self {
+        Self {
+            entries: Arena::new(),
+            levels: (0..LEVELS)
+                .map(|_| Level {
+                    occupied: 0,
+                    slots: (0..SLOTS).map(|_| Vec::new()).collect(),
+                })
+                .collect(),
+            overflow: Vec::new(),
+            expired: BinaryHeap::with_comparator(expiry_order),
+            elapsed: 0,
+            next_seq: 0,
+            stale: 0,
+        }
+    }
+
+    /// Current tick, the last one the wheel has been advanced to.
+    pub fn elapsed(&self) -> u64 {
+        self.elapsed
+    }
+
+    /// Number of items, expired or not.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Schedule an item for the given tick, a tick already reached makes it
+    /// expired right away.
+    pub fn insert(&mut self, val: T, deadline: u64) -> TimerHandle {
+        let seq = self.take_seq();
+        let idx = self.entries.insert(WheelEntry { val, deadline, seq });
+        self.place(idx);
+        TimerHandle(idx)
+    }
+
+    /// Remove an item, expired or not, and give it back.
+    pub fn cancel(&mut self, handle: TimerHandle) -> Option<T> {
+        let entry = self.entries.remove(handle.0).ok()?;
+        self.add_stale();
+        Some(entry.val)
+    }
+
+    /// Move the deadline of an item, expired or not.
+    pub fn reschedule(&mut self, handle: TimerHandle, deadline: u64) -> Result<(), QueueErr> {
+        let seq = self.take_seq();
+        let entry = self
+            .entries
+            .get_mut(handle.0)
+            .ok_or(QueueErr::UnknownHandle)?;
+        entry.deadline = deadline;
+        entry.seq = seq;
+        self.place(handle.0);
+        self.add_stale();
+        Ok(())
+    }
+
+    pub fn get(&self, handle: TimerHandle) -> Option<&T> {
+        self.entries.get(handle.0).map(|entry| &entry.val)
+    }
+
+    pub fn get_mut(&mut self, handle: TimerHandle) -> Option<&mut T> {
+        self.entries.get_mut(handle.0).map(|entry| &mut entry.val)
+    }
+
+    pub fn deadline(&self, handle: TimerHandle) -> Option<u64> {
+        self.entries.get(handle.0).map(|entry| entry.deadline)
+    }
+
+    /// Move time forward to the given tick, expiring every item whose
+    /// deadline is reached. A tick in the past does nothing.
+    pub fn advance(&mut self, now: u64) {
+        while let Some((level, start)) = self.next_slot().filter(|(_, start)| *start <= now) {
+            self.elapsed = start;
+            let refs = if level == LEVELS {
+                std::mem::take(&mut self.overflow)
+            } else {
+                let slot = Self::slot_of(start, level);
+                self.levels[level].occupied &= !(1 << slot);
+                std::mem::take(&mut self.levels[level].slots[slot])
+            };
+            for (idx, seq) in refs {
+                if self.is_current(idx, seq) {
+                    self.place(idx);
+                } else {
+                    self.stale -= 1;
+                }
+            }
+        }
+        self.elapsed = self.elapsed.max(now);
+    }
+
+    /// Remove the expired item with the earliest deadline.
+    pub fn pop_expired(&mut self) -> Option<T> {
+        while let Some((_, seq, idx)) = self.expired.pop() {
+            if self.is_current(idx, seq) {
+                return self.entries.remove(idx).ok().map(|entry| entry.val);
+            }
+            self.stale -= 1;
+        }
+        None
+    }
+
+    /// Earliest tick at which an item may expire: the current tick when some
+    /// already have, otherwise the start of the earliest slot holding items,
+    /// which is at or before their deadlines. Waiting until then before
+    /// advancing never misses a deadline, but cancelled items may make it
+    /// earlier than needed.
+    pub fn next_expiration(&self) -> Option<u64> {
+        if !self.expired.is_empty() {
+            return Some(self.elapsed);
+        }
+        self.next_slot().map(|(_, start)| start)
+    }
+
+    /// Handles of the items whose deadline is at or before the given tick, in
+    /// the order they would come out, whether the wheel has been advanced to
+    /// the tick or not. O(n log n), as every item is looked at.
+    pub fn due(&self, now: u64) -> Vec<TimerHandle> {
+        let mut due = self
+            .entries
+            .iter()
+            .filter(|(_, entry)| entry.deadline <= now)
+            .map(|(idx, entry)| (entry.deadline, entry.seq, idx))
+            .collect::<Vec<_>>();
+        due.sort_by_key(|(deadline, seq, _)| (*deadline, *seq));
+        due.into_iter()
+            .map(|(_, _, idx)| TimerHandle(idx))
+            .collect()
+    }
+
+    // Whether the reference points to the entry as it is scheduled now.
+    fn is_current(&self, idx: ArenaIndex, seq: u64) -> bool {
+        self.entries.get(idx).is_some_and(|entry| entry.seq == seq)
+    }
+
+    fn add_stale(&mut self) {
+        self.stale += 1;
+        if self.stale > self.entries.len().max(MIN_SWEEP) {
+            self.sweep();
+        }
+    }
+
+    // Drop every stale reference. This is O(n log n) for n references, the
+    // expired heap being rebuilt, but at least n / 2 of them are stale, so
+    // it's O(log n) amortized over the cancellations which made them stale.
+    fn sweep(&mut self) {
+        let entries = &self.entries;
+        let is_current = |&(idx, seq): &(ArenaIndex, u64)| {
+            entries.get(idx).is_some_and(|entry| entry.seq == seq)
+        };
+        for level in &mut self.levels {
+            for (slot, refs) in level.slots.iter_mut().enumerate() {
+                refs.retain(is_current);
+                if refs.is_empty() {
+                    level.occupied &= !(1 << slot);
+                }
+            }
+        }
+        self.overflow.retain(is_current);
+        let expired =
+            std::mem::replace(&mut self.expired, BinaryHeap::with_comparator(expiry_order));
+        self.expired = BinaryHeap::from_iter_with(
+            expired
+                .into_sorted_vec()
+                .into_iter()
+                .filter(|&(_, seq, idx)| is_current(&(idx, seq))),
+            expiry_order,
+        );
+        self.stale = 0;
+    }
+
+    fn take_seq(&mut self) -> u64 {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        seq
+    }
+
+    fn slot_of(tick: u64, level: usize) -> usize {
+        ((tick >> (SLOT_BITS * level as u32)) as usize) & (SLOTS - 1)
+    }
+
+    // Put the entry where its deadline says, relative to the current tick.
+    fn place(&mut self, idx: ArenaIndex) {
+        let entry = self.entries.get(idx).expect("the entry is in the arena");
+        let (deadline, seq) = (entry.deadline, entry.seq);
+        if deadline <= self.elapsed {
+            self.expired.push((deadline, seq, idx));
+        } else if deadline >> RANGE_BITS != self.elapsed >> RANGE_BITS {
+            self.overflow.push((idx, seq));
+        } else {
+            // Highest base 64 digit where the deadline differs from now.
+            let differing = 63 - (deadline ^ self.elapsed).leading_zeros();
+            let level = (differing / SLOT_BITS) as usize;
+            let slot = Self::slot_of(deadline, level);
+            self.levels[level].slots[slot].push((idx, seq));
+            self.levels[level].occupied |= 1 << slot;
+        }
+    }
+
+    // Level and start tick of the earliest slot holding entries, the overflow
+    // list being the level past the top one, which starts with the next
+    // rotation of the top level.
+    //
+    // The entries of a level are in slots after the current digit of that
+    // level, as the deadline is later than now, and the slots of a level all
+    // come before those of the levels above, so the first level with entries
+    // has the earliest slot.
+    fn next_slot(&self) -> Option<(usize, u64)> {
+        for (level, slots) in self.levels.iter().enumerate() {
+            let shift = SLOT_BITS * level as u32;
+            let current = Self::slot_of(self.elapsed, level);
+            let later = match current {
+                63 => 0,
+                current => slots.occupied & (u64::MAX << (current + 1)),
+            };
+            if later != 0 {
+                let window = self.elapsed & !((1u64 << (shift + SLOT_BITS)) - 1);
+                return Some((level, window | ((later.trailing_zeros() as u64) << shift)));
+            }
+        }
+        if self.overflow.is_empty() {
+            None
+        } else {
+            Some((LEVELS, ((self.elapsed >> RANGE_BITS) + 1) << RANGE_BITS))
+        }
+    }
+}
+
+impl<T> Default for TimingWheel<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::test_utils::XorShift;
+
+    #[test]
+    fn timing_wheel_expires_in_order() {
+        let mut wheel = TimingWheel::new();
+        wheel.insert("c", 70);
+        wheel.insert("a", 5);
+        wheel.insert("b", 5);
+        wheel.insert("d", 5_000);
+        assert_eq!(Some(5), wheel.next_expiration());
+        wheel.advance(4);
+        assert_eq!(None, wheel.pop_expired());
+        wheel.advance(100);
+        assert_eq!(100, wheel.elapsed());
+        let expired = std::iter::from_fn(|| wheel.pop_expired()).collect::<Vec<_>>();
+        assert_eq!(vec!["a", "b", "c"], expired);
+        assert_eq!(1, wheel.len());
+        // a deadline already reached expires at once
+        wheel.insert("e", 10);
+        assert_eq!(Some("e"), wheel.pop_expired());
+    }
+
+    #[test]
+    fn timing_wheel_cancel_reschedule() {
+        let mut wheel = TimingWheel::new();
+        let a = wheel.insert('a', 10);
+        let b = wheel.insert('b', 20);
+        assert_eq!(Some('a'), wheel.cancel(a));
+        assert_eq!(None, wheel.cancel(a));
+        assert_eq!(Err(QueueErr::UnknownHandle), wheel.reschedule(a, 5));
+        wheel.reschedule(b, 3_000).unwrap();
+        assert_eq!(Some(3_000), wheel.deadline(b));
+        wheel.advance(2_999);
+        // neither the cancelled item nor the old slot of b expire anything
+        assert_eq!(None, wheel.pop_expired());
+        *wheel.get_mut(b).unwrap() = 'c';
+        wheel.advance(3_000);
+        assert_eq!(Some('c'), wheel.pop_expired());
+        assert_eq!(None, wheel.get(b));
+        assert!(wheel.is_empty());
+    }
+
+    #[test]
+    fn timing_wheel_sweeps_stale_references() {
+        let mut wheel = TimingWheel::new();
+        let refs = |wheel: &TimingWheel<u32>| {
+            let slots = wheel.levels.iter().flat_map(|level| &level.slots);
+            slots.map(Vec::len).sum::<usize>() + wheel.overflow.len() + wheel.expired.len()
+        };
+        let far = wheel.insert(0, 1 << 40);
+        let near = wheel.insert(1, 10);
+        let expired = wheel.insert(2, 0);
+        for i in 0..10_000 {
+            wheel.reschedule(far, (1 << 40) + i).unwrap();
+            wheel.reschedule(near, 10 + i % 50).unwrap();
+            wheel.reschedule(expired, 0).unwrap();
+        }
+        assert!(refs(&wheel) <= 3 + MIN_SWEEP + 1);
+        for i in 0..1_000 {
+            let handle = wheel.insert(i, 1 << 40);
+            wheel.cancel(handle);
+        }
+        assert!(refs(&wheel) <= 3 + MIN_SWEEP + 1);
+        assert_eq!(Some(2), wheel.pop_expired());
+        wheel.advance(60);
+        assert_eq!(Some(1), wheel.pop_expired());
+        wheel.advance((1 << 40) + 9_999);
+        assert_eq!(Some(0), wheel.pop_expired());
+        assert!(wheel.is_empty());
+    }
+
+    #[test]
+    fn timing_wheel_far_deadlines() {
+        let mut wheel = TimingWheel::new();
+        let far = 1 << 40;
+        wheel.insert("far", far + 3);
+        wheel.insert("top", (1 << RANGE_BITS) - 1);
+        wheel.advance(far);
+        assert_eq!(Some("top"), wheel.pop_expired());
+        assert_eq!(None, wheel.pop_expired());
+        assert_eq!(Some(far + 3), wheel.next_expiration());
+        wheel.advance(far + 3);
+        assert_eq!(Some("far"), wheel.pop_expired());
+    }
+
+    #[test]
+    fn timing_wheel_against_model() {
+        let mut rng = XorShift::new(17);
+        let mut wheel = TimingWheel::new();
+        // (deadline, order of scheduling) -> item
+        let mut model = BTreeMap::new();
+        let mut handles = Vec::new();
+        let mut now = 0;
+        for step in 0..5_000u64 {
+            match rng.below(10) {
+                0..=4 => {
+                    let scale = 4 * rng.below(8);
+                    let deadline = now + rng.below(1 << scale);
+                    handles.push((wheel.insert(step, deadline), deadline, step));
+                    model.insert((deadline, step), step);
+                }
+                5 if !handles.is_empty() => {
+                    let (handle, deadline, seq) =
+                        handles.swap_remove(rng.below(handles.len() as u64) as usize);
+                    assert_eq!(model.remove(&(deadline, seq)), wheel.cancel(handle));
+                }
+                6 if !handles.is_empty() => {
+                    let pick = rng.below(handles.len() as u64) as usize;
+                    let (handle, deadline, seq) = handles[pick];
+                    if let Some(val) = model.remove(&(deadline, seq)) {
+                        let deadline = now + rng.below(5_000);
+                        wheel.reschedule(handle, deadline).unwrap();
+                        model.insert((deadline, step), val);
+                        handles[pick] = (handle, deadline, step);
+                    }
+                }
+                _ => {
+                    let scale = 3 * rng.below(6);
+                    now += rng.below(1 << scale);
+                    wheel.advance(now);
+                    while let Some(val) = wheel.pop_expired() {
+                        let (&key, _) = model.iter().next().unwrap();
+                        assert!(key.0 <= now);
+                        assert_eq!(model.remove(&key), Some(val));
+                    }
+                    assert!(model.keys().next().is_none_or(|key| key.0 > now));
+                    assert_eq!(0, wheel.due(now).len());
+                    if let Some(next) = model.keys().next() {
+                        assert!(wheel.next_expiration().unwrap() <= next.0);
+                    }
+                }
+            }
+            assert_eq!(model.len(), wheel.len());
+        }
+    }
+}
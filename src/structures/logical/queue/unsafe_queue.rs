@@ -0,0 +1,283 @@
+use std::{alloc, mem, ptr::NonNull};
+
+use super::{OverflowPolicy, Queue, QueueErr};
+
+/// Bounded queue managing its own heap buffer through a raw pointer, like
+/// [`Array`](crate::structures::physical::array::Array) does. The buffer is a
+/// ring of exactly `cap` slots, where only the `len` slots starting at `head`
+/// (wrapping around the end) are initialized. There is no `Option` per slot,
+/// the queue takes `size_of::<T>() * cap` bytes on the heap.
+///
+/// The tests of this module are meant to be run under Miri as well, with
+/// `cargo +nightly miri test unsafe_queue`.
+pub struct UnsafeQueue<T> {
+    ptr: NonNull<T>,
+    cap: usize,
+    head: usize,
+    len: usize,
+    policy: OverflowPolicy,
+}
+
+// SAFETY: The queue owns its items, like a VecDeque<T> would, so it can be
+// sent or shared across threads as long as the items can.
+unsafe impl<T: Send> Send for UnsafeQueue<T> {}
+unsafe impl<T: Sync> Sync for UnsafeQueue<T> {}
+
+impl<T> UnsafeQueue<T> {
+    pub fn with_capacity(cap: usize) -> Result<Self, QueueErr> {
+        Self::with_policy(cap, OverflowPolicy::default())
+    }
+
+    /// Create a queue which handles the enqueuing of an item while it is full
+    /// according to the given policy.
+    pub fn with_policy(cap: usize, policy: OverflowPolicy) -> Result<Self, QueueErr> {
+        if cap == 0 {
+            return Err(QueueErr::ZeroCapacityNotAllowed);
+        }
+
+        let layout = match alloc::Layout::array::<T>(cap) {
+            Ok(layout) => layout,
+            Err(_) => panic!("Not enough space to allocate the queue."),
+        };
+        // A zero sized type takes no space, so nothing is allocated and the
+        // dangling pointer is never dereferenced for real.
+        let ptr = if layout.size() == 0 {
+            NonNull::dangling()
+        } else {
+            // SAFETY: The layout has a non zero size.
+            let raw = unsafe { alloc::alloc(layout) as *mut T };
+            NonNull::new(raw).unwrap_or_else(|| alloc::handle_alloc_error(layout))
+        };
+
+        Ok(Self {
+            ptr,
+            cap,
+            head: 0,
+            len: 0,
+            policy,
+        })
+    }
+
+    pub fn cap(&self) -> usize {
+        self.cap
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn policy(&self) -> OverflowPolicy {
+        self.policy
+    }
+
+    // Translate an index relative to the front of the queue into a position in
+    // the buffer, only for initialized slots.
+    fn slot(&self, idx: usize) -> Option<usize> {
+        if idx < self.len {
+            Some((self.head + idx) % self.cap)
+        } else {
+            None
+        }
+    }
+}
+
+impl<T> Queue for UnsafeQueue<T> {
+    type Item = T;
+
+    fn enqueue(&mut self, val: Self::Item) -> Result<(), QueueErr> {
+        if self.is_full() {
+            match self.policy {
+                OverflowPolicy::Error => return Err(QueueErr::QueueOverflow),
+                OverflowPolicy::Block => return Err(QueueErr::WouldBlock),
+                OverflowPolicy::OverwriteOldest => {
+                    self.dequeue();
+                }
+            }
+        }
+
+        let tail = (self.head + self.len) % self.cap;
+        // SAFETY: len < cap, so the tail slot is inside the buffer and not
+        // initialized, write doesn't drop the garbage it overwrites.
+        unsafe { self.ptr.as_ptr().add(tail).write(val) };
+        self.len += 1;
+        Ok(())
+    }
+
+    fn dequeue(&mut self) -> Option<Self::Item> {
+        if self.is_empty() {
+            return None;
+        }
+        // SAFETY: the head slot is initialized as the queue is not empty, once
+        // read it is out of the live range, so it is never dropped twice.
+        let val = unsafe { self.ptr.as_ptr().add(self.head).read() };
+        self.head = (self.head + 1) % self.cap;
+        self.len -= 1;
+        Some(val)
+    }
+
+    fn peek_next(&self) -> Option<&Self::Item> {
+        self.peek(0)
+    }
+
+    fn peek_next_mut(&mut self) -> Option<&mut Self::Item> {
+        self.peek_mut(0)
+    }
+
+    fn peek(&self, idx: usize) -> Option<&Self::Item> {
+        // SAFETY: slot only gives positions of initialized slots, and the
+        // reference borrows the queue.
+        self.slot(idx)
+            .map(|slot| unsafe { &*self.ptr.as_ptr().add(slot) })
+    }
+
+    fn peek_mut(&mut self, idx: usize) -> Option<&mut Self::Item> {
+        // SAFETY: same as peek, the queue is borrowed mutably.
+        self.slot(idx)
+            .map(|slot| unsafe { &mut *self.ptr.as_ptr().add(slot) })
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn is_full(&self) -> bool {
+        self.len == self.cap
+    }
+}
+
+impl<T> Drop for UnsafeQueue<T> {
+    fn drop(&mut self) {
+        // The live items may wrap around the end of the buffer, so they are
+        // dropped as two slices: from head to the end, then from the start.
+        let first_len = self.len.min(self.cap - self.head);
+        // SAFETY: both slices only cover initialized slots.
+        unsafe {
+            std::ptr::drop_in_place(std::ptr::slice_from_raw_parts_mut(
+                self.ptr.as_ptr().add(self.head),
+                first_len,
+            ));
+            std::ptr::drop_in_place(std::ptr::slice_from_raw_parts_mut(
+                self.ptr.as_ptr(),
+                self.len - first_len,
+            ));
+        }
+        if mem::size_of::<T>() != 0 {
+            // SAFETY: The buffer has been allocated with this very layout.
+            unsafe {
+                alloc::dealloc(
+                    self.ptr.as_ptr() as *mut u8,
+                    alloc::Layout::array::<T>(self.cap).unwrap(),
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::rc::Rc;
+
+    use super::*;
+
+    #[test]
+    fn unsafe_queue_new() {
+        let queue = UnsafeQueue::<i32>::with_capacity(5).unwrap();
+        assert_eq!(5, queue.cap());
+        assert_eq!(0, queue.len());
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn unsafe_queue_new_zero_cap() {
+        let queue = UnsafeQueue::<i32>::with_capacity(0);
+        assert_eq!(Some(QueueErr::ZeroCapacityNotAllowed), queue.err());
+    }
+
+    #[test]
+    fn unsafe_queue_enqueue_dequeue() {
+        let mut queue = UnsafeQueue::with_capacity(2).unwrap();
+        queue.enqueue(1).unwrap();
+        queue.enqueue(2).unwrap();
+        assert!(queue.is_full());
+        assert_eq!(Err(QueueErr::QueueOverflow), queue.enqueue(3));
+        assert_eq!(Some(1), queue.dequeue());
+        assert_eq!(Some(2), queue.dequeue());
+        assert_eq!(None, queue.dequeue());
+    }
+
+    #[test]
+    fn unsafe_queue_wrap_around() {
+        let mut queue = UnsafeQueue::with_capacity(3).unwrap();
+        (1..=3).for_each(|v| queue.enqueue(v).unwrap());
+        assert_eq!(Some(1), queue.dequeue());
+        assert_eq!(Some(2), queue.dequeue());
+        queue.enqueue(4).unwrap();
+        queue.enqueue(5).unwrap();
+        assert_eq!(Some(&3), queue.peek_next());
+        assert_eq!(Some(&5), queue.peek(2));
+        assert_eq!(None, queue.peek(3));
+        queue.peek_mut(1).map(|v| *v = 40).unwrap();
+        assert_eq!(Some(3), queue.dequeue());
+        assert_eq!(Some(40), queue.dequeue());
+        assert_eq!(Some(5), queue.dequeue());
+        assert_eq!(None, queue.dequeue());
+    }
+
+    #[test]
+    fn unsafe_queue_overwrite_oldest() {
+        let mut queue = UnsafeQueue::with_policy(2, OverflowPolicy::OverwriteOldest).unwrap();
+        (1..=5).for_each(|v| queue.enqueue(v).unwrap());
+        assert_eq!(Some(4), queue.dequeue());
+        assert_eq!(Some(5), queue.dequeue());
+    }
+
+    #[test]
+    fn unsafe_queue_block() {
+        let mut queue = UnsafeQueue::with_policy(1, OverflowPolicy::Block).unwrap();
+        queue.enqueue(1).unwrap();
+        assert_eq!(Err(QueueErr::WouldBlock), queue.enqueue(2));
+    }
+
+    #[test]
+    fn unsafe_queue_drop_wrapped_items() {
+        let item = Rc::new(());
+        let mut queue = UnsafeQueue::with_capacity(4).unwrap();
+        (0..4).for_each(|_| queue.enqueue(Rc::clone(&item)).unwrap());
+        drop(queue.dequeue());
+        drop(queue.dequeue());
+        drop(queue.dequeue());
+        queue.enqueue(Rc::clone(&item)).unwrap();
+        // the two live items sit at both ends of the buffer
+        assert_eq!(3, Rc::strong_count(&item));
+        drop(queue);
+        assert_eq!(1, Rc::strong_count(&item));
+    }
+
+    #[test]
+    fn unsafe_queue_overwrite_drops_oldest() {
+        let item = Rc::new(());
+        let mut queue = UnsafeQueue::with_policy(2, OverflowPolicy::OverwriteOldest).unwrap();
+        (0..5).for_each(|_| queue.enqueue(Rc::clone(&item)).unwrap());
+        assert_eq!(3, Rc::strong_count(&item));
+    }
+
+    #[test]
+    fn unsafe_queue_heap_items() {
+        let mut queue = UnsafeQueue::with_capacity(2).unwrap();
+        queue.enqueue(String::from("a")).unwrap();
+        queue.enqueue(String::from("b")).unwrap();
+        queue.peek_next_mut().unwrap().push('c');
+        assert_eq!(Some(String::from("ac")), queue.dequeue());
+        assert_eq!(Some(String::from("b")), queue.dequeue());
+    }
+
+    #[test]
+    fn unsafe_queue_zero_sized_type() {
+        let mut queue = UnsafeQueue::with_capacity(2).unwrap();
+        queue.enqueue(()).unwrap();
+        queue.enqueue(()).unwrap();
+        assert!(queue.is_full());
+        assert_eq!(Some(()), queue.dequeue());
+        assert_eq!(1, queue.len());
+    }
+}
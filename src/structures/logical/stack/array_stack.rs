@@ -1,38 +1,65 @@
-use std::vec;
-
 use super::{Stack, StackErr};
+use crate::structures::physical::array::Array;
 
-#[derive(Debug)]
+/// Stack stored in an array, the top of the stack is the last item of the
+/// array. It is bounded when built with [`ArrayStack::with_capacity`], or grows
+/// on demand when built with [`ArrayStack::new`].
 pub struct ArrayStack<T> {
-    inner_stack: Box<[Option<T>]>,
+    // Slots past `len` are free, the items being Options so that zero sized
+    // items fit in the array too, as for the ArrayQueue.
+    inner_stack: Array<Option<T>>,
     len: usize,
+    cap: Option<usize>,
 }
 
 impl<T> ArrayStack<T> {
+    /// Create an unbounded stack, the inner array grows as items are pushed,
+    /// so the stack is never full.
+    pub fn new() -> Self {
+        Self {
+            inner_stack: Array::new(),
+            len: 0,
+            cap: None,
+        }
+    }
+
     pub fn with_capacity(capacity: usize) -> Result<Self, StackErr> {
         if capacity == 0 {
             return Err(StackErr::ZeroCapacityNotAllowed);
         };
 
-        // Option doesn't implement clone so vec![None; capacity] can't be used
-        let mut inner_stack = Vec::with_capacity(capacity);
-        (0..capacity).for_each(|_| {
-            inner_stack.push(None);
-        });
+        let mut inner_stack = Array::new();
+        (0..capacity).for_each(|_| inner_stack.push(None));
 
         Ok(Self {
-            inner_stack: inner_stack.into_boxed_slice(),
+            inner_stack,
             len: 0,
+            cap: Some(capacity),
         })
     }
 
+    /// Maximum number of items the stack can hold, `usize::MAX` when the stack
+    /// is unbounded.
     pub fn cap(&self) -> usize {
-        self.inner_stack.len()
+        self.cap.unwrap_or(usize::MAX)
     }
 
     pub fn len(&self) -> usize {
         self.len
     }
+
+    pub fn is_bounded(&self) -> bool {
+        self.cap.is_some()
+    }
+
+    // Position in the array of the item `idx` slots under the top.
+    fn slot(&self, idx: usize) -> Option<usize> {
+        if idx < self.len {
+            Some(self.len - 1 - idx)
+        } else {
+            None
+        }
+    }
 }
 
 impl<T> Stack for ArrayStack<T> {
@@ -42,35 +69,35 @@ impl<T> Stack for ArrayStack<T> {
         if self.is_full() {
             return Err(StackErr::StackOverflow);
         }
+        if self.len == self.inner_stack.len() {
+            self.inner_stack.push(None);
+        }
         self.inner_stack[self.len] = Some(val);
         self.len += 1;
         Ok(())
     }
 
     fn pop(&mut self) -> Option<Self::Item> {
-        let item = self.inner_stack[self.len].take();
-        self.len -= 1;
-        item
+        self.len = self.len.checked_sub(1)?;
+        self.inner_stack[self.len].take()
     }
 
     fn peek_next(&self) -> Option<&Self::Item> {
-        self.inner_stack
-            .get(self.len().saturating_sub(1))
-            .and_then(|item| item.as_ref())
+        self.peek(0)
     }
 
     fn peek_next_mut(&mut self) -> Option<&mut Self::Item> {
-        self.inner_stack
-            .get_mut(self.len().saturating_sub(1))
-            .and_then(|item| item.as_mut())
+        self.peek_mut(0)
     }
 
     fn peek(&self, idx: usize) -> Option<&Self::Item> {
-        self.inner_stack.get(idx).and_then(|item| item.as_ref())
+        self.slot(idx)
+            .and_then(|slot| self.inner_stack[slot].as_ref())
     }
 
     fn peek_mut(&mut self, idx: usize) -> Option<&mut Self::Item> {
-        self.inner_stack.get_mut(idx).and_then(|item| item.as_mut())
+        self.slot(idx)
+            .and_then(|slot| self.inner_stack[slot].as_mut())
     }
 
     fn is_empty(&self) -> bool {
@@ -78,7 +105,7 @@ impl<T> Stack for ArrayStack<T> {
     }
 
     fn is_full(&self) -> bool {
-        self.len == self.inner_stack.len()
+        self.cap.is_some_and(|cap| self.len == cap)
     }
 }
 
@@ -172,8 +199,9 @@ mod test {
         assert_eq!(None, stack_new_res.peek(0));
         stack_new_res.push(1).unwrap();
         stack_new_res.push(2).unwrap();
-        assert_eq!(Some(&1), stack_new_res.peek(0));
-        assert_eq!(Some(&2), stack_new_res.peek(1));
+        assert_eq!(Some(&2), stack_new_res.peek(0));
+        assert_eq!(Some(&1), stack_new_res.peek(1));
+        assert_eq!(None, stack_new_res.peek(2));
     }
 
     #[test]
@@ -182,9 +210,59 @@ mod test {
         assert_eq!(None, stack_new_res.peek(0));
         stack_new_res.push(1).unwrap();
         stack_new_res.push(2).unwrap();
-        assert_eq!(Some(&1), stack_new_res.peek(0));
-        assert_eq!(Some(&2), stack_new_res.peek(1));
-        stack_new_res.peek_mut(0).map(|v| *v = 7).unwrap();
-        assert_eq!(Some(&7), stack_new_res.peek(0));
+        assert_eq!(Some(&2), stack_new_res.peek(0));
+        assert_eq!(Some(&1), stack_new_res.peek(1));
+        stack_new_res.peek_mut(1).map(|v| *v = 7).unwrap();
+        assert_eq!(Some(&7), stack_new_res.peek(1));
+        assert_eq!(Some(&2), stack_new_res.peek(0));
+    }
+
+    #[test]
+    fn array_stack_new_unbounded() {
+        let mut stack = ArrayStack::<i32>::new();
+        assert!(!stack.is_bounded());
+        assert_eq!(usize::MAX, stack.cap());
+        (0..100).for_each(|i| stack.push(i).unwrap());
+        assert!(!stack.is_full());
+        assert_eq!(100, stack.len());
+        assert_eq!(Some(&99), stack.peek_next());
+        assert_eq!(Some(99), stack.pop());
+    }
+
+    #[test]
+    fn array_stack_push_overflow() {
+        let mut stack = ArrayStack::<i32>::with_capacity(1).unwrap();
+        stack.push(1).unwrap();
+        assert_eq!(Err(StackErr::StackOverflow), stack.push(2));
+    }
+
+    #[test]
+    fn array_stack_pop_empty() {
+        let mut stack = ArrayStack::<i32>::with_capacity(2).unwrap();
+        assert_eq!(None, stack.pop());
+        stack.push(1).unwrap();
+        assert_eq!(Some(1), stack.pop());
+        assert_eq!(None, stack.pop());
+        assert!(stack.is_empty());
+    }
+
+    #[test]
+    fn array_stack_with_capacity_preallocates() {
+        let mut stack = ArrayStack::<i32>::with_capacity(5).unwrap();
+        assert_eq!(5, stack.inner_stack.len());
+        (0..5).for_each(|v| stack.push(v).unwrap());
+        assert_eq!(5, stack.inner_stack.len());
+        assert_eq!(Some(&4), stack.peek_next());
+        assert_eq!(Some(&0), stack.peek(4));
+    }
+
+    #[test]
+    fn array_stack_zero_sized_items() {
+        let mut stack = ArrayStack::<()>::with_capacity(1).unwrap();
+        stack.push(()).unwrap();
+        assert!(stack.is_full());
+        assert_eq!(Err(StackErr::StackOverflow), stack.push(()));
+        assert_eq!(Some(()), stack.pop());
+        assert!(stack.is_empty());
     }
 }
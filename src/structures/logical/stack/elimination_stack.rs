@@ -0,0 +1,218 @@
+use std::{
+    cell::Cell,
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    ptr::{self, NonNull},
+};
+
+use super::treiber_stack::{TreiberNode, TreiberStack};
+use crate::structures::logical::sync::{spin_loop, AtomicPtr, CachePadded, Ordering};
+
+const ELIMINATION_SLOTS: usize = 8;
+// How long a push waits in a slot for a pop to take its item.
+const ELIMINATION_SPINS: usize = 64;
+
+thread_local! {
+    static SLOT_RNG: Cell<u64> = Cell::new({
+        let mut hasher = DefaultHasher::new();
+        std::thread::current().id().hash(&mut hasher);
+        hasher.finish().max(1)
+    });
+}
+
+// Xorshift step on a per thread state, so that threads spread over the slots
+// without sharing anything.
+fn random_slot() -> usize {
+    SLOT_RNG.with(|rng| {
+        let mut x = rng.get();
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        rng.set(x);
+        (x % ELIMINATION_SLOTS as u64) as usize
+    })
+}
+
+/// [`TreiberStack`] with elimination backoff, after Hendler, Shavit and
+/// Yerushalmi: when the compare and swap on the head fails because of
+/// contention, instead of retrying at once, the thread goes to a random slot
+/// of a small array, where a push and a pop meeting cancel each other out,
+/// the pop taking the item of the push without touching the head. A push
+/// followed right away by a pop leaves the stack as it was, so the result is
+/// still a valid LIFO order.
+///
+/// A slot holds the node offered by a waiting push, or a marker once a pop
+/// took it, which only the push resets, so a node is never taken twice nor
+/// withdrawn after being taken.
+pub struct EliminationStack<T> {
+    stack: TreiberStack<T>,
+    slots: Box<[CachePadded<AtomicPtr<TreiberNode<T>>>]>,
+}
+
+// SAFETY: Same as the TreiberStack, the slots only move items between threads.
+unsafe impl<T: Send> Send for EliminationStack<T> {}
+unsafe impl<T: Send> Sync for EliminationStack<T> {}
+
+impl<T> EliminationStack<T> {
+    pub fn new() -> Self {
+        Self {
+            stack: TreiberStack::new(),
+            slots: (0..ELIMINATION_SLOTS)
+                .map(|_| CachePadded(AtomicPtr::new(ptr::null_mut())))
+                .collect(),
+        }
+    }
+
+    pub fn push(&self, val: T) {
+        let mut node = TreiberNode::boxed(val);
+        loop {
+            match self.stack.try_push(node) {
+                Ok(()) => return,
+                Err(rejected) => node = rejected,
+            }
+            if self.eliminate_push(node) {
+                return;
+            }
+        }
+    }
+
+    pub fn pop(&self) -> Option<T> {
+        loop {
+            if let Ok(val) = self.stack.try_pop() {
+                return val;
+            }
+            if let Some(val) = self.eliminate_pop() {
+                return Some(val);
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stack.is_empty()
+    }
+
+    // Can't be the address of an allocated node.
+    fn taken() -> *mut TreiberNode<T> {
+        NonNull::dangling().as_ptr()
+    }
+
+    // Offer the node in a slot for a while, true when a pop took it.
+    fn eliminate_push(&self, node: *mut TreiberNode<T>) -> bool {
+        let slot = &self.slots[random_slot()];
+        if slot
+            .compare_exchange(ptr::null_mut(), node, Ordering::Release, Ordering::Relaxed)
+            .is_err()
+        {
+            return false;
+        }
+        for _ in 0..ELIMINATION_SPINS {
+            if slot.load(Ordering::Relaxed) == Self::taken() {
+                slot.store(ptr::null_mut(), Ordering::Relaxed);
+                return true;
+            }
+            spin_loop();
+        }
+        // Withdraw the node, unless a pop took it in the meantime.
+        match slot.compare_exchange(node, ptr::null_mut(), Ordering::Relaxed, Ordering::Relaxed) {
+            Ok(_) => false,
+            Err(_) => {
+                slot.store(ptr::null_mut(), Ordering::Relaxed);
+                true
+            }
+        }
+    }
+
+    // Take the node offered in a slot, if any.
+    fn eliminate_pop(&self) -> Option<T> {
+        let slot = &self.slots[random_slot()];
+        let node = slot.load(Ordering::Relaxed);
+        if node.is_null() || node == Self::taken() {
+            return None;
+        }
+        // The push may withdraw the node, or another pop take it, before the
+        // exchange, which then fails. If the address was offered again by
+        // another push in between, the exchange takes that node, which is
+        // offered as well, so it doesn't matter.
+        slot.compare_exchange(node, Self::taken(), Ordering::Acquire, Ordering::Relaxed)
+            .ok()?;
+        // SAFETY: The push stops touching the node once it is taken, and the
+        // acquire exchange made its item visible.
+        Some(unsafe { TreiberNode::into_val(node) })
+    }
+}
+
+impl<T> Default for EliminationStack<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(all(test, not(loom)))]
+mod test {
+    use std::{
+        sync::{Arc, Barrier},
+        thread,
+    };
+
+    use super::*;
+
+    #[test]
+    fn elimination_stack_lifo() {
+        let stack = EliminationStack::new();
+        assert!(stack.is_empty());
+        assert_eq!(None, stack.pop());
+        (1..=3).for_each(|v| stack.push(v));
+        let popped = std::iter::from_fn(|| stack.pop()).collect::<Vec<_>>();
+        assert_eq!(vec![3, 2, 1], popped);
+    }
+
+    #[test]
+    fn elimination_stack_slot_exchange() {
+        let stack = EliminationStack::new();
+        // an offered node is taken by a pop without going through the stack
+        let node = TreiberNode::boxed(7);
+        stack.slots[0].store(node, Ordering::Relaxed);
+        let popped = (0..1_000).find_map(|_| stack.eliminate_pop());
+        assert_eq!(Some(7), popped);
+        assert_eq!(
+            EliminationStack::<i32>::taken(),
+            stack.slots[0].load(Ordering::Relaxed)
+        );
+        assert!(stack.is_empty());
+    }
+
+    #[test]
+    fn elimination_stack_stress() {
+        const THREADS: usize = 8;
+        const ITEMS: usize = 5_000;
+
+        let stack = Arc::new(EliminationStack::new());
+        let barrier = Arc::new(Barrier::new(THREADS));
+        let handles = (0..THREADS)
+            .map(|thread| {
+                let stack = Arc::clone(&stack);
+                let barrier = Arc::clone(&barrier);
+                thread::spawn(move || {
+                    barrier.wait();
+                    let mut popped = Vec::new();
+                    for v in 0..ITEMS {
+                        stack.push(thread * ITEMS + v);
+                        if v % 2 == 1 {
+                            popped.extend(stack.pop());
+                            popped.extend(stack.pop());
+                        }
+                    }
+                    popped
+                })
+            })
+            .collect::<Vec<_>>();
+        let mut all = handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap())
+            .collect::<Vec<_>>();
+        all.extend(std::iter::from_fn(|| stack.pop()));
+        // every item has been popped exactly once
+        all.sort();
+        assert_eq!((0..THREADS * ITEMS).collect::<Vec<_>>(), all);
+    }
+}
@@ -15,11 +15,20 @@ impl<T> LinkedStackNode<T> {
 #[derive(Debug)]
 pub struct LinkedStack<T> {
     top: Option<Box<LinkedStackNode<T>>>,
-    cap: usize,
+    cap: Option<usize>,
     len: usize,
 }
 
 impl<T> LinkedStack<T> {
+    /// Create an unbounded stack, it is never full.
+    pub fn new() -> Self {
+        Self {
+            top: None,
+            cap: None,
+            len: 0,
+        }
+    }
+
     pub fn with_capacity(cap: usize) -> Result<Self, StackErr> {
         if cap == 0 {
             return Err(StackErr::ZeroCapacityNotAllowed);
@@ -27,29 +36,35 @@ impl<T> LinkedStack<T> {
 
         Ok(Self {
             top: None,
-            cap,
+            cap: Some(cap),
             len: 0,
         })
     }
 
+    /// Maximum number of items the stack can hold, `usize::MAX` when the stack
+    /// is unbounded.
     pub fn cap(&self) -> usize {
-        self.cap
+        self.cap.unwrap_or(usize::MAX)
+    }
+
+    pub fn is_bounded(&self) -> bool {
+        self.cap.is_some()
     }
 
     pub fn len(&self) -> usize {
         self.len
     }
 
-    pub fn iter(&self) -> LinkedStackIter<T> {
-        return LinkedStackIter {
+    pub fn iter(&self) -> LinkedStackIter<'_, T> {
+        LinkedStackIter {
             next: self.top.as_deref(),
-        };
+        }
     }
 
-    pub fn iter_mut(&mut self) -> LinkedStackIterMut<T> {
-        return LinkedStackIterMut {
+    pub fn iter_mut(&mut self) -> LinkedStackIterMut<'_, T> {
+        LinkedStackIterMut {
             next: self.top.as_deref_mut(),
-        };
+        }
     }
 }
 
@@ -128,7 +143,17 @@ impl<T> Stack for LinkedStack<T> {
     }
 
     fn is_full(&self) -> bool {
-        self.len == self.cap
+        self.cap.is_some_and(|cap| self.len == cap)
+    }
+}
+
+// Unlink the nodes one at a time, as the default drop of the Box chain would
+// recurse once per node and overflow the stack on a long list.
+impl<T> Drop for LinkedStack<T> {
+    fn drop(&mut self) {
+        while let Some(mut node) = self.top.take() {
+            self.top = node.next.take();
+        }
     }
 }
 
@@ -166,6 +191,13 @@ impl<'a, T> Iterator for LinkedStackIterMut<'a, T> {
 mod test {
     use super::*;
 
+    #[test]
+    fn linked_stack_drop_long_list() {
+        let mut linked_stack = LinkedStack::new();
+        (0..1_000_000u64).for_each(|v| linked_stack.push(v).unwrap());
+        drop(linked_stack);
+    }
+
     #[test]
     fn linked_stack_new() {
         let linked_stack = LinkedStack::<i32>::with_capacity(5);
@@ -274,4 +306,15 @@ mod test {
         assert_eq!(Some(&mut 3), linked_stack.peek_mut(0));
         assert_eq!(None, linked_stack.peek_mut(5));
     }
+
+    #[test]
+    fn linked_stack_new_unbounded() {
+        let mut linked_stack = LinkedStack::<i32>::new();
+        assert!(!linked_stack.is_bounded());
+        assert_eq!(usize::MAX, linked_stack.cap());
+        (0..100).for_each(|i| linked_stack.push(i).unwrap());
+        assert!(!linked_stack.is_full());
+        assert_eq!(100, linked_stack.len());
+        assert_eq!(Some(99), linked_stack.pop());
+    }
 }
@@ -0,0 +1,149 @@
+use std::marker::PhantomData;
+
+use super::{ArrayStack, Stack, StackErr};
+
+/// Item of a [`MinMaxStack`], it remembers the smallest and the greatest items
+/// from itself down to the bottom of the stack.
+pub struct MinMaxEntry<T> {
+    val: T,
+    min: T,
+    max: T,
+}
+
+/// Stack wrapper answering `min` and `max` in O(1). Every entry stores a copy
+/// of the smallest and greatest items found from it down to the bottom, so
+/// popping an item gives back the extremes of the items under it for free, at
+/// the cost of two clones per push.
+///
+/// The items can't be reached mutably: changing one would make the extremes
+/// stored in the entries above it wrong.
+pub struct MinMaxStack<T, S = ArrayStack<MinMaxEntry<T>>> {
+    inner: S,
+    item: PhantomData<T>,
+}
+
+impl<T: Ord + Clone> MinMaxStack<T> {
+    /// Create an unbounded stack, backed by an [`ArrayStack`].
+    pub fn new() -> Self {
+        Self::with_stack(ArrayStack::new())
+    }
+}
+
+impl<T: Ord + Clone> Default for MinMaxStack<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, S> MinMaxStack<T, S>
+where
+    T: Ord + Clone,
+    S: Stack<Item = MinMaxEntry<T>>,
+{
+    /// Wrap an empty stack, whose capacity becomes the capacity of the
+    /// wrapper.
+    pub fn with_stack(inner: S) -> Self {
+        assert!(inner.is_empty(), "the wrapped stack must be empty");
+        Self {
+            inner,
+            item: PhantomData,
+        }
+    }
+
+    pub fn push(&mut self, val: T) -> Result<(), StackErr> {
+        let (min, max) = match self.inner.peek_next() {
+            Some(top) => ((&top.min).min(&val).clone(), (&top.max).max(&val).clone()),
+            None => (val.clone(), val.clone()),
+        };
+        self.inner.push(MinMaxEntry { val, min, max })
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        self.inner.pop().map(|entry| entry.val)
+    }
+
+    pub fn peek_next(&self) -> Option<&T> {
+        self.inner.peek_next().map(|entry| &entry.val)
+    }
+
+    /// Item `idx` slots under the top, as for [`Stack::peek`].
+    pub fn peek(&self, idx: usize) -> Option<&T> {
+        self.inner.peek(idx).map(|entry| &entry.val)
+    }
+
+    /// Smallest item of the stack. O(1).
+    pub fn min(&self) -> Option<&T> {
+        self.inner.peek_next().map(|entry| &entry.min)
+    }
+
+    /// Greatest item of the stack. O(1).
+    pub fn max(&self) -> Option<&T> {
+        self.inner.peek_next().map(|entry| &entry.max)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.inner.is_full()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::structures::logical::stack::LinkedStack;
+    use crate::test_utils::XorShift;
+
+    #[test]
+    fn min_max_stack_push_pop() {
+        let mut stack = MinMaxStack::new();
+        assert_eq!(None, stack.min());
+        assert_eq!(None, stack.max());
+        [5, 3, 8, 3, 1]
+            .into_iter()
+            .for_each(|v| stack.push(v).unwrap());
+        assert_eq!(Some(&1), stack.min());
+        assert_eq!(Some(&8), stack.max());
+        assert_eq!(Some(1), stack.pop());
+        assert_eq!(Some(&3), stack.min());
+        stack.pop();
+        // the other 3 is still there
+        assert_eq!(Some(&3), stack.min());
+        stack.pop();
+        assert_eq!(Some(&5), stack.max());
+        assert_eq!(Some(&3), stack.peek_next());
+        assert_eq!(Some(&5), stack.peek(1));
+    }
+
+    #[test]
+    fn min_max_stack_bounded() {
+        let mut stack = MinMaxStack::with_stack(LinkedStack::with_capacity(2).unwrap());
+        stack.push("b").unwrap();
+        stack.push("a").unwrap();
+        assert!(stack.is_full());
+        assert_eq!(Err(StackErr::StackOverflow), stack.push("c"));
+        assert_eq!(Some(&"a"), stack.min());
+        assert_eq!(Some(&"b"), stack.max());
+    }
+
+    #[test]
+    fn min_max_stack_against_model() {
+        let mut rng = XorShift::new(7);
+        let mut stack = MinMaxStack::new();
+        let mut model = Vec::new();
+        for _ in 0..2_000 {
+            if rng.below(3) == 0 {
+                assert_eq!(model.pop(), stack.pop());
+            } else {
+                let val = rng.below(100);
+                model.push(val);
+                stack.push(val).unwrap();
+            }
+            assert_eq!(model.iter().min(), stack.min());
+            assert_eq!(model.iter().max(), stack.max());
+            assert_eq!(model.is_empty(), stack.is_empty());
+        }
+    }
+}
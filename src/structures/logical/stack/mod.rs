@@ -1,15 +1,32 @@
 #![allow(dead_code)]
-// TODO : implementations :
-//  - UnsafeStack, use heap allocated array managed directly by pointers
 
 pub use array_stack::ArrayStack;
+pub use elimination_stack::EliminationStack;
 pub use linked_stack::LinkedStack;
+pub use min_max_stack::{MinMaxEntry, MinMaxStack};
+pub use queue_stack::QueueStack;
 use std::fmt::Display;
+pub use treiber_stack::TreiberStack;
+pub use unsafe_stack::UnsafeStack;
 
 mod array_stack;
+mod elimination_stack;
 mod linked_stack;
+mod min_max_stack;
+mod queue_stack;
+mod treiber_stack;
+mod unsafe_stack;
 
-/// TODO doc + LIFO
+/// Last in, first out abstract data type: `pop` gives back the item pushed the
+/// most recently, which is called the top of the stack.
+///
+/// Indices given to `peek` and `peek_mut` count from the top: `peek(0)` is the
+/// top, the same item as `peek_next`, `peek(1)` is the item under it, and so
+/// on. An index past the bottom of the stack gives `None`.
+///
+/// A bounded stack is full once it holds as many items as its capacity, then
+/// `push` fails with [`StackErr::StackOverflow`]. An unbounded stack is never
+/// full.
 pub trait Stack {
     type Item;
     fn push(&mut self, val: Self::Item) -> Result<(), StackErr>;
@@ -43,3 +60,167 @@ impl Display for StackErr {
         }
     }
 }
+
+// Conformance suite every Stack implementor goes through, with randomized
+// sequences of operations checked against a Vec, whose last item is the top.
+// The macro takes the name of the generated test module, an expression
+// building an empty stack of i64 and its capacity, None when unbounded.
+#[cfg(test)]
+mod conformance {
+    use super::*;
+    use crate::structures::logical::deque::{ArrayDeque, LinkedDeque};
+    use crate::structures::logical::queue::{ArrayQueue, LinkedQueue};
+    use crate::test_utils::XorShift;
+
+    fn check_lifo_order<S: Stack<Item = i64>>(mut stack: S) {
+        assert!(stack.is_empty());
+        assert_eq!(None, stack.pop());
+        (0..5).for_each(|v| stack.push(v).unwrap());
+        assert!(!stack.is_empty());
+        let popped = std::iter::from_fn(|| stack.pop()).collect::<Vec<_>>();
+        assert_eq!(vec![4, 3, 2, 1, 0], popped);
+        assert!(stack.is_empty());
+        // popping an empty stack again doesn't break anything
+        assert_eq!(None, stack.pop());
+        stack.push(7).unwrap();
+        assert_eq!(Some(&7), stack.peek_next());
+    }
+
+    fn check_peek_from_top<S: Stack<Item = i64>>(mut stack: S) {
+        assert_eq!(None, stack.peek(0));
+        (1..=3).for_each(|v| stack.push(v).unwrap());
+        assert_eq!(Some(&3), stack.peek_next());
+        assert_eq!(Some(&3), stack.peek(0));
+        assert_eq!(Some(&2), stack.peek(1));
+        assert_eq!(Some(&1), stack.peek(2));
+        assert_eq!(None, stack.peek(3));
+        assert_eq!(None, stack.peek(usize::MAX));
+        *stack.peek_mut(2).unwrap() = 10;
+        *stack.peek_next_mut().unwrap() = 30;
+        assert_eq!(None, stack.peek_mut(3));
+        assert_eq!(Some(30), stack.pop());
+        assert_eq!(Some(2), stack.pop());
+        assert_eq!(Some(10), stack.pop());
+    }
+
+    fn check_overflow<S: Stack<Item = i64>>(mut stack: S, cap: Option<usize>) {
+        let Some(cap) = cap else {
+            (0..1_000).for_each(|v| stack.push(v).unwrap());
+            assert!(!stack.is_full());
+            return;
+        };
+        (0..cap as i64).for_each(|v| stack.push(v).unwrap());
+        assert!(stack.is_full());
+        assert_eq!(Err(StackErr::StackOverflow), stack.push(-1));
+        // the rejected item didn't replace anything
+        assert_eq!(Some(&(cap as i64 - 1)), stack.peek_next());
+        stack.pop();
+        assert!(!stack.is_full());
+        stack.push(-1).unwrap();
+        assert_eq!(Some(-1), stack.pop());
+    }
+
+    fn check_against_model<S: Stack<Item = i64>>(mut stack: S, cap: Option<usize>, seed: u64) {
+        let mut rng = XorShift::new(seed);
+        let mut model: Vec<i64> = Vec::new();
+        for _ in 0..1_000 {
+            match rng.below(8) {
+                0..=2 => {
+                    let val = rng.below(1_000) as i64;
+                    if cap.is_some_and(|cap| model.len() == cap) {
+                        assert_eq!(Err(StackErr::StackOverflow), stack.push(val));
+                    } else {
+                        assert_eq!(Ok(()), stack.push(val));
+                        model.push(val);
+                    }
+                }
+                3 | 4 => assert_eq!(model.pop(), stack.pop()),
+                5 => {
+                    let idx = rng.below(model.len() as u64 + 2) as usize;
+                    assert_eq!(model.iter().rev().nth(idx), stack.peek(idx));
+                }
+                6 => {
+                    let idx = rng.below(model.len() as u64 + 2) as usize;
+                    let expected = model.iter_mut().rev().nth(idx);
+                    let actual = stack.peek_mut(idx);
+                    assert_eq!(expected.is_some(), actual.is_some());
+                    if let (Some(expected), Some(actual)) = (expected, actual) {
+                        *expected += 1;
+                        *actual += 1;
+                    }
+                }
+                _ => assert_eq!(model.last(), stack.peek_next()),
+            }
+            assert_eq!(model.is_empty(), stack.is_empty());
+            assert_eq!(cap.is_some_and(|cap| model.len() == cap), stack.is_full());
+        }
+        while let Some(val) = model.pop() {
+            assert_eq!(Some(val), stack.pop());
+        }
+        assert_eq!(None, stack.pop());
+    }
+
+    macro_rules! stack_conformance {
+        ($name:ident, $new:expr, $cap:expr) => {
+            mod $name {
+                use super::*;
+
+                #[test]
+                fn lifo_order() {
+                    check_lifo_order($new);
+                }
+
+                #[test]
+                fn peek_from_top() {
+                    check_peek_from_top($new);
+                }
+
+                #[test]
+                fn overflow() {
+                    check_overflow($new, $cap);
+                }
+
+                #[test]
+                fn against_model() {
+                    (1..=5).for_each(|seed| check_against_model($new, $cap, seed));
+                }
+            }
+        };
+    }
+
+    stack_conformance!(array_stack, ArrayStack::new(), None);
+    stack_conformance!(
+        array_stack_bounded,
+        ArrayStack::with_capacity(8).unwrap(),
+        Some(8)
+    );
+    stack_conformance!(linked_stack, LinkedStack::new(), None);
+    stack_conformance!(
+        linked_stack_bounded,
+        LinkedStack::with_capacity(8).unwrap(),
+        Some(8)
+    );
+    stack_conformance!(
+        unsafe_stack,
+        UnsafeStack::with_capacity(8).unwrap(),
+        Some(8)
+    );
+    stack_conformance!(array_deque, ArrayDeque::new(), None);
+    stack_conformance!(
+        array_deque_bounded,
+        ArrayDeque::with_capacity(8).unwrap(),
+        Some(8)
+    );
+    stack_conformance!(linked_deque, LinkedDeque::new(), None);
+    stack_conformance!(
+        linked_deque_bounded,
+        LinkedDeque::with_capacity(8).unwrap(),
+        Some(8)
+    );
+    stack_conformance!(queue_stack, QueueStack::new(ArrayQueue::new()), None);
+    stack_conformance!(
+        queue_stack_bounded,
+        QueueStack::new(LinkedQueue::with_capacity(8).unwrap()),
+        Some(8)
+    );
+}
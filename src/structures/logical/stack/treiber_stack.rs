@@ -0,0 +1,248 @@
+use std::{mem::ManuallyDrop, ptr};
+
+use crate::structures::logical::hazard::HazardDomain;
+use crate::structures::logical::sync::{AtomicPtr, Ordering};
+
+// The item is moved out by the thread whose pop unlinks the node, the node
+// itself being freed later by the hazard domain, without dropping the item
+// again.
+pub(super) struct TreiberNode<T> {
+    val: ManuallyDrop<T>,
+    next: *mut TreiberNode<T>,
+}
+
+impl<T> TreiberNode<T> {
+    pub(super) fn boxed(val: T) -> *mut Self {
+        Box::into_raw(Box::new(Self {
+            val: ManuallyDrop::new(val),
+            next: ptr::null_mut(),
+        }))
+    }
+
+    /// Take the item out of a node which no other thread can reach, and free
+    /// the node.
+    ///
+    /// # Safety
+    ///
+    /// The node must come from `boxed` and not be shared.
+    pub(super) unsafe fn into_val(node: *mut Self) -> T {
+        let node = Box::from_raw(node);
+        ManuallyDrop::into_inner(node.val)
+    }
+}
+
+/// Lock-free unbounded stack for any number of threads, after R. K. Treiber:
+/// a singly linked list whose head is swapped with a compare and swap. Every
+/// method takes `&self`, so it is shared behind an `Arc`.
+///
+/// A popped node may still be read by a thread which loaded it as the head
+/// just before, so it is freed through hazard pointers, see
+/// [`hazard`](crate::structures::logical::hazard).
+///
+/// Like the [`BlockingQueue`](crate::structures::logical::queue::BlockingQueue),
+/// it doesn't implement the [`Stack`](super::Stack) trait, as an item can't be
+/// peeked while another thread may pop it.
+pub struct TreiberStack<T> {
+    head: AtomicPtr<TreiberNode<T>>,
+    domain: HazardDomain<TreiberNode<T>>,
+}
+
+// SAFETY: Items are moved in by one thread and out by another, never shared,
+// so they just have to be Send for the stack to be sent and shared.
+unsafe impl<T: Send> Send for TreiberStack<T> {}
+unsafe impl<T: Send> Sync for TreiberStack<T> {}
+
+impl<T> TreiberStack<T> {
+    pub fn new() -> Self {
+        Self {
+            head: AtomicPtr::new(ptr::null_mut()),
+            domain: HazardDomain::new(),
+        }
+    }
+
+    pub fn push(&self, val: T) {
+        let mut node = TreiberNode::boxed(val);
+        while let Err(rejected) = self.try_push(node) {
+            node = rejected;
+        }
+    }
+
+    pub fn pop(&self) -> Option<T> {
+        loop {
+            if let Ok(val) = self.try_pop() {
+                return val;
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.load(Ordering::Acquire).is_null()
+    }
+
+    /// Single attempt at pushing a node from [`TreiberNode::boxed`], giving
+    /// the node back when another thread changed the head in the meantime.
+    pub(super) fn try_push(&self, node: *mut TreiberNode<T>) -> Result<(), *mut TreiberNode<T>> {
+        let head = self.head.load(Ordering::Relaxed);
+        // SAFETY: The node is not shared until the exchange succeeds.
+        unsafe { (*node).next = head };
+        self.head
+            .compare_exchange_weak(head, node, Ordering::Release, Ordering::Relaxed)
+            .map(|_| ())
+            .map_err(|_| node)
+    }
+
+    /// Single attempt at popping, failing when another thread changed the
+    /// head in the meantime.
+    pub(super) fn try_pop(&self) -> Result<Option<T>, ()> {
+        let guard = self.domain.guard();
+        let head = guard.protect(&self.head);
+        if head.is_null() {
+            return Ok(None);
+        }
+        // SAFETY: The head is protected, it can't be freed even if another
+        // thread pops it, and its next pointer never changes once pushed.
+        let next = unsafe { (*head).next };
+        if self
+            .head
+            .compare_exchange_weak(head, next, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            return Err(());
+        }
+        guard.clear();
+        // SAFETY: The node has been unlinked by this thread, which is then the
+        // only one moving the item out, and retiring the node.
+        unsafe {
+            let val = ptr::read(&*(*head).val);
+            self.domain.retire(head);
+            Ok(Some(val))
+        }
+    }
+
+    #[cfg(test)]
+    fn retired_len(&self) -> usize {
+        self.domain.retired_len()
+    }
+}
+
+impl<T> Default for TreiberStack<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for TreiberStack<T> {
+    fn drop(&mut self) {
+        while self.pop().is_some() {}
+    }
+}
+
+#[cfg(all(test, not(loom)))]
+mod test {
+    use std::{
+        sync::{Arc, Barrier},
+        thread,
+    };
+
+    use super::*;
+
+    #[test]
+    fn treiber_stack_lifo() {
+        let stack = TreiberStack::new();
+        assert!(stack.is_empty());
+        assert_eq!(None, stack.pop());
+        (1..=3).for_each(|v| stack.push(v));
+        assert!(!stack.is_empty());
+        let popped = std::iter::from_fn(|| stack.pop()).collect::<Vec<_>>();
+        assert_eq!(vec![3, 2, 1], popped);
+        assert!(stack.is_empty());
+    }
+
+    #[test]
+    fn treiber_stack_drops_items() {
+        let counter = Arc::new(());
+        let stack = TreiberStack::new();
+        (0..100).for_each(|_| stack.push(Arc::clone(&counter)));
+        // popped items belong to the caller, the nodes are only retired
+        (0..80).for_each(|_| drop(stack.pop()));
+        assert_eq!(21, Arc::strong_count(&counter));
+        drop(stack);
+        assert_eq!(1, Arc::strong_count(&counter));
+    }
+
+    #[test]
+    fn treiber_stack_reclaims_nodes() {
+        let stack = TreiberStack::new();
+        for v in 0..10_000 {
+            stack.push(v);
+            stack.pop();
+        }
+        // reclaimed in batches, so the retired nodes never pile up
+        assert!(stack.retired_len() < 100);
+    }
+
+    #[test]
+    fn treiber_stack_stress() {
+        const THREADS: usize = 8;
+        const ITEMS: usize = 5_000;
+
+        let stack = Arc::new(TreiberStack::new());
+        let barrier = Arc::new(Barrier::new(THREADS));
+        let handles = (0..THREADS)
+            .map(|thread| {
+                let stack = Arc::clone(&stack);
+                let barrier = Arc::clone(&barrier);
+                thread::spawn(move || {
+                    barrier.wait();
+                    let mut popped = Vec::new();
+                    for v in 0..ITEMS {
+                        stack.push(thread * ITEMS + v);
+                        if v % 2 == 1 {
+                            popped.extend(stack.pop());
+                            popped.extend(stack.pop());
+                        }
+                    }
+                    popped
+                })
+            })
+            .collect::<Vec<_>>();
+        let mut all = handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap())
+            .collect::<Vec<_>>();
+        all.extend(std::iter::from_fn(|| stack.pop()));
+        // every item has been popped exactly once
+        all.sort();
+        assert_eq!((0..THREADS * ITEMS).collect::<Vec<_>>(), all);
+    }
+}
+
+#[cfg(all(test, loom))]
+mod loom_test {
+    use loom::{sync::Arc, thread};
+
+    use super::*;
+
+    #[test]
+    fn treiber_stack_loom() {
+        loom::model(|| {
+            let stack = Arc::new(TreiberStack::new());
+            let handles = (0..2)
+                .map(|v| {
+                    let stack = Arc::clone(&stack);
+                    thread::spawn(move || {
+                        stack.push(v);
+                        stack.pop()
+                    })
+                })
+                .collect::<Vec<_>>();
+            let mut popped = handles
+                .into_iter()
+                .filter_map(|handle| handle.join().unwrap())
+                .collect::<Vec<_>>();
+            popped.extend(std::iter::from_fn(|| stack.pop()));
+            popped.sort();
+            assert_eq!(vec![0, 1], popped);
+        });
+    }
+}
@@ -0,0 +1,226 @@
+use std::{alloc, mem, ptr::NonNull};
+
+use super::{Stack, StackErr};
+
+/// Bounded stack managing its own heap buffer through a raw pointer, like
+/// [`Array`](crate::structures::physical::array::Array) does. The buffer is
+/// allocated once with room for exactly `cap` items, and only the first `len`
+/// slots are initialized, so there is no `Option` per slot: the stack takes
+/// `size_of::<T>() * cap` bytes on the heap. The top of the stack is the last
+/// initialized slot.
+///
+/// The tests of this module are meant to be run under Miri as well, with
+/// `cargo +nightly miri test unsafe_stack`.
+pub struct UnsafeStack<T> {
+    ptr: NonNull<T>,
+    cap: usize,
+    len: usize,
+}
+
+// SAFETY: The stack owns its items, like a Vec<T> would, so it can be sent or
+// shared across threads as long as the items can.
+unsafe impl<T: Send> Send for UnsafeStack<T> {}
+unsafe impl<T: Sync> Sync for UnsafeStack<T> {}
+
+impl<T> UnsafeStack<T> {
+    pub fn with_capacity(cap: usize) -> Result<Self, StackErr> {
+        if cap == 0 {
+            return Err(StackErr::ZeroCapacityNotAllowed);
+        }
+
+        let layout = match alloc::Layout::array::<T>(cap) {
+            Ok(layout) => layout,
+            Err(_) => panic!("Not enough space to allocate the stack."),
+        };
+        // A zero sized type takes no space, so nothing is allocated and the
+        // dangling pointer is never dereferenced for real.
+        let ptr = if layout.size() == 0 {
+            NonNull::dangling()
+        } else {
+            // SAFETY: The layout has a non zero size.
+            let raw = unsafe { alloc::alloc(layout) as *mut T };
+            NonNull::new(raw).unwrap_or_else(|| alloc::handle_alloc_error(layout))
+        };
+
+        Ok(Self { ptr, cap, len: 0 })
+    }
+
+    pub fn cap(&self) -> usize {
+        self.cap
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    // Position in the buffer of the item `idx` slots under the top.
+    fn slot(&self, idx: usize) -> Option<usize> {
+        if idx < self.len {
+            Some(self.len - 1 - idx)
+        } else {
+            None
+        }
+    }
+}
+
+impl<T> Stack for UnsafeStack<T> {
+    type Item = T;
+
+    fn push(&mut self, val: Self::Item) -> Result<(), StackErr> {
+        if self.is_full() {
+            return Err(StackErr::StackOverflow);
+        }
+        // SAFETY: len < cap, so the slot is inside the buffer, and it is not
+        // initialized, write doesn't drop the garbage it overwrites.
+        unsafe { self.ptr.as_ptr().add(self.len).write(val) };
+        self.len += 1;
+        Ok(())
+    }
+
+    fn pop(&mut self) -> Option<Self::Item> {
+        if self.is_empty() {
+            return None;
+        }
+        // The length is decremented first, the slot is then seen as
+        // uninitialized, the value read is moved out and never dropped twice.
+        self.len -= 1;
+        // SAFETY: the slot was initialized, it was the top of the stack.
+        Some(unsafe { self.ptr.as_ptr().add(self.len).read() })
+    }
+
+    fn peek_next(&self) -> Option<&Self::Item> {
+        self.peek(0)
+    }
+
+    fn peek_next_mut(&mut self) -> Option<&mut Self::Item> {
+        self.peek_mut(0)
+    }
+
+    fn peek(&self, idx: usize) -> Option<&Self::Item> {
+        // SAFETY: slot only gives positions of initialized slots, and the
+        // reference borrows the stack.
+        self.slot(idx)
+            .map(|slot| unsafe { &*self.ptr.as_ptr().add(slot) })
+    }
+
+    fn peek_mut(&mut self, idx: usize) -> Option<&mut Self::Item> {
+        // SAFETY: same as peek, the stack is borrowed mutably.
+        self.slot(idx)
+            .map(|slot| unsafe { &mut *self.ptr.as_ptr().add(slot) })
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn is_full(&self) -> bool {
+        self.len == self.cap
+    }
+}
+
+impl<T> Drop for UnsafeStack<T> {
+    fn drop(&mut self) {
+        // Only the initialized slots hold a value to drop, the others are
+        // garbage which must not be touched.
+        // SAFETY: the first len slots are initialized.
+        unsafe {
+            std::ptr::drop_in_place(std::ptr::slice_from_raw_parts_mut(
+                self.ptr.as_ptr(),
+                self.len,
+            ));
+        }
+        if mem::size_of::<T>() != 0 {
+            // SAFETY: The buffer has been allocated with this very layout.
+            unsafe {
+                alloc::dealloc(
+                    self.ptr.as_ptr() as *mut u8,
+                    alloc::Layout::array::<T>(self.cap).unwrap(),
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::rc::Rc;
+
+    use super::*;
+
+    #[test]
+    fn unsafe_stack_new() {
+        let stack = UnsafeStack::<i32>::with_capacity(5).unwrap();
+        assert_eq!(5, stack.cap());
+        assert_eq!(0, stack.len());
+        assert!(stack.is_empty());
+    }
+
+    #[test]
+    fn unsafe_stack_new_zero_cap() {
+        let stack = UnsafeStack::<i32>::with_capacity(0);
+        assert_eq!(Some(StackErr::ZeroCapacityNotAllowed), stack.err());
+    }
+
+    #[test]
+    fn unsafe_stack_push_pop() {
+        let mut stack = UnsafeStack::with_capacity(3).unwrap();
+        stack.push(1).unwrap();
+        stack.push(2).unwrap();
+        stack.push(3).unwrap();
+        assert!(stack.is_full());
+        assert_eq!(Err(StackErr::StackOverflow), stack.push(4));
+        assert_eq!(Some(3), stack.pop());
+        assert_eq!(Some(2), stack.pop());
+        stack.push(5).unwrap();
+        assert_eq!(Some(5), stack.pop());
+        assert_eq!(Some(1), stack.pop());
+        assert_eq!(None, stack.pop());
+    }
+
+    #[test]
+    fn unsafe_stack_peek() {
+        let mut stack = UnsafeStack::with_capacity(3).unwrap();
+        assert_eq!(None, stack.peek_next());
+        stack.push(1).unwrap();
+        stack.push(2).unwrap();
+        assert_eq!(Some(&2), stack.peek_next());
+        assert_eq!(Some(&2), stack.peek(0));
+        assert_eq!(Some(&1), stack.peek(1));
+        assert_eq!(None, stack.peek(2));
+        stack.peek_mut(1).map(|v| *v = 10).unwrap();
+        stack.peek_next_mut().map(|v| *v = 20).unwrap();
+        assert_eq!(Some(20), stack.pop());
+        assert_eq!(Some(10), stack.pop());
+    }
+
+    #[test]
+    fn unsafe_stack_drop_partially_filled() {
+        let item = Rc::new(());
+        let mut stack = UnsafeStack::with_capacity(4).unwrap();
+        (0..3).for_each(|_| stack.push(Rc::clone(&item)).unwrap());
+        drop(stack.pop());
+        assert_eq!(3, Rc::strong_count(&item));
+        // only the two items left are dropped, the free slots are not read
+        drop(stack);
+        assert_eq!(1, Rc::strong_count(&item));
+    }
+
+    #[test]
+    fn unsafe_stack_heap_items() {
+        let mut stack = UnsafeStack::with_capacity(2).unwrap();
+        stack.push(String::from("a")).unwrap();
+        stack.push(String::from("b")).unwrap();
+        stack.peek_next_mut().unwrap().push('c');
+        assert_eq!(Some(String::from("bc")), stack.pop());
+    }
+
+    #[test]
+    fn unsafe_stack_zero_sized_type() {
+        let mut stack = UnsafeStack::with_capacity(2).unwrap();
+        stack.push(()).unwrap();
+        stack.push(()).unwrap();
+        assert!(stack.is_full());
+        assert_eq!(Some(()), stack.pop());
+        assert_eq!(1, stack.len());
+    }
+}
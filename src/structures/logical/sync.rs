@@ -0,0 +1,69 @@
+//! Synchronization primitives used by the lock-free structures, taken from
+//! `std`, or from `loom` when built with `--cfg loom` so that the loom tests
+//! explore every interleaving of their atomic operations.
+//!
+//! `loom` is not a dependency of the crate, to run the loom tests add it to
+//! the dev-dependencies, then run
+//! `RUSTFLAGS="--cfg loom" cargo test --release loom`.
+
+use std::ops::{Deref, DerefMut};
+
+#[cfg(loom)]
+pub(crate) use loom::{
+    cell::UnsafeCell,
+    hint::spin_loop,
+    sync::{
+        atomic::{fence, AtomicBool, AtomicPtr, AtomicUsize, Ordering},
+        Arc,
+    },
+};
+#[cfg(not(loom))]
+pub(crate) use std::{
+    hint::spin_loop,
+    sync::{
+        atomic::{fence, AtomicBool, AtomicPtr, AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+/// `std::cell::UnsafeCell` behind the closure based API of loom's one, which
+/// needs to see every access to the cell to detect data races.
+#[cfg(not(loom))]
+#[derive(Debug)]
+pub(crate) struct UnsafeCell<T>(std::cell::UnsafeCell<T>);
+
+#[cfg(not(loom))]
+impl<T> UnsafeCell<T> {
+    pub(crate) fn new(val: T) -> Self {
+        Self(std::cell::UnsafeCell::new(val))
+    }
+
+    pub(crate) fn with<R>(&self, f: impl FnOnce(*const T) -> R) -> R {
+        f(self.0.get())
+    }
+
+    pub(crate) fn with_mut<R>(&self, f: impl FnOnce(*mut T) -> R) -> R {
+        f(self.0.get())
+    }
+}
+
+/// Aligns its content on a cache line, so that two values written by
+/// different threads don't share one, otherwise each write would invalidate
+/// the line in the cache of the other thread (false sharing).
+#[derive(Debug, Default)]
+#[repr(align(64))]
+pub(crate) struct CachePadded<T>(pub(crate) T);
+
+impl<T> Deref for CachePadded<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for CachePadded<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
@@ -0,0 +1,736 @@
+#![allow(dead_code)]
+
+use super::{LinkedBinaryTree, NodeHandle, TreeErr};
+use crate::structures::logical::stack::{LinkedStack, Stack};
+use crate::structures::physical::array::Array;
+
+/// Binary tree stored in an [`Array`] in level order: the root is at 0, and
+/// the children of the node at `i` are at `2i + 1` and `2i + 2`, so no link is
+/// stored, a missing node leaving an empty slot. A node is reached through its
+/// position, which is also what insertions give back.
+///
+/// A complete tree fills a prefix of the array without any gap, which makes
+/// the layout ideal for heaps, while a degenerate tree of height h takes
+/// 2^h - 1 slots for h nodes. The array never ends with an empty slot, and
+/// the height is at most [`MAX_HEIGHT`](Self::MAX_HEIGHT), so that a deep
+/// tree fails with [`TreeErr::TooDeep`] rather than running out of memory.
+pub struct ArrayBinaryTree<T> {
+    slots: Array<Option<T>>,
+    len: usize,
+}
+
+impl<T> ArrayBinaryTree<T> {
+    /// Most levels a tree may have, which takes up to 2^20 - 1 slots.
+    pub const MAX_HEIGHT: usize = 20;
+
+    pub fn new() -> Self {
+        Self {
+            slots: Array::new(),
+            len: 0,
+        }
+    }
+
+    /// Build a tree from its level order, in the same format as
+    /// [`LinkedBinaryTree::from_level_order`](super::LinkedBinaryTree::from_level_order),
+    /// where the children of a missing node are not listed.
+    pub fn from_level_order(vals: impl IntoIterator<Item = Option<T>>) -> Result<Self, TreeErr> {
+        let mut tree = Self::new();
+        let mut vals = vals.into_iter();
+        if let Some(root) = vals.next().flatten() {
+            tree.insert_root(root)?;
+            // Positions grow in level order, so the parents whose children are
+            // read next are the nodes of the tree taken in position order.
+            let mut parent = 0;
+            while parent < tree.slots.len() {
+                if tree.slots[parent].is_some() {
+                    if let Some(val) = vals.next().flatten() {
+                        tree.insert_left(parent, val)?;
+                    }
+                    if let Some(val) = vals.next().flatten() {
+                        tree.insert_right(parent, val)?;
+                    }
+                }
+                parent += 1;
+            }
+        }
+        match vals.any(|val| val.is_some()) {
+            true => Err(TreeErr::OrphanValue),
+            false => Ok(tree),
+        }
+    }
+
+    /// The slots of the array, an empty slot standing for a missing node.
+    pub fn as_slice(&self) -> &[Option<T>] {
+        &self.slots
+    }
+
+    pub fn root(&self) -> Option<usize> {
+        (self.len > 0).then_some(0)
+    }
+
+    /// Insert the root of an empty tree.
+    pub fn insert_root(&mut self, val: T) -> Result<usize, TreeErr> {
+        if self.len > 0 {
+            return Err(TreeErr::Occupied);
+        }
+        self.place(0, val)?;
+        Ok(0)
+    }
+
+    /// Insert a value as the left child of a node which has none.
+    pub fn insert_left(&mut self, parent: usize, val: T) -> Result<usize, TreeErr> {
+        self.check(parent)?;
+        let pos = left_of(parent).ok_or(TreeErr::TooDeep)?;
+        self.insert_at(pos, val)
+    }
+
+    /// Insert a value as the right child of a node which has none.
+    pub fn insert_right(&mut self, parent: usize, val: T) -> Result<usize, TreeErr> {
+        self.check(parent)?;
+        let pos = right_of(parent).ok_or(TreeErr::TooDeep)?;
+        self.insert_at(pos, val)
+    }
+
+    /// Detach the node and all its descendants, and give them back as a tree
+    /// of their own, where they move to the positions they have under their
+    /// new root.
+    pub fn remove_subtree(&mut self, node: usize) -> Result<Self, TreeErr> {
+        self.check(node)?;
+        let mut subtree = Self::new();
+        // A node at depth d under the removed one, and r nodes to the right of
+        // its leftmost descendant at that depth, moves to 2^d - 1 + r.
+        let mut level = (node, 0);
+        let mut width = 1;
+        while level.0 < self.slots.len() {
+            let (first, moved) = level;
+            let end = self.slots.len().min(first + width);
+            for pos in first..end {
+                if let Some(val) = self.slots[pos].take() {
+                    // Nodes move up, so they fit wherever they were.
+                    subtree
+                        .place(moved + pos - first, val)
+                        .expect("a subtree is not deeper than its tree");
+                    self.len -= 1;
+                }
+            }
+            match left_of(first) {
+                Some(next) => level = (next, 2 * moved + 1),
+                None => break,
+            }
+            width *= 2;
+        }
+        self.trim();
+        Ok(subtree)
+    }
+
+    /// Put a new value in a node, giving the previous one back.
+    pub fn replace(&mut self, node: usize, val: T) -> Result<T, TreeErr> {
+        self.check(node)?;
+        Ok(self.slots[node].replace(val).expect("slot checked as used"))
+    }
+
+    pub fn get(&self, node: usize) -> Option<&T> {
+        self.as_slice().get(node)?.as_ref()
+    }
+
+    pub fn get_mut(&mut self, node: usize) -> Option<&mut T> {
+        match node < self.slots.len() {
+            true => self.slots[node].as_mut(),
+            false => None,
+        }
+    }
+
+    /// Parent of the node, `None` for the root or a missing node.
+    pub fn parent(&self, node: usize) -> Option<usize> {
+        self.get(node)?;
+        node.checked_sub(1).map(|pos| pos / 2)
+    }
+
+    pub fn left(&self, node: usize) -> Option<usize> {
+        self.get(node)?;
+        left_of(node).filter(|&pos| self.get(pos).is_some())
+    }
+
+    pub fn right(&self, node: usize) -> Option<usize> {
+        self.get(node)?;
+        right_of(node).filter(|&pos| self.get(pos).is_some())
+    }
+
+    /// Number of nodes.
+    pub fn count(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Every node has either no child or two. Like complete and perfect, it
+    /// is false for an empty tree.
+    pub fn is_full(&self) -> bool {
+        self.len > 0
+            && (0..self.slots.len())
+                .filter(|&pos| self.slots[pos].is_some())
+                .all(|pos| self.left(pos).is_some() == self.right(pos).is_some())
+    }
+
+    /// Every level is filled, the leaves all being at the same depth.
+    pub fn is_perfect(&self) -> bool {
+        self.is_complete() && (self.len + 1).is_power_of_two()
+    }
+
+    /// Every level but the last one is filled, and the nodes of the last one
+    /// are on the left, which is when the nodes fill the array without a gap.
+    pub fn is_complete(&self) -> bool {
+        self.len > 0 && self.len == self.slots.len()
+    }
+
+    /// The heights of the two subtrees of every node differ by one at most.
+    /// An empty tree is balanced.
+    pub fn is_balanced(&self) -> bool {
+        self.balanced_height(0).is_some()
+    }
+
+    /// Number of levels, 0 for an empty tree.
+    pub fn height(&self) -> usize {
+        // The last slot is used, and it is on the last level.
+        match self.slots.len() {
+            0 => 0,
+            len => len.ilog2() as usize + 1,
+        }
+    }
+
+    /// Number of edges between the root and the node.
+    pub fn depth_of(&self, node: usize) -> Result<usize, TreeErr> {
+        self.check(node)?;
+        Ok(depth(node))
+    }
+
+    /// Number of nodes without children.
+    pub fn leaf_count(&self) -> usize {
+        (0..self.slots.len())
+            .filter(|&pos| self.slots[pos].is_some())
+            .filter(|&pos| self.left(pos).is_none() && self.right(pos).is_none())
+            .count()
+    }
+
+    /// Number of nodes with at least a child.
+    pub fn internal_count(&self) -> usize {
+        self.len - self.leaf_count()
+    }
+
+    /// Number of nodes of the largest level.
+    pub fn width(&self) -> usize {
+        self.levels().map(|level| level.len()).max().unwrap_or(0)
+    }
+
+    /// Number of edges of the longest path between two nodes, which may not
+    /// go through the root.
+    pub fn diameter(&self) -> usize {
+        self.height_and_diameter(0).1
+    }
+
+    /// Values in pre-order: a node, then its left subtree, then its right one.
+    pub fn pre_order(&self) -> ArrayPreOrderIter<'_, T> {
+        let mut stack = LinkedStack::new();
+        if let Some(root) = self.root() {
+            push(&mut stack, root);
+        }
+        ArrayPreOrderIter { tree: self, stack }
+    }
+
+    /// Values in in-order: the left subtree of a node, the node, then its
+    /// right subtree.
+    pub fn in_order(&self) -> ArrayInOrderIter<'_, T> {
+        ArrayInOrderIter {
+            tree: self,
+            stack: LinkedStack::new(),
+            current: self.root(),
+        }
+    }
+
+    /// Values in post-order: both subtrees of a node, then the node.
+    pub fn post_order(&self) -> ArrayPostOrderIter<'_, T> {
+        let mut stack = LinkedStack::new();
+        if let Some(root) = self.root() {
+            push(&mut stack, (root, false));
+        }
+        ArrayPostOrderIter { tree: self, stack }
+    }
+
+    /// Values level by level, from the left to the right, which is the order
+    /// of the array.
+    pub fn level_order(&self) -> impl Iterator<Item = &T> + '_ {
+        self.slots.iter().flatten()
+    }
+
+    /// The values of each level, from the root down.
+    pub fn levels(&self) -> ArrayLevelsIter<'_, T> {
+        ArrayLevelsIter {
+            tree: self,
+            first: 0,
+        }
+    }
+
+    fn check(&self, node: usize) -> Result<(), TreeErr> {
+        match self.get(node) {
+            Some(_) => Ok(()),
+            None => Err(TreeErr::StaleHandle),
+        }
+    }
+
+    fn insert_at(&mut self, pos: usize, val: T) -> Result<usize, TreeErr> {
+        if self.get(pos).is_some() {
+            return Err(TreeErr::Occupied);
+        }
+        self.place(pos, val)?;
+        Ok(pos)
+    }
+
+    // Put the value at a free position, growing the array up to it, unless
+    // it is below the last level allowed.
+    fn place(&mut self, pos: usize, val: T) -> Result<(), TreeErr> {
+        if depth(pos) >= Self::MAX_HEIGHT {
+            return Err(TreeErr::TooDeep);
+        }
+        while self.slots.len() <= pos {
+            self.slots.push(None);
+        }
+        self.slots[pos] = Some(val);
+        self.len += 1;
+        Ok(())
+    }
+
+    // Drop the empty slots at the end.
+    fn trim(&mut self) {
+        while self.slots.last().is_some_and(|slot| slot.is_none()) {
+            self.slots.pop();
+        }
+    }
+
+    fn balanced_height(&self, pos: usize) -> Option<usize> {
+        if self.get(pos).is_none() {
+            return Some(0);
+        }
+        let left = left_of(pos).map_or(Some(0), |left| self.balanced_height(left))?;
+        let right = right_of(pos).map_or(Some(0), |right| self.balanced_height(right))?;
+        (left.abs_diff(right) <= 1).then_some(1 + left.max(right))
+    }
+
+    fn height_and_diameter(&self, pos: usize) -> (usize, usize) {
+        if self.get(pos).is_none() {
+            return (0, 0);
+        }
+        let (left_height, left_diameter) =
+            left_of(pos).map_or((0, 0), |left| self.height_and_diameter(left));
+        let (right_height, right_diameter) =
+            right_of(pos).map_or((0, 0), |right| self.height_and_diameter(right));
+        (
+            1 + left_height.max(right_height),
+            (left_height + right_height)
+                .max(left_diameter)
+                .max(right_diameter),
+        )
+    }
+}
+
+impl<T> Default for ArrayBinaryTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Link the nodes of the array, keeping the shape of the tree.
+impl<T> From<ArrayBinaryTree<T>> for LinkedBinaryTree<T> {
+    fn from(mut array: ArrayBinaryTree<T>) -> Self {
+        let mut tree = LinkedBinaryTree::new();
+        // Parents come before their children in the array, so their handles
+        // are known when the children are inserted.
+        let mut handles: Vec<Option<NodeHandle>> = Vec::with_capacity(array.slots.len());
+        for pos in 0..array.slots.len() {
+            let handle = array.slots[pos].take().map(|val| {
+                let inserted = match pos {
+                    0 => tree.insert_root(val),
+                    _ => {
+                        let parent = handles[(pos - 1) / 2].expect("a node has a parent");
+                        match pos % 2 {
+                            1 => tree.insert_left(parent, val),
+                            _ => tree.insert_right(parent, val),
+                        }
+                    }
+                };
+                inserted.expect("the position is free")
+            });
+            handles.push(handle);
+        }
+        tree
+    }
+}
+
+// Positions of the children, `None` when they would not fit in a usize.
+fn left_of(pos: usize) -> Option<usize> {
+    pos.checked_mul(2)?.checked_add(1)
+}
+
+fn right_of(pos: usize) -> Option<usize> {
+    pos.checked_mul(2)?.checked_add(2)
+}
+
+fn depth(pos: usize) -> usize {
+    // Level d holds the positions 2^d - 1 to 2^(d + 1) - 2.
+    (pos as u128 + 1).ilog2() as usize
+}
+
+fn push<V>(stack: &mut LinkedStack<V>, val: V) {
+    stack.push(val).expect("the stack is unbounded");
+}
+
+/// See [`ArrayBinaryTree::pre_order`].
+pub struct ArrayPreOrderIter<'a, T> {
+    tree: &'a ArrayBinaryTree<T>,
+    stack: LinkedStack<usize>,
+}
+
+impl<'a, T> Iterator for ArrayPreOrderIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let pos = self.stack.pop()?;
+        if let Some(right) = self.tree.right(pos) {
+            push(&mut self.stack, right);
+        }
+        if let Some(left) = self.tree.left(pos) {
+            push(&mut self.stack, left);
+        }
+        self.tree.get(pos)
+    }
+}
+
+/// See [`ArrayBinaryTree::in_order`].
+pub struct ArrayInOrderIter<'a, T> {
+    tree: &'a ArrayBinaryTree<T>,
+    stack: LinkedStack<usize>,
+    current: Option<usize>,
+}
+
+impl<'a, T> Iterator for ArrayInOrderIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(pos) = self.current {
+            push(&mut self.stack, pos);
+            self.current = self.tree.left(pos);
+        }
+        let pos = self.stack.pop()?;
+        self.current = self.tree.right(pos);
+        self.tree.get(pos)
+    }
+}
+
+/// See [`ArrayBinaryTree::post_order`].
+pub struct ArrayPostOrderIter<'a, T> {
+    tree: &'a ArrayBinaryTree<T>,
+    // Nodes along with whether their children have already been stacked.
+    stack: LinkedStack<(usize, bool)>,
+}
+
+impl<'a, T> Iterator for ArrayPostOrderIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (pos, expanded) = self.stack.pop()?;
+            if expanded {
+                return self.tree.get(pos);
+            }
+            push(&mut self.stack, (pos, true));
+            if let Some(right) = self.tree.right(pos) {
+                push(&mut self.stack, (right, false));
+            }
+            if let Some(left) = self.tree.left(pos) {
+                push(&mut self.stack, (left, false));
+            }
+        }
+    }
+}
+
+/// See [`ArrayBinaryTree::levels`].
+pub struct ArrayLevelsIter<'a, T> {
+    tree: &'a ArrayBinaryTree<T>,
+    // First position of the next level.
+    first: usize,
+}
+
+impl<'a, T> Iterator for ArrayLevelsIter<'a, T> {
+    type Item = Vec<&'a T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let slots = self.tree.as_slice();
+        if self.first >= slots.len() {
+            return None;
+        }
+        // The level starting at 2^d - 1 holds 2^d slots.
+        let end = slots.len().min(2 * self.first + 1);
+        let level = slots[self.first..end].iter().flatten().collect();
+        self.first = end;
+        Some(level)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::shapes;
+    use super::*;
+
+    // 1 with the children 2 and 3, 2 having a right child 4.
+    fn sample() -> ArrayBinaryTree<i32> {
+        ArrayBinaryTree::from_level_order([Some(1), Some(2), Some(3), None, Some(4)]).unwrap()
+    }
+
+    // Tree of the given shape, given as heap positions from 1, each node
+    // holding its position from 0.
+    fn from_shape(shape: &[u64]) -> ArrayBinaryTree<usize> {
+        let mut tree = ArrayBinaryTree::new();
+        let mut shape = shape.to_vec();
+        shape.sort();
+        for p in shape {
+            let pos = p as usize - 1;
+            match pos {
+                0 => tree.insert_root(pos),
+                _ if pos % 2 == 1 => tree.insert_left((pos - 1) / 2, pos),
+                _ => tree.insert_right((pos - 1) / 2, pos),
+            }
+            .unwrap();
+        }
+        tree
+    }
+
+    #[test]
+    fn array_binary_tree_layout() {
+        let binary_tree = sample();
+        assert_eq!(
+            &[Some(1), Some(2), Some(3), None, Some(4)],
+            binary_tree.as_slice()
+        );
+        assert_eq!(4, binary_tree.count());
+        assert_eq!(Some(0), binary_tree.root());
+        assert_eq!(Some(1), binary_tree.left(0));
+        assert_eq!(Some(2), binary_tree.right(0));
+        assert_eq!(None, binary_tree.left(1));
+        assert_eq!(Some(4), binary_tree.right(1));
+        assert_eq!(Some(1), binary_tree.parent(4));
+        assert_eq!(None, binary_tree.parent(0));
+        assert_eq!(None, binary_tree.parent(3));
+    }
+
+    #[test]
+    fn array_binary_tree_insert_remove() {
+        let mut binary_tree = ArrayBinaryTree::new();
+        assert!(binary_tree.is_empty());
+        assert_eq!(Err(TreeErr::StaleHandle), binary_tree.insert_left(0, 'z'));
+        let root = binary_tree.insert_root('a').unwrap();
+        assert_eq!(Err(TreeErr::Occupied), binary_tree.insert_root('b'));
+        let right = binary_tree.insert_right(root, 'c').unwrap();
+        let leaf = binary_tree.insert_left(right, 'f').unwrap();
+        assert_eq!(5, leaf);
+        assert_eq!(Err(TreeErr::Occupied), binary_tree.insert_left(right, 'g'));
+        assert_eq!(Ok('f'), binary_tree.replace(leaf, 'F'));
+        *binary_tree.get_mut(root).unwrap() = 'A';
+
+        let subtree = binary_tree.remove_subtree(right).unwrap();
+        assert_eq!(&[Some('A')], binary_tree.as_slice());
+        assert_eq!(&[Some('c'), Some('F')], subtree.as_slice());
+        assert_eq!(Err(TreeErr::StaleHandle), binary_tree.replace(leaf, 'x'));
+        assert_eq!(None, binary_tree.get(right));
+    }
+
+    #[test]
+    fn array_binary_tree_remove_deep_subtree() {
+        let mut binary_tree = from_shape(&[1, 2, 3, 5, 10, 11, 21]);
+        let subtree = binary_tree.remove_subtree(1).unwrap();
+        assert_eq!(&[Some(0), None, Some(2)], binary_tree.as_slice());
+        // 4 is the right child of the new root, 9 and 10 its children, and 20
+        // the right child of 9
+        assert_eq!(
+            vec![vec![&1], vec![&4], vec![&9, &10], vec![&20]],
+            subtree.levels().collect::<Vec<_>>()
+        );
+        assert_eq!(4, subtree.height());
+        assert_eq!(Some(&20), subtree.get(12));
+        assert_eq!(
+            vec![1, 4, 9, 20, 10],
+            subtree.pre_order().copied().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn array_binary_tree_traversals() {
+        let binary_tree = sample();
+        let collect = |iter: &mut dyn Iterator<Item = &i32>| iter.copied().collect::<Vec<_>>();
+        assert_eq!(vec![1, 2, 4, 3], collect(&mut binary_tree.pre_order()));
+        assert_eq!(vec![2, 4, 1, 3], collect(&mut binary_tree.in_order()));
+        assert_eq!(vec![4, 2, 3, 1], collect(&mut binary_tree.post_order()));
+        assert_eq!(vec![1, 2, 3, 4], collect(&mut binary_tree.level_order()));
+        assert_eq!(
+            vec![vec![&1], vec![&2, &3], vec![&4]],
+            binary_tree.levels().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn array_binary_tree_from_level_order() {
+        let binary_tree =
+            ArrayBinaryTree::from_level_order([Some(1), None, Some(3), Some(4), Some(5)]).unwrap();
+        assert_eq!(
+            &[Some(1), None, Some(3), None, None, Some(4), Some(5)],
+            binary_tree.as_slice()
+        );
+        assert!(ArrayBinaryTree::<i32>::from_level_order([None, None])
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            Err(TreeErr::OrphanValue),
+            ArrayBinaryTree::from_level_order([Some(1), None, None, Some(2)]).map(|_| ())
+        );
+    }
+
+    #[test]
+    fn array_binary_tree_complete_is_gap_free() {
+        for shape in shapes(7) {
+            let binary_tree = from_shape(&shape);
+            let slots = binary_tree.as_slice();
+            let gap_free = !slots.is_empty() && slots.iter().all(Option::is_some);
+            assert_eq!(gap_free, binary_tree.is_complete(), "{shape:?}");
+            // the array ends with a node, on the last level
+            assert!(slots.last().is_none_or(Option::is_some));
+            assert_eq!(
+                binary_tree.level_order().count(),
+                binary_tree.count(),
+                "{shape:?}"
+            );
+        }
+        // filling the array in order always gives a complete tree
+        let mut binary_tree = ArrayBinaryTree::new();
+        binary_tree.insert_root(0).unwrap();
+        for pos in 1..20 {
+            match pos % 2 {
+                1 => binary_tree.insert_left((pos - 1) / 2, pos),
+                _ => binary_tree.insert_right((pos - 2) / 2, pos),
+            }
+            .unwrap();
+            assert!(binary_tree.is_complete());
+            assert_eq!(pos + 1, binary_tree.as_slice().len());
+        }
+    }
+
+    #[test]
+    fn array_binary_tree_metrics() {
+        let binary_tree = sample();
+        assert_eq!(3, binary_tree.height());
+        assert_eq!(2, binary_tree.leaf_count());
+        assert_eq!(2, binary_tree.internal_count());
+        assert_eq!(2, binary_tree.width());
+        assert_eq!(3, binary_tree.diameter());
+        assert_eq!(Ok(2), binary_tree.depth_of(4));
+        assert_eq!(Err(TreeErr::StaleHandle), binary_tree.depth_of(3));
+        assert!(!binary_tree.is_full());
+        assert!(!binary_tree.is_complete());
+        assert!(!binary_tree.is_perfect());
+        assert!(binary_tree.is_balanced());
+
+        let perfect = ArrayBinaryTree::from_level_order((1..=7).map(Some)).unwrap();
+        assert!(perfect.is_perfect() && perfect.is_full() && perfect.is_complete());
+        assert_eq!(4, perfect.diameter());
+    }
+
+    #[test]
+    fn array_binary_tree_linked_round_trip() {
+        for shape in shapes(7) {
+            let array = from_shape(&shape);
+            let slots = array.as_slice().to_vec();
+            let linked = LinkedBinaryTree::from(array);
+            let array = from_shape(&shape);
+
+            assert_eq!(array.count(), linked.count());
+            assert_eq!(
+                array.pre_order().collect::<Vec<_>>(),
+                linked.pre_order().collect::<Vec<_>>()
+            );
+            assert_eq!(
+                array.in_order().collect::<Vec<_>>(),
+                linked.in_order().collect::<Vec<_>>()
+            );
+            assert_eq!(
+                array.post_order().collect::<Vec<_>>(),
+                linked.post_order().collect::<Vec<_>>()
+            );
+            assert_eq!(
+                array.levels().collect::<Vec<_>>(),
+                linked.levels().collect::<Vec<_>>()
+            );
+            let shape_of = |full, complete, perfect, balanced| (full, complete, perfect, balanced);
+            assert_eq!(
+                shape_of(
+                    linked.is_full(),
+                    linked.is_complete(),
+                    linked.is_perfect(),
+                    linked.is_balanced()
+                ),
+                shape_of(
+                    array.is_full(),
+                    array.is_complete(),
+                    array.is_perfect(),
+                    array.is_balanced()
+                ),
+                "{shape:?}"
+            );
+            assert_eq!(
+                (linked.height(), linked.width(), linked.diameter()),
+                (array.height(), array.width(), array.diameter()),
+                "{shape:?}"
+            );
+            assert_eq!(linked.leaf_count(), array.leaf_count());
+            assert_eq!(linked.internal_count(), array.internal_count());
+
+            let back = ArrayBinaryTree::try_from(linked).unwrap();
+            assert_eq!(slots.as_slice(), back.as_slice());
+        }
+    }
+
+    #[test]
+    fn array_binary_tree_chain_too_deep() {
+        // 2^40 - 1 slots, far more than the memory.
+        let mut linked = LinkedBinaryTree::new();
+        let mut node = linked.insert_root(0).unwrap();
+        for depth in 1..40 {
+            node = linked.insert_left(node, depth).unwrap();
+        }
+        assert_eq!(
+            Err(TreeErr::TooDeep),
+            ArrayBinaryTree::try_from(linked).map(|_| ())
+        );
+
+        // Positions past the last level are refused before the array grows up
+        // to them.
+        let mut array = ArrayBinaryTree::new();
+        array.insert_root(0).unwrap();
+        let past_last_level = (1 << ArrayBinaryTree::<usize>::MAX_HEIGHT) - 1;
+        assert_eq!(Err(TreeErr::TooDeep), array.place(past_last_level, 1));
+        assert_eq!(Err(TreeErr::TooDeep), array.place(usize::MAX, 1));
+        assert_eq!(1, array.as_slice().len());
+        assert_eq!(1, array.count());
+    }
+
+    #[test]
+    fn array_binary_tree_too_deep() {
+        let mut linked = LinkedBinaryTree::new();
+        let mut node = linked.insert_root(0).unwrap();
+        for depth in 1..=usize::BITS {
+            node = linked.insert_right(node, depth).unwrap();
+        }
+        assert_eq!(
+            Err(TreeErr::TooDeep),
+            ArrayBinaryTree::try_from(linked).map(|_| ())
+        );
+    }
+}
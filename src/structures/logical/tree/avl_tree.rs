@@ -0,0 +1,337 @@
+#![allow(dead_code)]
+
+use std::ops::RangeBounds;
+
+use super::search_tree::{SearchIter, SearchRange, SearchTree};
+use super::OrderedMap;
+use crate::structures::physical::arena::ArenaIndex;
+
+/// AVL tree: the heights of the two subtrees of every node differ by at most
+/// one, so the height of the tree stays below 1.44 log2(n + 2). Each node keeps
+/// the height of its subtree, and the nodes on the path of an insertion or a
+/// removal are rebalanced on the way back up, with a single rotation when the
+/// higher grandchild is on the outside (LL and RR cases), and a double one
+/// when it is on the inside (LR and RL cases).
+pub struct AvlTree<K, V> {
+    tree: SearchTree<K, V, usize>,
+}
+
+impl<K: Ord, V> AvlTree<K, V> {
+    pub fn new() -> Self {
+        Self {
+            tree: SearchTree::new(),
+        }
+    }
+
+    /// Same as [`new`](Self::new), counting the rotations done to rebalance
+    /// the tree, which [`rotations`](Self::rotations) gives.
+    pub fn with_rotation_counter() -> Self {
+        Self {
+            tree: SearchTree::with_rotation_counter(),
+        }
+    }
+
+    /// Number of rotations done so far, a double rotation counting as two.
+    /// None unless the tree was built with a counter.
+    pub fn rotations(&self) -> Option<u64> {
+        self.tree.rotations
+    }
+
+    /// Number of levels, 0 for an empty tree.
+    pub fn height(&self) -> usize {
+        self.height_of(self.tree.root)
+    }
+
+    /// Panic unless the keys are in order, and every node has the right size
+    /// and height and a balance factor in -1..=1.
+    pub fn assert_invariants(&self) {
+        if let Err(err) = self
+            .tree
+            .check_order_and_sizes()
+            .and_then(|_| self.check_balance(self.tree.root))
+        {
+            panic!("broken AVL tree: {err}");
+        }
+    }
+
+    fn check_balance(&self, idx: Option<ArenaIndex>) -> Result<usize, String> {
+        let Some(idx) = idx else {
+            return Ok(0);
+        };
+        let node = self.tree.node(idx);
+        let left = self.check_balance(node.left)?;
+        let right = self.check_balance(node.right)?;
+        if node.meta != 1 + left.max(right) {
+            return Err("the height of a subtree is wrong".to_string());
+        }
+        if left.abs_diff(right) > 1 {
+            return Err("a node is out of balance".to_string());
+        }
+        Ok(node.meta)
+    }
+
+    fn height_of(&self, idx: Option<ArenaIndex>) -> usize {
+        idx.map_or(0, |idx| self.tree.node(idx).meta)
+    }
+
+    // Height of the left subtree minus the height of the right one.
+    fn balance_factor(&self, idx: ArenaIndex) -> isize {
+        let node = self.tree.node(idx);
+        self.height_of(node.left) as isize - self.height_of(node.right) as isize
+    }
+
+    // Recompute the size and height of a node from its children.
+    fn update(&mut self, idx: ArenaIndex) {
+        self.tree.update_size(idx);
+        let node = self.tree.node(idx);
+        let height = 1 + self.height_of(node.left).max(self.height_of(node.right));
+        self.tree.node_mut(idx).meta = height;
+    }
+
+    fn rotate_left(&mut self, idx: ArenaIndex) -> ArenaIndex {
+        let pivot = self.tree.rotate_left(idx);
+        self.update(idx);
+        self.update(pivot);
+        pivot
+    }
+
+    fn rotate_right(&mut self, idx: ArenaIndex) -> ArenaIndex {
+        let pivot = self.tree.rotate_right(idx);
+        self.update(idx);
+        self.update(pivot);
+        pivot
+    }
+
+    // Update a node whose subtrees are balanced, and rotate it if they now
+    // differ in height by two, giving back the root of the subtree.
+    fn rebalance(&mut self, idx: ArenaIndex) -> ArenaIndex {
+        self.update(idx);
+        match self.balance_factor(idx) {
+            2 => {
+                let left = self.tree.node(idx).left.expect("the left side is higher");
+                if self.balance_factor(left) < 0 {
+                    // LR: the left child leans right, rotating it first makes
+                    // it an LL case.
+                    self.tree.node_mut(idx).left = Some(self.rotate_left(left));
+                }
+                self.rotate_right(idx)
+            }
+            -2 => {
+                let right = self.tree.node(idx).right.expect("the right side is higher");
+                if self.balance_factor(right) > 0 {
+                    // RL, mirror of LR.
+                    self.tree.node_mut(idx).right = Some(self.rotate_right(right));
+                }
+                self.rotate_left(idx)
+            }
+            _ => idx,
+        }
+    }
+
+    // Rebalance the nodes of a path from the root, deepest first, linking the
+    // subtree each of them roots in place of it.
+    fn rebalance_path(&mut self, path: Vec<ArenaIndex>) {
+        let mut path = path.into_iter().rev().peekable();
+        while let Some(idx) = path.next() {
+            let root = self.rebalance(idx);
+            if root != idx {
+                self.tree.relink(path.peek().copied(), idx, Some(root));
+            }
+        }
+    }
+}
+
+impl<K: Ord, V> Default for AvlTree<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord, V> OrderedMap for AvlTree<K, V> {
+    type Key = K;
+    type Value = V;
+    type Iter<'a>
+        = SearchIter<'a, K, V, usize>
+    where
+        Self: 'a;
+    type Range<'a>
+        = SearchRange<'a, K, V, usize>
+    where
+        Self: 'a;
+
+    fn insert(&mut self, key: K, val: V) -> Option<V> {
+        let (old, path) = self.tree.insert_unbalanced(key, val, 1);
+        self.rebalance_path(path);
+        old
+    }
+
+    fn get(&self, key: &K) -> Option<&V> {
+        self.tree.get(key)
+    }
+
+    fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        self.tree.get_mut(key)
+    }
+
+    /// Remove a key as in an unbalanced tree, a node with two children taking
+    /// the entry of its successor, then rebalance the path to the node which
+    /// was unlinked. Unlike an insertion, a removal may take a rotation at
+    /// every level.
+    fn remove(&mut self, key: &K) -> Option<V> {
+        let ((_, val), path) = self.tree.remove_unbalanced(key)?;
+        self.rebalance_path(path);
+        Some(val)
+    }
+
+    fn min(&self) -> Option<(&K, &V)> {
+        self.tree.min()
+    }
+
+    fn max(&self) -> Option<(&K, &V)> {
+        self.tree.max()
+    }
+
+    fn floor(&self, key: &K) -> Option<(&K, &V)> {
+        self.tree.floor(key)
+    }
+
+    fn ceiling(&self, key: &K) -> Option<(&K, &V)> {
+        self.tree.ceiling(key)
+    }
+
+    fn successor(&self, key: &K) -> Option<(&K, &V)> {
+        self.tree.successor(key)
+    }
+
+    fn predecessor(&self, key: &K) -> Option<(&K, &V)> {
+        self.tree.predecessor(key)
+    }
+
+    fn rank(&self, key: &K) -> usize {
+        self.tree.rank(key)
+    }
+
+    fn select(&self, rank: usize) -> Option<(&K, &V)> {
+        self.tree.select(rank)
+    }
+
+    fn len(&self) -> usize {
+        self.tree.len()
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        self.tree.iter()
+    }
+
+    fn range<R: RangeBounds<K>>(&self, range: R) -> Self::Range<'_> {
+        self.tree.range(range)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_utils::XorShift;
+
+    fn root_key(tree: &AvlTree<i32, ()>) -> i32 {
+        tree.tree.node(tree.tree.root.unwrap()).key
+    }
+
+    fn insert_all(keys: &[i32]) -> AvlTree<i32, ()> {
+        let mut tree = AvlTree::with_rotation_counter();
+        keys.iter().for_each(|&key| {
+            tree.insert(key, ());
+            tree.assert_invariants();
+        });
+        tree
+    }
+
+    #[test]
+    fn avl_tree_single_rotations() {
+        // LL: 3 then 2 then 1 leans left twice
+        let tree = insert_all(&[3, 2, 1]);
+        assert_eq!(2, root_key(&tree));
+        assert_eq!(2, tree.height());
+        assert_eq!(Some(1), tree.rotations());
+        // RR, the mirror
+        let tree = insert_all(&[1, 2, 3]);
+        assert_eq!(2, root_key(&tree));
+        assert_eq!(Some(1), tree.rotations());
+    }
+
+    #[test]
+    fn avl_tree_double_rotations() {
+        // LR: 1 goes left of 3, then 2 right of 1
+        let tree = insert_all(&[3, 1, 2]);
+        assert_eq!(2, root_key(&tree));
+        assert_eq!(2, tree.height());
+        assert_eq!(Some(2), tree.rotations());
+        // RL, the mirror
+        let tree = insert_all(&[1, 3, 2]);
+        assert_eq!(2, root_key(&tree));
+        assert_eq!(Some(2), tree.rotations());
+    }
+
+    #[test]
+    fn avl_tree_remove_rebalances() {
+        let mut tree = insert_all(&[2, 1, 3, 4]);
+        assert_eq!(Some(0), tree.rotations());
+        // without 1, the right side of 2 is two levels higher
+        assert_eq!(Some(()), tree.remove(&1));
+        tree.assert_invariants();
+        assert_eq!(3, root_key(&tree));
+        assert_eq!(Some(1), tree.rotations());
+    }
+
+    #[test]
+    fn avl_tree_rotations_are_opt_in() {
+        let mut tree = AvlTree::new();
+        (0..10).for_each(|key| {
+            tree.insert(key, ());
+        });
+        assert_eq!(None, tree.rotations());
+    }
+
+    #[test]
+    fn avl_tree_height_stays_logarithmic() {
+        let mut tree = AvlTree::new();
+        (0..1_023).for_each(|key| {
+            tree.insert(key, ());
+        });
+        tree.assert_invariants();
+        // sorted keys fill the tree level by level
+        assert_eq!(10, tree.height());
+        (0..1_023).step_by(3).for_each(|key| {
+            tree.remove(&key);
+        });
+        tree.assert_invariants();
+        assert!(tree.height() <= 10);
+    }
+
+    #[test]
+    fn avl_tree_invariants_hold_under_random_operations() {
+        let mut rng = XorShift::new(7);
+        let mut tree = AvlTree::new();
+        for _ in 0..3_000 {
+            let key = rng.below(200) as i32;
+            if rng.below(3) == 0 {
+                tree.remove(&key);
+            } else {
+                tree.insert(key, ());
+            }
+            tree.assert_invariants();
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "out of balance")]
+    fn avl_tree_assert_invariants_catches_imbalance() {
+        let mut tree = insert_all(&[2, 1, 3, 4]);
+        // unlink 1 behind the tree's back, fixing the size and height of 2
+        let root = tree.tree.root.unwrap();
+        tree.tree.node_mut(root).left = None;
+        tree.tree.node_mut(root).size = 3;
+        tree.tree.node_mut(root).meta = 3;
+        tree.assert_invariants();
+    }
+}
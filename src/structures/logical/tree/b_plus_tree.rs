@@ -0,0 +1,727 @@
+#![allow(dead_code)]
+
+use std::{mem, ops::Bound, ops::RangeBounds};
+
+use super::page::{into_page, page_header, page_order, PageEncode, NO_PAGE};
+use super::{OrderedMap, TreeErr};
+use crate::structures::physical::arena::{Arena, ArenaIndex};
+
+struct BPlusNode<K, V> {
+    keys: Vec<K>,
+    // Empty for an internal node.
+    vals: Vec<V>,
+    // Empty for a leaf, one more than the keys otherwise.
+    children: Vec<ArenaIndex>,
+    // Next leaf, in the order of the keys.
+    next: Option<ArenaIndex>,
+    // Number of entries in the subtree.
+    size: usize,
+}
+
+impl<K, V> BPlusNode<K, V> {
+    fn is_leaf(&self) -> bool {
+        self.children.is_empty()
+    }
+}
+
+/// B+ tree of minimum degree `B`: the entries are all in the leaves, and the
+/// internal nodes only hold copies of keys to route the searches, the keys
+/// of the child at `i` being at least the key at `i - 1` and lower than the
+/// key at `i`. Every node but the root holds between B - 1 and 2B - 1 keys,
+/// and all the leaves are at the same depth.
+///
+/// Each leaf links to the next one, so a range scan goes down to its first
+/// key, then only reads leaves. Internal nodes hold no values, so more keys
+/// fit in a page, see [`write_pages`](Self::write_pages).
+pub struct BPlusTree<K, V, const B: usize> {
+    nodes: Arena<BPlusNode<K, V>>,
+    root: ArenaIndex,
+}
+
+impl<K: Ord + Clone, V, const B: usize> BPlusTree<K, V, B> {
+    const MIN_KEYS: usize = B - 1;
+    const MAX_KEYS: usize = 2 * B - 1;
+
+    pub fn new() -> Self {
+        const { assert!(B >= 2, "the minimum degree of a B+ tree is at least 2") };
+        let mut nodes = Arena::new();
+        let root = nodes.insert(BPlusNode {
+            keys: Vec::new(),
+            vals: Vec::new(),
+            children: Vec::new(),
+            next: None,
+            size: 0,
+        });
+        Self { nodes, root }
+    }
+
+    /// Number of levels, 1 for a tree with a single leaf, even an empty one.
+    pub fn height(&self) -> usize {
+        let mut height = 1;
+        let mut idx = self.root;
+        while let Some(&child) = self.node(idx).children.first() {
+            height += 1;
+            idx = child;
+        }
+        height
+    }
+
+    /// Panic unless the keys are in order, every node but the root holds
+    /// between B - 1 and 2B - 1 keys, the leaves are all at the same depth
+    /// and linked in order, and the sizes are right.
+    pub fn assert_invariants(&self) {
+        let mut leaves = Vec::new();
+        if let Err(err) = self
+            .check_from(self.root, None, None, true, &mut leaves)
+            .and_then(|_| self.check_links(&leaves))
+        {
+            panic!("broken B+ tree: {err}");
+        }
+    }
+
+    // Check the subtree holds keys from `low` included to `high` excluded,
+    // giving its height and size, and collecting its leaves in order.
+    fn check_from(
+        &self,
+        idx: ArenaIndex,
+        low: Option<&K>,
+        high: Option<&K>,
+        root: bool,
+        leaves: &mut Vec<ArenaIndex>,
+    ) -> Result<(usize, usize), String> {
+        let node = self.node(idx);
+        if node.keys.len() > Self::MAX_KEYS || (!root && node.keys.len() < Self::MIN_KEYS) {
+            return Err(format!("a node holds {} keys", node.keys.len()));
+        }
+        let sorted = node.keys.windows(2).all(|pair| pair[0] < pair[1]);
+        let first = node
+            .keys
+            .first()
+            .zip(low)
+            .is_none_or(|(first, low)| first >= low);
+        let last = node
+            .keys
+            .last()
+            .zip(high)
+            .is_none_or(|(last, high)| last < high);
+        if !(sorted && first && last) {
+            return Err("keys are out of order".to_string());
+        }
+        if node.is_leaf() {
+            if node.vals.len() != node.keys.len() {
+                return Err("a leaf holds more values than keys".to_string());
+            }
+            leaves.push(idx);
+            return Ok((1, node.keys.len()));
+        }
+        if node.children.len() != node.keys.len() + 1 || !node.vals.is_empty() {
+            return Err("an internal node has the wrong number of children".to_string());
+        }
+        let mut height = None;
+        let mut size = 0;
+        for (pos, &child) in node.children.iter().enumerate() {
+            let low = pos.checked_sub(1).map(|pos| &node.keys[pos]).or(low);
+            let high = node.keys.get(pos).or(high);
+            let (child_height, child_size) = self.check_from(child, low, high, false, leaves)?;
+            if height.is_some_and(|height| height != child_height) {
+                return Err("leaves are at different depths".to_string());
+            }
+            height = Some(child_height);
+            size += child_size;
+        }
+        if node.size != size {
+            return Err("the size of a subtree is wrong".to_string());
+        }
+        Ok((1 + height.unwrap_or_default(), size))
+    }
+
+    fn check_links(&self, leaves: &[ArenaIndex]) -> Result<(), String> {
+        let nexts = leaves.iter().skip(1).map(|&leaf| Some(leaf)).chain([None]);
+        if leaves
+            .iter()
+            .zip(nexts)
+            .all(|(&leaf, next)| self.node(leaf).next == next)
+        {
+            Ok(())
+        } else {
+            Err("the leaves are not linked in order".to_string())
+        }
+    }
+
+    /// Write every node into a page of `P` bytes, in the layout described in
+    /// the [`page`](super::page) module, the root first and its descendants
+    /// breadth first. Internal nodes only hold keys, and each leaf ends with
+    /// the page of the next one, [`NO_PAGE`] for the last leaf. Fails if a
+    /// node doesn't fit in a page.
+    pub fn write_pages<const P: usize>(&self) -> Result<Vec<[u8; P]>, TreeErr>
+    where
+        K: PageEncode,
+        V: PageEncode,
+    {
+        let (order, pages) = page_order(self.root, |idx| &self.node(idx).children)?;
+        order
+            .iter()
+            .map(|&idx| {
+                let node = self.node(idx);
+                let mut bytes = page_header(node.is_leaf(), node.keys.len())?;
+                if node.is_leaf() {
+                    for (key, val) in node.keys.iter().zip(&node.vals) {
+                        key.encode(&mut bytes);
+                        val.encode(&mut bytes);
+                    }
+                    let next = node.next.map_or(NO_PAGE, |next| pages[&next]);
+                    next.encode(&mut bytes);
+                } else {
+                    node.keys.iter().for_each(|key| key.encode(&mut bytes));
+                    node.children
+                        .iter()
+                        .for_each(|child| pages[child].encode(&mut bytes));
+                }
+                into_page(&bytes)
+            })
+            .collect()
+    }
+
+    fn node(&self, idx: ArenaIndex) -> &BPlusNode<K, V> {
+        self.nodes.get(idx).expect("broken link in the tree")
+    }
+
+    fn node_mut(&mut self, idx: ArenaIndex) -> &mut BPlusNode<K, V> {
+        self.nodes.get_mut(idx).expect("broken link in the tree")
+    }
+
+    fn entry(&self, (leaf, pos): (ArenaIndex, usize)) -> (&K, &V) {
+        let node = self.node(leaf);
+        (&node.keys[pos], &node.vals[pos])
+    }
+
+    fn update_size(&mut self, idx: ArenaIndex) {
+        let node = self.node(idx);
+        let size = if node.is_leaf() {
+            node.keys.len()
+        } else {
+            node.children
+                .iter()
+                .map(|&child| self.node(child).size)
+                .sum()
+        };
+        self.node_mut(idx).size = size;
+    }
+
+    // Leaf where the key is, or would be.
+    fn leaf(&self, key: &K) -> ArenaIndex {
+        let mut idx = self.root;
+        loop {
+            let node = self.node(idx);
+            if node.is_leaf() {
+                return idx;
+            }
+            idx = node.children[node.keys.partition_point(|k| k <= key)];
+        }
+    }
+
+    fn first_leaf(&self) -> ArenaIndex {
+        let mut idx = self.root;
+        while let Some(&child) = self.node(idx).children.first() {
+            idx = child;
+        }
+        idx
+    }
+
+    fn last_leaf(&self, mut idx: ArenaIndex) -> ArenaIndex {
+        while let Some(&child) = self.node(idx).children.last() {
+            idx = child;
+        }
+        idx
+    }
+
+    // Greatest key lower than the given one, or equal if `or_equal`. When it
+    // is not in the leaf of the key, it is the last key of the subtree before
+    // the last turn right on the way down.
+    fn below(&self, key: &K, or_equal: bool) -> Option<(ArenaIndex, usize)> {
+        let mut idx = self.root;
+        let mut before = None;
+        while !self.node(idx).is_leaf() {
+            let node = self.node(idx);
+            let pos = node.keys.partition_point(|k| k <= key);
+            if pos > 0 {
+                before = Some(node.children[pos - 1]);
+            }
+            idx = node.children[pos];
+        }
+        let node = self.node(idx);
+        let pos = node
+            .keys
+            .partition_point(|k| k < key || (or_equal && k == key));
+        if pos > 0 {
+            return Some((idx, pos - 1));
+        }
+        let leaf = self.last_leaf(before?);
+        Some((leaf, self.node(leaf).keys.len() - 1))
+    }
+
+    // Least key greater than the given one, or equal if `or_equal`, which is
+    // in the leaf of the key, or the first of the next leaf.
+    fn above(&self, key: &K, or_equal: bool) -> Option<(ArenaIndex, usize)> {
+        let leaf = self.leaf(key);
+        let node = self.node(leaf);
+        let pos = node
+            .keys
+            .partition_point(|k| k < key || (!or_equal && k == key));
+        if pos < node.keys.len() {
+            Some((leaf, pos))
+        } else {
+            node.next.map(|next| (next, 0))
+        }
+    }
+
+    // Insert in the subtree, giving the previous value of the key, and the
+    // first key and the node of the right half if the root of the subtree
+    // was split.
+    fn insert_at(
+        &mut self,
+        idx: ArenaIndex,
+        key: K,
+        val: V,
+    ) -> (Option<V>, Option<(K, ArenaIndex)>) {
+        let node = self.node_mut(idx);
+        if node.is_leaf() {
+            match node.keys.binary_search(&key) {
+                Ok(pos) => return (Some(mem::replace(&mut node.vals[pos], val)), None),
+                Err(pos) => {
+                    node.keys.insert(pos, key);
+                    node.vals.insert(pos, val);
+                    node.size += 1;
+                }
+            }
+            let split = (node.keys.len() > Self::MAX_KEYS).then(|| self.split_leaf(idx));
+            return (None, split);
+        }
+        let pos = node.keys.partition_point(|k| *k <= key);
+        let child = node.children[pos];
+        let (old, split) = self.insert_at(child, key, val);
+        if old.is_some() {
+            return (old, None);
+        }
+        let node = self.node_mut(idx);
+        node.size += 1;
+        let Some((key, right)) = split else {
+            return (None, None);
+        };
+        node.keys.insert(pos, key);
+        node.children.insert(pos + 1, right);
+        let split = (node.keys.len() > Self::MAX_KEYS).then(|| self.split_internal(idx));
+        (None, split)
+    }
+
+    // Split a leaf of 2B entries in two leaves of B entries, the first key of
+    // the right one being copied up as a separator.
+    fn split_leaf(&mut self, idx: ArenaIndex) -> (K, ArenaIndex) {
+        let node = self.node_mut(idx);
+        let keys = node.keys.split_off(B);
+        let vals = node.vals.split_off(B);
+        let key = keys[0].clone();
+        let next = node.next;
+        let right = self.nodes.insert(BPlusNode {
+            keys,
+            vals,
+            children: Vec::new(),
+            next,
+            size: 0,
+        });
+        self.node_mut(idx).next = Some(right);
+        self.update_size(idx);
+        self.update_size(right);
+        (key, right)
+    }
+
+    // Split an internal node of 2B keys in a node of B keys and one of B - 1
+    // keys, the key between them moving up.
+    fn split_internal(&mut self, idx: ArenaIndex) -> (K, ArenaIndex) {
+        let node = self.node_mut(idx);
+        let keys = node.keys.split_off(B + 1);
+        let children = node.children.split_off(B + 1);
+        let key = node.keys.pop().expect("the node is full");
+        let right = self.nodes.insert(BPlusNode {
+            keys,
+            vals: Vec::new(),
+            children,
+            next: None,
+            size: 0,
+        });
+        self.update_size(idx);
+        self.update_size(right);
+        (key, right)
+    }
+
+    // Remove a key from the subtree, fixing the children left with too few
+    // keys on the way back up.
+    fn remove_at(&mut self, idx: ArenaIndex, key: &K) -> Option<V> {
+        let node = self.node_mut(idx);
+        if node.is_leaf() {
+            let pos = node.keys.binary_search(key).ok()?;
+            node.keys.remove(pos);
+            node.size -= 1;
+            return Some(node.vals.remove(pos));
+        }
+        let pos = node.keys.partition_point(|k| k <= key);
+        let child = node.children[pos];
+        let val = self.remove_at(child, key)?;
+        self.node_mut(idx).size -= 1;
+        if self.node(child).keys.len() < Self::MIN_KEYS {
+            self.refill(idx, pos);
+        }
+        Some(val)
+    }
+
+    // Give the child at `pos`, left with B - 2 keys, a key from a sibling
+    // which can spare one, or merge it with a sibling.
+    fn refill(&mut self, parent: ArenaIndex, pos: usize) {
+        let node = self.node(parent);
+        let left = pos.checked_sub(1).map(|pos| node.children[pos]);
+        let right = node.children.get(pos + 1).copied();
+        let spare = |idx: ArenaIndex| self.node(idx).keys.len() > Self::MIN_KEYS;
+        if left.is_some_and(spare) {
+            self.borrow_from_left(parent, pos);
+        } else if right.is_some_and(spare) {
+            self.borrow_from_right(parent, pos);
+        } else if right.is_some() {
+            self.merge(parent, pos);
+        } else {
+            self.merge(parent, pos - 1);
+        }
+    }
+
+    // A leaf takes the last entry of its left sibling, whose key becomes the
+    // separator between them. An internal node takes the separator and the
+    // last child of its sibling, whose last key goes up instead.
+    fn borrow_from_left(&mut self, parent: ArenaIndex, pos: usize) {
+        let (left, child) = {
+            let node = self.node(parent);
+            (node.children[pos - 1], node.children[pos])
+        };
+        let node = self.node_mut(left);
+        let key = node.keys.pop().expect("the sibling has keys to spare");
+        if node.is_leaf() {
+            let val = node.vals.pop().expect("a leaf holds values");
+            self.node_mut(parent).keys[pos - 1] = key.clone();
+            let node = self.node_mut(child);
+            node.keys.insert(0, key);
+            node.vals.insert(0, val);
+        } else {
+            let grandchild = node.children.pop().expect("an internal node has children");
+            let separator = mem::replace(&mut self.node_mut(parent).keys[pos - 1], key);
+            let node = self.node_mut(child);
+            node.keys.insert(0, separator);
+            node.children.insert(0, grandchild);
+        }
+        self.update_size(left);
+        self.update_size(child);
+    }
+
+    // Mirror of `borrow_from_left`, the separator of two leaves becoming the
+    // new first key of the right one.
+    fn borrow_from_right(&mut self, parent: ArenaIndex, pos: usize) {
+        let (child, right) = {
+            let node = self.node(parent);
+            (node.children[pos], node.children[pos + 1])
+        };
+        let node = self.node_mut(right);
+        let key = node.keys.remove(0);
+        if node.is_leaf() {
+            let val = node.vals.remove(0);
+            self.node_mut(parent).keys[pos] = node.keys[0].clone();
+            let node = self.node_mut(child);
+            node.keys.push(key);
+            node.vals.push(val);
+        } else {
+            let grandchild = node.children.remove(0);
+            let separator = mem::replace(&mut self.node_mut(parent).keys[pos], key);
+            let node = self.node_mut(child);
+            node.keys.push(separator);
+            node.children.push(grandchild);
+        }
+        self.update_size(right);
+        self.update_size(child);
+    }
+
+    // Merge the children at `pos` and `pos + 1`. The separator between them
+    // goes down into internal nodes, and is dropped between leaves, the
+    // right leaf being unlinked.
+    fn merge(&mut self, parent: ArenaIndex, pos: usize) {
+        let node = self.node_mut(parent);
+        let separator = node.keys.remove(pos);
+        let left = node.children[pos];
+        let right = node.children.remove(pos + 1);
+        let right = self.nodes.remove(right).expect("linked node");
+        let node = self.node_mut(left);
+        if node.is_leaf() {
+            node.next = right.next;
+        } else {
+            node.keys.push(separator);
+        }
+        node.keys.extend(right.keys);
+        node.vals.extend(right.vals);
+        node.children.extend(right.children);
+        self.update_size(left);
+    }
+}
+
+impl<K: Ord + Clone, V, const B: usize> Default for BPlusTree<K, V, B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord + Clone, V, const B: usize> OrderedMap for BPlusTree<K, V, B> {
+    type Key = K;
+    type Value = V;
+    type Iter<'a>
+        = BPlusIter<'a, K, V, B>
+    where
+        Self: 'a;
+    type Range<'a>
+        = BPlusIter<'a, K, V, B>
+    where
+        Self: 'a;
+
+    fn insert(&mut self, key: K, val: V) -> Option<V> {
+        let (old, split) = self.insert_at(self.root, key, val);
+        if let Some((key, right)) = split {
+            // The tree grows from the top, a new root taking the separator of
+            // the two halves of the old one.
+            let root = self.nodes.insert(BPlusNode {
+                keys: vec![key],
+                vals: Vec::new(),
+                children: vec![self.root, right],
+                next: None,
+                size: 0,
+            });
+            self.update_size(root);
+            self.root = root;
+        }
+        old
+    }
+
+    fn get(&self, key: &K) -> Option<&V> {
+        let node = self.node(self.leaf(key));
+        node.keys.binary_search(key).ok().map(|pos| &node.vals[pos])
+    }
+
+    fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let node = self.node_mut(self.leaf(key));
+        let pos = node.keys.binary_search(key).ok()?;
+        Some(&mut node.vals[pos])
+    }
+
+    /// Remove a key from its leaf. The separators equal to the key, if any,
+    /// stay in the internal nodes, where they still route the searches.
+    fn remove(&mut self, key: &K) -> Option<V> {
+        let val = self.remove_at(self.root, key)?;
+        let root = self.node(self.root);
+        if root.keys.is_empty() && !root.is_leaf() {
+            // The tree shrinks from the top, when the children of the root
+            // are merged.
+            let child = root.children[0];
+            self.nodes.remove(self.root).expect("linked node");
+            self.root = child;
+        }
+        Some(val)
+    }
+
+    fn min(&self) -> Option<(&K, &V)> {
+        let node = self.node(self.first_leaf());
+        Some((node.keys.first()?, node.vals.first()?))
+    }
+
+    fn max(&self) -> Option<(&K, &V)> {
+        let node = self.node(self.last_leaf(self.root));
+        Some((node.keys.last()?, node.vals.last()?))
+    }
+
+    fn floor(&self, key: &K) -> Option<(&K, &V)> {
+        self.below(key, true).map(|found| self.entry(found))
+    }
+
+    fn ceiling(&self, key: &K) -> Option<(&K, &V)> {
+        self.above(key, true).map(|found| self.entry(found))
+    }
+
+    fn successor(&self, key: &K) -> Option<(&K, &V)> {
+        self.above(key, false).map(|found| self.entry(found))
+    }
+
+    fn predecessor(&self, key: &K) -> Option<(&K, &V)> {
+        self.below(key, false).map(|found| self.entry(found))
+    }
+
+    fn rank(&self, key: &K) -> usize {
+        let mut rank = 0;
+        let mut idx = self.root;
+        while !self.node(idx).is_leaf() {
+            let node = self.node(idx);
+            let pos = node.keys.partition_point(|k| k <= key);
+            let before = node.children.iter().take(pos);
+            rank += before.map(|&child| self.node(child).size).sum::<usize>();
+            idx = node.children[pos];
+        }
+        rank + self.node(idx).keys.partition_point(|k| k < key)
+    }
+
+    fn select(&self, mut rank: usize) -> Option<(&K, &V)> {
+        if rank >= self.len() {
+            return None;
+        }
+        let mut idx = self.root;
+        while !self.node(idx).is_leaf() {
+            for &child in &self.node(idx).children {
+                let size = self.node(child).size;
+                if rank < size {
+                    idx = child;
+                    break;
+                }
+                rank -= size;
+            }
+        }
+        Some(self.entry((idx, rank)))
+    }
+
+    fn len(&self) -> usize {
+        self.node(self.root).size
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        BPlusIter {
+            tree: self,
+            leaf: Some(self.first_leaf()),
+            pos: 0,
+            last: None,
+        }
+    }
+
+    fn range<R: RangeBounds<K>>(&self, range: R) -> Self::Range<'_> {
+        let start = match range.start_bound() {
+            Bound::Included(key) => self.above(key, true),
+            Bound::Excluded(key) => self.above(key, false),
+            Bound::Unbounded => Some((self.first_leaf(), 0)),
+        };
+        // Same as the binary search trees, the end is turned into the last
+        // key of the range found in the tree.
+        let last = match range.end_bound() {
+            Bound::Included(key) => self.below(key, true).map(Some),
+            Bound::Excluded(key) => self.below(key, false).map(Some),
+            Bound::Unbounded => Some(None),
+        };
+        let (start, last) = match (start, last) {
+            (Some(start), Some(last)) => (Some(start), last.map(|found| self.entry(found).0)),
+            _ => (None, None),
+        };
+        BPlusIter {
+            tree: self,
+            leaf: start.map(|(leaf, _)| leaf),
+            pos: start.map_or(0, |(_, pos)| pos),
+            last,
+        }
+    }
+}
+
+/// Entries of a B+ tree in order, possibly up to a key, going from leaf to
+/// leaf through their links.
+pub struct BPlusIter<'a, K, V, const B: usize> {
+    tree: &'a BPlusTree<K, V, B>,
+    // Leaf of the next entry, None once done.
+    leaf: Option<ArenaIndex>,
+    pos: usize,
+    // Last key to give, None when the range is not bounded at the end.
+    last: Option<&'a K>,
+}
+
+impl<'a, K: Ord + Clone, V, const B: usize> Iterator for BPlusIter<'a, K, V, B> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let node = self.tree.node(self.leaf?);
+            if self.pos == node.keys.len() {
+                self.leaf = node.next;
+                self.pos = 0;
+                continue;
+            }
+            let key = &node.keys[self.pos];
+            if self.last.is_some_and(|last| key > last) {
+                self.leaf = None;
+                return None;
+            }
+            self.pos += 1;
+            return Some((key, &node.vals[self.pos - 1]));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn b_plus_tree_range_scan_goes_through_leaves() {
+        let mut tree = BPlusTree::<_, _, 2>::new();
+        (0..100).rev().for_each(|key| {
+            tree.insert(key, key);
+        });
+        assert!(tree.iter().map(|(key, _)| *key).eq(0..100));
+        assert!(tree.range(17..=62).map(|(key, _)| *key).eq(17..=62));
+        assert!(tree.range(..3).map(|(key, _)| *key).eq(0..3));
+        assert!(tree.range(99..).map(|(key, _)| *key).eq(99..100));
+        assert_eq!(None, tree.range(100..).next());
+    }
+
+    #[test]
+    fn b_plus_tree_separators_outlive_their_keys() {
+        let mut tree = BPlusTree::<_, _, 2>::new();
+        (1..=4).for_each(|key| {
+            tree.insert(key, ());
+        });
+        // 3 still separates the leaves [1, 2] and [4]
+        assert_eq!(Some(()), tree.remove(&3));
+        tree.assert_invariants();
+        assert_eq!(vec![3], tree.node(tree.root).keys);
+        assert_eq!(None, tree.get(&3));
+        assert_eq!(Some((&2, &())), tree.floor(&3));
+        assert_eq!(Some((&4, &())), tree.ceiling(&3));
+        assert_eq!(2, tree.rank(&3));
+    }
+
+    #[test]
+    fn b_plus_tree_write_pages() {
+        let mut tree = BPlusTree::<u16, u8, 2>::new();
+        for key in 1..=4 {
+            tree.insert(key, key as u8 * 10);
+        }
+        // the leaf split in two when 4 came in, and 3 was copied up
+        let pages = tree.write_pages::<16>().unwrap();
+        assert_eq!(
+            vec![
+                [0, 1, 0, 3, 0, 1, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0],
+                [1, 2, 0, 1, 0, 10, 2, 0, 20, 2, 0, 0, 0, 0, 0, 0],
+                [1, 2, 0, 3, 0, 30, 4, 0, 40, 255, 255, 255, 255, 0, 0, 0],
+            ],
+            pages
+        );
+        assert_eq!(
+            Err(TreeErr::PageOverflow),
+            tree.write_pages::<12>().map(|_| ())
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "not linked in order")]
+    fn b_plus_tree_assert_invariants_catches_broken_link() {
+        let mut tree = BPlusTree::<_, _, 2>::new();
+        (1..=4).for_each(|key| {
+            tree.insert(key, ());
+        });
+        let leaf = tree.first_leaf();
+        tree.node_mut(leaf).next = None;
+        tree.assert_invariants();
+    }
+}
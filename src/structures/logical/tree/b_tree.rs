@@ -0,0 +1,711 @@
+#![allow(dead_code)]
+
+use std::{mem, ops::Bound, ops::RangeBounds};
+
+use super::page::{into_page, page_header, page_order, PageEncode};
+use super::{OrderedMap, TreeErr};
+use crate::structures::logical::stack::{LinkedStack, Stack};
+use crate::structures::physical::arena::{Arena, ArenaIndex};
+
+struct BTreeNode<K, V> {
+    keys: Vec<K>,
+    vals: Vec<V>,
+    // Empty for a leaf, one more than the keys otherwise.
+    children: Vec<ArenaIndex>,
+    // Number of keys in the subtree.
+    size: usize,
+}
+
+impl<K, V> BTreeNode<K, V> {
+    fn is_leaf(&self) -> bool {
+        self.children.is_empty()
+    }
+}
+
+/// B-tree of minimum degree `B`: every node but the root holds between
+/// B - 1 and 2B - 1 keys, an internal node having one more child than keys,
+/// and all the leaves are at the same depth. With many keys per node, the
+/// tree is shallow, and a node fits in a page of a disk, see
+/// [`write_pages`](Self::write_pages).
+///
+/// Insertion and removal go down from the root in a single pass: a full node
+/// is split before going down into it, and a node with the fewest keys takes
+/// one from a sibling, or is merged with it, so there is always room.
+pub struct BTree<K, V, const B: usize> {
+    nodes: Arena<BTreeNode<K, V>>,
+    root: ArenaIndex,
+}
+
+impl<K: Ord, V, const B: usize> BTree<K, V, B> {
+    const MIN_KEYS: usize = B - 1;
+    const MAX_KEYS: usize = 2 * B - 1;
+
+    pub fn new() -> Self {
+        const { assert!(B >= 2, "the minimum degree of a B-tree is at least 2") };
+        let mut nodes = Arena::new();
+        let root = nodes.insert(BTreeNode {
+            keys: Vec::new(),
+            vals: Vec::new(),
+            children: Vec::new(),
+            size: 0,
+        });
+        Self { nodes, root }
+    }
+
+    /// Number of levels, 1 for a tree with a single leaf, even an empty one.
+    pub fn height(&self) -> usize {
+        let mut height = 1;
+        let mut idx = self.root;
+        while let Some(&child) = self.node(idx).children.first() {
+            height += 1;
+            idx = child;
+        }
+        height
+    }
+
+    /// Panic unless the keys are in order, every node but the root holds
+    /// between B - 1 and 2B - 1 keys, the leaves are all at the same depth
+    /// and the sizes are right.
+    pub fn assert_invariants(&self) {
+        if let Err(err) = self.check_from(self.root, None, None, true) {
+            panic!("broken B-tree: {err}");
+        }
+    }
+
+    // Check the subtree holds keys between `low` and `high`, giving its
+    // height and size.
+    fn check_from(
+        &self,
+        idx: ArenaIndex,
+        low: Option<&K>,
+        high: Option<&K>,
+        root: bool,
+    ) -> Result<(usize, usize), String> {
+        let node = self.node(idx);
+        if node.keys.len() > Self::MAX_KEYS || (!root && node.keys.len() < Self::MIN_KEYS) {
+            return Err(format!("a node holds {} keys", node.keys.len()));
+        }
+        if node.vals.len() != node.keys.len() {
+            return Err("a node holds more values than keys".to_string());
+        }
+        if !is_sorted(low.into_iter().chain(&node.keys).chain(high)) {
+            return Err("keys are out of order".to_string());
+        }
+        if node.is_leaf() {
+            return Ok((1, node.keys.len()));
+        }
+        if node.children.len() != node.keys.len() + 1 {
+            return Err("an internal node has the wrong number of children".to_string());
+        }
+        let mut height = None;
+        let mut size = node.keys.len();
+        for (pos, &child) in node.children.iter().enumerate() {
+            let low = pos.checked_sub(1).map(|pos| &node.keys[pos]).or(low);
+            let high = node.keys.get(pos).or(high);
+            let (child_height, child_size) = self.check_from(child, low, high, false)?;
+            if height.is_some_and(|height| height != child_height) {
+                return Err("leaves are at different depths".to_string());
+            }
+            height = Some(child_height);
+            size += child_size;
+        }
+        if node.size != size {
+            return Err("the size of a subtree is wrong".to_string());
+        }
+        Ok((1 + height.unwrap_or_default(), size))
+    }
+
+    /// Write every node into a page of `P` bytes, in the layout described in
+    /// the [`page`](super::page) module, the root first and its descendants
+    /// breadth first. Fails if a node doesn't fit in a page.
+    pub fn write_pages<const P: usize>(&self) -> Result<Vec<[u8; P]>, TreeErr>
+    where
+        K: PageEncode,
+        V: PageEncode,
+    {
+        let (order, pages) = page_order(self.root, |idx| &self.node(idx).children)?;
+        order
+            .iter()
+            .map(|&idx| {
+                let node = self.node(idx);
+                let mut bytes = page_header(node.is_leaf(), node.keys.len())?;
+                for (key, val) in node.keys.iter().zip(&node.vals) {
+                    key.encode(&mut bytes);
+                    val.encode(&mut bytes);
+                }
+                node.children
+                    .iter()
+                    .for_each(|child| pages[child].encode(&mut bytes));
+                into_page(&bytes)
+            })
+            .collect()
+    }
+
+    fn node(&self, idx: ArenaIndex) -> &BTreeNode<K, V> {
+        self.nodes.get(idx).expect("broken link in the tree")
+    }
+
+    fn node_mut(&mut self, idx: ArenaIndex) -> &mut BTreeNode<K, V> {
+        self.nodes.get_mut(idx).expect("broken link in the tree")
+    }
+
+    fn entry(&self, (idx, pos): (ArenaIndex, usize)) -> (&K, &V) {
+        let node = self.node(idx);
+        (&node.keys[pos], &node.vals[pos])
+    }
+
+    fn update_size(&mut self, idx: ArenaIndex) {
+        let node = self.node(idx);
+        let size = node.keys.len()
+            + node
+                .children
+                .iter()
+                .map(|&child| self.node(child).size)
+                .sum::<usize>();
+        self.node_mut(idx).size = size;
+    }
+
+    fn find(&self, key: &K) -> Option<(ArenaIndex, usize)> {
+        let mut idx = self.root;
+        loop {
+            let node = self.node(idx);
+            match node.keys.binary_search(key) {
+                Ok(pos) => return Some((idx, pos)),
+                Err(pos) => idx = *node.children.get(pos)?,
+            }
+        }
+    }
+
+    // Greatest key lower than the given one, or equal if `or_equal`. The keys
+    // of the child after it are between it and the next key, so they are
+    // better candidates.
+    fn below(&self, key: &K, or_equal: bool) -> Option<(ArenaIndex, usize)> {
+        let mut cur = Some(self.root);
+        let mut best = None;
+        while let Some(idx) = cur {
+            let node = self.node(idx);
+            let pos = node
+                .keys
+                .partition_point(|k| k < key || (or_equal && k == key));
+            if pos > 0 {
+                best = Some((idx, pos - 1));
+            }
+            cur = node.children.get(pos).copied();
+        }
+        best
+    }
+
+    // Least key greater than the given one, or equal if `or_equal`.
+    fn above(&self, key: &K, or_equal: bool) -> Option<(ArenaIndex, usize)> {
+        let mut cur = Some(self.root);
+        let mut best = None;
+        while let Some(idx) = cur {
+            let node = self.node(idx);
+            let pos = node
+                .keys
+                .partition_point(|k| k < key || (!or_equal && k == key));
+            if pos < node.keys.len() {
+                best = Some((idx, pos));
+            }
+            cur = node.children.get(pos).copied();
+        }
+        best
+    }
+
+    // Split the full child at `pos` in two nodes of B - 1 keys, its median
+    // key going up into the parent, which is not full.
+    fn split_child(&mut self, parent: ArenaIndex, pos: usize) {
+        let child = self.node(parent).children[pos];
+        let node = self.node_mut(child);
+        let keys = node.keys.split_off(B);
+        let vals = node.vals.split_off(B);
+        let children = if node.is_leaf() {
+            Vec::new()
+        } else {
+            node.children.split_off(B)
+        };
+        let key = node.keys.pop().expect("the node is full");
+        let val = node.vals.pop().expect("the node is full");
+        let right = self.nodes.insert(BTreeNode {
+            keys,
+            vals,
+            children,
+            size: 0,
+        });
+        self.update_size(child);
+        self.update_size(right);
+        let parent = self.node_mut(parent);
+        parent.keys.insert(pos, key);
+        parent.vals.insert(pos, val);
+        parent.children.insert(pos + 1, right);
+    }
+
+    // Make sure the child at `pos` has more than B - 1 keys before going down
+    // into it, taking a key from a sibling through the parent, or merging it
+    // with a sibling. Gives the node to go down into.
+    fn make_room(&mut self, parent: ArenaIndex, pos: usize) -> ArenaIndex {
+        let node = self.node(parent);
+        let child = node.children[pos];
+        if self.node(child).keys.len() > Self::MIN_KEYS {
+            return child;
+        }
+        let left = pos.checked_sub(1).map(|pos| node.children[pos]);
+        let right = node.children.get(pos + 1).copied();
+        let spare = |idx: ArenaIndex| self.node(idx).keys.len() > Self::MIN_KEYS;
+        if left.is_some_and(spare) {
+            self.borrow_from_left(parent, pos);
+            child
+        } else if right.is_some_and(spare) {
+            self.borrow_from_right(parent, pos);
+            child
+        } else if right.is_some() {
+            self.merge(parent, pos);
+            child
+        } else {
+            self.merge(parent, pos - 1);
+            left.expect("a parent has at least two children")
+        }
+    }
+
+    // The last key of the left sibling goes up into the parent, and the key
+    // between them goes down to the front of the child.
+    fn borrow_from_left(&mut self, parent: ArenaIndex, pos: usize) {
+        let (left, child) = {
+            let node = self.node(parent);
+            (node.children[pos - 1], node.children[pos])
+        };
+        let node = self.node_mut(left);
+        let key = node.keys.pop().expect("the sibling has keys to spare");
+        let val = node.vals.pop().expect("the sibling has keys to spare");
+        let grandchild = node.children.pop();
+        let node = self.node_mut(parent);
+        let key = mem::replace(&mut node.keys[pos - 1], key);
+        let val = mem::replace(&mut node.vals[pos - 1], val);
+        let node = self.node_mut(child);
+        node.keys.insert(0, key);
+        node.vals.insert(0, val);
+        if let Some(grandchild) = grandchild {
+            node.children.insert(0, grandchild);
+        }
+        self.update_size(left);
+        self.update_size(child);
+    }
+
+    // Mirror of `borrow_from_left`.
+    fn borrow_from_right(&mut self, parent: ArenaIndex, pos: usize) {
+        let (child, right) = {
+            let node = self.node(parent);
+            (node.children[pos], node.children[pos + 1])
+        };
+        let node = self.node_mut(right);
+        let key = node.keys.remove(0);
+        let val = node.vals.remove(0);
+        let grandchild = (!node.is_leaf()).then(|| node.children.remove(0));
+        let node = self.node_mut(parent);
+        let key = mem::replace(&mut node.keys[pos], key);
+        let val = mem::replace(&mut node.vals[pos], val);
+        let node = self.node_mut(child);
+        node.keys.push(key);
+        node.vals.push(val);
+        node.children.extend(grandchild);
+        self.update_size(right);
+        self.update_size(child);
+    }
+
+    // Merge the children at `pos` and `pos + 1`, which have B - 1 keys, with
+    // the key between them, into a full node.
+    fn merge(&mut self, parent: ArenaIndex, pos: usize) {
+        let node = self.node_mut(parent);
+        let key = node.keys.remove(pos);
+        let val = node.vals.remove(pos);
+        let left = node.children[pos];
+        let right = node.children.remove(pos + 1);
+        let right = self.nodes.remove(right).expect("linked node");
+        let node = self.node_mut(left);
+        node.keys.push(key);
+        node.vals.push(val);
+        node.keys.extend(right.keys);
+        node.vals.extend(right.vals);
+        node.children.extend(right.children);
+        self.update_size(left);
+    }
+
+    // Remove a key of the subtree, whose root has more than B - 1 keys
+    // unless it is the root of the tree.
+    fn remove_from(&mut self, mut idx: ArenaIndex, key: &K) -> V {
+        loop {
+            self.node_mut(idx).size -= 1;
+            let node = self.node(idx);
+            let found = node.keys.binary_search(key);
+            if node.is_leaf() {
+                let pos = found.expect("the key is in the subtree");
+                let node = self.node_mut(idx);
+                node.keys.remove(pos);
+                return node.vals.remove(pos);
+            }
+            let pos = match found {
+                Ok(pos) => pos,
+                Err(pos) => {
+                    idx = self.make_room(idx, pos);
+                    continue;
+                }
+            };
+            // The key is in an internal node, it takes the entry before it,
+            // or after it, from a child which can spare a key. When neither
+            // can, both are merged around the key, which goes down with them.
+            let (left, right) = (node.children[pos], node.children[pos + 1]);
+            let entry = if self.node(left).keys.len() > Self::MIN_KEYS {
+                self.remove_last(left)
+            } else if self.node(right).keys.len() > Self::MIN_KEYS {
+                self.remove_first(right)
+            } else {
+                self.merge(idx, pos);
+                idx = left;
+                continue;
+            };
+            let node = self.node_mut(idx);
+            node.keys[pos] = entry.0;
+            return mem::replace(&mut node.vals[pos], entry.1);
+        }
+    }
+
+    fn remove_first(&mut self, mut idx: ArenaIndex) -> (K, V) {
+        loop {
+            let node = self.node_mut(idx);
+            node.size -= 1;
+            if node.is_leaf() {
+                return (node.keys.remove(0), node.vals.remove(0));
+            }
+            idx = self.make_room(idx, 0);
+        }
+    }
+
+    fn remove_last(&mut self, mut idx: ArenaIndex) -> (K, V) {
+        loop {
+            let node = self.node_mut(idx);
+            node.size -= 1;
+            if node.is_leaf() {
+                let key = node.keys.pop().expect("the node has keys to spare");
+                let val = node.vals.pop().expect("the node has keys to spare");
+                return (key, val);
+            }
+            let last = node.children.len() - 1;
+            idx = self.make_room(idx, last);
+        }
+    }
+}
+
+fn is_sorted<'a, K: Ord + 'a>(keys: impl Iterator<Item = &'a K> + Clone) -> bool {
+    keys.clone().zip(keys.skip(1)).all(|(a, b)| a < b)
+}
+
+impl<K: Ord, V, const B: usize> Default for BTree<K, V, B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord, V, const B: usize> OrderedMap for BTree<K, V, B> {
+    type Key = K;
+    type Value = V;
+    type Iter<'a>
+        = BTreeIter<'a, K, V, B>
+    where
+        Self: 'a;
+    type Range<'a>
+        = BTreeRange<'a, K, V, B>
+    where
+        Self: 'a;
+
+    fn insert(&mut self, key: K, val: V) -> Option<V> {
+        if let Some((idx, pos)) = self.find(&key) {
+            return Some(mem::replace(&mut self.node_mut(idx).vals[pos], val));
+        }
+        if self.node(self.root).keys.len() == Self::MAX_KEYS {
+            // The tree grows from the top, a new root taking the median key
+            // of the old one.
+            let root = self.nodes.insert(BTreeNode {
+                keys: Vec::new(),
+                vals: Vec::new(),
+                children: vec![self.root],
+                size: self.node(self.root).size,
+            });
+            self.root = root;
+            self.split_child(root, 0);
+        }
+        let mut idx = self.root;
+        loop {
+            let node = self.node_mut(idx);
+            node.size += 1;
+            let mut pos = node.keys.partition_point(|k| *k < key);
+            if node.is_leaf() {
+                node.keys.insert(pos, key);
+                node.vals.insert(pos, val);
+                return None;
+            }
+            let child = node.children[pos];
+            if self.node(child).keys.len() == Self::MAX_KEYS {
+                self.split_child(idx, pos);
+                if self.node(idx).keys[pos] < key {
+                    pos += 1;
+                }
+            }
+            idx = self.node(idx).children[pos];
+        }
+    }
+
+    fn get(&self, key: &K) -> Option<&V> {
+        self.find(key).map(|found| self.entry(found).1)
+    }
+
+    fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let (idx, pos) = self.find(key)?;
+        Some(&mut self.node_mut(idx).vals[pos])
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        // Going down changes the tree, which must not happen for nothing.
+        self.find(key)?;
+        let val = self.remove_from(self.root, key);
+        let root = self.node(self.root);
+        if root.keys.is_empty() && !root.is_leaf() {
+            // The tree shrinks from the top, when the children of the root
+            // are merged.
+            let child = root.children[0];
+            self.nodes.remove(self.root).expect("linked node");
+            self.root = child;
+        }
+        Some(val)
+    }
+
+    fn min(&self) -> Option<(&K, &V)> {
+        let mut idx = self.root;
+        while let Some(&child) = self.node(idx).children.first() {
+            idx = child;
+        }
+        let node = self.node(idx);
+        Some((node.keys.first()?, node.vals.first()?))
+    }
+
+    fn max(&self) -> Option<(&K, &V)> {
+        let mut idx = self.root;
+        while let Some(&child) = self.node(idx).children.last() {
+            idx = child;
+        }
+        let node = self.node(idx);
+        Some((node.keys.last()?, node.vals.last()?))
+    }
+
+    fn floor(&self, key: &K) -> Option<(&K, &V)> {
+        self.below(key, true).map(|found| self.entry(found))
+    }
+
+    fn ceiling(&self, key: &K) -> Option<(&K, &V)> {
+        self.above(key, true).map(|found| self.entry(found))
+    }
+
+    fn successor(&self, key: &K) -> Option<(&K, &V)> {
+        self.above(key, false).map(|found| self.entry(found))
+    }
+
+    fn predecessor(&self, key: &K) -> Option<(&K, &V)> {
+        self.below(key, false).map(|found| self.entry(found))
+    }
+
+    fn rank(&self, key: &K) -> usize {
+        let mut rank = 0;
+        let mut cur = Some(self.root);
+        while let Some(idx) = cur {
+            let node = self.node(idx);
+            let pos = node.keys.partition_point(|k| k < key);
+            let before = node.children.iter().take(pos);
+            rank += pos + before.map(|&child| self.node(child).size).sum::<usize>();
+            cur = node.children.get(pos).copied();
+            if node.keys.get(pos) == Some(key) {
+                return rank + cur.map_or(0, |child| self.node(child).size);
+            }
+        }
+        rank
+    }
+
+    fn select(&self, mut rank: usize) -> Option<(&K, &V)> {
+        if rank >= self.len() {
+            return None;
+        }
+        let mut idx = self.root;
+        'down: loop {
+            let node = self.node(idx);
+            if node.is_leaf() {
+                return Some(self.entry((idx, rank)));
+            }
+            for (pos, &child) in node.children.iter().enumerate() {
+                let size = self.node(child).size;
+                if rank < size {
+                    idx = child;
+                    continue 'down;
+                }
+                rank -= size;
+                if rank == 0 {
+                    return Some(self.entry((idx, pos)));
+                }
+                rank -= 1;
+            }
+            unreachable!("the rank is lower than the size of the subtree");
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.node(self.root).size
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        BTreeIter::seek(self, Bound::Unbounded)
+    }
+
+    fn range<R: RangeBounds<K>>(&self, range: R) -> Self::Range<'_> {
+        // Same as the binary search trees, the end is turned into the last
+        // key of the range found in the tree.
+        let last = match range.end_bound() {
+            Bound::Included(key) => self.below(key, true).map(Some),
+            Bound::Excluded(key) => self.below(key, false).map(Some),
+            Bound::Unbounded => Some(None),
+        };
+        let mut iter = BTreeIter::seek(self, range.start_bound());
+        let last = match last {
+            Some(last) => last.map(|found| self.entry(found).0),
+            None => {
+                iter.stack = LinkedStack::new();
+                None
+            }
+        };
+        BTreeRange { iter, last }
+    }
+}
+
+/// In-order iterator over the entries of a B-tree, with a stack holding, for
+/// each node on the path to the current one, the position of its next key.
+pub struct BTreeIter<'a, K, V, const B: usize> {
+    tree: &'a BTree<K, V, B>,
+    stack: LinkedStack<(ArenaIndex, usize)>,
+}
+
+impl<'a, K: Ord, V, const B: usize> BTreeIter<'a, K, V, B> {
+    // Start at the first key after the bound.
+    fn seek(tree: &'a BTree<K, V, B>, start: Bound<&K>) -> Self {
+        let mut stack = LinkedStack::new();
+        let mut cur = Some(tree.root);
+        while let Some(idx) = cur {
+            let node = tree.node(idx);
+            let pos = match start {
+                Bound::Included(key) => node.keys.partition_point(|k| k < key),
+                Bound::Excluded(key) => node.keys.partition_point(|k| k <= key),
+                Bound::Unbounded => 0,
+            };
+            stack.push((idx, pos)).expect("the stack is unbounded");
+            cur = node.children.get(pos).copied();
+        }
+        Self { tree, stack }
+    }
+}
+
+impl<'a, K: Ord, V, const B: usize> Iterator for BTreeIter<'a, K, V, B> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (idx, pos) = self.stack.pop()?;
+            let node = self.tree.node(idx);
+            if pos == node.keys.len() {
+                continue;
+            }
+            self.stack
+                .push((idx, pos + 1))
+                .expect("the stack is unbounded");
+            // The child after the key comes next, from its first key.
+            let mut cur = node.children.get(pos + 1).copied();
+            while let Some(child) = cur {
+                self.stack.push((child, 0)).expect("the stack is unbounded");
+                cur = self.tree.node(child).children.first().copied();
+            }
+            return Some((&node.keys[pos], &node.vals[pos]));
+        }
+    }
+}
+
+/// Entries of a B-tree whose keys are in a range, in order.
+pub struct BTreeRange<'a, K, V, const B: usize> {
+    iter: BTreeIter<'a, K, V, B>,
+    // Last key to give, None when the range is not bounded at the end.
+    last: Option<&'a K>,
+}
+
+impl<'a, K: Ord, V, const B: usize> Iterator for BTreeRange<'a, K, V, B> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (key, val) = self.iter.next()?;
+        if self.last.is_some_and(|last| key > last) {
+            self.iter.stack = LinkedStack::new();
+            return None;
+        }
+        Some((key, val))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn b_tree_grows_and_shrinks_from_the_root() {
+        let mut tree = BTree::<_, _, 2>::new();
+        assert_eq!(1, tree.height());
+        (0..1_000).for_each(|key| {
+            tree.insert(key, ());
+        });
+        // with at least 2 children per node below the root, and 1 key in it
+        assert!(tree.height() <= 10);
+        (0..1_000).for_each(|key| {
+            assert_eq!(Some(()), tree.remove(&key));
+        });
+        tree.assert_invariants();
+        assert_eq!(1, tree.height());
+        assert!(tree.is_empty());
+    }
+
+    #[test]
+    fn b_tree_write_pages() {
+        let mut tree = BTree::<u16, u8, 2>::new();
+        for key in 1..=4 {
+            tree.insert(key, key as u8 * 10);
+        }
+        // the root was full with 3 keys, 2 went up when 4 came in
+        let pages = tree.write_pages::<16>().unwrap();
+        assert_eq!(
+            vec![
+                [0, 1, 0, 2, 0, 20, 1, 0, 0, 0, 2, 0, 0, 0, 0, 0],
+                [1, 1, 0, 1, 0, 10, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                [1, 2, 0, 3, 0, 30, 4, 0, 40, 0, 0, 0, 0, 0, 0, 0],
+            ],
+            pages
+        );
+        assert_eq!(
+            Err(TreeErr::PageOverflow),
+            tree.write_pages::<8>().map(|_| ())
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "a node holds 0 keys")]
+    fn b_tree_assert_invariants_catches_underfull_node() {
+        let mut tree = BTree::<_, _, 2>::new();
+        (1..=4).for_each(|key| {
+            tree.insert(key, ());
+        });
+        let leaf = tree.node(tree.root).children[0];
+        let node = tree.node_mut(leaf);
+        node.keys.clear();
+        node.vals.clear();
+        tree.assert_invariants();
+    }
+}
@@ -0,0 +1,205 @@
+#![allow(dead_code)]
+
+use std::ops::RangeBounds;
+
+use super::search_tree::{SearchIter, SearchRange, SearchTree};
+use super::OrderedMap;
+
+/// Binary search tree, with no balancing at all: the keys of the left subtree
+/// of a node are lower than its key, and the keys of its right subtree
+/// greater. Operations are O(h), which is O(log n) on average when keys come
+/// in random order, but O(n) when they come sorted, the tree then being a
+/// list.
+pub struct BinarySearchTree<K, V> {
+    tree: SearchTree<K, V, ()>,
+}
+
+impl<K: Ord, V> BinarySearchTree<K, V> {
+    pub fn new() -> Self {
+        Self {
+            tree: SearchTree::new(),
+        }
+    }
+
+    /// Number of levels, 0 for an empty tree.
+    pub fn height(&self) -> usize {
+        self.tree.height()
+    }
+}
+
+impl<K: Ord, V> Default for BinarySearchTree<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord, V> OrderedMap for BinarySearchTree<K, V> {
+    type Key = K;
+    type Value = V;
+    type Iter<'a>
+        = SearchIter<'a, K, V, ()>
+    where
+        Self: 'a;
+    type Range<'a>
+        = SearchRange<'a, K, V, ()>
+    where
+        Self: 'a;
+
+    fn insert(&mut self, key: K, val: V) -> Option<V> {
+        let (old, path) = self.tree.insert_unbalanced(key, val, ());
+        path.into_iter()
+            .for_each(|idx| self.tree.node_mut(idx).size += 1);
+        old
+    }
+
+    fn get(&self, key: &K) -> Option<&V> {
+        self.tree.get(key)
+    }
+
+    fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        self.tree.get_mut(key)
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        let ((_, val), path) = self.tree.remove_unbalanced(key)?;
+        path.into_iter()
+            .for_each(|idx| self.tree.node_mut(idx).size -= 1);
+        Some(val)
+    }
+
+    fn min(&self) -> Option<(&K, &V)> {
+        self.tree.min()
+    }
+
+    fn max(&self) -> Option<(&K, &V)> {
+        self.tree.max()
+    }
+
+    fn floor(&self, key: &K) -> Option<(&K, &V)> {
+        self.tree.floor(key)
+    }
+
+    fn ceiling(&self, key: &K) -> Option<(&K, &V)> {
+        self.tree.ceiling(key)
+    }
+
+    fn successor(&self, key: &K) -> Option<(&K, &V)> {
+        self.tree.successor(key)
+    }
+
+    fn predecessor(&self, key: &K) -> Option<(&K, &V)> {
+        self.tree.predecessor(key)
+    }
+
+    fn rank(&self, key: &K) -> usize {
+        self.tree.rank(key)
+    }
+
+    fn select(&self, rank: usize) -> Option<(&K, &V)> {
+        self.tree.select(rank)
+    }
+
+    fn len(&self) -> usize {
+        self.tree.len()
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        self.tree.iter()
+    }
+
+    fn range<R: RangeBounds<K>>(&self, range: R) -> Self::Range<'_> {
+        self.tree.range(range)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn sample() -> BinarySearchTree<i32, char> {
+        // 50 at the root, 30 and 70 under it, and so on
+        let mut tree = BinarySearchTree::new();
+        for (key, val) in [
+            (50, 'a'),
+            (30, 'b'),
+            (70, 'c'),
+            (20, 'd'),
+            (40, 'e'),
+            (60, 'f'),
+        ] {
+            assert_eq!(None, tree.insert(key, val));
+        }
+        tree
+    }
+
+    fn keys(tree: &BinarySearchTree<i32, char>) -> Vec<i32> {
+        tree.iter().map(|(key, _)| *key).collect()
+    }
+
+    #[test]
+    fn binary_search_tree_remove_leaf() {
+        let mut tree = sample();
+        assert_eq!(Some('d'), tree.remove(&20));
+        assert_eq!(vec![30, 40, 50, 60, 70], keys(&tree));
+        assert_eq!(3, tree.height());
+        assert_eq!(None, tree.remove(&20));
+    }
+
+    #[test]
+    fn binary_search_tree_remove_one_child() {
+        let mut tree = sample();
+        // 70 only has 60 on its left, which takes its place
+        assert_eq!(Some('c'), tree.remove(&70));
+        assert_eq!(vec![20, 30, 40, 50, 60], keys(&tree));
+        assert_eq!(Some((&60, &'f')), tree.max());
+        assert_eq!(4, tree.rank(&60));
+    }
+
+    #[test]
+    fn binary_search_tree_remove_two_children() {
+        let mut tree = sample();
+        // the root takes the entry of 60, its successor
+        assert_eq!(Some('a'), tree.remove(&50));
+        assert_eq!(vec![20, 30, 40, 60, 70], keys(&tree));
+        assert_eq!(Some((&60, &'f')), tree.select(3));
+        // 30 takes the entry of 40, a leaf right under it
+        assert_eq!(Some('b'), tree.remove(&30));
+        assert_eq!(vec![20, 40, 60, 70], keys(&tree));
+        assert_eq!(3, tree.height());
+        for key in [20, 40, 60, 70] {
+            assert!(tree.remove(&key).is_some());
+        }
+        assert!(tree.is_empty());
+        assert_eq!(0, tree.height());
+    }
+
+    #[test]
+    fn binary_search_tree_sorted_keys_make_a_list() {
+        let mut tree = BinarySearchTree::new();
+        (0..100).for_each(|key| {
+            tree.insert(key, ());
+        });
+        assert_eq!(100, tree.height());
+        assert_eq!(Some((&42, &())), tree.select(42));
+    }
+
+    #[test]
+    fn binary_search_tree_height_of_long_list() {
+        const LEN: usize = 200_000;
+        // Inserting sorted keys one by one takes quadratic time, so the list
+        // is linked directly, each key being the parent of the previous ones.
+        let mut tree = BinarySearchTree::new();
+        for key in 0..LEN {
+            let idx = tree.tree.leaf(key, (), ());
+            let below = tree.tree.root.replace(idx);
+            let node = tree.tree.node_mut(idx);
+            node.left = below;
+            node.size = key + 1;
+        }
+        assert_eq!(LEN, tree.height());
+        assert_eq!(Some((&42, &())), tree.select(42));
+        // The iterators stack the whole list, and are dropped with it.
+        assert_eq!(Some((&0, &())), tree.iter().next());
+        assert_eq!(Some((&1, &())), tree.range(1..).next());
+    }
+}
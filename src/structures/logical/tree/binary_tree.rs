@@ -1,349 +1,1435 @@
 #![allow(dead_code)]
 
 use std::{
-    cell::{Cell, RefCell},
-    collections::VecDeque,
-    fmt::Display,
-    ops::BitXorAssign,
-    rc::{Rc, Weak},
-    result,
+    collections::{HashMap, HashSet, VecDeque},
+    hash::Hash,
 };
 
-use crate::algo::recursion;
+use super::{ArrayBinaryTree, TreeErr};
+use crate::structures::logical::queue::{ArrayQueue, Queue};
+use crate::structures::logical::stack::{LinkedStack, Stack};
+use crate::structures::physical::arena::{Arena, ArenaIndex};
 
-// TODO: Make a binary tree based on an array ?
+/// Handle to a node of a [`LinkedBinaryTree`], given when the node is
+/// inserted. It becomes stale once the node has been removed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NodeHandle(ArenaIndex);
 
-// Maybe over engineered, Box<T> should be Ok for a Binary Tree
-type LinkedBinaryTreeNodeRef<T> = Rc<RefCell<LinkedBinaryTreeNode<T>>>;
+struct LinkedBinaryTreeNode<T> {
+    val: T,
+    parent: Option<ArenaIndex>,
+    left: Option<ArenaIndex>,
+    right: Option<ArenaIndex>,
+}
+
+impl<T> LinkedBinaryTreeNode<T> {
+    fn new(val: T, parent: Option<ArenaIndex>) -> Self {
+        Self {
+            val,
+            parent,
+            left: None,
+            right: None,
+        }
+    }
+}
 
-pub struct LinkedBinaryTree<T: Display> {
-    root: Option<LinkedBinaryTreeNodeRef<T>>,
+/// Binary tree whose nodes are stored in an [`Arena`] and linked to each
+/// other by index, the parent included. Nodes are reached through the
+/// [`NodeHandle`] given when they are inserted, a handle on a removed node
+/// being rejected with [`TreeErr::StaleHandle`].
+pub struct LinkedBinaryTree<T> {
+    nodes: Arena<LinkedBinaryTreeNode<T>>,
+    root: Option<ArenaIndex>,
 }
 
-impl<T: Display> LinkedBinaryTree<T> {
+impl<T> LinkedBinaryTree<T> {
     pub fn new() -> Self {
-        Self { root: None }
+        Self {
+            nodes: Arena::new(),
+            root: None,
+        }
     }
 
-    fn count(&self) -> usize {
-        if let Some(root) = &self.root {
-            root.borrow().count()
-        } else {
-            0
+    /// Build a tree from its level order, where `None` stands for a missing
+    /// node, and the children of a missing node are not listed. For instance
+    /// `[1, 2, 3, None, 4]` is a root 1 with the children 2 and 3, 2 having
+    /// only a right child 4. Trailing `None` are allowed, but a value with no
+    /// node left to hang it under is rejected.
+    pub fn from_level_order(vals: impl IntoIterator<Item = Option<T>>) -> Result<Self, TreeErr> {
+        let mut tree = Self::new();
+        let mut vals = vals.into_iter();
+        let Some(root) = vals.next().flatten() else {
+            return match vals.any(|val| val.is_some()) {
+                true => Err(TreeErr::OrphanValue),
+                false => Ok(tree),
+            };
+        };
+        // Nodes whose children are still to be read, in level order.
+        let mut parents = VecDeque::from([tree.insert_root(root)?]);
+        while let Some(parent) = parents.pop_front() {
+            if let Some(val) = vals.next().flatten() {
+                parents.push_back(tree.insert_left(parent, val)?);
+            }
+            if let Some(val) = vals.next().flatten() {
+                parents.push_back(tree.insert_right(parent, val)?);
+            }
+        }
+        match vals.any(|val| val.is_some()) {
+            true => Err(TreeErr::OrphanValue),
+            false => Ok(tree),
         }
     }
 
-    fn is_full(&self) -> bool {
-        if let Some(root) = &self.root {
-            root.borrow().is_full()
-        } else {
-            false
+    pub fn root(&self) -> Option<NodeHandle> {
+        self.root.map(NodeHandle)
+    }
+
+    /// Insert the root of an empty tree.
+    pub fn insert_root(&mut self, val: T) -> Result<NodeHandle, TreeErr> {
+        if self.root.is_some() {
+            return Err(TreeErr::Occupied);
         }
+        let idx = self.nodes.insert(LinkedBinaryTreeNode::new(val, None));
+        self.root = Some(idx);
+        Ok(NodeHandle(idx))
     }
 
-    fn is_perfect(&self) -> bool {
-        todo!()
+    /// Insert a value as the left child of a node which has none.
+    pub fn insert_left(&mut self, parent: NodeHandle, val: T) -> Result<NodeHandle, TreeErr> {
+        self.insert_child(parent, val, true)
     }
 
-    fn is_complete(&self) -> bool {
-        if let Some(root) = &self.root {
-            root.borrow().is_complete()
-        } else {
-            false
+    /// Insert a value as the right child of a node which has none.
+    pub fn insert_right(&mut self, parent: NodeHandle, val: T) -> Result<NodeHandle, TreeErr> {
+        self.insert_child(parent, val, false)
+    }
+
+    /// Detach the node and all its descendants, and give them back as a tree
+    /// of their own. Handles on the removed nodes become stale.
+    pub fn remove_subtree(&mut self, node: NodeHandle) -> Result<Self, TreeErr> {
+        let idx = self.check(node)?;
+        match self.node(idx).parent {
+            Some(parent) => {
+                let parent = self.node_mut(parent);
+                if parent.left == Some(idx) {
+                    parent.left = None;
+                } else {
+                    parent.right = None;
+                }
+            }
+            None => self.root = None,
         }
+        let mut subtree = Self::new();
+        subtree.root = Some(self.move_subtree(idx, &mut subtree));
+        Ok(subtree)
+    }
+
+    /// Put a new value in a node, giving the previous one back.
+    pub fn replace(&mut self, node: NodeHandle, val: T) -> Result<T, TreeErr> {
+        let idx = self.check(node)?;
+        Ok(std::mem::replace(&mut self.node_mut(idx).val, val))
+    }
+
+    pub fn get(&self, node: NodeHandle) -> Option<&T> {
+        self.nodes.get(node.0).map(|node| &node.val)
+    }
+
+    pub fn get_mut(&mut self, node: NodeHandle) -> Option<&mut T> {
+        self.nodes.get_mut(node.0).map(|node| &mut node.val)
+    }
+
+    /// Parent of the node, `None` for the root or a stale handle.
+    pub fn parent(&self, node: NodeHandle) -> Option<NodeHandle> {
+        self.nodes.get(node.0)?.parent.map(NodeHandle)
+    }
+
+    pub fn left(&self, node: NodeHandle) -> Option<NodeHandle> {
+        self.nodes.get(node.0)?.left.map(NodeHandle)
+    }
+
+    pub fn right(&self, node: NodeHandle) -> Option<NodeHandle> {
+        self.nodes.get(node.0)?.right.map(NodeHandle)
+    }
+
+    /// Number of nodes.
+    pub fn count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// Every node has either no child or two. Like complete and perfect, it
+    /// is false for an empty tree.
+    pub fn is_full(&self) -> bool {
+        // Every node of the arena is in the tree, none has a lone child.
+        self.root.is_some()
+            && self
+                .nodes
+                .iter()
+                .all(|(_, node)| node.left.is_some() == node.right.is_some())
     }
 
-    fn is_balanced(&self) -> bool {
-        todo!()
+    /// Every level is filled, the leaves all being at the same depth.
+    pub fn is_perfect(&self) -> bool {
+        // A tree of h levels holds at most 2^h - 1 nodes, it is perfect when
+        // it holds exactly that many.
+        let height = self.height();
+        height > 0 && height < usize::BITS as usize && self.count() == (1 << height) - 1
     }
 
-    // TODO try to implement in iteration with a stack variable.
-    fn traverse_pre_order(&self, result_recip: &mut String) {
-        if let Some(root) = &self.root {
-            root.borrow().traverse_pre_order(result_recip);
+    /// Every level but the last one is filled, and the nodes of the last one
+    /// are on the left: going through the tree in level order, no node comes
+    /// after a missing child.
+    pub fn is_complete(&self) -> bool {
+        if self.root.is_none() {
+            return false;
         }
+        let mut queue = VecDeque::from([self.root]);
+        let mut gap = false;
+        while let Some(slot) = queue.pop_front() {
+            match slot {
+                Some(_) if gap => return false,
+                Some(idx) => {
+                    let node = self.node(idx);
+                    queue.push_back(node.left);
+                    queue.push_back(node.right);
+                }
+                None => gap = true,
+            }
+        }
+        true
     }
 
-    // TODO try to implement in iteration with a stack variable.
-    fn traverse_in_order(&self, result_recip: &mut String) {
-        if let Some(root) = &self.root {
-            root.borrow().traverse_in_order(result_recip);
+    /// The heights of the two subtrees of every node differ by one at most.
+    /// An empty tree is balanced.
+    pub fn is_balanced(&self) -> bool {
+        // Height of every subtree, or None as soon as a node is not balanced.
+        self.fold_up(Some(0usize), |left, right| {
+            let (left, right) = (left?, right?);
+            (left.abs_diff(right) <= 1).then_some(1 + left.max(right))
+        })
+        .is_some()
+    }
+
+    /// Number of levels, 0 for an empty tree.
+    pub fn height(&self) -> usize {
+        self.fold_up(0, |left, right| 1 + left.max(right))
+    }
+
+    /// Number of edges between the root and the node.
+    pub fn depth_of(&self, node: NodeHandle) -> Result<usize, TreeErr> {
+        let mut idx = self.check(node)?;
+        let mut depth = 0;
+        while let Some(parent) = self.node(idx).parent {
+            idx = parent;
+            depth += 1;
         }
+        Ok(depth)
+    }
+
+    /// Number of nodes without children.
+    pub fn leaf_count(&self) -> usize {
+        self.nodes
+            .iter()
+            .filter(|(_, node)| node.left.is_none() && node.right.is_none())
+            .count()
+    }
+
+    /// Number of nodes with at least a child.
+    pub fn internal_count(&self) -> usize {
+        self.count() - self.leaf_count()
     }
 
-    // TODO try to implement in iteration with a stack variable.
-    fn traverse_post_order(&self, result_recip: &mut String) {
-        if let Some(root) = &self.root {
-            root.borrow().traverse_post_order(result_recip);
+    /// Number of nodes of the largest level.
+    pub fn width(&self) -> usize {
+        let mut level = Vec::from_iter(self.root);
+        let mut width = 0;
+        while !level.is_empty() {
+            width = width.max(level.len());
+            level = level
+                .into_iter()
+                .flat_map(|idx| {
+                    let node = self.node(idx);
+                    node.left.into_iter().chain(node.right)
+                })
+                .collect();
         }
+        width
     }
 
-    fn traverse_level_order(&self, result_recip: &mut String) {
-        // TODO: implement method len on the tree, to then use ArrayQueue.
-        let mut queue = VecDeque::with_capacity(10);
-        if let Some(root) = &self.root {
-            result_recip.push_str(format!("{} ", root.borrow().val).as_str());
-            queue.push_back(root.clone());
-            while !queue.is_empty() {
-                let node_ref = queue.pop_front().unwrap();
-                if let Some(ref left) = node_ref.clone().borrow().left {
-                    result_recip.push_str(format!("{} ", left.borrow().val).as_str());
-                    queue.push_back(left.clone());
-                }
-                if let Some(ref right) = node_ref.clone().borrow().right {
-                    result_recip.push_str(format!("{} ", right.borrow().val).as_str());
-                    queue.push_back(right.clone());
-                }
-            }
+    /// Number of edges of the longest path between two nodes, which may not
+    /// go through the root.
+    pub fn diameter(&self) -> usize {
+        // The longest path of a subtree either goes through its root, joining
+        // the deepest leaves of both sides, or stays in one of the sides.
+        let (_, diameter) = self.fold_up((0, 0), |(left_height, left), (right_height, right)| {
+            let through = left_height + right_height;
+            (
+                1 + left_height.max(right_height),
+                through.max(left).max(right),
+            )
+        });
+        diameter
+    }
+
+    /// Values in pre-order: a node, then its left subtree, then its right one.
+    pub fn pre_order(&self) -> PreOrderIter<'_, T> {
+        let mut stack = LinkedStack::new();
+        if let Some(root) = self.root {
+            push(&mut stack, root);
         }
+        PreOrderIter { tree: self, stack }
     }
-}
 
-// TODO: try to implement Iter using Stack or DFS cases and Queue for BFS
+    /// Values in in-order: the left subtree of a node, the node, then its
+    /// right subtree.
+    pub fn in_order(&self) -> InOrderIter<'_, T> {
+        InOrderIter {
+            tree: self,
+            stack: LinkedStack::new(),
+            current: self.root,
+        }
+    }
 
-struct LinkedBinaryTreeNode<T: Display> {
-    val: T,
-    left: Option<LinkedBinaryTreeNodeRef<T>>,
-    right: Option<LinkedBinaryTreeNodeRef<T>>,
-}
+    /// Values in post-order: both subtrees of a node, then the node.
+    pub fn post_order(&self) -> PostOrderIter<'_, T> {
+        let mut stack = LinkedStack::new();
+        if let Some(root) = self.root {
+            push(&mut stack, (root, false));
+        }
+        PostOrderIter { tree: self, stack }
+    }
 
-impl<T: Display> LinkedBinaryTreeNode<T> {
-    pub fn new(val: T) -> Self {
-        Self {
-            val,
-            left: None,
-            right: None,
+    /// Values level by level, from the left to the right.
+    pub fn level_order(&self) -> LevelOrderIter<'_, T> {
+        LevelOrderIter {
+            tree: self,
+            queue: self.node_queue(),
         }
     }
 
-    pub fn count(&self) -> usize {
-        let left_count = if let Some(left) = &self.left {
-            left.borrow().count()
-        } else {
-            0
+    /// The values of each level, from the root down.
+    pub fn levels(&self) -> LevelsIter<'_, T> {
+        LevelsIter {
+            tree: self,
+            queue: self.node_queue(),
+        }
+    }
+
+    /// Visit the values in in-order, with Morris' traversal, which needs no
+    /// stack: before going down the left subtree of a node, the right link of
+    /// its predecessor, which is empty, is pointed to the node, to climb back
+    /// once the left subtree is done. The link is reset at that point, so the
+    /// tree is back to its shape at the end, even if the visit panics. It
+    /// takes `&mut self` as the links change during the traversal.
+    pub fn morris_in_order(&mut self, mut visit: impl FnMut(&T)) {
+        let mut walk = MorrisWalk {
+            current: self.root,
+            tree: self,
         };
+        while let Some(idx) = walk.next_node() {
+            visit(&walk.tree.node(idx).val);
+        }
+    }
+
+    // Queue large enough for every node, so that it never has to grow.
+    fn node_queue(&self) -> ArrayQueue<ArenaIndex> {
+        let mut queue =
+            ArrayQueue::with_capacity(self.count().max(1)).expect("the capacity is not zero");
+        if let Some(root) = self.root {
+            enqueue(&mut queue, root);
+        }
+        queue
+    }
+
+    fn node(&self, idx: ArenaIndex) -> &LinkedBinaryTreeNode<T> {
+        self.nodes.get(idx).expect("broken link in the tree")
+    }
+
+    fn node_mut(&mut self, idx: ArenaIndex) -> &mut LinkedBinaryTreeNode<T> {
+        self.nodes.get_mut(idx).expect("broken link in the tree")
+    }
+
+    fn check(&self, node: NodeHandle) -> Result<ArenaIndex, TreeErr> {
+        match self.nodes.contains(node.0) {
+            true => Ok(node.0),
+            false => Err(TreeErr::StaleHandle),
+        }
+    }
 
-        let right_count = if let Some(right) = &self.right {
-            right.borrow().count()
+    fn insert_child(
+        &mut self,
+        parent: NodeHandle,
+        val: T,
+        left: bool,
+    ) -> Result<NodeHandle, TreeErr> {
+        let parent = self.check(parent)?;
+        let node = self.node(parent);
+        if (if left { node.left } else { node.right }).is_some() {
+            return Err(TreeErr::Occupied);
+        }
+        let idx = self
+            .nodes
+            .insert(LinkedBinaryTreeNode::new(val, Some(parent)));
+        let node = self.node_mut(parent);
+        if left {
+            node.left = Some(idx);
         } else {
-            0
-        };
+            node.right = Some(idx);
+        }
+        Ok(NodeHandle(idx))
+    }
+
+    // Link a node under its parent, on the given side, or make it the root.
+    fn attach(&mut self, idx: ArenaIndex, parent: Option<(ArenaIndex, bool)>) {
+        match parent {
+            Some((parent, true)) => self.node_mut(parent).left = Some(idx),
+            Some((parent, false)) => self.node_mut(parent).right = Some(idx),
+            None => self.root = Some(idx),
+        }
+    }
+
+    // Move the subtree rooted at idx into another tree, and give its new
+    // index. The nodes still to move are kept on a stack, along with their
+    // new parent, rather than recursing, so a degenerate tree doesn't blow
+    // the call stack, as for every walk below.
+    fn move_subtree(&mut self, idx: ArenaIndex, into: &mut Self) -> ArenaIndex {
+        let mut moved_root = None;
+        let mut stack = vec![(idx, None)];
+        while let Some((idx, parent)) = stack.pop() {
+            let node = self.nodes.remove(idx).expect("broken link in the tree");
+            let moved = into.nodes.insert(LinkedBinaryTreeNode::new(
+                node.val,
+                parent.map(|(parent, _)| parent),
+            ));
+            match parent {
+                Some(_) => into.attach(moved, parent),
+                None => moved_root = Some(moved),
+            }
+            stack.extend(node.left.map(|left| (left, Some((moved, true)))));
+            stack.extend(node.right.map(|right| (right, Some((moved, false)))));
+        }
+        moved_root.expect("the subtree has a root")
+    }
 
-        left_count + right_count + 1
+    // Combine the results of the subtrees from the leaves up: every node gets
+    // the results of its two children, `empty` standing for a missing one,
+    // and the result of the root is given, `empty` for an empty tree.
+    fn fold_up<R: Copy>(&self, empty: R, combine: impl Fn(R, R) -> R) -> R {
+        // A node is popped before its children, so the reverse order has
+        // every node after its children.
+        let mut order = Vec::with_capacity(self.count());
+        let mut stack = Vec::from_iter(self.root);
+        while let Some(idx) = stack.pop() {
+            order.push(idx);
+            let node = self.node(idx);
+            stack.extend(node.left.into_iter().chain(node.right));
+        }
+        // Results of the subtrees, by slot of their root.
+        let mut results = vec![empty; self.nodes.cap()];
+        let result =
+            |results: &[R], idx: Option<ArenaIndex>| idx.map_or(empty, |idx| results[idx.slot()]);
+        for idx in order.into_iter().rev() {
+            let node = self.node(idx);
+            results[idx.slot()] =
+                combine(result(&results, node.left), result(&results, node.right));
+        }
+        result(&results, self.root)
     }
+}
 
-    pub fn set_left(&mut self, val: T) {
-        self.left = Some(Rc::new(RefCell::new(LinkedBinaryTreeNode::new(val))));
+/// Rebuilding a tree from its traversals. Values are told apart by equality,
+/// so they must all be different, otherwise the tree can't be found back.
+impl<T: Clone + Eq + Hash> LinkedBinaryTree<T> {
+    /// Rebuild the tree whose pre-order and in-order are given. The first
+    /// value of the pre-order is the root, which splits the in-order into the
+    /// left and the right subtrees, and so on for every subtree.
+    pub fn from_pre_in_order(pre_order: &[T], in_order: &[T]) -> Result<Self, TreeErr> {
+        let positions = positions(in_order, pre_order)?;
+        let mut tree = Self::new();
+        tree.build_from_in_order(&mut pre_order.iter(), &positions, in_order.len(), false)?;
+        Ok(tree)
     }
 
-    pub fn set_right(&mut self, val: T) {
-        self.right = Some(Rc::new(RefCell::new(LinkedBinaryTreeNode::new(val))));
+    /// Rebuild the tree whose post-order and in-order are given, like
+    /// [`from_pre_in_order`](Self::from_pre_in_order) going through the
+    /// post-order backward, which gives a node before its right subtree.
+    pub fn from_post_in_order(post_order: &[T], in_order: &[T]) -> Result<Self, TreeErr> {
+        let positions = positions(in_order, post_order)?;
+        let mut tree = Self::new();
+        let mut next = post_order.iter().rev();
+        tree.build_from_in_order(&mut next, &positions, in_order.len(), true)?;
+        Ok(tree)
     }
 
-    pub fn is_full(&self) -> bool {
-        match (&self.left, &self.right) {
-            (Some(left), Some(right)) => left.borrow().is_full() && right.borrow().is_full(),
-            (None, None) => true,
-            _ => false,
+    /// Rebuild the full tree whose pre-order and post-order are given. Without
+    /// the in-order, a lone child could be on either side, so only full trees
+    /// can be rebuilt, sequences of any other tree being rejected. The value
+    /// following a node in the pre-order is its left child, which ends the
+    /// left subtree in the post-order.
+    pub fn from_pre_post_order(pre_order: &[T], post_order: &[T]) -> Result<Self, TreeErr> {
+        let post_positions = positions(post_order, pre_order)?;
+        let mut tree = Self::new();
+        if !pre_order.is_empty() {
+            tree.build_from_pre_post(pre_order, post_order, &post_positions)?;
         }
+        Ok(tree)
     }
 
-    pub fn is_complete(&self) -> bool {
-        match (&self.left, &self.right) {
-            (Some(left), Some(right)) => {
-                left.borrow().is_complete() && right.borrow().is_complete()
+    // Build the tree made of the in-order values in `0..len`, taking the root
+    // of every subtree from `next`. The subtrees still to build are kept on a
+    // stack, along with the parent to link them to, rather than recursing,
+    // so that a degenerate tree doesn't blow the call stack. Going backward,
+    // the right subtree comes first.
+    fn build_from_in_order<'a>(
+        &mut self,
+        next: &mut impl Iterator<Item = &'a T>,
+        positions: &HashMap<&T, usize>,
+        len: usize,
+        backward: bool,
+    ) -> Result<(), TreeErr>
+    where
+        T: 'a,
+    {
+        let mut stack = vec![(0..len, None)];
+        while let Some((range, parent)) = stack.pop() {
+            if range.is_empty() {
+                continue;
+            }
+            let val = next.next().ok_or(TreeErr::InconsistentTraversals)?;
+            let at = positions[val];
+            if !range.contains(&at) {
+                return Err(TreeErr::InconsistentTraversals);
+            }
+            let idx = self.nodes.insert(LinkedBinaryTreeNode::new(
+                val.clone(),
+                parent.map(|(parent, _)| parent),
+            ));
+            self.attach(idx, parent);
+            // The subtree pushed last is built first.
+            let left = (range.start..at, Some((idx, true)));
+            let right = (at + 1..range.end, Some((idx, false)));
+            if backward {
+                stack.extend([left, right]);
+            } else {
+                stack.extend([right, left]);
             }
-            (None, Some(_)) => false,
-            _ => true,
         }
+        Ok(())
     }
 
-    pub fn traverse_pre_order(&self, result_recip: &mut String) {
-        result_recip.push_str(format!("{} ", self.val).as_str());
-        if let Some(left) = &self.left {
-            left.borrow().traverse_pre_order(result_recip);
+    // Build the tree whose pre-order and post-order are given, each subtree
+    // being the `size` values from `pre` in the pre-order, and from `post` in
+    // the post-order. Checking that both agree on the root of every subtree is
+    // enough for the rebuilt tree to have these traversals, as every position
+    // of both is then used once. As above, the subtrees still to build are
+    // kept on a stack.
+    fn build_from_pre_post(
+        &mut self,
+        pre_order: &[T],
+        post_order: &[T],
+        post_positions: &HashMap<&T, usize>,
+    ) -> Result<(), TreeErr> {
+        let mut stack = vec![((0, 0, pre_order.len()), None)];
+        while let Some(((pre, post, size), parent)) = stack.pop() {
+            let val = &pre_order[pre];
+            if *val != post_order[post + size - 1] {
+                return Err(TreeErr::InconsistentTraversals);
+            }
+            let idx = self.nodes.insert(LinkedBinaryTreeNode::new(
+                val.clone(),
+                parent.map(|(parent, _)| parent),
+            ));
+            self.attach(idx, parent);
+            if size == 1 {
+                continue;
+            }
+            // The left child follows the node in the pre-order, and ends the
+            // left subtree in the post-order. The right subtree must not be
+            // empty.
+            let left_end = post_positions[&pre_order[pre + 1]];
+            if left_end < post || left_end + 2 >= post + size {
+                return Err(TreeErr::InconsistentTraversals);
+            }
+            let left_size = left_end - post + 1;
+            let left = (pre + 1, post, left_size);
+            let right = (pre + 1 + left_size, post + left_size, size - 1 - left_size);
+            stack.extend([(left, Some((idx, true))), (right, Some((idx, false)))]);
+        }
+        Ok(())
+    }
+}
+
+// Position of each value of `reference`, which must hold the same values as
+// `other`, each one once.
+fn positions<'a, T: Eq + Hash>(
+    reference: &'a [T],
+    other: &[T],
+) -> Result<HashMap<&'a T, usize>, TreeErr> {
+    let mut positions = HashMap::with_capacity(reference.len());
+    for (at, val) in reference.iter().enumerate() {
+        if positions.insert(val, at).is_some() {
+            return Err(TreeErr::DuplicateValue);
+        }
+    }
+    if other.len() != reference.len() {
+        return Err(TreeErr::InconsistentTraversals);
+    }
+    let mut seen = HashSet::with_capacity(other.len());
+    for val in other {
+        if !seen.insert(val) {
+            return Err(TreeErr::DuplicateValue);
+        }
+        if !positions.contains_key(val) {
+            return Err(TreeErr::InconsistentTraversals);
+        }
+    }
+    Ok(positions)
+}
+
+/// Move the nodes to the positions they have in an array, see
+/// [`ArrayBinaryTree`]. Fails when the tree is higher than
+/// [`ArrayBinaryTree::MAX_HEIGHT`], as the array grows twice longer with every
+/// level, so that a degenerate tree of a few dozen nodes would take more
+/// memory than there is.
+impl<T> TryFrom<LinkedBinaryTree<T>> for ArrayBinaryTree<T> {
+    type Error = TreeErr;
+
+    fn try_from(mut tree: LinkedBinaryTree<T>) -> Result<Self, Self::Error> {
+        // Checked up front rather than by the insertions, which would only
+        // fail once the upper levels have been moved.
+        if tree.height() > Self::MAX_HEIGHT {
+            return Err(TreeErr::TooDeep);
+        }
+        let mut array = ArrayBinaryTree::new();
+        let Some(root) = tree.root else {
+            return Ok(array);
+        };
+        let mut queue = VecDeque::from([(root, None)]);
+        while let Some((idx, parent)) = queue.pop_front() {
+            let node = tree.nodes.remove(idx).expect("broken link in the tree");
+            let pos = match parent {
+                None => array.insert_root(node.val)?,
+                Some((parent, true)) => array.insert_left(parent, node.val)?,
+                Some((parent, false)) => array.insert_right(parent, node.val)?,
+            };
+            queue.extend(node.left.map(|left| (left, Some((pos, true)))));
+            queue.extend(node.right.map(|right| (right, Some((pos, false)))));
         }
-        if let Some(right) = &self.right {
-            right.borrow().traverse_pre_order(result_recip);
+        Ok(array)
+    }
+}
+
+impl<T> Default for LinkedBinaryTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// The stacks are unbounded and the queues hold every node, so they can't
+// reject an item.
+fn push<V>(stack: &mut LinkedStack<V>, val: V) {
+    stack.push(val).expect("the stack is unbounded");
+}
+
+fn enqueue(queue: &mut ArrayQueue<ArenaIndex>, idx: ArenaIndex) {
+    queue
+        .enqueue(idx)
+        .expect("the queue can hold every node of the tree");
+}
+
+/// See [`LinkedBinaryTree::pre_order`].
+pub struct PreOrderIter<'a, T> {
+    tree: &'a LinkedBinaryTree<T>,
+    // Roots of the subtrees still to go through, the next one on top.
+    stack: LinkedStack<ArenaIndex>,
+}
+
+impl<'a, T> Iterator for PreOrderIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.tree.node(self.stack.pop()?);
+        if let Some(right) = node.right {
+            push(&mut self.stack, right);
+        }
+        if let Some(left) = node.left {
+            push(&mut self.stack, left);
+        }
+        Some(&node.val)
+    }
+}
+
+/// See [`LinkedBinaryTree::in_order`].
+pub struct InOrderIter<'a, T> {
+    tree: &'a LinkedBinaryTree<T>,
+    // Nodes whose left subtree is being gone through.
+    stack: LinkedStack<ArenaIndex>,
+    // Root of the next subtree to go through.
+    current: Option<ArenaIndex>,
+}
+
+impl<'a, T> Iterator for InOrderIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(idx) = self.current {
+            push(&mut self.stack, idx);
+            self.current = self.tree.node(idx).left;
         }
+        let node = self.tree.node(self.stack.pop()?);
+        self.current = node.right;
+        Some(&node.val)
     }
+}
 
-    pub fn traverse_in_order(&self, result_recip: &mut String) {
-        if let Some(left) = &self.left {
-            left.borrow().traverse_in_order(result_recip);
+/// See [`LinkedBinaryTree::post_order`].
+pub struct PostOrderIter<'a, T> {
+    tree: &'a LinkedBinaryTree<T>,
+    // Nodes along with whether their children have already been stacked, in
+    // which case they come next.
+    stack: LinkedStack<(ArenaIndex, bool)>,
+}
+
+impl<'a, T> Iterator for PostOrderIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (idx, expanded) = self.stack.pop()?;
+            let node = self.tree.node(idx);
+            if expanded {
+                return Some(&node.val);
+            }
+            push(&mut self.stack, (idx, true));
+            if let Some(right) = node.right {
+                push(&mut self.stack, (right, false));
+            }
+            if let Some(left) = node.left {
+                push(&mut self.stack, (left, false));
+            }
         }
-        result_recip.push_str(format!("{} ", self.val).as_str());
-        if let Some(right) = &self.right {
-            right.borrow().traverse_in_order(result_recip);
+    }
+}
+
+/// See [`LinkedBinaryTree::level_order`].
+pub struct LevelOrderIter<'a, T> {
+    tree: &'a LinkedBinaryTree<T>,
+    queue: ArrayQueue<ArenaIndex>,
+}
+
+impl<'a, T> Iterator for LevelOrderIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.tree.node(self.queue.dequeue()?);
+        for child in [node.left, node.right].into_iter().flatten() {
+            enqueue(&mut self.queue, child);
         }
+        Some(&node.val)
     }
+}
+
+/// See [`LinkedBinaryTree::levels`].
+pub struct LevelsIter<'a, T> {
+    tree: &'a LinkedBinaryTree<T>,
+    // Holds exactly the nodes of the next level.
+    queue: ArrayQueue<ArenaIndex>,
+}
+
+impl<'a, T> Iterator for LevelsIter<'a, T> {
+    type Item = Vec<&'a T>;
 
-    pub fn traverse_post_order(&self, result_recip: &mut String) {
-        if let Some(left) = &self.left {
-            left.borrow().traverse_post_order(result_recip);
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.queue.is_empty() {
+            return None;
         }
-        if let Some(right) = &self.right {
-            right.borrow().traverse_post_order(result_recip);
+        let mut level = Vec::with_capacity(self.queue.len());
+        for _ in 0..self.queue.len() {
+            let node = self.tree.node(self.queue.dequeue()?);
+            for child in [node.left, node.right].into_iter().flatten() {
+                enqueue(&mut self.queue, child);
+            }
+            level.push(&node.val);
+        }
+        Some(level)
+    }
+}
+
+// Runs the Morris traversal, and finishes it when dropped early, which puts
+// back the links of the tree.
+struct MorrisWalk<'a, T> {
+    tree: &'a mut LinkedBinaryTree<T>,
+    current: Option<ArenaIndex>,
+}
+
+impl<T> MorrisWalk<'_, T> {
+    fn next_node(&mut self) -> Option<ArenaIndex> {
+        while let Some(idx) = self.current {
+            let Some(left) = self.tree.node(idx).left else {
+                // Nothing on the left, the node comes next, then its right
+                // subtree, or the node the link leads back to.
+                self.current = self.tree.node(idx).right;
+                return Some(idx);
+            };
+            // The predecessor is the rightmost node of the left subtree,
+            // unless its right link already leads back here.
+            let mut pred = left;
+            while let Some(right) = self.tree.node(pred).right {
+                if right == idx {
+                    break;
+                }
+                pred = right;
+            }
+            if self.tree.node(pred).right.is_none() {
+                self.tree.node_mut(pred).right = Some(idx);
+                self.current = Some(left);
+            } else {
+                // Back from the left subtree.
+                self.tree.node_mut(pred).right = None;
+                self.current = self.tree.node(idx).right;
+                return Some(idx);
+            }
         }
-        result_recip.push_str(format!("{} ", self.val).as_str());
+        None
+    }
+}
+
+impl<T> Drop for MorrisWalk<'_, T> {
+    fn drop(&mut self) {
+        while self.next_node().is_some() {}
     }
 }
 
 #[cfg(test)]
 mod test {
-    use std::sync::BarrierWaitResult;
-
+    use super::super::shapes;
     use super::*;
 
+    // 1 with the children 2 and 3, 2 having a right child 4.
+    fn sample() -> LinkedBinaryTree<i32> {
+        LinkedBinaryTree::from_level_order([Some(1), Some(2), Some(3), None, Some(4)]).unwrap()
+    }
+
     #[test]
     fn linked_binary_tree_new() {
         let binary_tree = LinkedBinaryTree::<i32>::new();
-        assert!(binary_tree.root.is_none());
+        assert!(binary_tree.is_empty());
+        assert_eq!(None, binary_tree.root());
+        assert_eq!(0, binary_tree.count());
+    }
+
+    #[test]
+    fn linked_binary_tree_insert_children() {
+        let mut binary_tree = LinkedBinaryTree::new();
+        let root = binary_tree.insert_root(1).unwrap();
+        assert_eq!(Err(TreeErr::Occupied), binary_tree.insert_root(5));
+        let left = binary_tree.insert_left(root, 2).unwrap();
+        let right = binary_tree.insert_right(root, 3).unwrap();
+        assert_eq!(Err(TreeErr::Occupied), binary_tree.insert_left(root, 5));
+        let leaf = binary_tree.insert_right(left, 4).unwrap();
+
+        assert_eq!(Some(root), binary_tree.root());
+        assert_eq!(Some(left), binary_tree.left(root));
+        assert_eq!(Some(right), binary_tree.right(root));
+        assert_eq!(None, binary_tree.left(left));
+        assert_eq!(Some(leaf), binary_tree.right(left));
+        assert_eq!(Some(left), binary_tree.parent(leaf));
+        assert_eq!(Some(root), binary_tree.parent(right));
+        assert_eq!(None, binary_tree.parent(root));
+        assert_eq!(Some(&4), binary_tree.get(leaf));
+        assert_eq!(4, binary_tree.count());
+    }
+
+    #[test]
+    fn linked_binary_tree_replace() {
+        let mut binary_tree = sample();
+        let root = binary_tree.root().unwrap();
+        assert_eq!(Ok(1), binary_tree.replace(root, 10));
+        *binary_tree.get_mut(root).unwrap() += 1;
+        assert_eq!(Some(&11), binary_tree.get(root));
+    }
+
+    #[test]
+    fn linked_binary_tree_remove_subtree() {
+        let mut binary_tree = sample();
+        let root = binary_tree.root().unwrap();
+        let left = binary_tree.left(root).unwrap();
+        let leaf = binary_tree.right(left).unwrap();
+
+        let subtree = binary_tree.remove_subtree(left).unwrap();
+        assert_eq!(2, binary_tree.count());
+        assert_eq!(None, binary_tree.left(root));
+        assert_eq!(None, binary_tree.get(leaf));
+        assert_eq!(Err(TreeErr::StaleHandle), binary_tree.insert_left(left, 5));
+        assert_eq!(
+            vec![1, 3],
+            binary_tree.pre_order().copied().collect::<Vec<_>>()
+        );
+
+        // the removed nodes keep their shape
+        assert_eq!(2, subtree.count());
+        let sub_root = subtree.root().unwrap();
+        assert_eq!(None, subtree.parent(sub_root));
+        assert_eq!(Some(&2), subtree.get(sub_root));
+        let sub_leaf = subtree.right(sub_root).unwrap();
+        assert_eq!(Some(&4), subtree.get(sub_leaf));
+        assert_eq!(Some(sub_root), subtree.parent(sub_leaf));
+
+        // the slot is free again
+        binary_tree.insert_left(root, 5).unwrap();
+        let removed = binary_tree.remove_subtree(root).unwrap();
+        assert!(binary_tree.is_empty());
+        assert_eq!(3, removed.count());
+    }
+
+    #[test]
+    fn linked_binary_tree_from_level_order() {
+        let empty = LinkedBinaryTree::<i32>::from_level_order([]).unwrap();
+        assert!(empty.is_empty());
+        let empty = LinkedBinaryTree::<i32>::from_level_order([None, None]).unwrap();
+        assert!(empty.is_empty());
+
+        // the missing node 2 lists no children, so 4 and 5 go under 3
+        let binary_tree =
+            LinkedBinaryTree::from_level_order([Some(1), None, Some(3), Some(4), Some(5), None])
+                .unwrap();
+        assert_eq!(
+            vec![1, 3, 4, 5],
+            binary_tree.level_order().copied().collect::<Vec<_>>()
+        );
+        let three = binary_tree.right(binary_tree.root().unwrap()).unwrap();
+        let four = binary_tree.left(three).unwrap();
+        assert_eq!(Some(&4), binary_tree.get(four));
+
+        assert_eq!(
+            Err(TreeErr::OrphanValue),
+            LinkedBinaryTree::from_level_order([Some(1), None, None, Some(2)]).map(|_| ())
+        );
+        assert_eq!(
+            Err(TreeErr::OrphanValue),
+            LinkedBinaryTree::from_level_order([None, Some(1)]).map(|_| ())
+        );
     }
 
     #[test]
     fn linked_binary_tree_is_full() {
         let mut binary_tree = LinkedBinaryTree::<i32>::new();
-        binary_tree.root = Some(Rc::new(RefCell::new(LinkedBinaryTreeNode::new(1))));
+        let root = binary_tree.insert_root(1).unwrap();
         assert!(binary_tree.is_full());
-        binary_tree.root.as_mut().unwrap().borrow_mut().set_left(2);
+        let left = binary_tree.insert_left(root, 2).unwrap();
         assert!(!binary_tree.is_full());
-        binary_tree.root.as_mut().unwrap().borrow_mut().set_right(3);
+        binary_tree.insert_right(root, 3).unwrap();
         assert!(binary_tree.is_full());
-        binary_tree
-            .root
-            .as_mut()
-            .unwrap()
-            .borrow_mut()
-            .left
-            .as_mut()
-            .unwrap()
-            .borrow_mut()
-            .set_right(4);
+        binary_tree.insert_right(left, 4).unwrap();
         assert!(!binary_tree.is_full());
     }
 
     #[test]
     fn linked_binary_tree_is_complete() {
         let mut binary_tree = LinkedBinaryTree::<i32>::new();
-        binary_tree.root = Some(Rc::new(RefCell::new(LinkedBinaryTreeNode::new(1))));
-        binary_tree.root.as_mut().unwrap().borrow_mut().set_left(2);
+        let root = binary_tree.insert_root(1).unwrap();
+        let left = binary_tree.insert_left(root, 2).unwrap();
         assert!(binary_tree.is_complete());
-        binary_tree.root.as_mut().unwrap().borrow_mut().set_right(3);
+        binary_tree.insert_right(root, 3).unwrap();
         assert!(binary_tree.is_complete());
-        binary_tree
-            .root
-            .as_mut()
-            .unwrap()
-            .borrow_mut()
-            .left
-            .as_mut()
-            .unwrap()
-            .borrow_mut()
-            .set_right(4);
+        binary_tree.insert_right(left, 4).unwrap();
         assert!(!binary_tree.is_complete());
     }
 
+    #[test]
+    fn linked_binary_tree_is_complete_gaps() {
+        let complete = |vals: &[Option<i32>]| {
+            LinkedBinaryTree::from_level_order(vals.iter().copied())
+                .unwrap()
+                .is_complete()
+        };
+        assert!(complete(&[Some(1), Some(2)]));
+        assert!(complete(&[Some(1), Some(2), Some(3), Some(4)]));
+        // the left child has children, the right one is missing
+        assert!(!complete(&[Some(1), Some(2), None, Some(4)]));
+        // gap in the middle of the last level
+        assert!(!complete(&[
+            Some(1),
+            Some(2),
+            Some(3),
+            Some(4),
+            None,
+            Some(6)
+        ]));
+        assert!(!complete(&[Some(1), Some(2), Some(3), None, None, Some(6)]));
+        assert!(!LinkedBinaryTree::<i32>::new().is_complete());
+    }
+
+    #[test]
+    fn linked_binary_tree_metrics() {
+        let empty = LinkedBinaryTree::<i32>::new();
+        assert_eq!(
+            (0, 0, 0, 0, 0),
+            (
+                empty.height(),
+                empty.leaf_count(),
+                empty.internal_count(),
+                empty.width(),
+                empty.diameter()
+            )
+        );
+        assert!(empty.is_balanced());
+        assert!(!empty.is_perfect());
+
+        let binary_tree = sample();
+        assert_eq!(3, binary_tree.height());
+        assert_eq!(2, binary_tree.leaf_count());
+        assert_eq!(2, binary_tree.internal_count());
+        assert_eq!(2, binary_tree.width());
+        assert_eq!(3, binary_tree.diameter());
+        let root = binary_tree.root().unwrap();
+        let leaf = binary_tree.right(binary_tree.left(root).unwrap()).unwrap();
+        assert_eq!(Ok(0), binary_tree.depth_of(root));
+        assert_eq!(Ok(2), binary_tree.depth_of(leaf));
+
+        // the longest path stays in the left subtree
+        let lopsided = LinkedBinaryTree::from_level_order([
+            Some(1),
+            Some(2),
+            None,
+            Some(3),
+            Some(4),
+            Some(5),
+            None,
+            None,
+            Some(6),
+        ])
+        .unwrap();
+        assert_eq!(4, lopsided.diameter());
+        assert_eq!(4, lopsided.height());
+        assert!(!lopsided.is_balanced());
+    }
+
+    #[test]
+    fn linked_binary_tree_long_chain() {
+        const LEN: usize = 200_000;
+        let mut tree = LinkedBinaryTree::new();
+        let mut node = tree.insert_root(0).unwrap();
+        let mut second = node;
+        for v in 1..LEN {
+            node = tree.insert_left(node, v).unwrap();
+            if v == 1 {
+                second = node;
+            }
+        }
+        assert_eq!(LEN, tree.height());
+        assert_eq!(LEN - 1, tree.diameter());
+        assert!(!tree.is_balanced());
+        assert!(!tree.is_full());
+        let subtree = tree.remove_subtree(second).unwrap();
+        assert_eq!(LEN - 1, subtree.height());
+        assert_eq!(1, tree.count());
+    }
+
+    #[test]
+    fn linked_binary_tree_iters_dropped_early_on_long_chain() {
+        const LEN: usize = 200_000;
+        let mut tree = LinkedBinaryTree::new();
+        let mut node = tree.insert_root(0).unwrap();
+        for v in 1..LEN {
+            node = tree.insert_left(node, v).unwrap();
+        }
+        // Each iterator stacks the whole chain, and is dropped with it.
+        assert_eq!(Some(&0), tree.pre_order().next());
+        assert_eq!(Some(&(LEN - 1)), tree.in_order().next());
+        assert_eq!(Some(&(LEN - 1)), tree.post_order().next());
+        assert_eq!(Some(&0), tree.level_order().next());
+    }
+
+    #[test]
+    fn linked_binary_tree_depth_of_stale_node() {
+        let mut binary_tree = sample();
+        let root = binary_tree.root().unwrap();
+        let right = binary_tree.right(root).unwrap();
+        binary_tree.remove_subtree(right).unwrap();
+        assert_eq!(Err(TreeErr::StaleHandle), binary_tree.depth_of(right));
+    }
+
+    // Tree of the given shape, each node holding its position, along with the
+    // handle of each position. The positions must be sorted.
+    fn from_shape(shape: &[u64]) -> (LinkedBinaryTree<u64>, HashMap<u64, NodeHandle>) {
+        let mut tree = LinkedBinaryTree::new();
+        let mut handles = HashMap::new();
+        for &p in shape {
+            let handle = match p {
+                1 => tree.insert_root(p),
+                _ if p % 2 == 0 => tree.insert_left(handles[&(p / 2)], p),
+                _ => tree.insert_right(handles[&(p / 2)], p),
+            };
+            handles.insert(p, handle.unwrap());
+        }
+        (tree, handles)
+    }
+
+    #[test]
+    fn linked_binary_tree_shapes_exhaustive() {
+        let all = shapes(7);
+        // Catalan numbers
+        assert_eq!(1 + 1 + 2 + 5 + 14 + 42 + 132 + 429, all.len());
+        for mut shape in all {
+            shape.sort();
+            let (tree, handles) = from_shape(&shape);
+
+            let set = shape.iter().copied().collect::<HashSet<_>>();
+            let depth = |p: u64| p.ilog2() as usize;
+            let children = |p: u64| (set.contains(&(2 * p)), set.contains(&(2 * p + 1)));
+            fn height(set: &HashSet<u64>, p: u64) -> usize {
+                match set.contains(&p) {
+                    true => 1 + height(set, 2 * p).max(height(set, 2 * p + 1)),
+                    false => 0,
+                }
+            }
+            let distance = |mut a: u64, mut b: u64| {
+                let mut edges = 0;
+                while a != b {
+                    if a > b {
+                        a /= 2;
+                    } else {
+                        b /= 2;
+                    }
+                    edges += 1;
+                }
+                edges
+            };
+            let n = shape.len();
+            let leaves = shape.iter().filter(|&&p| children(p) == (false, false));
+            let mut levels = HashMap::new();
+            shape
+                .iter()
+                .for_each(|&p| *levels.entry(depth(p)).or_insert(0) += 1);
+
+            let expect_height = height(&set, 1);
+            assert_eq!(expect_height, tree.height(), "{shape:?}");
+            assert_eq!(leaves.count(), tree.leaf_count(), "{shape:?}");
+            assert_eq!(n, tree.leaf_count() + tree.internal_count());
+            assert_eq!(
+                levels.values().copied().max().unwrap_or(0),
+                tree.width(),
+                "{shape:?}"
+            );
+            let diameter = shape
+                .iter()
+                .flat_map(|&a| shape.iter().map(move |&b| (a, b)))
+                .map(|(a, b)| distance(a, b))
+                .max()
+                .unwrap_or(0);
+            assert_eq!(diameter, tree.diameter(), "{shape:?}");
+            let complete = n > 0 && shape.iter().copied().eq(1..=n as u64);
+            assert_eq!(complete, tree.is_complete(), "{shape:?}");
+            let perfect = complete && (n + 1).is_power_of_two();
+            assert_eq!(perfect, tree.is_perfect(), "{shape:?}");
+            let full = n > 0 && shape.iter().all(|&p| children(p).0 == children(p).1);
+            assert_eq!(full, tree.is_full(), "{shape:?}");
+            let balanced = shape
+                .iter()
+                .all(|&p| height(&set, 2 * p).abs_diff(height(&set, 2 * p + 1)) <= 1);
+            assert_eq!(balanced, tree.is_balanced(), "{shape:?}");
+            for (&p, &handle) in &handles {
+                assert_eq!(Ok(depth(p)), tree.depth_of(handle));
+            }
+        }
+    }
+
     #[test]
     fn linked_binary_tree_trav_pre_order() {
-        let mut binary_tree = LinkedBinaryTree::<i32>::new();
-        binary_tree.root = Some(Rc::new(RefCell::new(LinkedBinaryTreeNode::new(1))));
-        binary_tree.root.as_mut().unwrap().borrow_mut().set_left(2);
-        binary_tree.root.as_mut().unwrap().borrow_mut().set_right(3);
-        binary_tree
-            .root
-            .as_mut()
-            .unwrap()
-            .borrow_mut()
-            .left
-            .as_mut()
-            .unwrap()
-            .borrow_mut()
-            .set_right(4);
-        let mut result = String::new();
-        binary_tree.traverse_pre_order(&mut result);
-        assert_eq!("1 2 4 3 ", result.as_str());
+        assert_eq!(
+            vec![1, 2, 4, 3],
+            sample().pre_order().copied().collect::<Vec<_>>()
+        );
     }
 
     #[test]
     fn linked_binary_tree_trav_in_order() {
-        let mut binary_tree = LinkedBinaryTree::<i32>::new();
-        binary_tree.root = Some(Rc::new(RefCell::new(LinkedBinaryTreeNode::new(1))));
-        binary_tree.root.as_mut().unwrap().borrow_mut().set_left(2);
-        binary_tree.root.as_mut().unwrap().borrow_mut().set_right(3);
-        binary_tree
-            .root
-            .as_mut()
-            .unwrap()
-            .borrow_mut()
-            .left
-            .as_mut()
-            .unwrap()
-            .borrow_mut()
-            .set_right(4);
-        let mut result = String::new();
-        binary_tree.traverse_in_order(&mut result);
-        assert_eq!("2 4 1 3 ", result.as_str());
+        assert_eq!(
+            vec![2, 4, 1, 3],
+            sample().in_order().copied().collect::<Vec<_>>()
+        );
     }
 
     #[test]
     fn linked_binary_tree_trav_post_order() {
-        let mut binary_tree = LinkedBinaryTree::<i32>::new();
-        binary_tree.root = Some(Rc::new(RefCell::new(LinkedBinaryTreeNode::new(1))));
-        binary_tree.root.as_mut().unwrap().borrow_mut().set_left(2);
-        binary_tree.root.as_mut().unwrap().borrow_mut().set_right(3);
-        binary_tree
-            .root
-            .as_mut()
-            .unwrap()
-            .borrow_mut()
-            .left
-            .as_mut()
-            .unwrap()
-            .borrow_mut()
-            .set_right(4);
-        let mut result = String::new();
-        binary_tree.traverse_post_order(&mut result);
-        assert_eq!("4 2 3 1 ", result.as_str());
+        assert_eq!(
+            vec![4, 2, 3, 1],
+            sample().post_order().copied().collect::<Vec<_>>()
+        );
     }
 
     #[test]
     fn linked_binary_tree_trav_level_order() {
-        let mut binary_tree = LinkedBinaryTree::<i32>::new();
-        binary_tree.root = Some(Rc::new(RefCell::new(LinkedBinaryTreeNode::new(1))));
-        binary_tree.root.as_mut().unwrap().borrow_mut().set_left(2);
-        binary_tree.root.as_mut().unwrap().borrow_mut().set_right(3);
-        binary_tree
-            .root
-            .as_mut()
-            .unwrap()
-            .borrow_mut()
-            .left
-            .as_mut()
-            .unwrap()
-            .borrow_mut()
-            .set_right(4);
-        let mut result = String::new();
-        binary_tree.traverse_level_order(&mut result);
-        assert_eq!("1 2 3 4 ", result.as_str());
+        assert_eq!(
+            vec![1, 2, 3, 4],
+            sample().level_order().copied().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn linked_binary_tree_levels() {
+        let binary_tree = LinkedBinaryTree::from_level_order(
+            [1, 2, 3, 0, 4, 5, 0, 0, 6].map(|v| (v != 0).then_some(v)),
+        )
+        .unwrap();
+        let levels = binary_tree.levels().collect::<Vec<_>>();
+        assert_eq!(vec![vec![&1], vec![&2, &3], vec![&4, &5], vec![&6]], levels);
+        assert_eq!(None, LinkedBinaryTree::<i32>::new().levels().next());
+    }
+
+    #[test]
+    fn linked_binary_tree_morris_in_order() {
+        let mut binary_tree = sample();
+        let mut visited = Vec::new();
+        binary_tree.morris_in_order(|&v| visited.push(v));
+        assert_eq!(vec![2, 4, 1, 3], visited);
+        // the links are back in place
+        assert_eq!(
+            vec![1, 2, 4, 3],
+            binary_tree.pre_order().copied().collect::<Vec<_>>()
+        );
+        assert_eq!(3, binary_tree.height());
+    }
+
+    #[test]
+    fn linked_binary_tree_morris_panic_restores_links() {
+        let mut binary_tree = sample();
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            // panic while the right link of 4 leads back to the root
+            binary_tree.morris_in_order(|&v| assert_ne!(4, v));
+        }));
+        assert!(result.is_err());
+        assert_eq!(
+            vec![4, 2, 3, 1],
+            binary_tree.post_order().copied().collect::<Vec<_>>()
+        );
+        assert_eq!(3, binary_tree.height());
+    }
+
+    #[test]
+    fn linked_binary_tree_iters_exhaustive() {
+        // Recursive traversals of the shape, by position.
+        fn pre(shape: &[u64], p: u64, out: &mut Vec<u64>) {
+            if shape.contains(&p) {
+                out.push(p);
+                pre(shape, 2 * p, out);
+                pre(shape, 2 * p + 1, out);
+            }
+        }
+        fn ino(shape: &[u64], p: u64, out: &mut Vec<u64>) {
+            if shape.contains(&p) {
+                ino(shape, 2 * p, out);
+                out.push(p);
+                ino(shape, 2 * p + 1, out);
+            }
+        }
+        fn post(shape: &[u64], p: u64, out: &mut Vec<u64>) {
+            if shape.contains(&p) {
+                post(shape, 2 * p, out);
+                post(shape, 2 * p + 1, out);
+                out.push(p);
+            }
+        }
+
+        for mut shape in shapes(6) {
+            shape.sort();
+            let (mut tree, _) = from_shape(&shape);
+            let expect = |walk: fn(&[u64], u64, &mut Vec<u64>)| {
+                let mut out = Vec::new();
+                walk(&shape, 1, &mut out);
+                out
+            };
+            let collect = |iter: &mut dyn Iterator<Item = &u64>| iter.copied().collect::<Vec<_>>();
+            assert_eq!(expect(pre), collect(&mut tree.pre_order()));
+            assert_eq!(expect(ino), collect(&mut tree.in_order()));
+            assert_eq!(expect(post), collect(&mut tree.post_order()));
+            // positions grow level by level, from the left to the right
+            assert_eq!(shape, collect(&mut tree.level_order()));
+            let flattened = tree.levels().flatten().copied().collect::<Vec<_>>();
+            assert_eq!(shape, flattened);
+            assert!(tree
+                .levels()
+                .all(|level| { level.iter().all(|v| v.ilog2() == level[0].ilog2()) }));
+            let mut visited = Vec::new();
+            tree.morris_in_order(|&p| visited.push(p));
+            assert_eq!(expect(ino), visited);
+            assert_eq!(expect(pre), collect(&mut tree.pre_order()));
+        }
+    }
+
+    #[test]
+    fn linked_binary_tree_from_pre_in_order() {
+        let binary_tree =
+            LinkedBinaryTree::from_pre_in_order(&[1, 2, 4, 3], &[2, 4, 1, 3]).unwrap();
+        assert_eq!(
+            vec![1, 2, 3, 4],
+            binary_tree.level_order().copied().collect::<Vec<_>>()
+        );
+        let root = binary_tree.root().unwrap();
+        let two = binary_tree.left(root).unwrap();
+        assert_eq!(None, binary_tree.left(two));
+        assert_eq!(Some(&4), binary_tree.get(binary_tree.right(two).unwrap()));
+        assert_eq!(
+            Some(two),
+            binary_tree.parent(binary_tree.right(two).unwrap())
+        );
+
+        let empty = LinkedBinaryTree::<i32>::from_pre_in_order(&[], &[]).unwrap();
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn linked_binary_tree_from_post_in_order() {
+        let binary_tree =
+            LinkedBinaryTree::from_post_in_order(&[4, 2, 3, 1], &[2, 4, 1, 3]).unwrap();
+        assert_eq!(
+            vec![1, 2, 4, 3],
+            binary_tree.pre_order().copied().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn linked_binary_tree_from_pre_post_order() {
+        let binary_tree =
+            LinkedBinaryTree::from_pre_post_order(&[1, 2, 4, 5, 3], &[4, 5, 2, 3, 1]).unwrap();
+        assert_eq!(
+            vec![4, 2, 5, 1, 3],
+            binary_tree.in_order().copied().collect::<Vec<_>>()
+        );
+        assert!(binary_tree.is_full());
+        // 2 could be the left or the right child of 1
+        assert_eq!(
+            Err(TreeErr::InconsistentTraversals),
+            LinkedBinaryTree::from_pre_post_order(&[1, 2], &[2, 1]).map(|_| ())
+        );
+    }
+
+    #[test]
+    fn linked_binary_tree_rebuild_errors() {
+        let err = |result: Result<LinkedBinaryTree<i32>, TreeErr>| result.map(|_| ()).unwrap_err();
+        assert_eq!(
+            TreeErr::DuplicateValue,
+            err(LinkedBinaryTree::from_pre_in_order(&[1, 2, 2], &[2, 1, 2]))
+        );
+        assert_eq!(
+            TreeErr::DuplicateValue,
+            err(LinkedBinaryTree::from_post_in_order(&[1, 1, 2], &[1, 2, 3]))
+        );
+        assert_eq!(
+            TreeErr::InconsistentTraversals,
+            err(LinkedBinaryTree::from_pre_in_order(&[1, 2], &[1, 2, 3]))
+        );
+        assert_eq!(
+            TreeErr::InconsistentTraversals,
+            err(LinkedBinaryTree::from_pre_in_order(&[1, 2, 4], &[1, 2, 3]))
+        );
+        // 2 should be in the left subtree of 1, which only holds 3
+        assert_eq!(
+            TreeErr::InconsistentTraversals,
+            err(LinkedBinaryTree::from_pre_in_order(&[1, 2, 3], &[3, 1, 2]))
+        );
+        assert_eq!(
+            TreeErr::InconsistentTraversals,
+            err(LinkedBinaryTree::from_post_in_order(&[1, 2, 3], &[2, 3, 1]))
+        );
+        // the roots don't match
+        assert_eq!(
+            TreeErr::InconsistentTraversals,
+            err(LinkedBinaryTree::from_pre_post_order(
+                &[1, 2, 3],
+                &[2, 1, 3]
+            ))
+        );
+        assert_eq!(
+            TreeErr::DuplicateValue,
+            err(LinkedBinaryTree::from_pre_post_order(
+                &[1, 2, 3],
+                &[2, 2, 1]
+            ))
+        );
+    }
+
+    #[test]
+    fn linked_binary_tree_rebuild_exhaustive() {
+        let orders = |tree: &LinkedBinaryTree<u64>| {
+            (
+                tree.pre_order().copied().collect::<Vec<_>>(),
+                tree.in_order().copied().collect::<Vec<_>>(),
+                tree.post_order().copied().collect::<Vec<_>>(),
+                tree.level_order().copied().collect::<Vec<_>>(),
+            )
+        };
+        for mut shape in shapes(7) {
+            shape.sort();
+            let (tree, _) = from_shape(&shape);
+            let expected = orders(&tree);
+            let (pre, ino, post, _) = &expected;
+
+            let rebuilt = LinkedBinaryTree::from_pre_in_order(pre, ino).unwrap();
+            assert_eq!(expected, orders(&rebuilt));
+            let rebuilt = LinkedBinaryTree::from_post_in_order(post, ino).unwrap();
+            assert_eq!(expected, orders(&rebuilt));
+            match LinkedBinaryTree::from_pre_post_order(pre, post) {
+                Ok(rebuilt) => assert_eq!(expected, orders(&rebuilt)),
+                Err(err) => {
+                    assert!(!tree.is_full(), "{shape:?}");
+                    assert_eq!(TreeErr::InconsistentTraversals, err);
+                }
+            }
+            // children are linked back to their parents
+            let mut nodes = Vec::from_iter(rebuilt.root());
+            while let Some(node) = nodes.pop() {
+                for child in [rebuilt.left(node), rebuilt.right(node)]
+                    .into_iter()
+                    .flatten()
+                {
+                    assert_eq!(Some(node), rebuilt.parent(child));
+                    nodes.push(child);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn linked_binary_tree_rebuild_long_chain() {
+        const LEN: usize = 200_000;
+        // Chain of right children, the pre-order being the in-order.
+        let chain = Vec::from_iter(0..LEN);
+        let pre = chain.clone();
+        let ino = chain.clone();
+        let post = Vec::from_iter((0..LEN).rev());
+        let rebuilt = LinkedBinaryTree::from_pre_in_order(&pre, &ino).unwrap();
+        assert_eq!(LEN, rebuilt.height());
+        assert!(rebuilt.pre_order().eq(&pre));
+        let rebuilt = LinkedBinaryTree::from_post_in_order(&post, &ino).unwrap();
+        assert_eq!(LEN, rebuilt.height());
+        assert!(rebuilt.post_order().eq(&post));
+
+        // The pre-order and post-order only rebuild full trees, so every node
+        // of the chain also gets a leaf on the left.
+        let full = LEN / 2;
+        let mut pre = Vec::with_capacity(2 * full + 1);
+        let mut post = Vec::with_capacity(2 * full + 1);
+        for v in 0..full {
+            pre.extend([2 * v, 2 * v + 1]);
+            post.push(2 * v + 1);
+        }
+        pre.push(2 * full);
+        post.push(2 * full);
+        post.extend((0..full).rev().map(|v| 2 * v));
+        let rebuilt = LinkedBinaryTree::from_pre_post_order(&pre, &post).unwrap();
+        assert_eq!(full + 1, rebuilt.height());
+        assert!(rebuilt.is_full());
+        assert!(rebuilt.pre_order().eq(&pre));
+        assert!(rebuilt.post_order().eq(&post));
     }
 
     #[test]
     fn linked_binary_tree_count() {
-        let mut binary_tree = LinkedBinaryTree::<i32>::new();
-        binary_tree.root = Some(Rc::new(RefCell::new(LinkedBinaryTreeNode::new(1))));
-        binary_tree.root.as_mut().unwrap().borrow_mut().set_left(2);
-        binary_tree.root.as_mut().unwrap().borrow_mut().set_right(3);
-        binary_tree
-            .root
-            .as_mut()
-            .unwrap()
-            .borrow_mut()
-            .left
-            .as_mut()
-            .unwrap()
-            .borrow_mut()
-            .set_right(4);
-        assert_eq!(4, binary_tree.count());
+        assert_eq!(4, sample().count());
     }
 }
@@ -0,0 +1,426 @@
+#![allow(dead_code)]
+
+use std::{mem, ops::RangeBounds};
+
+use super::red_black_tree::Color;
+use super::search_tree::{Entry, EntryMap, SearchIter, SearchNode, SearchRange, SearchTree};
+use super::OrderedMap;
+use crate::structures::physical::arena::ArenaIndex;
+
+/// Left-leaning red-black tree, after Sedgewick: a red-black tree where a red
+/// node is always a left child, so that the tree maps one to one onto a 2-3
+/// tree, a black node with a red left child standing for a 3-node. It does
+/// more rotations than a plain red-black tree, but both insertion and removal
+/// fit in a few lines, fixing the colors on the way back up.
+pub struct LeftLeaningRedBlackTree<K, V> {
+    tree: SearchTree<K, V, Color>,
+}
+
+impl<K: Ord, V> LeftLeaningRedBlackTree<K, V> {
+    pub fn new() -> Self {
+        Self {
+            tree: SearchTree::new(),
+        }
+    }
+
+    /// Same as [`new`](Self::new), counting the rotations done to rebalance
+    /// the tree, which [`rotations`](Self::rotations) gives.
+    pub fn with_rotation_counter() -> Self {
+        Self {
+            tree: SearchTree::with_rotation_counter(),
+        }
+    }
+
+    /// Number of rotations done so far, None unless the tree was built with a
+    /// counter.
+    pub fn rotations(&self) -> Option<u64> {
+        self.tree.rotations
+    }
+
+    /// Number of levels, 0 for an empty tree.
+    pub fn height(&self) -> usize {
+        self.tree.height()
+    }
+
+    /// Panic unless the keys are in order, the sizes right, and the colors
+    /// follow the rules of a red-black tree, with no red right child.
+    pub fn assert_invariants(&self) {
+        if let Err(err) = self
+            .tree
+            .check_order_and_sizes()
+            .and_then(|_| self.tree.check_colors(true))
+        {
+            panic!("broken left-leaning red-black tree: {err}");
+        }
+    }
+
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V, Self> {
+        let search = self.tree.search(&key);
+        Entry::new(self, key, search)
+    }
+
+    fn left(&self, idx: ArenaIndex) -> Option<ArenaIndex> {
+        self.tree.node(idx).left
+    }
+
+    fn right(&self, idx: ArenaIndex) -> Option<ArenaIndex> {
+        self.tree.node(idx).right
+    }
+
+    fn left_is_red(&self, idx: Option<ArenaIndex>) -> bool {
+        self.tree.is_red(idx.and_then(|idx| self.left(idx)))
+    }
+
+    // The rotated node keeps the color of the subtree root, and the node it
+    // rotates down turns red, so the link between them stays red.
+    fn rotate_left(&mut self, idx: ArenaIndex) -> ArenaIndex {
+        let pivot = self.tree.rotate_left(idx);
+        self.tree.set_color(pivot, self.tree.node(idx).meta);
+        self.tree.set_color(idx, Color::Red);
+        pivot
+    }
+
+    fn rotate_right(&mut self, idx: ArenaIndex) -> ArenaIndex {
+        let pivot = self.tree.rotate_right(idx);
+        self.tree.set_color(pivot, self.tree.node(idx).meta);
+        self.tree.set_color(idx, Color::Red);
+        pivot
+    }
+
+    // Flip the colors of a node and its two children, which splits a 4-node
+    // when the node is black, and merges a 2-3 tree node with its siblings
+    // when it is red.
+    fn flip_colors(&mut self, idx: ArenaIndex) {
+        let node = self.tree.node(idx);
+        for idx in [Some(idx), node.left, node.right].into_iter().flatten() {
+            let node = self.tree.node_mut(idx);
+            node.meta = match node.meta {
+                Color::Red => Color::Black,
+                Color::Black => Color::Red,
+            };
+        }
+    }
+
+    // Lean a red right child left, straighten two red left children in a row,
+    // and split a node with two red children, giving back the root of the
+    // subtree with its size up to date.
+    fn fix_up(&mut self, mut idx: ArenaIndex) -> ArenaIndex {
+        if self.tree.is_red(self.right(idx)) && !self.tree.is_red(self.left(idx)) {
+            idx = self.rotate_left(idx);
+        }
+        if self.tree.is_red(self.left(idx)) && self.left_is_red(self.left(idx)) {
+            idx = self.rotate_right(idx);
+        }
+        if self.tree.is_red(self.left(idx)) && self.tree.is_red(self.right(idx)) {
+            self.flip_colors(idx);
+        }
+        self.tree.update_size(idx);
+        idx
+    }
+
+    // Make the left child, or one of its children, red before going down to
+    // it, borrowing from the right child when the left one is a 2-node.
+    fn move_red_left(&mut self, mut idx: ArenaIndex) -> ArenaIndex {
+        self.flip_colors(idx);
+        if self.left_is_red(self.right(idx)) {
+            let right = self.right(idx).expect("red left child");
+            self.tree.node_mut(idx).right = Some(self.rotate_right(right));
+            idx = self.rotate_left(idx);
+            self.flip_colors(idx);
+        }
+        idx
+    }
+
+    // Mirror of `move_red_left`.
+    fn move_red_right(&mut self, mut idx: ArenaIndex) -> ArenaIndex {
+        self.flip_colors(idx);
+        if self.left_is_red(self.left(idx)) {
+            idx = self.rotate_right(idx);
+            self.flip_colors(idx);
+        }
+        idx
+    }
+
+    // Rank of the node at the end of the path from the root, counting the
+    // nodes of the left subtrees the path leaves behind.
+    fn rank_at(&self, path: &[ArenaIndex], idx: ArenaIndex) -> usize {
+        let below = path.iter().skip(1).chain([&idx]);
+        let passed = path
+            .iter()
+            .zip(below)
+            .filter(|&(&parent, &child)| self.right(parent) == Some(child))
+            .map(|(&parent, _)| 1 + self.tree.size(self.left(parent)))
+            .sum::<usize>();
+        passed + self.tree.size(self.left(idx))
+    }
+
+    // Remove the node of the given rank in the subtree, whose root or left
+    // child is red, giving the new root of the subtree and the entry of the
+    // node. The node is told by its rank rather than its key, which the
+    // descent can't borrow from the tree it is changing, rotations keeping
+    // the order of the nodes of a subtree and so their ranks.
+    fn remove_from(&mut self, mut idx: ArenaIndex, rank: usize) -> (Option<ArenaIndex>, (K, V)) {
+        let removed;
+        if rank < self.tree.size(self.left(idx)) {
+            if !self.tree.is_red(self.left(idx)) && !self.left_is_red(self.left(idx)) {
+                idx = self.move_red_left(idx);
+            }
+            let left = self.left(idx).expect("the node is in the subtree");
+            let (left, entry) = self.remove_from(left, rank);
+            self.tree.node_mut(idx).left = left;
+            removed = entry;
+        } else {
+            if self.tree.is_red(self.left(idx)) {
+                idx = self.rotate_right(idx);
+            }
+            if rank == self.tree.size(self.left(idx)) && self.right(idx).is_none() {
+                // With no right child, and no red left one, the node is a leaf.
+                let node = self.tree.nodes.remove(idx).expect("linked node");
+                return (None, (node.key, node.val));
+            }
+            if !self.tree.is_red(self.right(idx)) && !self.left_is_red(self.right(idx)) {
+                idx = self.move_red_right(idx);
+            }
+            let right = self.right(idx).expect("the node is in the subtree");
+            let left_size = self.tree.size(self.left(idx));
+            if rank == left_size {
+                // The node takes the entry of its successor, which is removed
+                // from the right subtree instead.
+                let (right, successor) = self.remove_min_at(right);
+                self.tree.node_mut(idx).right = right;
+                let node = self.tree.node_mut(idx);
+                removed = (
+                    mem::replace(&mut node.key, successor.key),
+                    mem::replace(&mut node.val, successor.val),
+                );
+            } else {
+                let (right, entry) = self.remove_from(right, rank - left_size - 1);
+                self.tree.node_mut(idx).right = right;
+                removed = entry;
+            }
+        }
+        (Some(self.fix_up(idx)), removed)
+    }
+
+    // Remove the least key of the subtree, giving the new root of the
+    // subtree and the node of the key.
+    fn remove_min_at(
+        &mut self,
+        mut idx: ArenaIndex,
+    ) -> (Option<ArenaIndex>, SearchNode<K, V, Color>) {
+        let Some(left) = self.left(idx) else {
+            return (None, self.tree.nodes.remove(idx).expect("linked node"));
+        };
+        if !self.tree.is_red(Some(left)) && !self.left_is_red(Some(left)) {
+            idx = self.move_red_left(idx);
+        }
+        let left = self.left(idx).expect("the left child is kept");
+        let (left, min) = self.remove_min_at(left);
+        self.tree.node_mut(idx).left = left;
+        (Some(self.fix_up(idx)), min)
+    }
+}
+
+impl<K: Ord, V> Default for LeftLeaningRedBlackTree<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord, V> EntryMap<K, V> for LeftLeaningRedBlackTree<K, V> {
+    fn entry_at(&self, idx: ArenaIndex) -> (&K, &V) {
+        self.tree.key_value(idx)
+    }
+
+    fn entry_at_mut(&mut self, idx: ArenaIndex) -> (&K, &mut V) {
+        self.tree.key_value_mut(idx)
+    }
+
+    /// Link a red leaf, and fix the colors going back up the path, every
+    /// node of which may become the child of its own child.
+    fn insert_under(&mut self, path: Vec<ArenaIndex>, left: bool, key: K, val: V) -> ArenaIndex {
+        let leaf = self.tree.link_leaf(&path, left, key, val, Color::Red);
+        for (depth, &idx) in path.iter().enumerate().rev() {
+            let top = self.fix_up(idx);
+            let parent = depth.checked_sub(1).map(|depth| path[depth]);
+            self.tree.relink(parent, idx, Some(top));
+        }
+        let root = self.tree.root.expect("a node was inserted");
+        self.tree.set_color(root, Color::Black);
+        leaf
+    }
+
+    /// Remove a node going down from the root, making sure the current node
+    /// is never a 2-node of the 2-3 tree, so that the node is removed from a
+    /// 3-node or a 4-node, which doesn't change any black height. The
+    /// temporary 4-nodes are split on the way back up. The path is only used
+    /// to find the rank of the node, as the descent changes the tree.
+    fn remove_at(&mut self, path: Vec<ArenaIndex>, idx: ArenaIndex) -> (K, V) {
+        let rank = self.rank_at(&path, idx);
+        let root = self.tree.root.expect("the node is in the tree");
+        if !self.tree.is_red(self.left(root)) && !self.tree.is_red(self.right(root)) {
+            self.tree.set_color(root, Color::Red);
+        }
+        let (root, entry) = self.remove_from(root, rank);
+        self.tree.root = root;
+        if let Some(root) = root {
+            self.tree.set_color(root, Color::Black);
+        }
+        entry
+    }
+}
+
+impl<K: Ord, V> OrderedMap for LeftLeaningRedBlackTree<K, V> {
+    type Key = K;
+    type Value = V;
+    type Iter<'a>
+        = SearchIter<'a, K, V, Color>
+    where
+        Self: 'a;
+    type Range<'a>
+        = SearchRange<'a, K, V, Color>
+    where
+        Self: 'a;
+
+    fn insert(&mut self, key: K, val: V) -> Option<V> {
+        match self.tree.search(&key) {
+            (_, Ok(idx)) => Some(mem::replace(&mut self.tree.node_mut(idx).val, val)),
+            (path, Err(left)) => {
+                self.insert_under(path, left, key, val);
+                None
+            }
+        }
+    }
+
+    fn get(&self, key: &K) -> Option<&V> {
+        self.tree.get(key)
+    }
+
+    fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        self.tree.get_mut(key)
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        match self.tree.search(key) {
+            (path, Ok(idx)) => Some(self.remove_at(path, idx).1),
+            (_, Err(_)) => None,
+        }
+    }
+
+    fn min(&self) -> Option<(&K, &V)> {
+        self.tree.min()
+    }
+
+    fn max(&self) -> Option<(&K, &V)> {
+        self.tree.max()
+    }
+
+    fn floor(&self, key: &K) -> Option<(&K, &V)> {
+        self.tree.floor(key)
+    }
+
+    fn ceiling(&self, key: &K) -> Option<(&K, &V)> {
+        self.tree.ceiling(key)
+    }
+
+    fn successor(&self, key: &K) -> Option<(&K, &V)> {
+        self.tree.successor(key)
+    }
+
+    fn predecessor(&self, key: &K) -> Option<(&K, &V)> {
+        self.tree.predecessor(key)
+    }
+
+    fn rank(&self, key: &K) -> usize {
+        self.tree.rank(key)
+    }
+
+    fn select(&self, rank: usize) -> Option<(&K, &V)> {
+        self.tree.select(rank)
+    }
+
+    fn len(&self) -> usize {
+        self.tree.len()
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        self.tree.iter()
+    }
+
+    fn range<R: RangeBounds<K>>(&self, range: R) -> Self::Range<'_> {
+        self.tree.range(range)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::structures::logical::tree::RedBlackTree;
+    use crate::test_utils::XorShift;
+
+    #[test]
+    fn left_leaning_red_black_tree_sorted_keys_stay_balanced() {
+        let mut tree = LeftLeaningRedBlackTree::new();
+        (0..1_000).for_each(|key| {
+            tree.insert(key, ());
+            tree.assert_invariants();
+        });
+        assert!(tree.height() < 20);
+        (0..1_000).step_by(2).for_each(|key| {
+            assert_eq!(Some(()), tree.remove(&key));
+            tree.assert_invariants();
+        });
+        assert_eq!(None, tree.remove(&0));
+        assert_eq!(500, tree.len());
+    }
+
+    #[test]
+    fn left_leaning_red_black_tree_rotates_more() {
+        let mut llrb = LeftLeaningRedBlackTree::with_rotation_counter();
+        let mut rb = RedBlackTree::with_rotation_counter();
+        let mut rng = XorShift::new(3);
+        for _ in 0..1_000 {
+            let key = rng.below(10_000);
+            llrb.insert(key, ());
+            rb.insert(key, ());
+        }
+        assert!(llrb.rotations() > rb.rotations());
+    }
+
+    #[test]
+    fn left_leaning_red_black_tree_entry() {
+        let mut tree = LeftLeaningRedBlackTree::new();
+        for word in ["b", "a", "b", "c", "b", "a"] {
+            tree.entry(word)
+                .and_modify(|count| *count += 1)
+                .or_insert(1);
+        }
+        assert_eq!(
+            vec![(&"a", &2), (&"b", &3), (&"c", &1)],
+            tree.iter().collect::<Vec<_>>()
+        );
+        match tree.entry("d") {
+            Entry::Vacant(entry) => assert_eq!(&mut 4, entry.insert(4)),
+            Entry::Occupied(_) => panic!("d is not in the tree"),
+        }
+        assert_eq!(Some((&"d", &4)), tree.max());
+        match tree.entry("a") {
+            Entry::Occupied(entry) => assert_eq!(2, entry.remove()),
+            Entry::Vacant(_) => panic!("a is in the tree"),
+        }
+        assert_eq!(Some((&"b", &3)), tree.min());
+        tree.assert_invariants();
+    }
+
+    #[test]
+    #[should_panic(expected = "leans right")]
+    fn left_leaning_red_black_tree_assert_invariants_catches_right_red() {
+        let mut tree = LeftLeaningRedBlackTree::new();
+        tree.insert(1, ());
+        let root = tree.tree.root.unwrap();
+        let leaf = tree.tree.leaf(2, (), Color::Red);
+        tree.tree.node_mut(root).right = Some(leaf);
+        tree.tree.update_size(root);
+        tree.assert_invariants();
+    }
+}
@@ -1,3 +1,429 @@
-pub use binary_tree::LinkedBinaryTree;
+#![allow(dead_code)]
 
+use std::{fmt::Display, ops::RangeBounds};
+
+pub use array_binary_tree::{
+    ArrayBinaryTree, ArrayInOrderIter, ArrayLevelsIter, ArrayPostOrderIter, ArrayPreOrderIter,
+};
+pub use avl_tree::AvlTree;
+pub use b_plus_tree::{BPlusIter, BPlusTree};
+pub use b_tree::{BTree, BTreeIter, BTreeRange};
+pub use binary_search_tree::BinarySearchTree;
+pub use binary_tree::{
+    InOrderIter, LevelOrderIter, LevelsIter, LinkedBinaryTree, NodeHandle, PostOrderIter,
+    PreOrderIter,
+};
+
+pub use left_leaning_red_black_tree::LeftLeaningRedBlackTree;
+pub use page::{PageEncode, NO_PAGE};
+pub use red_black_tree::{Color, RedBlackTree};
+pub use search_tree::{Entry, EntryMap, OccupiedEntry, SearchIter, SearchRange, VacantEntry};
+
+mod array_binary_tree;
+mod avl_tree;
+mod b_plus_tree;
+mod b_tree;
+mod binary_search_tree;
 mod binary_tree;
+mod left_leaning_red_black_tree;
+mod page;
+mod red_black_tree;
+mod search_tree;
+
+/// Map whose keys are kept in order, which answers queries about the keys
+/// around a given one, and about their positions.
+pub trait OrderedMap {
+    type Key: Ord;
+    type Value;
+    type Iter<'a>: Iterator<Item = (&'a Self::Key, &'a Self::Value)>
+    where
+        Self: 'a;
+    type Range<'a>: Iterator<Item = (&'a Self::Key, &'a Self::Value)>
+    where
+        Self: 'a;
+
+    /// Insert an entry, giving back the previous value of the key if any.
+    fn insert(&mut self, key: Self::Key, val: Self::Value) -> Option<Self::Value>;
+    fn get(&self, key: &Self::Key) -> Option<&Self::Value>;
+    fn get_mut(&mut self, key: &Self::Key) -> Option<&mut Self::Value>;
+    fn remove(&mut self, key: &Self::Key) -> Option<Self::Value>;
+    fn contains_key(&self, key: &Self::Key) -> bool {
+        self.get(key).is_some()
+    }
+    fn min(&self) -> Option<(&Self::Key, &Self::Value)>;
+    fn max(&self) -> Option<(&Self::Key, &Self::Value)>;
+    /// Entry of the greatest key lower than or equal to the given one.
+    fn floor(&self, key: &Self::Key) -> Option<(&Self::Key, &Self::Value)>;
+    /// Entry of the least key greater than or equal to the given one.
+    fn ceiling(&self, key: &Self::Key) -> Option<(&Self::Key, &Self::Value)>;
+    /// Entry of the least key strictly greater than the given one, which
+    /// doesn't have to be in the map.
+    fn successor(&self, key: &Self::Key) -> Option<(&Self::Key, &Self::Value)>;
+    /// Entry of the greatest key strictly lower than the given one.
+    fn predecessor(&self, key: &Self::Key) -> Option<(&Self::Key, &Self::Value)>;
+    /// Number of keys lower than the given one.
+    fn rank(&self, key: &Self::Key) -> usize;
+    /// Entry whose key has the given rank, that is the key at this position
+    /// in order, from 0.
+    fn select(&self, rank: usize) -> Option<(&Self::Key, &Self::Value)>;
+    fn len(&self) -> usize;
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// Entries in the order of their keys.
+    fn iter(&self) -> Self::Iter<'_>;
+    fn range<R: RangeBounds<Self::Key>>(&self, range: R) -> Self::Range<'_>;
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum TreeErr {
+    /// The handle points to a node which has been removed from the tree.
+    StaleHandle,
+    /// There already is a node where the new one should go.
+    Occupied,
+    /// A level order lists a value under no node.
+    OrphanValue,
+    /// A value appears more than once in a traversal, which can't be told
+    /// apart from the others when rebuilding the tree.
+    DuplicateValue,
+    /// The traversals don't hold the same values, or are not traversals of
+    /// the same tree.
+    InconsistentTraversals,
+    /// The tree would be higher than an array tree allows.
+    TooDeep,
+    /// A node doesn't fit in a page.
+    PageOverflow,
+}
+
+impl std::error::Error for TreeErr {}
+
+impl Display for TreeErr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TreeErr::StaleHandle => write!(
+                f,
+                "The handle points to a node which is no longer in this tree"
+            ),
+            TreeErr::Occupied => write!(f, "There already is a node at this position"),
+            TreeErr::OrphanValue => write!(f, "The value has no parent node to go under"),
+            TreeErr::DuplicateValue => write!(f, "The value appears more than once"),
+            TreeErr::InconsistentTraversals => {
+                write!(f, "The traversals don't come from the same tree")
+            }
+            TreeErr::TooDeep => write!(f, "The tree is too deep to be stored in an array"),
+            TreeErr::PageOverflow => write!(f, "The node doesn't fit in a page"),
+        }
+    }
+}
+
+// Every shape of binary tree with up to `n` nodes, as the positions of the
+// nodes in the numbering of a heap: the root is 1, and the children of p are
+// 2p and 2p + 1.
+#[cfg(test)]
+fn shapes(n: usize) -> Vec<Vec<u64>> {
+    // Move a shape under the position p.
+    fn graft(shape: &[u64], p: u64) -> impl Iterator<Item = u64> + '_ {
+        shape.iter().map(move |&q| {
+            let depth = q.ilog2();
+            (p << depth) + (q - (1 << depth))
+        })
+    }
+    let mut by_size = vec![vec![vec![]]];
+    for size in 1..=n {
+        let mut sized = Vec::new();
+        for left in 0..size {
+            for l in &by_size[left] {
+                for r in &by_size[size - 1 - left] {
+                    let mut shape = vec![1];
+                    shape.extend(graft(l, 2));
+                    shape.extend(graft(r, 3));
+                    sized.push(shape);
+                }
+            }
+        }
+        by_size.push(sized);
+    }
+    by_size.into_iter().flatten().collect()
+}
+
+// Conformance suite every OrderedMap implementor goes through, with randomized
+// sequences of operations checked against a BTreeMap. The macro takes the name
+// of the generated test module, an expression building an empty map of i64 to
+// i64, and the check of its invariants run after every operation if it has
+// one. The maps with an entry API go through `check_entries_against_model`
+// as well.
+#[cfg(test)]
+mod conformance {
+    use std::collections::BTreeMap;
+    use std::ops::Bound;
+
+    use super::*;
+    use crate::test_utils::XorShift;
+
+    fn check_queries<M: OrderedMap<Key = i64, Value = i64>>(mut map: M) {
+        assert!(map.is_empty());
+        assert_eq!(None, map.min());
+        assert_eq!(None, map.floor(&0));
+        assert_eq!(None, map.select(0));
+        assert_eq!(0, map.rank(&0));
+        for key in [30, 10, 50, 20, 40] {
+            assert_eq!(None, map.insert(key, key * 10));
+        }
+        assert_eq!(Some(300), map.insert(30, 3));
+        assert_eq!(5, map.len());
+        assert!(map.contains_key(&30));
+        assert!(!map.contains_key(&35));
+        assert_eq!(Some((&10, &100)), map.min());
+        assert_eq!(Some((&50, &500)), map.max());
+        assert_eq!(Some((&20, &200)), map.floor(&25));
+        assert_eq!(Some((&30, &3)), map.floor(&30));
+        assert_eq!(None, map.floor(&5));
+        assert_eq!(Some((&30, &3)), map.ceiling(&25));
+        assert_eq!(None, map.ceiling(&55));
+        assert_eq!(Some((&40, &400)), map.successor(&30));
+        assert_eq!(Some((&40, &400)), map.successor(&35));
+        assert_eq!(None, map.successor(&50));
+        assert_eq!(Some((&20, &200)), map.predecessor(&30));
+        assert_eq!(None, map.predecessor(&10));
+        assert_eq!(2, map.rank(&30));
+        assert_eq!(3, map.rank(&35));
+        assert_eq!(5, map.rank(&60));
+        assert_eq!(Some((&40, &400)), map.select(3));
+        assert_eq!(None, map.select(5));
+        let keys =
+            |range: Vec<(&i64, &i64)>| range.into_iter().map(|(k, _)| *k).collect::<Vec<_>>();
+        assert_eq!(vec![20, 30, 40], keys(map.range(15..45).collect()));
+        assert_eq!(vec![20, 30], keys(map.range(20..40).collect()));
+        assert_eq!(vec![20, 30, 40], keys(map.range(20..=40).collect()));
+        assert_eq!(vec![40, 50], keys(map.range(31..).collect()));
+        assert_eq!(vec![10], keys(map.range(..20).collect()));
+        assert!(map.range(41..50).next().is_none());
+        assert!(map.range(..10).next().is_none());
+        assert!(map.range(60..).next().is_none());
+        *map.get_mut(&30).unwrap() = 300;
+        assert_eq!(Some(&300), map.get(&30));
+        assert_eq!(Some(300), map.remove(&30));
+        assert_eq!(None, map.remove(&30));
+        assert_eq!(vec![10, 20, 40, 50], keys(map.iter().collect()));
+    }
+
+    fn check_sorted_keys<M: OrderedMap<Key = i64, Value = i64>>(mut map: M) {
+        (0..1_000).for_each(|key| assert_eq!(None, map.insert(key, -key)));
+        (0..1_000)
+            .rev()
+            .step_by(2)
+            .for_each(|key| assert_eq!(Some(-key), map.remove(&key)));
+        assert_eq!(500, map.len());
+        assert!(map.iter().map(|(k, _)| *k).eq((0..1_000).step_by(2)));
+        assert_eq!(Some((&500, &-500)), map.select(250));
+    }
+
+    // Random bound over the keys, which are drawn below 100.
+    fn bound(rng: &mut XorShift) -> Bound<i64> {
+        let key = rng.below(110) as i64 - 5;
+        match rng.below(3) {
+            0 => Bound::Included(key),
+            1 => Bound::Excluded(key),
+            _ => Bound::Unbounded,
+        }
+    }
+
+    fn check_against_model<M: OrderedMap<Key = i64, Value = i64>>(
+        mut map: M,
+        check: fn(&M),
+        seed: u64,
+    ) {
+        let mut rng = XorShift::new(seed);
+        let mut model = BTreeMap::new();
+        for _ in 0..2_000 {
+            let key = rng.below(100) as i64;
+            match rng.below(10) {
+                0..=2 => {
+                    let val = rng.below(1_000) as i64;
+                    assert_eq!(model.insert(key, val), map.insert(key, val));
+                }
+                3 | 4 => assert_eq!(model.remove(&key), map.remove(&key)),
+                5 => {
+                    assert_eq!(model.get(&key), map.get(&key));
+                    if let (Some(expected), Some(actual)) = (model.get_mut(&key), map.get_mut(&key))
+                    {
+                        *expected += 1;
+                        *actual += 1;
+                    }
+                }
+                6 => {
+                    assert_eq!(model.range(..=key).next_back(), map.floor(&key));
+                    assert_eq!(model.range(key..).next(), map.ceiling(&key));
+                    assert_eq!(model.range(..key).next_back(), map.predecessor(&key));
+                    let after = (Bound::Excluded(key), Bound::Unbounded);
+                    assert_eq!(model.range(after).next(), map.successor(&key));
+                }
+                7 => {
+                    assert_eq!(model.range(..key).count(), map.rank(&key));
+                    let rank = rng.below(model.len() as u64 + 2) as usize;
+                    assert_eq!(model.iter().nth(rank), map.select(rank));
+                }
+                8 => {
+                    let (start, end) = (bound(&mut rng), bound(&mut rng));
+                    // BTreeMap panics on a range ending before it starts
+                    let empty = match (start, end) {
+                        (Bound::Included(s), Bound::Included(e)) => s > e,
+                        (
+                            Bound::Included(s) | Bound::Excluded(s),
+                            Bound::Included(e) | Bound::Excluded(e),
+                        ) => s >= e,
+                        _ => false,
+                    };
+                    if empty {
+                        assert_eq!(0, map.range((start, end)).count());
+                    } else {
+                        assert!(model.range((start, end)).eq(map.range((start, end))));
+                    }
+                }
+                _ => {
+                    assert_eq!(model.first_key_value(), map.min());
+                    assert_eq!(model.last_key_value(), map.max());
+                    assert!(model.iter().eq(map.iter()));
+                }
+            }
+            check(&map);
+            assert_eq!(model.len(), map.len());
+        }
+        assert!(model.iter().eq(map.iter()));
+    }
+
+    // The model is only ever updated through its own entry API, so that a
+    // wrong value given back by the map's entries shows up in the comparison.
+    fn check_entries_against_model<M: OrderedMap<Key = i64, Value = i64> + EntryMap<i64, i64>>(
+        mut map: M,
+        entry: for<'a> fn(&'a mut M, i64) -> Entry<'a, i64, i64, M>,
+        check: fn(&M),
+        seed: u64,
+    ) {
+        let mut rng = XorShift::new(seed);
+        let mut model = BTreeMap::new();
+        for _ in 0..2_000 {
+            let key = rng.below(100) as i64;
+            match rng.below(5) {
+                0 => {
+                    *entry(&mut map, key).or_insert(0) += 1;
+                    *model.entry(key).or_insert(0) += 1;
+                }
+                1 => assert_eq!(
+                    model.entry(key).and_modify(|val| *val *= 2).or_insert(1),
+                    entry(&mut map, key)
+                        .and_modify(|val| *val *= 2)
+                        .or_insert(1)
+                ),
+                2 => match entry(&mut map, key) {
+                    Entry::Occupied(occupied) => {
+                        assert_eq!(model.remove_entry(&key), Some(occupied.remove_entry()))
+                    }
+                    Entry::Vacant(vacant) => {
+                        assert_eq!(&key, vacant.key());
+                        assert!(!model.contains_key(&key));
+                    }
+                },
+                3 => {
+                    let val = rng.below(1_000) as i64;
+                    match entry(&mut map, key) {
+                        Entry::Occupied(mut occupied) => {
+                            assert_eq!(model.get(&key), Some(occupied.get()));
+                            assert_eq!(model.insert(key, val), Some(occupied.insert(val)));
+                        }
+                        Entry::Vacant(vacant) => {
+                            assert_eq!(None, model.insert(key, val));
+                            assert_eq!(val, *vacant.insert(val));
+                        }
+                    }
+                }
+                _ => assert_eq!(model.remove(&key), map.remove(&key)),
+            }
+            check(&map);
+            assert_eq!(model.len(), map.len());
+        }
+        assert!(model.iter().eq(map.iter()));
+    }
+
+    macro_rules! ordered_map_conformance {
+        ($name:ident, $new:expr) => {
+            ordered_map_conformance!($name, $new, |_| {});
+        };
+        ($name:ident, $new:expr, $check:expr) => {
+            mod $name {
+                use super::*;
+
+                #[test]
+                fn queries() {
+                    check_queries($new);
+                }
+
+                #[test]
+                fn sorted_keys() {
+                    check_sorted_keys($new);
+                }
+
+                #[test]
+                fn against_model() {
+                    (1..=5).for_each(|seed| check_against_model($new, $check, seed));
+                }
+            }
+        };
+    }
+
+    ordered_map_conformance!(binary_search_tree, BinarySearchTree::new());
+    ordered_map_conformance!(avl_tree, AvlTree::new(), AvlTree::assert_invariants);
+    ordered_map_conformance!(
+        red_black_tree,
+        RedBlackTree::new(),
+        RedBlackTree::assert_invariants
+    );
+    ordered_map_conformance!(
+        left_leaning_red_black_tree,
+        LeftLeaningRedBlackTree::new(),
+        LeftLeaningRedBlackTree::assert_invariants
+    );
+
+    #[test]
+    fn red_black_tree_entries_against_model() {
+        (1..=5).for_each(|seed| {
+            check_entries_against_model(
+                RedBlackTree::new(),
+                RedBlackTree::entry,
+                RedBlackTree::assert_invariants,
+                seed,
+            )
+        });
+    }
+
+    #[test]
+    fn left_leaning_red_black_tree_entries_against_model() {
+        (1..=5).for_each(|seed| {
+            check_entries_against_model(
+                LeftLeaningRedBlackTree::new(),
+                LeftLeaningRedBlackTree::entry,
+                LeftLeaningRedBlackTree::assert_invariants,
+                seed,
+            )
+        });
+    }
+
+    ordered_map_conformance!(b_tree_2, BTree::<_, _, 2>::new(), BTree::assert_invariants);
+    ordered_map_conformance!(b_tree_3, BTree::<_, _, 3>::new(), BTree::assert_invariants);
+    ordered_map_conformance!(b_tree_8, BTree::<_, _, 8>::new(), BTree::assert_invariants);
+    ordered_map_conformance!(
+        b_plus_tree_2,
+        BPlusTree::<_, _, 2>::new(),
+        BPlusTree::assert_invariants
+    );
+    ordered_map_conformance!(
+        b_plus_tree_3,
+        BPlusTree::<_, _, 3>::new(),
+        BPlusTree::assert_invariants
+    );
+    ordered_map_conformance!(
+        b_plus_tree_8,
+        BPlusTree::<_, _, 8>::new(),
+        BPlusTree::assert_invariants
+    );
+}
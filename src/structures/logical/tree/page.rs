@@ -0,0 +1,142 @@
+//! Fixed-size byte pages the nodes of the B-trees are written into, one node
+//! per page, as they would be stored on disk.
+//!
+//! A page starts with a header: one byte, 1 for a leaf and 0 for an internal
+//! node, then the number of keys as a little-endian u16. The keys follow,
+//! each one followed by its value in the nodes which hold values. The page
+//! numbers of the children of an internal node come last, as little-endian
+//! u32, and in a B+ tree leaf the page number of the next leaf. The rest of
+//! the page is zeroed. Pages are numbered breadth first, the root being page
+//! 0.
+
+use std::collections::HashMap;
+
+use super::TreeErr;
+use crate::structures::physical::arena::ArenaIndex;
+
+/// Page number standing for no page, such as the next leaf of the last one.
+pub const NO_PAGE: u32 = u32::MAX;
+
+/// Value which can be written into a page, with a fixed width for integers
+/// and a little-endian u32 length before the bytes of a string.
+pub trait PageEncode {
+    fn encode(&self, out: &mut Vec<u8>);
+}
+
+macro_rules! page_encode_int {
+    ($($int:ty),*) => {
+        $(
+            impl PageEncode for $int {
+                fn encode(&self, out: &mut Vec<u8>) {
+                    out.extend_from_slice(&self.to_le_bytes());
+                }
+            }
+        )*
+    };
+}
+
+page_encode_int!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
+// A usize is written as an u64, so that pages don't depend on the platform.
+impl PageEncode for usize {
+    fn encode(&self, out: &mut Vec<u8>) {
+        (*self as u64).encode(out);
+    }
+}
+
+impl PageEncode for bool {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.push(u8::from(*self));
+    }
+}
+
+impl PageEncode for str {
+    fn encode(&self, out: &mut Vec<u8>) {
+        (self.len() as u32).encode(out);
+        out.extend_from_slice(self.as_bytes());
+    }
+}
+
+impl PageEncode for String {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.as_str().encode(out);
+    }
+}
+
+// Nothing is written for the values of a set.
+impl PageEncode for () {
+    fn encode(&self, _out: &mut Vec<u8>) {}
+}
+
+/// Start the bytes of a node with its header.
+pub(super) fn page_header(leaf: bool, keys: usize) -> Result<Vec<u8>, TreeErr> {
+    let keys = u16::try_from(keys).map_err(|_| TreeErr::PageOverflow)?;
+    let mut bytes = vec![u8::from(leaf)];
+    keys.encode(&mut bytes);
+    Ok(bytes)
+}
+
+/// Page number of the node at this position in breadth first order.
+pub(super) fn page_number(position: usize) -> Result<u32, TreeErr> {
+    u32::try_from(position)
+        .ok()
+        .filter(|&page| page != NO_PAGE)
+        .ok_or(TreeErr::PageOverflow)
+}
+
+/// Nodes of a tree in the order of their pages, the root first and its
+/// descendants breadth first, with the page of each node.
+pub(super) fn page_order<'a>(
+    root: ArenaIndex,
+    children: impl Fn(ArenaIndex) -> &'a [ArenaIndex],
+) -> Result<(Vec<ArenaIndex>, HashMap<ArenaIndex, u32>), TreeErr> {
+    let mut order = vec![root];
+    let mut next = 0;
+    while let Some(&idx) = order.get(next) {
+        order.extend_from_slice(children(idx));
+        next += 1;
+    }
+    let pages = order
+        .iter()
+        .enumerate()
+        .map(|(position, &idx)| Ok((idx, page_number(position)?)))
+        .collect::<Result<_, _>>()?;
+    Ok((order, pages))
+}
+
+/// Copy the bytes of a node into a zeroed page.
+pub(super) fn into_page<const P: usize>(bytes: &[u8]) -> Result<[u8; P], TreeErr> {
+    let mut page = [0; P];
+    page.get_mut(..bytes.len())
+        .ok_or(TreeErr::PageOverflow)?
+        .copy_from_slice(bytes);
+    Ok(page)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn page_encode_values() {
+        let mut out = Vec::new();
+        0x0102_u16.encode(&mut out);
+        (-1_i32).encode(&mut out);
+        "ab".encode(&mut out);
+        ().encode(&mut out);
+        true.encode(&mut out);
+        assert_eq!(
+            vec![2, 1, 255, 255, 255, 255, 2, 0, 0, 0, b'a', b'b', 1],
+            out
+        );
+    }
+
+    #[test]
+    fn page_overflow() {
+        assert_eq!(Ok([1, 2, 0, 0]), into_page::<4>(&[1, 2]));
+        assert_eq!(Ok([1, 2]), into_page::<2>(&[1, 2]));
+        assert_eq!(Err(TreeErr::PageOverflow), into_page::<1>(&[1, 2]));
+        assert_eq!(Err(TreeErr::PageOverflow), page_header(true, 1 << 16));
+        assert_eq!(Err(TreeErr::PageOverflow), page_number(NO_PAGE as usize));
+    }
+}
@@ -0,0 +1,492 @@
+#![allow(dead_code)]
+
+use std::{mem, ops::RangeBounds};
+
+use super::search_tree::{Entry, EntryMap, SearchIter, SearchRange, SearchTree};
+use super::OrderedMap;
+use crate::structures::physical::arena::ArenaIndex;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    Red,
+    Black,
+}
+
+// Color queries and the invariant checker, shared with the left-leaning
+// variant.
+impl<K: Ord, V> SearchTree<K, V, Color> {
+    /// Whether the node is red, an empty subtree being black.
+    pub(super) fn is_red(&self, idx: Option<ArenaIndex>) -> bool {
+        idx.is_some_and(|idx| self.node(idx).meta == Color::Red)
+    }
+
+    pub(super) fn set_color(&mut self, idx: ArenaIndex, color: Color) {
+        self.node_mut(idx).meta = color;
+    }
+
+    /// Check the root is black, no red node has a red child, and every path
+    /// from the root to an empty subtree goes through as many black nodes,
+    /// giving that number. With `left_leaning`, check no right child is red
+    /// either.
+    pub(super) fn check_colors(&self, left_leaning: bool) -> Result<usize, String> {
+        if self.is_red(self.root) {
+            return Err("the root is red".to_string());
+        }
+        self.check_colors_from(self.root, left_leaning)
+    }
+
+    fn check_colors_from(
+        &self,
+        idx: Option<ArenaIndex>,
+        left_leaning: bool,
+    ) -> Result<usize, String> {
+        let Some(idx) = idx else {
+            return Ok(0);
+        };
+        let node = self.node(idx);
+        if node.meta == Color::Red && (self.is_red(node.left) || self.is_red(node.right)) {
+            return Err("a red node has a red child".to_string());
+        }
+        if left_leaning && self.is_red(node.right) {
+            return Err("a red node leans right".to_string());
+        }
+        let left = self.check_colors_from(node.left, left_leaning)?;
+        let right = self.check_colors_from(node.right, left_leaning)?;
+        if left != right {
+            return Err("the black heights differ".to_string());
+        }
+        Ok(left + usize::from(node.meta == Color::Black))
+    }
+}
+
+/// Red-black tree: every node is red or black, the root is black, a red node
+/// has no red child, and every path from a node down to an empty subtree goes
+/// through as many black nodes. The longest path being at most twice the
+/// shortest, the height stays below 2 log2(n + 1). Rebalancing is mostly
+/// recoloring, with at most two rotations per insertion and three per
+/// removal.
+pub struct RedBlackTree<K, V> {
+    tree: SearchTree<K, V, Color>,
+}
+
+impl<K: Ord, V> RedBlackTree<K, V> {
+    pub fn new() -> Self {
+        Self {
+            tree: SearchTree::new(),
+        }
+    }
+
+    /// Same as [`new`](Self::new), counting the rotations done to rebalance
+    /// the tree, which [`rotations`](Self::rotations) gives.
+    pub fn with_rotation_counter() -> Self {
+        Self {
+            tree: SearchTree::with_rotation_counter(),
+        }
+    }
+
+    /// Number of rotations done so far, None unless the tree was built with a
+    /// counter.
+    pub fn rotations(&self) -> Option<u64> {
+        self.tree.rotations
+    }
+
+    /// Number of levels, 0 for an empty tree.
+    pub fn height(&self) -> usize {
+        self.tree.height()
+    }
+
+    /// Panic unless the keys are in order, the sizes right, and the colors
+    /// follow the rules of a red-black tree.
+    pub fn assert_invariants(&self) {
+        if let Err(err) = self
+            .tree
+            .check_order_and_sizes()
+            .and_then(|_| self.tree.check_colors(false))
+        {
+            panic!("broken red-black tree: {err}");
+        }
+    }
+
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V, Self> {
+        let search = self.tree.search(&key);
+        Entry::new(self, key, search)
+    }
+
+    // Restore the colors after the red `node` was linked under the last node
+    // of the path from the root.
+    fn fix_insert(&mut self, mut path: Vec<ArenaIndex>, mut node: ArenaIndex) {
+        while let Some(parent) = path.pop() {
+            if !self.tree.is_red(Some(parent)) {
+                break;
+            }
+            let grandparent = path.pop().expect("the root is black");
+            let parent_left = self.tree.node(grandparent).left == Some(parent);
+            let uncle = if parent_left {
+                self.tree.node(grandparent).right
+            } else {
+                self.tree.node(grandparent).left
+            };
+            if let Some(uncle) = uncle.filter(|&uncle| self.tree.is_red(Some(uncle))) {
+                // The black of the grandparent goes down to both its
+                // children, and the grandparent, now red, may be under a red
+                // node in turn.
+                self.tree.set_color(parent, Color::Black);
+                self.tree.set_color(uncle, Color::Black);
+                self.tree.set_color(grandparent, Color::Red);
+                node = grandparent;
+                continue;
+            }
+            let mut parent = parent;
+            if (self.tree.node(parent).left == Some(node)) != parent_left {
+                // The node is an inner grandchild, rotating the parent makes
+                // it an outer one.
+                parent = self.rotate_toward(parent, parent_left);
+                if parent_left {
+                    self.tree.node_mut(grandparent).left = Some(parent);
+                } else {
+                    self.tree.node_mut(grandparent).right = Some(parent);
+                }
+            }
+            self.tree.set_color(parent, Color::Black);
+            self.tree.set_color(grandparent, Color::Red);
+            let top = self.rotate_toward(grandparent, !parent_left);
+            self.tree
+                .relink(path.last().copied(), grandparent, Some(top));
+            break;
+        }
+        let root = self.tree.root.expect("a node was inserted");
+        self.tree.set_color(root, Color::Black);
+    }
+
+    // Restore the colors after a black node was unlinked, `node` taking its
+    // place as the left child, or the right one, of the last node of the
+    // path, or as the root if the path is empty. The paths through `node` are
+    // then short of a black node, which is said to be double black.
+    fn fix_remove(
+        &mut self,
+        mut path: Vec<ArenaIndex>,
+        mut node: Option<ArenaIndex>,
+        mut left: bool,
+    ) {
+        loop {
+            if let Some(red) = node.filter(|&idx| self.tree.is_red(Some(idx))) {
+                self.tree.set_color(red, Color::Black);
+                return;
+            }
+            // At the root, every path is short of a black node.
+            let Some(&parent) = path.last() else {
+                return;
+            };
+            let mut sibling = self.sibling(parent, left);
+            if self.tree.is_red(Some(sibling)) {
+                // A red sibling is rotated above the parent, then the new
+                // sibling is one of its children, which are black.
+                self.tree.set_color(sibling, Color::Black);
+                self.tree.set_color(parent, Color::Red);
+                let top = self.rotate_toward(parent, left);
+                path.pop();
+                self.tree.relink(path.last().copied(), parent, Some(top));
+                path.extend([top, parent]);
+                sibling = self.sibling(parent, left);
+            }
+            let (near, far) = self.nephews(sibling, left);
+            if !self.tree.is_red(near) && !self.tree.is_red(far) {
+                // The sibling turns red so that its paths lose a black node as
+                // well, and the parent is short of one instead.
+                self.tree.set_color(sibling, Color::Red);
+                node = path.pop();
+                left = path
+                    .last()
+                    .is_some_and(|&grandparent| self.tree.node(grandparent).left == node);
+                continue;
+            }
+            if !self.tree.is_red(far) {
+                // Only the inner nephew is red, rotating the sibling makes it
+                // the outer one.
+                self.tree.set_color(near.expect("red node"), Color::Black);
+                self.tree.set_color(sibling, Color::Red);
+                sibling = self.rotate_toward(sibling, !left);
+                if left {
+                    self.tree.node_mut(parent).right = Some(sibling);
+                } else {
+                    self.tree.node_mut(parent).left = Some(sibling);
+                }
+            }
+            // The outer nephew is red: the sibling is rotated in place of the
+            // parent, which turns black and fills in the missing black node.
+            let (_, far) = self.nephews(sibling, left);
+            let color = self.tree.node(parent).meta;
+            self.tree.set_color(sibling, color);
+            self.tree.set_color(parent, Color::Black);
+            self.tree.set_color(far.expect("red node"), Color::Black);
+            let top = self.rotate_toward(parent, left);
+            path.pop();
+            self.tree.relink(path.last().copied(), parent, Some(top));
+            return;
+        }
+    }
+
+    fn sibling(&self, parent: ArenaIndex, left: bool) -> ArenaIndex {
+        let parent = self.tree.node(parent);
+        if left { parent.right } else { parent.left }
+            .expect("a node short of a black node has a sibling")
+    }
+
+    // Children of the sibling of a `left` node, the nearest to it first.
+    fn nephews(&self, sibling: ArenaIndex, left: bool) -> (Option<ArenaIndex>, Option<ArenaIndex>) {
+        let sibling = self.tree.node(sibling);
+        if left {
+            (sibling.left, sibling.right)
+        } else {
+            (sibling.right, sibling.left)
+        }
+    }
+
+    // Rotate the node down toward its left side, or its right one.
+    fn rotate_toward(&mut self, idx: ArenaIndex, left: bool) -> ArenaIndex {
+        if left {
+            self.tree.rotate_left(idx)
+        } else {
+            self.tree.rotate_right(idx)
+        }
+    }
+}
+
+impl<K: Ord, V> Default for RedBlackTree<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord, V> EntryMap<K, V> for RedBlackTree<K, V> {
+    fn entry_at(&self, idx: ArenaIndex) -> (&K, &V) {
+        self.tree.key_value(idx)
+    }
+
+    fn entry_at_mut(&mut self, idx: ArenaIndex) -> (&K, &mut V) {
+        self.tree.key_value_mut(idx)
+    }
+
+    fn insert_under(&mut self, path: Vec<ArenaIndex>, left: bool, key: K, val: V) -> ArenaIndex {
+        let leaf = self.tree.link_leaf(&path, left, key, val, Color::Red);
+        path.iter()
+            .for_each(|&idx| self.tree.node_mut(idx).size += 1);
+        self.fix_insert(path, leaf);
+        leaf
+    }
+
+    /// Remove a node as in an unbalanced tree, a node with two children
+    /// taking the entry of its successor. Unlinking a red node breaks no
+    /// rule, and unlinking a black one leaves its paths short of a black
+    /// node, which is fixed going up.
+    fn remove_at(&mut self, mut path: Vec<ArenaIndex>, idx: ArenaIndex) -> (K, V) {
+        // Node to unlink, which has at most one child.
+        let node = self.tree.node(idx);
+        let (unlinked, child) = match (node.left, node.right) {
+            (Some(_), Some(right)) => {
+                path.push(idx);
+                let mut successor = right;
+                while let Some(left) = self.tree.node(successor).left {
+                    path.push(successor);
+                    successor = left;
+                }
+                (successor, self.tree.node(successor).right)
+            }
+            (child, None) | (None, child) => (idx, child),
+        };
+        let parent = path.last().copied();
+        let left = parent.is_some_and(|parent| self.tree.node(parent).left == Some(unlinked));
+        self.tree.relink(parent, unlinked, child);
+        let removed = self.tree.nodes.remove(unlinked).expect("linked node");
+        path.iter()
+            .for_each(|&idx| self.tree.node_mut(idx).size -= 1);
+        if removed.meta == Color::Black {
+            self.fix_remove(path, child, left);
+        }
+        if unlinked == idx {
+            return (removed.key, removed.val);
+        }
+        let node = self.tree.node_mut(idx);
+        (
+            mem::replace(&mut node.key, removed.key),
+            mem::replace(&mut node.val, removed.val),
+        )
+    }
+}
+
+impl<K: Ord, V> OrderedMap for RedBlackTree<K, V> {
+    type Key = K;
+    type Value = V;
+    type Iter<'a>
+        = SearchIter<'a, K, V, Color>
+    where
+        Self: 'a;
+    type Range<'a>
+        = SearchRange<'a, K, V, Color>
+    where
+        Self: 'a;
+
+    fn insert(&mut self, key: K, val: V) -> Option<V> {
+        match self.tree.search(&key) {
+            (_, Ok(idx)) => Some(mem::replace(&mut self.tree.node_mut(idx).val, val)),
+            (path, Err(left)) => {
+                self.insert_under(path, left, key, val);
+                None
+            }
+        }
+    }
+
+    fn get(&self, key: &K) -> Option<&V> {
+        self.tree.get(key)
+    }
+
+    fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        self.tree.get_mut(key)
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        match self.tree.search(key) {
+            (path, Ok(idx)) => Some(self.remove_at(path, idx).1),
+            (_, Err(_)) => None,
+        }
+    }
+
+    fn min(&self) -> Option<(&K, &V)> {
+        self.tree.min()
+    }
+
+    fn max(&self) -> Option<(&K, &V)> {
+        self.tree.max()
+    }
+
+    fn floor(&self, key: &K) -> Option<(&K, &V)> {
+        self.tree.floor(key)
+    }
+
+    fn ceiling(&self, key: &K) -> Option<(&K, &V)> {
+        self.tree.ceiling(key)
+    }
+
+    fn successor(&self, key: &K) -> Option<(&K, &V)> {
+        self.tree.successor(key)
+    }
+
+    fn predecessor(&self, key: &K) -> Option<(&K, &V)> {
+        self.tree.predecessor(key)
+    }
+
+    fn rank(&self, key: &K) -> usize {
+        self.tree.rank(key)
+    }
+
+    fn select(&self, rank: usize) -> Option<(&K, &V)> {
+        self.tree.select(rank)
+    }
+
+    fn len(&self) -> usize {
+        self.tree.len()
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        self.tree.iter()
+    }
+
+    fn range<R: RangeBounds<K>>(&self, range: R) -> Self::Range<'_> {
+        self.tree.range(range)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn red_black_tree_sorted_keys_stay_balanced() {
+        let mut tree = RedBlackTree::with_rotation_counter();
+        (0..1_000).for_each(|key| {
+            tree.insert(key, ());
+            tree.assert_invariants();
+        });
+        // 2 log2(1001) is just below 20
+        assert!(tree.height() < 20);
+        assert!(tree.rotations().unwrap() < 1_000);
+        (0..1_000).rev().step_by(2).for_each(|key| {
+            assert_eq!(Some(()), tree.remove(&key));
+            tree.assert_invariants();
+        });
+        assert_eq!(500, tree.len());
+    }
+
+    #[test]
+    fn red_black_tree_remove_every_case() {
+        // between them, these orders go through the red sibling, black
+        // nephews, inner and outer nephew cases on both sides
+        let orders = [
+            [3, 1, 5, 0, 2, 4, 6],
+            [0, 1, 2, 3, 4, 5, 6],
+            [6, 5, 4, 3, 2, 1, 0],
+            [3, 5, 1, 6, 4, 2, 0],
+        ];
+        for insert in orders {
+            for remove in orders {
+                let mut tree = RedBlackTree::new();
+                insert.iter().for_each(|&key| {
+                    tree.insert(key, key * 10);
+                });
+                for (removed, key) in remove.iter().enumerate() {
+                    assert_eq!(Some(key * 10), tree.remove(key));
+                    tree.assert_invariants();
+                    assert_eq!(6 - removed, tree.len());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn red_black_tree_entry() {
+        let mut tree = RedBlackTree::new();
+        for word in ["b", "a", "b", "c", "b", "a"] {
+            *tree.entry(word).or_insert(0) += 1;
+        }
+        assert_eq!(
+            vec![(&"a", &2), (&"b", &3), (&"c", &1)],
+            tree.iter().collect::<Vec<_>>()
+        );
+        tree.entry("c")
+            .and_modify(|count| *count *= 10)
+            .or_default();
+        tree.entry("d")
+            .and_modify(|count| *count *= 10)
+            .or_default();
+        assert_eq!(Some(&10), tree.get(&"c"));
+        assert_eq!(Some(&0), tree.get(&"d"));
+        match tree.entry("a") {
+            Entry::Occupied(mut entry) => assert_eq!(2, entry.insert(7)),
+            Entry::Vacant(_) => panic!("a is in the tree"),
+        }
+        assert_eq!(Some(&7), tree.get(&"a"));
+        match tree.entry("b") {
+            Entry::Occupied(entry) => assert_eq!(("b", 3), entry.remove_entry()),
+            Entry::Vacant(_) => panic!("b is in the tree"),
+        }
+        assert_eq!(None, tree.get(&"b"));
+        tree.assert_invariants();
+    }
+
+    #[test]
+    #[should_panic(expected = "red node has a red child")]
+    fn red_black_tree_assert_invariants_catches_red_red() {
+        let mut tree = RedBlackTree::new();
+        (0..3).for_each(|key| {
+            tree.insert(key, ());
+        });
+        // 1 is black at the root with red children, linking a red 3 under 2
+        let root = tree.tree.root.unwrap();
+        let right = tree.tree.node(root).right.unwrap();
+        let leaf = tree.tree.leaf(3, (), Color::Red);
+        tree.tree.node_mut(right).right = Some(leaf);
+        tree.tree.update_size(right);
+        tree.tree.update_size(root);
+        tree.assert_invariants();
+    }
+}
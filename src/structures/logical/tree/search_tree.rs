@@ -0,0 +1,659 @@
+#![allow(dead_code)]
+
+use std::{cmp::Ordering, marker::PhantomData, mem, ops::Bound, ops::RangeBounds};
+
+use crate::structures::logical::stack::{LinkedStack, Stack};
+use crate::structures::physical::arena::{Arena, ArenaIndex};
+
+// Node of a search tree, `meta` being whatever the balancing of the tree
+// needs, a height, a color, or nothing at all. `size` is the number of nodes
+// of the subtree, which gives the rank of a key in O(h).
+pub struct SearchNode<K, V, M> {
+    pub(super) key: K,
+    pub(super) val: V,
+    pub(super) left: Option<ArenaIndex>,
+    pub(super) right: Option<ArenaIndex>,
+    pub(super) size: usize,
+    pub(super) meta: M,
+}
+
+/// Nodes and read-only queries shared by the binary search trees, which only
+/// differ by how they insert and remove keys to stay balanced. Every query is
+/// O(h), h being the height of the tree.
+pub(super) struct SearchTree<K, V, M> {
+    pub(super) nodes: Arena<SearchNode<K, V, M>>,
+    pub(super) root: Option<ArenaIndex>,
+    // Number of rotations done so far, when asked for.
+    pub(super) rotations: Option<u64>,
+}
+
+impl<K: Ord, V, M> SearchTree<K, V, M> {
+    pub(super) fn new() -> Self {
+        Self {
+            nodes: Arena::new(),
+            root: None,
+            rotations: None,
+        }
+    }
+
+    /// Same as [`new`](Self::new), counting the rotations.
+    pub(super) fn with_rotation_counter() -> Self {
+        Self {
+            rotations: Some(0),
+            ..Self::new()
+        }
+    }
+
+    pub(super) fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub(super) fn node(&self, idx: ArenaIndex) -> &SearchNode<K, V, M> {
+        self.nodes.get(idx).expect("broken link in the tree")
+    }
+
+    pub(super) fn node_mut(&mut self, idx: ArenaIndex) -> &mut SearchNode<K, V, M> {
+        self.nodes.get_mut(idx).expect("broken link in the tree")
+    }
+
+    /// New node without children, to be linked by the caller.
+    pub(super) fn leaf(&mut self, key: K, val: V, meta: M) -> ArenaIndex {
+        self.nodes.insert(SearchNode {
+            key,
+            val,
+            left: None,
+            right: None,
+            size: 1,
+            meta,
+        })
+    }
+
+    pub(super) fn size(&self, idx: Option<ArenaIndex>) -> usize {
+        idx.map_or(0, |idx| self.node(idx).size)
+    }
+
+    /// Recompute the size of a node from its children.
+    pub(super) fn update_size(&mut self, idx: ArenaIndex) {
+        let node = self.node(idx);
+        let size = 1 + self.size(node.left) + self.size(node.right);
+        self.node_mut(idx).size = size;
+    }
+
+    /// Replace the child `old` of `parent`, or the root when there is no
+    /// parent, by `new`.
+    pub(super) fn relink(
+        &mut self,
+        parent: Option<ArenaIndex>,
+        old: ArenaIndex,
+        new: Option<ArenaIndex>,
+    ) {
+        match parent {
+            Some(parent) => {
+                let parent = self.node_mut(parent);
+                if parent.left == Some(old) {
+                    parent.left = new;
+                } else {
+                    parent.right = new;
+                }
+            }
+            None => self.root = new,
+        }
+    }
+
+    fn count_rotation(&mut self) {
+        if let Some(rotations) = &mut self.rotations {
+            *rotations += 1;
+        }
+    }
+
+    /// Make the right child of the node the root of the subtree, and give it
+    /// back. The sizes are kept up to date, the metadata is up to the caller.
+    pub(super) fn rotate_left(&mut self, idx: ArenaIndex) -> ArenaIndex {
+        self.count_rotation();
+        let pivot = self
+            .node(idx)
+            .right
+            .expect("rotating without a right child");
+        self.node_mut(idx).right = self.node(pivot).left;
+        self.node_mut(pivot).left = Some(idx);
+        self.update_size(idx);
+        self.update_size(pivot);
+        pivot
+    }
+
+    /// Mirror of [`rotate_left`](Self::rotate_left).
+    pub(super) fn rotate_right(&mut self, idx: ArenaIndex) -> ArenaIndex {
+        self.count_rotation();
+        let pivot = self.node(idx).left.expect("rotating without a left child");
+        self.node_mut(idx).left = self.node(pivot).right;
+        self.node_mut(pivot).right = Some(idx);
+        self.update_size(idx);
+        self.update_size(pivot);
+        pivot
+    }
+
+    /// Number of levels, 0 for an empty tree. Counted level by level rather
+    /// than recursively, as an unbalanced tree may be a list of any length.
+    pub(super) fn height(&self) -> usize {
+        let mut height = 0;
+        let mut level = Vec::from_iter(self.root);
+        while !level.is_empty() {
+            height += 1;
+            level = level
+                .into_iter()
+                .flat_map(|idx| {
+                    let node = self.node(idx);
+                    node.left.into_iter().chain(node.right)
+                })
+                .collect();
+        }
+        height
+    }
+
+    pub(super) fn find(&self, key: &K) -> Option<ArenaIndex> {
+        let mut cur = self.root;
+        while let Some(idx) = cur {
+            let node = self.node(idx);
+            cur = match key.cmp(&node.key) {
+                Ordering::Less => node.left,
+                Ordering::Greater => node.right,
+                Ordering::Equal => return Some(idx),
+            };
+        }
+        None
+    }
+
+    pub(super) fn get(&self, key: &K) -> Option<&V> {
+        self.find(key).map(|idx| &self.node(idx).val)
+    }
+
+    pub(super) fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let idx = self.find(key)?;
+        Some(&mut self.node_mut(idx).val)
+    }
+
+    pub(super) fn key_value(&self, idx: ArenaIndex) -> (&K, &V) {
+        let node = self.node(idx);
+        (&node.key, &node.val)
+    }
+
+    pub(super) fn key_value_mut(&mut self, idx: ArenaIndex) -> (&K, &mut V) {
+        let node = self.node_mut(idx);
+        (&node.key, &mut node.val)
+    }
+
+    /// Link a new leaf under the last node of the path from the root, as its
+    /// left child or its right one, or as the root when the path is empty.
+    /// The sizes are up to the caller.
+    pub(super) fn link_leaf(
+        &mut self,
+        path: &[ArenaIndex],
+        left: bool,
+        key: K,
+        val: V,
+        meta: M,
+    ) -> ArenaIndex {
+        let leaf = self.leaf(key, val, meta);
+        match path.last() {
+            Some(&parent) if left => self.node_mut(parent).left = Some(leaf),
+            Some(&parent) => self.node_mut(parent).right = Some(leaf),
+            None => self.root = Some(leaf),
+        }
+        leaf
+    }
+
+    /// Insert a key as an unbalanced tree would, giving back its previous
+    /// value, or else linking a new leaf and giving the path from the root
+    /// down to it, whose subtrees all grew by a node, for the caller to
+    /// update. The path is empty when the key was already there.
+    pub(super) fn insert_unbalanced(
+        &mut self,
+        key: K,
+        val: V,
+        meta: M,
+    ) -> (Option<V>, Vec<ArenaIndex>) {
+        match self.search(&key) {
+            (_, Ok(idx)) => (
+                Some(mem::replace(&mut self.node_mut(idx).val, val)),
+                Vec::new(),
+            ),
+            (path, Err(left)) => {
+                self.link_leaf(&path, left, key, val, meta);
+                (None, path)
+            }
+        }
+    }
+
+    /// Remove a key as an unbalanced tree would: a leaf is just unlinked, a
+    /// node with one child is replaced by that child, and a node with two
+    /// children takes the entry of its successor, the least key of its right
+    /// subtree, which has no left child, so it falls in one of the first two
+    /// cases. Gives the entry of the key, and the path from the root down to
+    /// the node which was unlinked, whose subtrees all lost a node, for the
+    /// caller to update.
+    pub(super) fn remove_unbalanced(&mut self, key: &K) -> Option<((K, V), Vec<ArenaIndex>)> {
+        let (mut path, found) = self.search(key);
+        let idx = found.ok()?;
+        let node = self.node(idx);
+        let entry = match (node.left, node.right) {
+            (Some(_), Some(right)) => {
+                path.push(idx);
+                let mut successor = right;
+                while let Some(left) = self.node(successor).left {
+                    path.push(successor);
+                    successor = left;
+                }
+                let successor_right = self.node(successor).right;
+                self.relink(path.last().copied(), successor, successor_right);
+                let successor = self.nodes.remove(successor).expect("linked node");
+                let node = self.node_mut(idx);
+                (
+                    mem::replace(&mut node.key, successor.key),
+                    mem::replace(&mut node.val, successor.val),
+                )
+            }
+            (child, None) | (None, child) => {
+                self.relink(path.last().copied(), idx, child);
+                let node = self.nodes.remove(idx).expect("linked node");
+                (node.key, node.val)
+            }
+        };
+        Some((entry, path))
+    }
+
+    /// Search a key, giving the path from the root down to its node, which
+    /// is not part of the path, or else to the node it would go under, along
+    /// with whether it would be its left child.
+    pub(super) fn search(&self, key: &K) -> (Vec<ArenaIndex>, Result<ArenaIndex, bool>) {
+        let mut path = Vec::new();
+        let mut cur = self.root;
+        let mut left = false;
+        while let Some(idx) = cur {
+            let node = self.node(idx);
+            (cur, left) = match key.cmp(&node.key) {
+                Ordering::Less => (node.left, true),
+                Ordering::Greater => (node.right, false),
+                Ordering::Equal => return (path, Ok(idx)),
+            };
+            path.push(idx);
+        }
+        (path, Err(left))
+    }
+
+    pub(super) fn min_from(&self, mut idx: ArenaIndex) -> ArenaIndex {
+        while let Some(left) = self.node(idx).left {
+            idx = left;
+        }
+        idx
+    }
+
+    pub(super) fn max_from(&self, mut idx: ArenaIndex) -> ArenaIndex {
+        while let Some(right) = self.node(idx).right {
+            idx = right;
+        }
+        idx
+    }
+
+    pub(super) fn min(&self) -> Option<(&K, &V)> {
+        self.root.map(|root| self.key_value(self.min_from(root)))
+    }
+
+    pub(super) fn max(&self) -> Option<(&K, &V)> {
+        self.root.map(|root| self.key_value(self.max_from(root)))
+    }
+
+    // Greatest key lower than the given one, or equal if `or_equal`.
+    fn below(&self, key: &K, or_equal: bool) -> Option<ArenaIndex> {
+        let mut cur = self.root;
+        let mut best = None;
+        while let Some(idx) = cur {
+            let node = self.node(idx);
+            match key.cmp(&node.key) {
+                Ordering::Equal if or_equal => return Some(idx),
+                Ordering::Greater => {
+                    best = Some(idx);
+                    cur = node.right;
+                }
+                _ => cur = node.left,
+            }
+        }
+        best
+    }
+
+    // Least key greater than the given one, or equal if `or_equal`.
+    fn above(&self, key: &K, or_equal: bool) -> Option<ArenaIndex> {
+        let mut cur = self.root;
+        let mut best = None;
+        while let Some(idx) = cur {
+            let node = self.node(idx);
+            match key.cmp(&node.key) {
+                Ordering::Equal if or_equal => return Some(idx),
+                Ordering::Less => {
+                    best = Some(idx);
+                    cur = node.left;
+                }
+                _ => cur = node.right,
+            }
+        }
+        best
+    }
+
+    pub(super) fn floor(&self, key: &K) -> Option<(&K, &V)> {
+        self.below(key, true).map(|idx| self.key_value(idx))
+    }
+
+    pub(super) fn ceiling(&self, key: &K) -> Option<(&K, &V)> {
+        self.above(key, true).map(|idx| self.key_value(idx))
+    }
+
+    pub(super) fn successor(&self, key: &K) -> Option<(&K, &V)> {
+        self.above(key, false).map(|idx| self.key_value(idx))
+    }
+
+    pub(super) fn predecessor(&self, key: &K) -> Option<(&K, &V)> {
+        self.below(key, false).map(|idx| self.key_value(idx))
+    }
+
+    pub(super) fn rank(&self, key: &K) -> usize {
+        let mut cur = self.root;
+        let mut rank = 0;
+        while let Some(idx) = cur {
+            let node = self.node(idx);
+            match key.cmp(&node.key) {
+                Ordering::Less => cur = node.left,
+                Ordering::Greater => {
+                    rank += 1 + self.size(node.left);
+                    cur = node.right;
+                }
+                Ordering::Equal => return rank + self.size(node.left),
+            }
+        }
+        rank
+    }
+
+    pub(super) fn select(&self, mut rank: usize) -> Option<(&K, &V)> {
+        let mut cur = self.root;
+        while let Some(idx) = cur {
+            let node = self.node(idx);
+            let left = self.size(node.left);
+            match rank.cmp(&left) {
+                Ordering::Less => cur = node.left,
+                Ordering::Greater => {
+                    rank -= left + 1;
+                    cur = node.right;
+                }
+                Ordering::Equal => return Some(self.key_value(idx)),
+            }
+        }
+        None
+    }
+
+    pub(super) fn iter(&self) -> SearchIter<'_, K, V, M> {
+        SearchIter::seek(self, Bound::Unbounded)
+    }
+
+    pub(super) fn range<R: RangeBounds<K>>(&self, range: R) -> SearchRange<'_, K, V, M> {
+        // The end is turned into the last key of the range found in the tree,
+        // so that the iterator doesn't have to own a copy of the bound.
+        let last = match range.end_bound() {
+            Bound::Included(key) => self.below(key, true).map(Some),
+            Bound::Excluded(key) => self.below(key, false).map(Some),
+            Bound::Unbounded => Some(None),
+        };
+        let mut iter = SearchIter::seek(self, range.start_bound());
+        let last = match last {
+            Some(last) => last.map(|idx| &self.node(idx).key),
+            None => {
+                // No key of the tree is before the end.
+                iter.stack = LinkedStack::new();
+                None
+            }
+        };
+        SearchRange { iter, last }
+    }
+
+    /// Check the keys are in order and the sizes right, giving the height.
+    pub(super) fn check_order_and_sizes(&self) -> Result<usize, String> {
+        self.check_from(self.root, None, None)
+    }
+
+    fn check_from(
+        &self,
+        idx: Option<ArenaIndex>,
+        low: Option<&K>,
+        high: Option<&K>,
+    ) -> Result<usize, String> {
+        let Some(idx) = idx else {
+            return Ok(0);
+        };
+        let node = self.node(idx);
+        if low.is_some_and(|low| node.key <= *low) || high.is_some_and(|high| node.key >= *high) {
+            return Err("keys are out of order".to_string());
+        }
+        let left = self.check_from(node.left, low, Some(&node.key))?;
+        let right = self.check_from(node.right, Some(&node.key), high)?;
+        if node.size != 1 + self.size(node.left) + self.size(node.right) {
+            return Err("the size of a subtree is wrong".to_string());
+        }
+        Ok(1 + left.max(right))
+    }
+}
+
+/// In-order iterator over the entries of a search tree, with a stack holding
+/// the nodes whose left subtree is being gone through.
+pub struct SearchIter<'a, K, V, M> {
+    tree: &'a SearchTree<K, V, M>,
+    stack: LinkedStack<ArenaIndex>,
+}
+
+impl<'a, K: Ord, V, M> SearchIter<'a, K, V, M> {
+    // Start at the first key after the bound, stacking the nodes on the way
+    // whose key is after it as well.
+    fn seek(tree: &'a SearchTree<K, V, M>, start: Bound<&K>) -> Self {
+        let mut stack = LinkedStack::new();
+        let mut cur = tree.root;
+        while let Some(idx) = cur {
+            let node = tree.node(idx);
+            let after = match start {
+                Bound::Included(key) => node.key >= *key,
+                Bound::Excluded(key) => node.key > *key,
+                Bound::Unbounded => true,
+            };
+            if after {
+                stack.push(idx).expect("the stack is unbounded");
+                cur = node.left;
+            } else {
+                cur = node.right;
+            }
+        }
+        Self { tree, stack }
+    }
+}
+
+impl<'a, K: Ord, V, M> Iterator for SearchIter<'a, K, V, M> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let idx = self.stack.pop()?;
+        let node = self.tree.node(idx);
+        let mut cur = node.right;
+        while let Some(child) = cur {
+            self.stack.push(child).expect("the stack is unbounded");
+            cur = self.tree.node(child).left;
+        }
+        Some((&node.key, &node.val))
+    }
+}
+
+/// Entries of a search tree whose keys are in a range, in order.
+pub struct SearchRange<'a, K, V, M> {
+    iter: SearchIter<'a, K, V, M>,
+    // Last key to give, None when the range is not bounded at the end.
+    last: Option<&'a K>,
+}
+
+impl<'a, K: Ord, V, M> Iterator for SearchRange<'a, K, V, M> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (key, val) = self.iter.next()?;
+        if self.last.is_some_and(|last| key > last) {
+            self.iter.stack = LinkedStack::new();
+            return None;
+        }
+        Some((key, val))
+    }
+}
+
+/// Map built on a search tree whose keys can be reached through an
+/// [`Entry`], giving its nodes, and inserting or removing them the way it
+/// keeps balanced.
+pub trait EntryMap<K, V> {
+    fn entry_at(&self, idx: ArenaIndex) -> (&K, &V);
+
+    fn entry_at_mut(&mut self, idx: ArenaIndex) -> (&K, &mut V);
+
+    /// Insert a key missing from the map under the last node of the path
+    /// from the root, as its left child or its right one, or as the root
+    /// when the path is empty, giving the node of the key.
+    fn insert_under(&mut self, path: Vec<ArenaIndex>, left: bool, key: K, val: V) -> ArenaIndex;
+
+    /// Remove the node at the end of the path from the root, giving its
+    /// entry.
+    fn remove_at(&mut self, path: Vec<ArenaIndex>, idx: ArenaIndex) -> (K, V);
+}
+
+/// Entry of a key in a map `T`, whether the key is in the map or not, to
+/// update, insert or remove its value with a single search: the entry keeps
+/// the path the search went down, which inserting or removing starts from.
+pub enum Entry<'a, K, V, T> {
+    Occupied(OccupiedEntry<'a, K, V, T>),
+    Vacant(VacantEntry<'a, K, V, T>),
+}
+
+impl<'a, K: 'a, V: 'a, T: EntryMap<K, V>> Entry<'a, K, V, T> {
+    /// Entry of a key given the outcome of its search, see
+    /// [`SearchTree::search`]. The key is dropped when it is in the map.
+    pub(super) fn new(
+        map: &'a mut T,
+        key: K,
+        (path, found): (Vec<ArenaIndex>, Result<ArenaIndex, bool>),
+    ) -> Self {
+        match found {
+            Ok(idx) => Entry::Occupied(OccupiedEntry {
+                map,
+                path,
+                idx,
+                entry: PhantomData,
+            }),
+            Err(left) => Entry::Vacant(VacantEntry {
+                map,
+                key,
+                path,
+                left,
+                val: PhantomData,
+            }),
+        }
+    }
+
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Occupied(entry) => entry.key(),
+            Entry::Vacant(entry) => &entry.key,
+        }
+    }
+
+    pub fn or_insert(self, val: V) -> &'a mut V {
+        self.or_insert_with(|| val)
+    }
+
+    pub fn or_insert_with(self, f: impl FnOnce() -> V) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(f()),
+        }
+    }
+
+    pub fn or_default(self) -> &'a mut V
+    where
+        V: Default,
+    {
+        self.or_insert_with(V::default)
+    }
+
+    /// Update the value if the key is in the map.
+    pub fn and_modify(mut self, f: impl FnOnce(&mut V)) -> Self {
+        if let Entry::Occupied(entry) = &mut self {
+            f(entry.get_mut());
+        }
+        self
+    }
+}
+
+/// Entry of a key in the map, holding on to the map, the node of the key and
+/// the path from the root down to it.
+pub struct OccupiedEntry<'a, K, V, T> {
+    map: &'a mut T,
+    path: Vec<ArenaIndex>,
+    idx: ArenaIndex,
+    entry: PhantomData<(K, V)>,
+}
+
+impl<'a, K: 'a, V: 'a, T: EntryMap<K, V>> OccupiedEntry<'a, K, V, T> {
+    pub fn key(&self) -> &K {
+        self.map.entry_at(self.idx).0
+    }
+
+    pub fn get(&self) -> &V {
+        self.map.entry_at(self.idx).1
+    }
+
+    pub fn get_mut(&mut self) -> &mut V {
+        self.map.entry_at_mut(self.idx).1
+    }
+
+    pub fn into_mut(self) -> &'a mut V {
+        self.map.entry_at_mut(self.idx).1
+    }
+
+    /// Replace the value, giving back the previous one.
+    pub fn insert(&mut self, val: V) -> V {
+        mem::replace(self.get_mut(), val)
+    }
+
+    /// Remove the key from the map, giving back its value.
+    pub fn remove(self) -> V {
+        self.remove_entry().1
+    }
+
+    /// Remove the key from the map, giving back the key and its value.
+    pub fn remove_entry(self) -> (K, V) {
+        self.map.remove_at(self.path, self.idx)
+    }
+}
+
+/// Entry of a key missing from the map, holding on to the map and the path
+/// from the root down to the node the key would go under.
+pub struct VacantEntry<'a, K, V, T> {
+    map: &'a mut T,
+    key: K,
+    path: Vec<ArenaIndex>,
+    // Whether the key would be the left child of the last node of the path.
+    left: bool,
+    val: PhantomData<V>,
+}
+
+impl<'a, K: 'a, V: 'a, T: EntryMap<K, V>> VacantEntry<'a, K, V, T> {
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    pub fn into_key(self) -> K {
+        self.key
+    }
+
+    pub fn insert(self, val: V) -> &'a mut V {
+        let idx = self.map.insert_under(self.path, self.left, self.key, val);
+        self.map.entry_at_mut(idx).1
+    }
+}
@@ -0,0 +1,386 @@
+#![allow(dead_code)]
+
+use std::fmt::Display;
+
+/// Handle to a value stored in an [`Arena`]. On top of the slot position, it
+/// carries the generation of the slot at the time of the insertion, this way
+/// a handle kept after a removal can be detected as stale, even if the slot
+/// has been reused by another value since.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ArenaIndex {
+    idx: usize,
+    generation: u64,
+}
+
+impl ArenaIndex {
+    /// Position of the slot in the arena, mostly useful for debugging or to
+    /// build side tables indexed by slot.
+    pub fn slot(&self) -> usize {
+        self.idx
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+}
+
+#[derive(Debug)]
+enum ArenaSlot<T> {
+    Occupied {
+        generation: u64,
+        val: T,
+    },
+    Free {
+        generation: u64,
+        next_free: Option<usize>,
+    },
+}
+
+/// Arena (also called slab) allocator, values are stored in a contiguous
+/// `Vec` of slots and freed slots are chained together in a free list so they
+/// can be reused by the next insertions. Apart from the amortized growth of
+/// the `Vec`, inserting or removing a value never touches the allocator, which
+/// makes it a good backend for node based structures like linked lists or
+/// trees: nodes address each other by [`ArenaIndex`] instead of pointers.
+///
+/// Insertion, removal and access are all O(1).
+#[derive(Debug)]
+pub struct Arena<T> {
+    slots: Vec<ArenaSlot<T>>,
+    free_head: Option<usize>,
+    len: usize,
+}
+
+impl<T> Arena<T> {
+    pub fn new() -> Self {
+        Self {
+            slots: Vec::new(),
+            free_head: None,
+            len: 0,
+        }
+    }
+
+    pub fn with_capacity(cap: usize) -> Self {
+        Self {
+            slots: Vec::with_capacity(cap),
+            free_head: None,
+            len: 0,
+        }
+    }
+
+    /// Number of live values in the arena.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Number of slots, free or occupied, currently owned by the arena.
+    pub fn cap(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Store the value in a free slot if there is one, otherwise a new slot is
+    /// appended. The returned index is the only way to reach the value.
+    pub fn insert(&mut self, val: T) -> ArenaIndex {
+        self.insert_with(|_| val)
+    }
+
+    /// Same as insert, but the value is built knowing the index it will be
+    /// stored at, which is handy for values pointing to themselves, or pointed
+    /// by a value which has to know them back.
+    pub fn insert_with(&mut self, build: impl FnOnce(ArenaIndex) -> T) -> ArenaIndex {
+        let idx = match self.free_head {
+            Some(idx) => match self.slots[idx] {
+                ArenaSlot::Free { generation, .. } => ArenaIndex { idx, generation },
+                ArenaSlot::Occupied { .. } => unreachable!("free list points to a used slot"),
+            },
+            None => ArenaIndex {
+                idx: self.slots.len(),
+                generation: 0,
+            },
+        };
+        let val = build(idx);
+        let occupied = ArenaSlot::Occupied {
+            generation: idx.generation,
+            val,
+        };
+        match self.free_head {
+            Some(_) => {
+                if let ArenaSlot::Free { next_free, .. } =
+                    std::mem::replace(&mut self.slots[idx.idx], occupied)
+                {
+                    self.free_head = next_free;
+                }
+            }
+            None => self.slots.push(occupied),
+        }
+        self.len += 1;
+        idx
+    }
+
+    /// Remove the value pointed by the index. The generation of the slot is
+    /// bumped, so every copy of `idx` becomes stale.
+    pub fn remove(&mut self, idx: ArenaIndex) -> Result<T, ArenaErr> {
+        self.validate(idx)?;
+        let freed = ArenaSlot::Free {
+            generation: idx.generation + 1,
+            next_free: self.free_head,
+        };
+        match std::mem::replace(&mut self.slots[idx.idx], freed) {
+            ArenaSlot::Occupied { val, .. } => {
+                self.free_head = Some(idx.idx);
+                self.len -= 1;
+                Ok(val)
+            }
+            ArenaSlot::Free { .. } => unreachable!("slot checked as occupied"),
+        }
+    }
+
+    pub fn contains(&self, idx: ArenaIndex) -> bool {
+        self.validate(idx).is_ok()
+    }
+
+    pub fn get(&self, idx: ArenaIndex) -> Option<&T> {
+        match self.slots.get(idx.idx) {
+            Some(ArenaSlot::Occupied { generation, val }) if *generation == idx.generation => {
+                Some(val)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn get_mut(&mut self, idx: ArenaIndex) -> Option<&mut T> {
+        match self.slots.get_mut(idx.idx) {
+            Some(ArenaSlot::Occupied { generation, val }) if *generation == idx.generation => {
+                Some(val)
+            }
+            _ => None,
+        }
+    }
+
+    /// Remove every value, all the indices given so far become stale.
+    pub fn clear(&mut self) {
+        let slots = std::mem::take(&mut self.slots);
+        self.free_head = None;
+        self.len = 0;
+        for (idx, slot) in slots.into_iter().enumerate().rev() {
+            let generation = match slot {
+                ArenaSlot::Occupied { generation, .. } => generation + 1,
+                ArenaSlot::Free { generation, .. } => generation,
+            };
+            self.slots.push(ArenaSlot::Free {
+                generation,
+                next_free: self.free_head,
+            });
+            self.free_head = Some(idx);
+        }
+        // Slots have been pushed in reverse order, so put them back in place,
+        // the free list was built with the original positions.
+        self.slots.reverse();
+    }
+
+    pub fn iter(&self) -> ArenaIter<'_, T> {
+        ArenaIter {
+            inner: self.slots.iter().enumerate(),
+        }
+    }
+
+    /// Tell why an index can't be used on this arena, if it can't.
+    pub fn validate(&self, idx: ArenaIndex) -> Result<(), ArenaErr> {
+        match self.slots.get(idx.idx) {
+            None => Err(ArenaErr::IndexOutOfBounds),
+            Some(ArenaSlot::Occupied { generation, .. }) if *generation == idx.generation => Ok(()),
+            Some(_) => Err(ArenaErr::StaleIndex),
+        }
+    }
+}
+
+impl<T> Default for Arena<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> IntoIterator for Arena<T> {
+    type Item = (ArenaIndex, T);
+    type IntoIter = ArenaIntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        ArenaIntoIter {
+            inner: self.slots.into_iter().enumerate(),
+        }
+    }
+}
+
+pub struct ArenaIter<'a, T> {
+    inner: std::iter::Enumerate<std::slice::Iter<'a, ArenaSlot<T>>>,
+}
+
+impl<'a, T> Iterator for ArenaIter<'a, T> {
+    type Item = (ArenaIndex, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for (idx, slot) in self.inner.by_ref() {
+            if let ArenaSlot::Occupied { generation, val } = slot {
+                let generation = *generation;
+                return Some((ArenaIndex { idx, generation }, val));
+            }
+        }
+        None
+    }
+}
+
+pub struct ArenaIntoIter<T> {
+    inner: std::iter::Enumerate<std::vec::IntoIter<ArenaSlot<T>>>,
+}
+
+impl<T> Iterator for ArenaIntoIter<T> {
+    type Item = (ArenaIndex, T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for (idx, slot) in self.inner.by_ref() {
+            if let ArenaSlot::Occupied { generation, val } = slot {
+                return Some((ArenaIndex { idx, generation }, val));
+            }
+        }
+        None
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ArenaErr {
+    IndexOutOfBounds,
+    StaleIndex,
+}
+
+impl std::error::Error for ArenaErr {}
+
+impl Display for ArenaErr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArenaErr::IndexOutOfBounds => {
+                write!(f, "The index does not point to a slot of the arena")
+            }
+            ArenaErr::StaleIndex => write!(
+                f,
+                "The index points to a value which has been removed from the arena"
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn arena_new() {
+        let arena = Arena::<i32>::new();
+        assert!(arena.is_empty());
+        assert_eq!(0, arena.cap());
+    }
+
+    #[test]
+    fn arena_insert_get() {
+        let mut arena = Arena::new();
+        let one = arena.insert(1);
+        let two = arena.insert(2);
+        assert_eq!(2, arena.len());
+        assert_eq!(Some(&1), arena.get(one));
+        assert_eq!(Some(&2), arena.get(two));
+    }
+
+    #[test]
+    fn arena_get_mut() {
+        let mut arena = Arena::new();
+        let one = arena.insert(1);
+        arena.get_mut(one).map(|v| *v = 10).unwrap();
+        assert_eq!(Some(&10), arena.get(one));
+    }
+
+    #[test]
+    fn arena_remove() {
+        let mut arena = Arena::new();
+        let one = arena.insert(1);
+        assert_eq!(Ok(1), arena.remove(one));
+        assert_eq!(0, arena.len());
+        assert_eq!(None, arena.get(one));
+        assert_eq!(Err(ArenaErr::StaleIndex), arena.remove(one));
+    }
+
+    #[test]
+    fn arena_reuse_free_slot() {
+        let mut arena = Arena::new();
+        let one = arena.insert(1);
+        arena.insert(2);
+        arena.remove(one).unwrap();
+        let three = arena.insert(3);
+        // the freed slot is reused, no new slot is appended
+        assert_eq!(2, arena.cap());
+        assert_eq!(one.slot(), three.slot());
+        assert_ne!(one, three);
+        assert_eq!(None, arena.get(one));
+        assert_eq!(Some(&3), arena.get(three));
+    }
+
+    #[test]
+    fn arena_out_of_bounds() {
+        let mut arena = Arena::new();
+        let one = arena.insert(1);
+        let mut other = Arena::<i32>::new();
+        assert_eq!(Err(ArenaErr::IndexOutOfBounds), other.remove(one));
+        assert!(!other.contains(one));
+    }
+
+    #[test]
+    fn arena_clear() {
+        let mut arena = Arena::new();
+        let one = arena.insert(1);
+        let two = arena.insert(2);
+        arena.clear();
+        assert!(arena.is_empty());
+        assert!(!arena.contains(one));
+        assert!(!arena.contains(two));
+        let three = arena.insert(3);
+        assert_eq!(one.slot(), three.slot());
+        assert_eq!(2, arena.cap());
+    }
+
+    #[test]
+    fn arena_iter() {
+        let mut arena = Arena::new();
+        let one = arena.insert(1);
+        let two = arena.insert(2);
+        let three = arena.insert(3);
+        arena.remove(two).unwrap();
+        let mut iter = arena.iter();
+        assert_eq!(Some((one, &1)), iter.next());
+        assert_eq!(Some((three, &3)), iter.next());
+        assert_eq!(None, iter.next());
+    }
+
+    #[test]
+    fn arena_into_iter() {
+        let mut arena = Arena::new();
+        let one = arena.insert(1);
+        let two = arena.insert(2);
+        arena.remove(one).unwrap();
+        assert_eq!(vec![(two, 2)], arena.into_iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn arena_insert_with() {
+        let mut arena = Arena::new();
+        let one = arena.insert(None);
+        arena.remove(one).unwrap();
+        // the value knows where it is stored, even in a reused slot
+        let itself = arena.insert_with(Some);
+        assert_eq!(Some(&Some(itself)), arena.get(itself));
+        let other = arena.insert_with(Some);
+        assert_eq!(Some(&Some(other)), arena.get(other));
+        assert_eq!(2, arena.len());
+    }
+}
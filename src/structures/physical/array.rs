@@ -4,12 +4,14 @@ use std::cmp::Ordering;
 use std::{
     alloc,
     io::Write,
-    isize, mem,
+    mem,
     ops::{Deref, DerefMut},
     process::id,
     ptr::NonNull,
 };
 
+/// Growable array over a raw allocation. It doesn't take zero sized types,
+/// such as the () values of a set: wrap them in an Option, or use a Vec.
 pub struct Array<T> {
     ptr: NonNull<T>,
     cap: usize,
@@ -37,7 +39,7 @@ impl<T> Array<T> {
             // SAFETY: We are sure that the pointer is not null or it is
             // pointing to an invalid address, because we use an assertion
             // on the idx value at the beginning of the fn.
-            return self.ptr.as_ptr().add(idx).as_ref().unwrap();
+            self.ptr.as_ptr().add(idx).as_ref().unwrap()
         }
         // To avoid unsafe, and because we impl Deref en it returns a slice of
         // the array, we can use the brackets syntax, Slices implements Index.
@@ -299,6 +301,12 @@ impl<T> DerefMut for Array<T> {
     }
 }
 
+// The raw pointer makes the array neither Send nor Sync by default, but the
+// array owns its items like a Vec<T> does, so it can cross threads as long as
+// the items can.
+unsafe impl<T: Send> Send for Array<T> {}
+unsafe impl<T: Sync> Sync for Array<T> {}
+
 // We implement Drop here to be sure to not leak lot of resources.
 impl<T> Drop for Array<T> {
     fn drop(&mut self) {
@@ -0,0 +1,442 @@
+#![allow(dead_code)]
+
+use super::LinkedList;
+use crate::structures::physical::arena::{Arena, ArenaErr, ArenaIndex};
+
+#[derive(Debug)]
+struct DoublyLinkedNode<T> {
+    val: T,
+    prev: Option<ArenaIndex>,
+    next: Option<ArenaIndex>,
+}
+
+/// Doubly linked list whose nodes live in an [`Arena`] and are linked by
+/// generational indices instead of pointers, so there is no `Box` and no
+/// `unsafe` involved, and pushing a value doesn't allocate a node on its own.
+///
+/// Every insertion returns an [`ArenaIndex`] acting as a stable handle on the
+/// node, it stays valid whatever happens to the other nodes, until the node
+/// itself is removed. Using a handle after that returns an [`ArenaErr`]
+/// instead of reaching (or corrupting) another node.
+#[derive(Debug)]
+pub struct DoublyLinkedList<T> {
+    nodes: Arena<DoublyLinkedNode<T>>,
+    head: Option<ArenaIndex>,
+    tail: Option<ArenaIndex>,
+}
+
+impl<T> DoublyLinkedList<T> {
+    pub fn new() -> Self {
+        Self {
+            nodes: Arena::new(),
+            head: None,
+            tail: None,
+        }
+    }
+
+    pub fn with_capacity(cap: usize) -> Self {
+        Self {
+            nodes: Arena::with_capacity(cap),
+            head: None,
+            tail: None,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Insert a value before the head, O(1).
+    pub fn push_front(&mut self, val: T) -> ArenaIndex {
+        let new_head = self.nodes.insert(DoublyLinkedNode {
+            val,
+            prev: None,
+            next: self.head,
+        });
+        match self.head {
+            Some(old_head) => self.node_mut(old_head).prev = Some(new_head),
+            None => self.tail = Some(new_head),
+        }
+        self.head = Some(new_head);
+        new_head
+    }
+
+    /// Insert a value after the tail, O(1).
+    pub fn push_back(&mut self, val: T) -> ArenaIndex {
+        let new_tail = self.nodes.insert(DoublyLinkedNode {
+            val,
+            prev: self.tail,
+            next: None,
+        });
+        match self.tail {
+            Some(old_tail) => self.node_mut(old_tail).next = Some(new_tail),
+            None => self.head = Some(new_tail),
+        }
+        self.tail = Some(new_tail);
+        new_tail
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.head.map(|head| self.unlink(head))
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        self.tail.map(|tail| self.unlink(tail))
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        self.head.map(|head| &self.node(head).val)
+    }
+
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        self.head.map(|head| &mut self.node_mut(head).val)
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        self.tail.map(|tail| &self.node(tail).val)
+    }
+
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        self.tail.map(|tail| &mut self.node_mut(tail).val)
+    }
+
+    /// Handle of the first node, if any.
+    pub fn front_handle(&self) -> Option<ArenaIndex> {
+        self.head
+    }
+
+    /// Handle of the last node, if any.
+    pub fn back_handle(&self) -> Option<ArenaIndex> {
+        self.tail
+    }
+
+    /// Handle of the node following the one pointed by `handle`.
+    pub fn next_handle(&self, handle: ArenaIndex) -> Result<Option<ArenaIndex>, ArenaErr> {
+        self.checked_node(handle).map(|node| node.next)
+    }
+
+    /// Handle of the node preceding the one pointed by `handle`.
+    pub fn prev_handle(&self, handle: ArenaIndex) -> Result<Option<ArenaIndex>, ArenaErr> {
+        self.checked_node(handle).map(|node| node.prev)
+    }
+
+    pub fn get(&self, handle: ArenaIndex) -> Result<&T, ArenaErr> {
+        self.checked_node(handle).map(|node| &node.val)
+    }
+
+    pub fn get_mut(&mut self, handle: ArenaIndex) -> Result<&mut T, ArenaErr> {
+        self.checked_node(handle)?;
+        Ok(&mut self.node_mut(handle).val)
+    }
+
+    /// Insert a value right after the node pointed by `handle`, O(1).
+    pub fn insert_after(&mut self, handle: ArenaIndex, val: T) -> Result<ArenaIndex, ArenaErr> {
+        let next = self.checked_node(handle)?.next;
+        let new_node = self.nodes.insert(DoublyLinkedNode {
+            val,
+            prev: Some(handle),
+            next,
+        });
+        self.node_mut(handle).next = Some(new_node);
+        match next {
+            Some(next) => self.node_mut(next).prev = Some(new_node),
+            None => self.tail = Some(new_node),
+        }
+        Ok(new_node)
+    }
+
+    /// Insert a value right before the node pointed by `handle`, O(1).
+    pub fn insert_before(&mut self, handle: ArenaIndex, val: T) -> Result<ArenaIndex, ArenaErr> {
+        let prev = self.checked_node(handle)?.prev;
+        let new_node = self.nodes.insert(DoublyLinkedNode {
+            val,
+            prev,
+            next: Some(handle),
+        });
+        self.node_mut(handle).prev = Some(new_node);
+        match prev {
+            Some(prev) => self.node_mut(prev).next = Some(new_node),
+            None => self.head = Some(new_node),
+        }
+        Ok(new_node)
+    }
+
+    /// Remove the node pointed by `handle` wherever it is in the list, O(1).
+    /// A stale handle, whose node has already been removed, is rejected and
+    /// the list is left untouched.
+    pub fn remove(&mut self, handle: ArenaIndex) -> Result<T, ArenaErr> {
+        self.checked_node(handle)?;
+        Ok(self.unlink(handle))
+    }
+
+    pub fn contains(&self, handle: ArenaIndex) -> bool {
+        self.nodes.contains(handle)
+    }
+
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.head = None;
+        self.tail = None;
+    }
+
+    pub fn iter(&self) -> DoublyLinkedIter<'_, T> {
+        DoublyLinkedIter {
+            list: self,
+            front: self.head,
+            back: self.tail,
+            remaining: self.len(),
+        }
+    }
+
+    // The handle must have been checked before, a missing node here means
+    // that the links of the list are broken.
+    fn node(&self, handle: ArenaIndex) -> &DoublyLinkedNode<T> {
+        self.nodes.get(handle).expect("broken link in the list")
+    }
+
+    fn node_mut(&mut self, handle: ArenaIndex) -> &mut DoublyLinkedNode<T> {
+        self.nodes.get_mut(handle).expect("broken link in the list")
+    }
+
+    fn checked_node(&self, handle: ArenaIndex) -> Result<&DoublyLinkedNode<T>, ArenaErr> {
+        self.nodes.validate(handle)?;
+        Ok(self.node(handle))
+    }
+
+    fn unlink(&mut self, handle: ArenaIndex) -> T {
+        let node = self.nodes.remove(handle).expect("broken link in the list");
+        match node.prev {
+            Some(prev) => self.node_mut(prev).next = node.next,
+            None => self.head = node.next,
+        }
+        match node.next {
+            Some(next) => self.node_mut(next).prev = node.prev,
+            None => self.tail = node.prev,
+        }
+        node.val
+    }
+}
+
+impl<T> Default for DoublyLinkedList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> LinkedList<T> for DoublyLinkedList<T> {
+    fn push(&mut self, val: T) {
+        self.push_front(val);
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        self.pop_front()
+    }
+
+    fn peek(&self) -> Option<&T> {
+        self.front()
+    }
+
+    fn peek_mut(&mut self) -> Option<&mut T> {
+        self.front_mut()
+    }
+
+    fn reverse(&mut self) {
+        // Reversing only swaps the links of each node, the handles given so
+        // far are still pointing to the same values.
+        let mut curr = self.head;
+        while let Some(handle) = curr {
+            let node = self.node_mut(handle);
+            std::mem::swap(&mut node.prev, &mut node.next);
+            curr = node.prev;
+        }
+        std::mem::swap(&mut self.head, &mut self.tail);
+    }
+}
+
+pub struct DoublyLinkedIter<'a, T> {
+    list: &'a DoublyLinkedList<T>,
+    front: Option<ArenaIndex>,
+    back: Option<ArenaIndex>,
+    remaining: usize,
+}
+
+impl<'a, T> Iterator for DoublyLinkedIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.front.map(|handle| {
+            let node = self.list.node(handle);
+            self.front = node.next;
+            self.remaining -= 1;
+            &node.val
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T> DoubleEndedIterator for DoublyLinkedIter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.back.map(|handle| {
+            let node = self.list.node(handle);
+            self.back = node.prev;
+            self.remaining -= 1;
+            &node.val
+        })
+    }
+}
+
+impl<T> ExactSizeIterator for DoublyLinkedIter<'_, T> {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn doubly_linked_list_new() {
+        let list = DoublyLinkedList::<i32>::new();
+        assert!(list.is_empty());
+        assert!(list.head.is_none());
+        assert!(list.tail.is_none());
+    }
+
+    #[test]
+    fn doubly_linked_list_push_front_back() {
+        let mut list = DoublyLinkedList::new();
+        list.push_back(2);
+        list.push_front(1);
+        list.push_back(3);
+        assert_eq!(3, list.len());
+        assert_eq!(Some(&1), list.front());
+        assert_eq!(Some(&3), list.back());
+        assert_eq!(vec![&1, &2, &3], list.iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn doubly_linked_list_pop_front_back() {
+        let mut list = DoublyLinkedList::new();
+        list.push_back(1);
+        list.push_back(2);
+        list.push_back(3);
+        assert_eq!(Some(1), list.pop_front());
+        assert_eq!(Some(3), list.pop_back());
+        assert_eq!(Some(2), list.pop_back());
+        assert_eq!(None, list.pop_front());
+        assert!(list.head.is_none());
+        assert!(list.tail.is_none());
+    }
+
+    #[test]
+    fn doubly_linked_list_remove_by_handle() {
+        let mut list = DoublyLinkedList::new();
+        list.push_back(1);
+        let two = list.push_back(2);
+        list.push_back(3);
+        assert_eq!(Ok(2), list.remove(two));
+        assert_eq!(vec![&1, &3], list.iter().collect::<Vec<_>>());
+        assert_eq!(vec![&3, &1], list.iter().rev().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn doubly_linked_list_remove_stale_handle() {
+        let mut list = DoublyLinkedList::new();
+        let one = list.push_back(1);
+        list.remove(one).unwrap();
+        // the slot of the removed node is reused by the new one
+        let two = list.push_back(2);
+        assert_eq!(one.slot(), two.slot());
+        assert_eq!(Err(ArenaErr::StaleIndex), list.remove(one));
+        assert_eq!(Err(ArenaErr::StaleIndex), list.get(one));
+        assert_eq!(vec![&2], list.iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn doubly_linked_list_insert_after_before() {
+        let mut list = DoublyLinkedList::new();
+        let two = list.push_back(2);
+        list.insert_after(two, 4).unwrap();
+        list.insert_before(two, 1).unwrap();
+        let three = list.insert_after(two, 3).unwrap();
+        assert_eq!(vec![&1, &2, &3, &4], list.iter().collect::<Vec<_>>());
+        assert_eq!(Some(&1), list.front());
+        assert_eq!(Some(&4), list.back());
+        list.remove(three).unwrap();
+        assert_eq!(
+            Err(ArenaErr::StaleIndex),
+            list.insert_after(three, 5).map(|_| ())
+        );
+    }
+
+    #[test]
+    fn doubly_linked_list_get_mut() {
+        let mut list = DoublyLinkedList::new();
+        let one = list.push_back(1);
+        *list.get_mut(one).unwrap() = 7;
+        assert_eq!(Ok(&7), list.get(one));
+    }
+
+    #[test]
+    fn doubly_linked_list_handles_navigation() {
+        let mut list = DoublyLinkedList::new();
+        let one = list.push_back(1);
+        let two = list.push_back(2);
+        assert_eq!(Some(one), list.front_handle());
+        assert_eq!(Some(two), list.back_handle());
+        assert_eq!(Ok(Some(two)), list.next_handle(one));
+        assert_eq!(Ok(None), list.next_handle(two));
+        assert_eq!(Ok(Some(one)), list.prev_handle(two));
+    }
+
+    #[test]
+    fn doubly_linked_list_linked_list_trait() {
+        let mut list = DoublyLinkedList::new();
+        LinkedList::push(&mut list, 1);
+        LinkedList::push(&mut list, 2);
+        assert_eq!(Some(&2), LinkedList::peek(&list));
+        if let Some(v) = LinkedList::peek_mut(&mut list) {
+            *v = 7;
+        }
+        assert_eq!(Some(7), LinkedList::pop(&mut list));
+        assert_eq!(Some(1), LinkedList::pop(&mut list));
+        assert_eq!(None, LinkedList::pop(&mut list));
+    }
+
+    #[test]
+    fn doubly_linked_list_reverse() {
+        let mut list = DoublyLinkedList::new();
+        let one = list.push_back(1);
+        list.push_back(2);
+        list.push_back(3);
+        list.reverse();
+        assert_eq!(vec![&3, &2, &1], list.iter().collect::<Vec<_>>());
+        assert_eq!(Some(one), list.back_handle());
+        list.push_back(0);
+        assert_eq!(vec![&3, &2, &1, &0], list.iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn doubly_linked_list_iter_both_ends() {
+        let mut list = DoublyLinkedList::new();
+        list.push_back(1);
+        list.push_back(2);
+        list.push_back(3);
+        let mut iter = list.iter();
+        assert_eq!(Some(&1), iter.next());
+        assert_eq!(Some(&3), iter.next_back());
+        assert_eq!(Some(&2), iter.next());
+        assert_eq!(None, iter.next_back());
+        assert_eq!(None, iter.next());
+    }
+}
@@ -1,7 +1,11 @@
+#![allow(dead_code)]
+
 use std::fmt::Display;
 
+pub use doubly_linked_list::{DoublyLinkedIter, DoublyLinkedList};
 pub use singly_linked_list::SinglyLinkedList;
 
+mod doubly_linked_list;
 mod singly_linked_list;
 
 /// Linked list abstract data type. Contains all the expected behaviours that
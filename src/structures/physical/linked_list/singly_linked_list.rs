@@ -23,13 +23,13 @@ impl<T> SinglyLinkedList<T> {
         Self { head: None }
     }
 
-    pub fn iter(&self) -> SinglyLinkedIter<T> {
+    pub fn iter(&self) -> SinglyLinkedIter<'_, T> {
         SinglyLinkedIter {
             next: self.head.as_deref(),
         }
     }
 
-    pub fn iter_mut(&mut self) -> SinglyLinkedIterMut<T> {
+    pub fn iter_mut(&mut self) -> SinglyLinkedIterMut<'_, T> {
         SinglyLinkedIterMut {
             next: self.head.as_deref_mut(),
         }
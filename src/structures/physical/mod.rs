@@ -1,8 +1,9 @@
+pub mod arena;
 pub mod array;
-mod linked_list;
+pub mod linked_list;
 pub mod matrix;
 pub mod singly_linked_list;
 
-pub use linked_list::LinkedList;
+pub use linked_list::{DoublyLinkedList, LinkedList};
 
 // TODO: implement String structure.